@@ -446,6 +446,11 @@ pub fn coerce_unsized_info<'tcx>(tcx: TyCtxt<'tcx>, impl_did: LocalDefId) -> Coe
                            for a coercion between structures with one field \
                            being coerced, none found"
                 )
+                .span_label(span, "no field needs coercion")
+                .span_note(
+                    tcx.def_span(def_a.did()),
+                    format!("no field of `{source}` changes type when coercing to `{target}`"),
+                )
                 .emit();
                 return err_info;
             } else if diff_fields.len() > 1 {
@@ -456,15 +461,15 @@ pub fn coerce_unsized_info<'tcx>(tcx: TyCtxt<'tcx>, impl_did: LocalDefId) -> Coe
                     tcx.def_span(impl_did)
                 };
 
-                struct_span_err!(
+                let mut err = struct_span_err!(
                     tcx.sess,
                     span,
                     E0375,
                     "implementing the trait \
                                                 `CoerceUnsized` requires multiple \
                                                 coercions"
-                )
-                .note(
+                );
+                err.note(
                     "`CoerceUnsized` may only be implemented for \
                           a coercion between structures with one field being coerced",
                 )
@@ -477,8 +482,14 @@ pub fn coerce_unsized_info<'tcx>(tcx: TyCtxt<'tcx>, impl_did: LocalDefId) -> Coe
                         .collect::<Vec<_>>()
                         .join(", ")
                 ))
-                .span_label(span, "requires multiple coercions")
-                .emit();
+                .span_label(span, "requires multiple coercions");
+                for &(i, a, b) in &diff_fields {
+                    err.span_label(
+                        tcx.def_span(fields[i].did),
+                        format!("this field changes from `{a}` to `{b}`"),
+                    );
+                }
+                err.emit();
                 return err_info;
             }
 
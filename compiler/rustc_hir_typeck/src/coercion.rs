@@ -977,7 +977,7 @@ fn coerce_unsafe_ptr(
         // Check that the types which they point at are compatible.
         let a_unsafe = self.tcx.mk_ptr(ty::TypeAndMut { mutbl: mutbl_b, ty: mt_a.ty });
         // Although references and unsafe ptrs have the same
-        // representation, we still register an Adjust::DerefRef so that
+        // representation, we still register a deref and a raw borrow so that
         // regionck knows that the region for `a` must be valid here.
         if is_ref {
             self.unify_and(a_unsafe, b, |target| {
@@ -7,6 +7,7 @@
 use either::Either;
 use hir_expand::{
     attrs::{collect_attrs, Attr, AttrId, RawAttrs},
+    mod_path::PathKind,
     HirFileId, InFile,
 };
 use itertools::Itertools;
@@ -249,6 +250,29 @@ pub fn is_proc_macro_attribute(&self) -> bool {
     pub fn is_proc_macro_derive(&self) -> bool {
         self.by_key("proc_macro_derive").exists()
     }
+
+    /// Whether the item is annotated with `#[rust_analyzer::skip_match_check]`, which turns off
+    /// match exhaustiveness checking for the item and everything nested in it.
+    pub fn has_skip_match_check(&self) -> bool {
+        self.iter().any(|attr| {
+            attr.path.kind == PathKind::Plain
+                && matches!(
+                    attr.path.segments(),
+                    [tool, name] if tool.to_smol_str() == "rust_analyzer"
+                        && name.to_smol_str() == "skip_match_check"
+                )
+        })
+    }
+
+    /// Whether the item is annotated with `#[allow(..)]` for the lint `lint`, e.g.
+    /// `illegal_floating_point_literal_pattern`.
+    pub fn allows_lint(&self, lint: &str) -> bool {
+        self.by_key("allow").tt_values().any(|tt| {
+            tt.token_trees.iter().any(
+                |tt| matches!(tt, tt::TokenTree::Leaf(tt::Leaf::Ident(ident)) if ident.text == lint),
+            )
+        })
+    }
 }
 
 impl AttrsWithOwner {
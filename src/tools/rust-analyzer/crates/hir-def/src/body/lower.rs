@@ -949,16 +949,14 @@ fn collect_pat_(&mut self, pat: ast::Pat, binding_list: &mut BindingList) -> Pat
                         .collect(),
                 }
             }
-            ast::Pat::LiteralPat(lit) => {
-                if let Some(ast_lit) = lit.literal() {
-                    let expr = Expr::Literal(ast_lit.kind().into());
+            ast::Pat::LiteralPat(lit) => match pat_literal_to_hir(&lit) {
+                Some((hir_lit, ast_lit)) => {
                     let expr_ptr = AstPtr::new(&ast::Expr::Literal(ast_lit));
-                    let expr_id = self.alloc_expr(expr, expr_ptr);
+                    let expr_id = self.alloc_expr(Expr::Literal(hir_lit), expr_ptr);
                     Pat::Lit(expr_id)
-                } else {
-                    Pat::Missing
                 }
-            }
+                None => Pat::Missing,
+            },
             ast::Pat::RestPat(_) => {
                 // `RestPat` requires special handling and should not be mapped
                 // to a Pat. Here we are using `Pat::Missing` as a fallback for
@@ -993,13 +991,38 @@ fn collect_pat_(&mut self, pat: ast::Pat, binding_list: &mut BindingList) -> Pat
                 }
                 None => Pat::Missing,
             },
-            // FIXME: implement
-            ast::Pat::RangePat(_) => Pat::Missing,
+            ast::Pat::RangePat(range) => {
+                let start = range.start().map(|it| self.collect_range_pat_endpoint(it));
+                let end = range.end().map(|it| self.collect_range_pat_endpoint(it));
+                match (start, end, range.op_kind()) {
+                    (Some(None), _, _) | (_, Some(None), _) | (_, _, None) => Pat::Missing,
+                    (start, end, Some(range_type)) => {
+                        Pat::Range { start: start.flatten(), end: end.flatten(), range_type }
+                    }
+                }
+            }
         };
         let ptr = AstPtr::new(&pat);
         self.alloc_pat(pattern, Either::Left(ptr))
     }
 
+    /// An endpoint of a range pattern is a literal, possibly negative, or a path to a constant,
+    /// which is lowered as the corresponding expression.
+    fn collect_range_pat_endpoint(&mut self, pat: ast::Pat) -> Option<ExprId> {
+        match pat {
+            ast::Pat::LiteralPat(lit) => {
+                let (hir_lit, ast_lit) = pat_literal_to_hir(&lit)?;
+                let expr_ptr = AstPtr::new(&ast::Expr::Literal(ast_lit));
+                Some(self.alloc_expr(Expr::Literal(hir_lit), expr_ptr))
+            }
+            ast::Pat::PathPat(p) => {
+                let path = self.expander.parse_path(self.db, p.path()?)?;
+                Some(self.alloc_expr_desugared(Expr::Path(path)))
+            }
+            _ => None,
+        }
+    }
+
     fn collect_pat_opt_(&mut self, pat: Option<ast::Pat>, binding_list: &mut BindingList) -> PatId {
         match pat {
             Some(pat) => self.collect_pat_(pat, binding_list),
@@ -1053,6 +1076,17 @@ fn add_definition_to_binding(&mut self, binding_id: BindingId, pat_id: PatId) {
     }
 }
 
+/// The literal of a literal pattern, with the sign of a negative literal pattern like `-1` folded
+/// into its value.
+fn pat_literal_to_hir(lit: &ast::LiteralPat) -> Option<(Literal, ast::Literal)> {
+    let ast_lit = lit.literal()?;
+    let mut hir_lit: Literal = ast_lit.kind().into();
+    if lit.minus_token().is_some() {
+        hir_lit = hir_lit.negate()?;
+    }
+    Some((hir_lit, ast_lit))
+}
+
 impl From<ast::LiteralKind> for Literal {
     fn from(ast_lit_kind: ast::LiteralKind) -> Self {
         match ast_lit_kind {
@@ -507,10 +507,17 @@ fn print_pat(&mut self, pat: PatId) {
                 });
                 w!(self, "}}");
             }
-            Pat::Range { start, end } => {
-                self.print_expr(*start);
-                w!(self, "...");
-                self.print_expr(*end);
+            Pat::Range { start, end, range_type } => {
+                if let Some(start) = start {
+                    self.print_expr(*start);
+                }
+                match range_type {
+                    ast::RangeOp::Exclusive => w!(self, ".."),
+                    ast::RangeOp::Inclusive => w!(self, "..="),
+                }
+                if let Some(end) = end {
+                    self.print_expr(*end);
+                }
             }
             Pat::Slice { prefix, slice, suffix } => {
                 w!(self, "[");
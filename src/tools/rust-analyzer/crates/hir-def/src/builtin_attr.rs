@@ -12,7 +12,7 @@
 use rustc_hash::FxHashMap;
 
 /// Ignored attribute namespaces used by tools.
-pub const TOOL_MODULES: &[&str] = &["rustfmt", "clippy"];
+pub const TOOL_MODULES: &[&str] = &["rustfmt", "clippy", "rust_analyzer"];
 
 pub struct BuiltinAttribute {
     pub name: &'static str,
@@ -92,6 +92,25 @@ pub enum Literal {
     Float(FloatTypeWrapper, Option<BuiltinFloat>),
 }
 
+impl Literal {
+    /// The value of `-lit`, as written in a negative literal pattern like `-1`, or `None` if the
+    /// literal can't be negated or its negation is below `i128::MIN`.
+    pub fn negate(self) -> Option<Self> {
+        match self {
+            // A suffixed literal above `i128::MAX` has wrapped around, so this wraps back: the
+            // literal `-170141183460469231731687303715884105728i128` is `i128::MIN`.
+            Literal::Int(value, suffix) => Some(Literal::Int(value.wrapping_neg(), suffix)),
+            Literal::Uint(value, None) if value <= i128::MIN.unsigned_abs() => {
+                Some(Literal::Int((value as i128).wrapping_neg(), None))
+            }
+            Literal::Float(value, suffix) => {
+                Some(Literal::Float(FloatTypeWrapper::new(-value.into_f64()), suffix))
+            }
+            _ => None,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub enum Expr {
     /// This is produced if the syntax tree does not have a required expression piece.
@@ -458,7 +477,7 @@ pub enum Pat {
     Tuple { args: Box<[PatId]>, ellipsis: Option<usize> },
     Or(Box<[PatId]>),
     Record { path: Option<Box<Path>>, args: Box<[RecordFieldPat]>, ellipsis: bool },
-    Range { start: ExprId, end: ExprId },
+    Range { start: Option<ExprId>, end: Option<ExprId>, range_type: RangeOp },
     Slice { prefix: Box<[PatId]>, slice: Option<PatId>, suffix: Box<[PatId]> },
     Path(Box<Path>),
     Lit(ExprId),
@@ -109,3 +109,31 @@ fn test_clone_expand_with_const_generics() {
 impl <const T0: usize, T1: core::clone::Clone, > core::clone::Clone for Foo<T0, T1, > {}"#]],
     );
 }
+
+#[test]
+fn test_coerce_pointee_expand() {
+    check(
+        r#"
+//- minicore: coerce_pointee
+use core::marker::CoercePointee;
+
+#[derive(CoercePointee)]
+struct MyPtr<#[pointee] T: ?Sized, U> {
+    ptr: *const T,
+    extra: U,
+}
+"#,
+        expect![[r#"
+use core::marker::CoercePointee;
+
+#[derive(CoercePointee)]
+struct MyPtr<#[pointee] T: ?Sized, U> {
+    ptr: *const T,
+    extra: U,
+}
+
+impl <T0: ?Sized+core::marker::Unsize<__S> , T1, __S: ?Sized> core::ops::CoerceUnsized<MyPtr<__S, T1, >> for MyPtr<T0, T1, > {}
+impl <T0: ?Sized+core::marker::Unsize<__S> , T1, __S: ?Sized> core::ops::DispatchFromDyn<MyPtr<__S, T1, >> for MyPtr<T0, T1, > {}
+impl <T0: ?Sized+core::marker::Unsize<__S> , T1, __S: ?Sized> core::ops::DispatchFromDyn<MyPtr<__S, T1, >> for MyPtr<T0, T1, > {}"#]],
+    );
+}
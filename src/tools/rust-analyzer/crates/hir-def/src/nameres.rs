@@ -125,6 +125,8 @@ pub struct DefMap {
 
     edition: Edition,
     recursion_limit: Option<u32>,
+    /// The `#![pattern_complexity]` limit on the work done checking a single match.
+    pattern_complexity: Option<u32>,
     diagnostics: Vec<DefDiagnostic>,
 }
 
@@ -282,6 +284,7 @@ fn empty(krate: CrateId, edition: Edition, module_data: ModuleData) -> DefMap {
             krate,
             edition,
             recursion_limit: None,
+            pattern_complexity: None,
             extern_prelude: FxHashMap::default(),
             exported_derives: FxHashMap::default(),
             fn_proc_macro_mapping: FxHashMap::default(),
@@ -502,6 +505,7 @@ fn shrink_to_fit(&mut self) {
             block: _,
             edition: _,
             recursion_limit: _,
+            pattern_complexity: _,
             krate: _,
             prelude: _,
             root: _,
@@ -531,6 +535,10 @@ pub fn diagnostics(&self) -> &[DefDiagnostic] {
     pub fn recursion_limit(&self) -> Option<u32> {
         self.recursion_limit
     }
+
+    pub fn pattern_complexity(&self) -> Option<u32> {
+        self.pattern_complexity
+    }
 }
 
 impl ModuleData {
@@ -289,6 +289,15 @@ fn seed_with_top_level(&mut self) {
                     continue;
                 }
 
+                if *attr_name == hir_expand::name![pattern_complexity] {
+                    if let Some(limit) = attr.string_value() {
+                        if let Ok(limit) = limit.parse() {
+                            self.def_map.pattern_complexity = Some(limit);
+                        }
+                    }
+                    continue;
+                }
+
                 if *attr_name == hir_expand::name![crate_type] {
                     if let Some("proc-macro") = attr.string_value().map(SmolStr::as_str) {
                         self.is_proc_macro = true;
@@ -1,11 +1,12 @@
 //! Builtin derives.
 
 use base_db::{CrateOrigin, LangCrateOrigin};
+use itertools::Itertools;
 use tracing::debug;
 
 use crate::tt::{self, TokenId};
 use syntax::{
-    ast::{self, AstNode, HasGenericParams, HasModuleItem, HasName},
+    ast::{self, AstNode, HasAttrs, HasGenericParams, HasModuleItem, HasName},
     match_ast,
 };
 
@@ -51,7 +52,8 @@ fn find_by_name(name: &name::Name) -> Option<Self> {
     Ord => ord_expand,
     PartialOrd => partial_ord_expand,
     Eq => eq_expand,
-    PartialEq => partial_eq_expand
+    PartialEq => partial_eq_expand,
+    CoercePointee => coerce_pointee_expand
 }
 
 pub fn find_builtin_derive(ident: &name::Name) -> Option<BuiltinDeriveExpander> {
@@ -62,6 +64,8 @@ struct BasicAdtInfo {
     name: tt::Ident,
     /// `Some(ty)` if it's a const param of type `ty`, `None` if it's a type param.
     param_types: Vec<Option<tt::Subtree>>,
+    /// Index into `param_types` of the type param marked `#[pointee]`, or of the only type param.
+    pointee: Option<usize>,
 }
 
 fn parse_adt(tt: &tt::Subtree) -> Result<BasicAdtInfo, ExpandError> {
@@ -93,9 +97,22 @@ fn parse_adt(tt: &tt::Subtree) -> Result<BasicAdtInfo, ExpandError> {
     let name_token_id =
         token_map.token_by_range(name.syntax().text_range()).unwrap_or_else(TokenId::unspecified);
     let name_token = tt::Ident { span: name_token_id, text: name.text().into() };
+    let params: Vec<_> =
+        params.into_iter().flat_map(|param_list| param_list.type_or_const_params()).collect();
+    let type_params = || {
+        params.iter().enumerate().filter_map(|(idx, param)| match param {
+            ast::TypeOrConstParam::Type(param) => Some((idx, param)),
+            ast::TypeOrConstParam::Const(_) => None,
+        })
+    };
+    let pointee = type_params()
+        .find(|(_, param)| {
+            param.attrs().any(|attr| attr.simple_name().as_deref() == Some("pointee"))
+        })
+        .or_else(|| type_params().exactly_one().ok())
+        .map(|(idx, _)| idx);
     let param_types = params
         .into_iter()
-        .flat_map(|param_list| param_list.type_or_const_params())
         .map(|param| {
             if let ast::TypeOrConstParam::Const(param) = param {
                 let ty = param
@@ -108,7 +125,7 @@ fn parse_adt(tt: &tt::Subtree) -> Result<BasicAdtInfo, ExpandError> {
             }
         })
         .collect();
-    Ok(BasicAdtInfo { name: name_token, param_types })
+    Ok(BasicAdtInfo { name: name_token, param_types, pointee })
 }
 
 fn expand_simple_derive(tt: &tt::Subtree, trait_path: tt::Subtree) -> ExpandResult<tt::Subtree> {
@@ -237,3 +254,56 @@ fn partial_ord_expand(
     let krate = find_builtin_crate(db, id);
     expand_simple_derive(tt, quote! { #krate::cmp::PartialOrd })
 }
+
+fn coerce_pointee_expand(
+    db: &dyn ExpandDatabase,
+    id: MacroCallId,
+    tt: &tt::Subtree,
+) -> ExpandResult<tt::Subtree> {
+    let krate = find_builtin_crate(db, id);
+    let info = match parse_adt(tt) {
+        Ok(info) => info,
+        Err(e) => return ExpandResult::with_err(tt::Subtree::empty(), e),
+    };
+    let Some(pointee) = info.pointee else {
+        return ExpandResult::with_err(
+            tt::Subtree::empty(),
+            ExpandError::Other("`CoercePointee` requires a `#[pointee]` type parameter".into()),
+        );
+    };
+    let target =
+        tt::Leaf::Ident(tt::Ident { span: tt::TokenId::unspecified(), text: "__S".into() });
+    let mut params = Vec::new();
+    let mut args = Vec::new();
+    let mut target_args = Vec::new();
+    for (idx, param_ty) in info.param_types.into_iter().enumerate() {
+        let ident = tt::Leaf::Ident(tt::Ident {
+            span: tt::TokenId::unspecified(),
+            text: format!("T{idx}").into(),
+        });
+        let ident_ = ident.clone();
+        let target_arg = if idx == pointee { target.clone() } else { ident.clone() };
+        params.push(match param_ty {
+            Some(ty) => quote! { const #ident : #ty , },
+            None if idx == pointee => {
+                let (krate, target) = (krate.clone(), target.clone());
+                quote! { #ident : ?Sized + #krate::marker::Unsize<#target> , }
+            }
+            None => quote! { #ident , },
+        });
+        args.push(quote! { #ident_ , });
+        target_args.push(quote! { #target_arg , });
+    }
+    let make_impl = |trait_path: tt::Subtree| {
+        let (params, args, target_args) = (params.clone(), args.clone(), target_args.clone());
+        let (name, name_, target) = (info.name.clone(), info.name.clone(), target.clone());
+        quote! {
+            impl < ##params #target : ?Sized > #trait_path < #name < ##target_args > >
+                for #name_ < ##args > {}
+        }
+    };
+    let krate_ = krate.clone();
+    let mut expanded = make_impl(quote! { #krate::ops::CoerceUnsized });
+    expanded.token_trees.extend(make_impl(quote! { #krate_::ops::DispatchFromDyn }).token_trees);
+    ExpandResult::ok(expanded)
+}
@@ -104,6 +104,7 @@ pub fn find_builtin_macro(
     (unreachable, Unreachable) => unreachable_expand,
     (log_syntax, LogSyntax) => log_syntax_expand,
     (trace_macros, TraceMacros) => trace_macros_expand,
+    (pattern_type, PatternType) => pattern_type_expand,
 
     EAGER:
     (compile_error, CompileError) => compile_error_expand,
@@ -159,6 +160,29 @@ fn trace_macros_expand(
     ExpandResult::ok(quote! {})
 }
 
+fn pattern_type_expand(
+    _db: &dyn ExpandDatabase,
+    _id: MacroCallId,
+    tt: &tt::Subtree,
+) -> ExpandResult<tt::Subtree> {
+    // `pattern_type!(u32 is 1..)` expands to its base type `u32`. The pattern is read from the
+    // macro call when lowering the type, see `hir_ty::PatternTypeRange`.
+    let is_keyword = |tt: &tt::TokenTree| match tt {
+        tt::TokenTree::Leaf(tt::Leaf::Ident(ident)) => ident.text == "is",
+        _ => false,
+    };
+    let Some(is_pos) = tt.token_trees.iter().position(is_keyword) else {
+        return ExpandResult::with_err(
+            tt::Subtree::empty(),
+            ExpandError::Other("expected `is` in `pattern_type!`".into()),
+        );
+    };
+    ExpandResult::ok(tt::Subtree {
+        delimiter: tt::Delimiter::unspecified(),
+        token_trees: tt.token_trees[..is_pos].to_vec(),
+    })
+}
+
 fn stringify_expand(
     _db: &dyn ExpandDatabase,
     _id: MacroCallId,
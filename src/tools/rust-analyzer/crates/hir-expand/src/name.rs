@@ -316,6 +316,7 @@ macro_rules! known_names {
         log_syntax,
         module_path,
         option_env,
+        pattern_type,
         std_panic,
         stringify,
         trace_macros,
@@ -330,6 +331,7 @@ macro_rules! known_names {
         PartialOrd,
         Eq,
         PartialEq,
+        CoercePointee,
         // Builtin attributes
         bench,
         cfg_accessible,
@@ -340,6 +342,7 @@ macro_rules! known_names {
         test,
         test_case,
         recursion_limit,
+        pattern_complexity,
         feature,
         // known methods of lang items
         call_once,
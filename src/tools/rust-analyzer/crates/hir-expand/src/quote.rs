@@ -106,6 +106,8 @@ macro_rules! __quote {
     ( < ) => {$crate::__quote!(@PUNCT '<')};
     ( > ) => {$crate::__quote!(@PUNCT '>')};
     ( ! ) => {$crate::__quote!(@PUNCT '!')};
+    ( ? ) => {$crate::__quote!(@PUNCT '?')};
+    ( + ) => {$crate::__quote!(@PUNCT '+')};
 
     ( $first:tt $($tail:tt)+ ) => {
         {
@@ -12,7 +12,8 @@
 use hir_def::{
     expr::Movability,
     lang_item::{lang_attr, LangItem, LangItemTarget},
-    AssocItemId, GenericDefId, HasModule, ItemContainerId, Lookup, ModuleId, TypeAliasId,
+    AssocItemId, EnumVariantId, GenericDefId, HasModule, ItemContainerId, Lookup, ModuleId,
+    TypeAliasId, VariantId,
 };
 use hir_expand::name::name;
 
@@ -212,6 +213,28 @@ fn opaque_ty_data(&self, id: chalk_ir::OpaqueTyId<Interner>) -> Arc<OpaqueTyDatu
                 };
                 chalk_ir::Binders::new(binders, bound)
             }
+            crate::ImplTraitId::TypeAliasImplTrait(alias, idx) => {
+                let datas = self
+                    .db
+                    .type_alias_impl_traits(alias)
+                    .expect("impl trait id without impl traits");
+                let (datas, binders) = (*datas).as_ref().into_value_and_skipped_binders();
+                let data = &datas.impl_traits[idx];
+                let bound = OpaqueTyDatumBound {
+                    bounds: make_single_type_binders(data.bounds.skip_binders().to_vec()),
+                    where_clauses: chalk_ir::Binders::empty(Interner, vec![]),
+                };
+                chalk_ir::Binders::new(binders, bound)
+            }
+            crate::ImplTraitId::PatternType(_) => {
+                // FIXME: pattern types are `Copy` and `Clone` if their base type is
+                let bound = OpaqueTyDatumBound {
+                    bounds: make_single_type_binders(vec![]),
+                    where_clauses: chalk_ir::Binders::empty(Interner, vec![]),
+                };
+                // The opaque type has 1 parameter, the base type.
+                make_single_type_binders(bound)
+            }
             crate::ImplTraitId::AsyncBlockTypeImplTrait(..) => {
                 if let Some((future_trait, future_output)) = self
                     .db
@@ -599,25 +622,38 @@ pub(crate) fn struct_datum_query(
     let chalk_ir::AdtId(adt_id) = struct_id;
     let generic_params = generics(db.upcast(), adt_id.into());
     let upstream = adt_id.module(db.upcast()).krate() != krate;
-    let where_clauses = {
-        let generic_params = generics(db.upcast(), adt_id.into());
-        let bound_vars = generic_params.bound_vars_subst(db, DebruijnIndex::INNERMOST);
-        convert_where_clauses(db, adt_id.into(), &bound_vars)
-    };
+    let bound_vars = generic_params.bound_vars_subst(db, DebruijnIndex::INNERMOST);
+    let where_clauses = convert_where_clauses(db, adt_id.into(), &bound_vars);
     let flags = rust_ir::AdtFlags {
         upstream,
         // FIXME set fundamental and phantom_data flags correctly
         fundamental: false,
         phantom_data: false,
     };
-    // FIXME provide enum variants properly (for auto traits)
-    let variant = rust_ir::AdtVariantDatum {
-        fields: Vec::new(), // FIXME add fields (only relevant for auto traits),
+    // The fields are used for auto traits, and for unsizing a struct through its last field.
+    let variant_datum = |variant: VariantId| rust_ir::AdtVariantDatum {
+        fields: db
+            .field_types(variant)
+            .iter()
+            .map(|(_, ty)| ty.clone().substitute(Interner, &bound_vars))
+            .collect(),
+    };
+    let (kind, variants) = match adt_id {
+        hir_def::AdtId::StructId(id) => (rust_ir::AdtKind::Struct, vec![variant_datum(id.into())]),
+        hir_def::AdtId::UnionId(id) => (rust_ir::AdtKind::Union, vec![variant_datum(id.into())]),
+        hir_def::AdtId::EnumId(id) => {
+            let variants = db
+                .enum_data(id)
+                .variants
+                .iter()
+                .map(|(local_id, _)| variant_datum(EnumVariantId { parent: id, local_id }.into()))
+                .collect();
+            (rust_ir::AdtKind::Enum, variants)
+        }
     };
-    let struct_datum_bound = rust_ir::AdtDatumBound { variants: vec![variant], where_clauses };
+    let struct_datum_bound = rust_ir::AdtDatumBound { variants, where_clauses };
     let struct_datum = StructDatum {
-        // FIXME set ADT kind
-        kind: rust_ir::AdtKind::Struct,
+        kind,
         id: struct_id,
         binders: make_binders(db, &generic_params, struct_datum_bound),
         flags,
@@ -12,8 +12,9 @@
 use crate::{
     db::HirDatabase, from_assoc_type_id, from_chalk_trait_id, from_foreign_def_id,
     from_placeholder_idx, to_chalk_trait_id, utils::generics, AdtId, AliasEq, AliasTy, Binders,
-    CallableDefId, CallableSig, DynTy, FnPointer, ImplTraitId, Interner, Lifetime, ProjectionTy,
-    QuantifiedWhereClause, Substitution, TraitRef, Ty, TyBuilder, TyKind, TypeFlags, WhereClause,
+    CallableDefId, CallableSig, DynTy, FnPointer, ImplTraitId, Interner, Lifetime,
+    PatternTypeRange, ProjectionTy, QuantifiedWhereClause, Substitution, TraitRef, Ty, TyBuilder,
+    TyKind, TypeFlags, WhereClause,
 };
 
 pub trait TyExt {
@@ -32,6 +33,8 @@ pub trait TyExt {
     fn as_reference(&self) -> Option<(&Ty, Lifetime, Mutability)>;
     fn as_reference_or_ptr(&self) -> Option<(&Ty, Rawness, Mutability)>;
     fn as_generic_def(&self, db: &dyn HirDatabase) -> Option<GenericDefId>;
+    /// If this is a pattern type like `u32 is 1..`, returns its base type and its values.
+    fn as_pattern_type(&self, db: &dyn HirDatabase) -> Option<(&Ty, PatternTypeRange)>;
 
     fn callable_def(&self, db: &dyn HirDatabase) -> Option<CallableDefId>;
     fn callable_sig(&self, db: &dyn HirDatabase) -> Option<CallableSig>;
@@ -161,6 +164,20 @@ fn as_generic_def(&self, db: &dyn HirDatabase) -> Option<GenericDefId> {
         }
     }
 
+    fn as_pattern_type(&self, db: &dyn HirDatabase) -> Option<(&Ty, PatternTypeRange)> {
+        match self.kind(Interner) {
+            TyKind::OpaqueType(opaque_ty_id, subst) => {
+                match db.lookup_intern_impl_trait_id((*opaque_ty_id).into()) {
+                    ImplTraitId::PatternType(range) => {
+                        Some((subst.at(Interner, 0).assert_ty_ref(Interner), range))
+                    }
+                    _ => None,
+                }
+            }
+            _ => None,
+        }
+    }
+
     fn callable_def(&self, db: &dyn HirDatabase) -> Option<CallableDefId> {
         match self.kind(Interner) {
             &TyKind::FnDef(def, ..) => Some(db.lookup_intern_callable_def(def.into())),
@@ -244,6 +261,14 @@ fn impl_trait_bounds(&self, db: &dyn HirDatabase) -> Option<Vec<QuantifiedWhereC
                             data.substitute(Interner, &subst).into_value_and_skipped_binders().0
                         })
                     }
+                    ImplTraitId::TypeAliasImplTrait(alias, idx) => {
+                        db.type_alias_impl_traits(alias).map(|it| {
+                            let data =
+                                (*it).as_ref().map(|tait| tait.impl_traits[idx].bounds.clone());
+                            data.substitute(Interner, &subst).into_value_and_skipped_binders().0
+                        })
+                    }
+                    ImplTraitId::PatternType(_) => None,
                 }
             }
             TyKind::Alias(AliasTy::Opaque(opaque_ty)) => {
@@ -256,8 +281,17 @@ fn impl_trait_bounds(&self, db: &dyn HirDatabase) -> Option<Vec<QuantifiedWhereC
                             data.substitute(Interner, &opaque_ty.substitution)
                         })
                     }
+                    ImplTraitId::TypeAliasImplTrait(alias, idx) => {
+                        db.type_alias_impl_traits(alias).map(|it| {
+                            let data =
+                                (*it).as_ref().map(|tait| tait.impl_traits[idx].bounds.clone());
+                            data.substitute(Interner, &opaque_ty.substitution)
+                        })
+                    }
                     // It always has an parameter for Future::Output type.
                     ImplTraitId::AsyncBlockTypeImplTrait(..) => unreachable!(),
+                    // Pattern types are only lowered to `TyKind::OpaqueType`.
+                    ImplTraitId::PatternType(_) => None,
                 };
 
                 predicates.map(|it| it.into_value_and_skipped_binders().0)
@@ -164,6 +164,24 @@ fn g(i: Foo<Foo<i32>>) -> i32 {
     );
 }
 
+#[test]
+fn raw_pointer_casts() {
+    check_number(
+        r#"
+    const GOAL: i32 = {
+        let arr = [1, 2, 3];
+        let p = &arr as *const i32;
+        let q = &arr as *const [i32; 3] as *mut [i32; 3] as *const u8 as *const i32;
+        let mut x = 5;
+        let m = &mut x as *mut i32;
+        let c = m as *const i32;
+        unsafe { *p + *q + *c }
+    };
+    "#,
+        7,
+    );
+}
+
 #[test]
 fn overloaded_deref() {
     // FIXME: We should support this.
@@ -872,6 +890,27 @@ fn array_and_index() {
     );
 }
 
+#[test]
+fn coerce_unsized_struct() {
+    check_number(
+        r#"
+    //- minicore: coerce_unsized, index, slice
+    use core::{marker::Unsize, ops::CoerceUnsized};
+
+    struct Ptr<'a, T: ?Sized> { tag: u8, inner: &'a T }
+    impl<'a, T: ?Sized + Unsize<U>, U: ?Sized> CoerceUnsized<Ptr<'a, U>> for Ptr<'a, T> {}
+
+    const GOAL: usize = {
+        let a = [1, 2, 3];
+        let p = Ptr { tag: 4, inner: &a };
+        let q: Ptr<'_, [i32]> = p;
+        q.inner.len() + q.tag as usize
+    };
+        "#,
+        7,
+    );
+}
+
 #[test]
 fn byte_string() {
     check_number(
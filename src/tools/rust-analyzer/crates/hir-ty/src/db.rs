@@ -9,7 +9,7 @@
     expr::ExprId,
     layout::{Layout, LayoutError, TargetDataLayout},
     AdtId, BlockId, ConstId, ConstParamId, DefWithBodyId, EnumVariantId, FunctionId, GenericDefId,
-    ImplId, LifetimeParamId, LocalFieldId, TypeOrConstParamId, VariantId,
+    ImplId, LifetimeParamId, LocalFieldId, TypeAliasId, TypeOrConstParamId, VariantId,
 };
 use la_arena::ArenaMap;
 use smallvec::SmallVec;
@@ -17,11 +17,12 @@
 use crate::{
     chalk_db,
     consteval::ConstEvalError,
+    diagnostics::{BodyValidationDiagnostic, LoweredPatterns},
     method_resolution::{InherentImpls, TraitImpls, TyFingerprint},
     mir::{BorrowckResult, MirBody, MirLowerError},
-    Binders, CallableDefId, Const, FnDefId, GenericArg, ImplTraitId, InferenceResult, Interner,
-    PolyFnSig, QuantifiedWhereClause, ReturnTypeImplTraits, Substitution, TraitRef, Ty, TyDefId,
-    ValueTyDefId,
+    Binders, CallableDefId, CoerceUnsizedInfo, Const, FnDefId, GenericArg, ImplTraitId,
+    InferenceResult, Interner, PolyFnSig, QuantifiedWhereClause, ReturnTypeImplTraits,
+    Substitution, TraitRef, Ty, TyDefId, ValueTyDefId,
 };
 use hir_expand::name::Name;
 
@@ -41,6 +42,14 @@ pub trait HirDatabase: DefDatabase + Upcast<dyn DefDatabase> {
     #[salsa::invoke(crate::mir::borrowck_query)]
     fn borrowck(&self, def: DefWithBodyId) -> Result<Arc<BorrowckResult>, MirLowerError>;
 
+    #[salsa::invoke(crate::diagnostics::lowered_patterns_query)]
+    fn lowered_patterns(&self, def: DefWithBodyId) -> Arc<LoweredPatterns>;
+
+    /// The diagnostics of [`BodyValidationDiagnostic::collect`], among which match checking, which
+    /// is expensive enough to be worth caching for bodies that didn't change.
+    #[salsa::invoke(crate::diagnostics::body_validation_diagnostics_query)]
+    fn body_validation_diagnostics(&self, def: DefWithBodyId) -> Arc<[BodyValidationDiagnostic]>;
+
     #[salsa::invoke(crate::lower::ty_query)]
     #[salsa::cycle(crate::lower::ty_recover)]
     fn ty(&self, def: TyDefId) -> Binders<Ty>;
@@ -69,6 +78,10 @@ pub trait HirDatabase: DefDatabase + Upcast<dyn DefDatabase> {
     #[salsa::invoke(crate::lower::field_types_query)]
     fn field_types(&self, var: VariantId) -> Arc<ArenaMap<LocalFieldId, Binders<Ty>>>;
 
+    /// Caches `CoerceUnsized` kinds for impls on custom types.
+    #[salsa::invoke(crate::infer::coerce_unsized_info_query)]
+    fn coerce_unsized_info(&self, def: ImplId) -> CoerceUnsizedInfo;
+
     #[salsa::invoke(crate::layout::layout_of_adt_query)]
     #[salsa::cycle(crate::layout::layout_of_adt_recover)]
     fn layout_of_adt(&self, def: AdtId, subst: Substitution) -> Result<Layout, LayoutError>;
@@ -85,6 +98,12 @@ fn return_type_impl_traits(
         def: FunctionId,
     ) -> Option<Arc<Binders<ReturnTypeImplTraits>>>;
 
+    #[salsa::invoke(crate::lower::type_alias_impl_traits)]
+    fn type_alias_impl_traits(
+        &self,
+        def: TypeAliasId,
+    ) -> Option<Arc<Binders<ReturnTypeImplTraits>>>;
+
     #[salsa::invoke(crate::lower::generic_predicates_for_param_query)]
     #[salsa::cycle(crate::lower::generic_predicates_for_param_recover)]
     fn generic_predicates_for_param(
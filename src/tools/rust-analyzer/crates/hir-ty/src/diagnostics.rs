@@ -7,11 +7,21 @@
 pub use crate::diagnostics::{
     decl_check::{incorrect_case, IncorrectCase},
     expr::{
-        record_literal_missing_fields, record_pattern_missing_fields, BodyValidationDiagnostic,
+        check_match_arms, fill_match_arms, is_refutable, match_decision_tree,
+        pretty_print_match_patterns, record_literal_missing_fields, record_pattern_missing_fields,
+        taken_match_arms, unreachable_patterns, ArmReachability, BodyValidationDiagnostic,
+        MatchCheckArm, MatchCheckError, MatchCheckReport, TakenMatchArm, UnreachablePattern,
+        UnreachablePatternKind,
     },
+    match_check::{decision_tree::DecisionTreeFormat, PatSourceOptions, WitnessPat},
     unsafe_check::{missing_unsafe, unsafe_expressions, UnsafeExpr},
 };
 
+pub(crate) use crate::diagnostics::{
+    expr::{body_validation_diagnostics_query, lowered_patterns_query},
+    match_check::{IntWidth, LoweredPatterns},
+};
+
 #[derive(Debug, PartialEq, Eq)]
 pub struct IncoherentImpl {
     pub file_id: hir_expand::HirFileId,
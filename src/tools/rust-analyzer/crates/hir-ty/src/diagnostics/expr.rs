@@ -2,35 +2,49 @@
 //! through the body using inference results: mismatched arg counts, missing
 //! fields, etc.
 
+use std::borrow::Cow;
 use std::fmt;
+use std::iter;
 use std::sync::Arc;
 
 use either::Either;
 use hir_def::lang_item::LangItem;
-use hir_def::{resolver::HasResolver, AdtId, AssocItemId, DefWithBodyId, HasModule};
+use hir_def::{
+    attr::Attrs, resolver::HasResolver, AdtId, AssocItemId, AttrDefId, DefWithBodyId, EnumId,
+    HasModule,
+};
 use hir_def::{ItemContainerId, Lookup};
 use hir_expand::name;
+use hir_expand::name::Name;
+use hir_expand::InFile;
 use itertools::Itertools;
 use rustc_hash::FxHashSet;
+use stdx::format_to;
+use syntax::{ast, AstPtr};
 use typed_arena::Arena;
 
 use crate::{
     db::HirDatabase,
     diagnostics::match_check::{
         self,
+        decision_tree::{DecisionTree, DecisionTreeArm, DecisionTreeFormat},
         deconstruct_pat::DeconstructedPat,
-        usefulness::{compute_match_usefulness, MatchCheckCtx},
+        pat_analysis::MatchCheckCtx,
+        usefulness::{Reachability, TooComplex},
+        LoweredPatterns, PatCtxt, PatternError, WitnessPat,
     },
     display::HirDisplay,
+    infer::reveal_opaque_ty,
     InferenceResult, Ty, TyExt,
 };
 
 pub(crate) use hir_def::{
     body::Body,
-    expr::{Expr, ExprId, MatchArm, Pat, PatId},
+    expr::{Expr, ExprId, MatchArm, Pat, PatId, Statement},
     LocalFieldId, VariantId,
 };
 
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum BodyValidationDiagnostic {
     RecordMissingFields {
         record: Either<ExprId, PatId>,
@@ -44,8 +58,39 @@ pub enum BodyValidationDiagnostic {
         match_expr: ExprId,
         uncovered_patterns: String,
     },
+    MatchCheckTooComplex {
+        match_expr: ExprId,
+    },
+    /// A match with arms that expand to too many or-pattern alternatives, which were checked as
+    /// if their or-patterns were wildcards. Missing arms may then go unreported.
+    MatchCheckApproximated {
+        match_expr: ExprId,
+    },
+    MismatchedTupleStructPatArgCount {
+        pat: PatId,
+        expected: usize,
+        found: usize,
+    },
+    RecordPatNoSuchFields {
+        pat: PatId,
+        fields: Vec<Name>,
+    },
+    /// A range pattern sharing only an endpoint with the range patterns before it.
+    OverlappingRangeEndpoints {
+        pat: PatId,
+        /// The shared values, e.g. `5_u8`.
+        overlaps: Vec<String>,
+    },
+    /// A floating-point literal in a pattern, unless `illegal_floating_point_literal_pattern` is
+    /// allowed.
+    FloatLiteralPattern {
+        literal: ExprId,
+    },
 }
 
+/// The rustc lint for floating-point literals in patterns, which can be allowed with `#[allow]`.
+const FLOAT_LITERAL_PATTERN_LINT: &str = "illegal_floating_point_literal_pattern";
+
 impl BodyValidationDiagnostic {
     pub fn collect(db: &dyn HirDatabase, owner: DefWithBodyId) -> Vec<BodyValidationDiagnostic> {
         let _p = profile::span("BodyValidationDiagnostic::collect");
@@ -56,21 +101,224 @@ pub fn collect(db: &dyn HirDatabase, owner: DefWithBodyId) -> Vec<BodyValidation
     }
 }
 
+/// An arm of a `match`, or an alternative of an or-pattern in one, that can never be reached: the
+/// arms and alternatives before it match all the values it matches.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UnreachablePattern {
+    pub pat: PatId,
+    pub kind: UnreachablePatternKind,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnreachablePatternKind {
+    Arm,
+    OrAlternative,
+}
+
+/// The unreachable arms and or-pattern alternatives of the `match` expressions of a body. Patterns
+/// that fail to lower are checked as matching nothing, and are never reported themselves.
+pub fn unreachable_patterns(db: &dyn HirDatabase, owner: DefWithBodyId) -> Vec<UnreachablePattern> {
+    let _p = profile::span("unreachable_patterns");
+    let body = db.body(owner);
+    let mut validator = ExprValidator::new(owner, db.infer(owner));
+    validator.validate_match_patterns(db, &body);
+    validator.unreachable_patterns
+}
+
+/// The arm a `match` takes when its scrutinee is known at compile time, by its index.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TakenMatchArm {
+    pub match_expr: ExprId,
+    pub arm: usize,
+}
+
+/// The arms taken by the `match` expressions of a body whose scrutinee is known at compile time,
+/// like a literal or a constant. See [`match_check::taken_arm`].
+pub fn taken_match_arms(db: &dyn HirDatabase, owner: DefWithBodyId) -> Vec<TakenMatchArm> {
+    let _p = profile::span("taken_match_arms");
+    let body = db.body(owner);
+    let infer = db.infer(owner);
+    let lowered_patterns = db.lowered_patterns(owner);
+    body.exprs
+        .iter()
+        .filter(|(_, expr)| matches!(expr, Expr::Match { .. }))
+        .filter_map(|(match_expr, _)| {
+            let arm = match_check::taken_arm::taken_arm(
+                db,
+                owner,
+                &body,
+                &infer,
+                &lowered_patterns,
+                match_expr,
+            )?;
+            Some(TakenMatchArm { match_expr, arm })
+        })
+        .collect()
+}
+
+/// An arm to check with [`check_match_arms`]: a pattern of the body, and its guard if it has one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MatchCheckArm {
+    pub pat: PatId,
+    pub guard: Option<ExprId>,
+}
+
+/// The exhaustiveness and reachability of a list of arms, see [`check_match_arms`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MatchCheckReport {
+    /// The patterns no arm matches, or nothing if the arms are exhaustive. They contain no
+    /// or-patterns, so that each of them can be rendered as an arm.
+    pub missing_patterns: Vec<WitnessPat>,
+    /// The reachability of each arm, in order.
+    pub arms: Vec<ArmReachability>,
+    /// Whether some arms expanded to too many alternatives and were checked as if their
+    /// or-patterns were wildcards: there may be more missing patterns, and those arms are
+    /// considered reachable.
+    pub is_approximate: bool,
+}
+
+/// Whether an arm checked by [`check_match_arms`] is reachable. Alternatives are identified by
+/// their syntax, which stays valid after the check and across edits elsewhere in the file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ArmReachability {
+    /// The arm is reachable, but these alternatives of its or-patterns aren't. Alternatives
+    /// without syntax, e.g. from a macro expansion, are left out.
+    Reachable {
+        unreachable_alternatives: Vec<InFile<AstPtr<ast::Pat>>>,
+    },
+    Unreachable,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MatchCheckError {
+    /// The type of the scrutinee isn't known.
+    UnknownType,
+    /// The pattern doesn't have the type of the scrutinee, or it can't be lowered, e.g. because a
+    /// path in it doesn't resolve.
+    InvalidPattern(PatId),
+    /// Checking the arms takes more work than the `pattern_complexity` limit allows.
+    TooComplex,
+}
+
+/// Checks `arms`, patterns of the body of `owner`, against a scrutinee of type `scrut_ty` like the
+/// arms of a `match`: whether they are exhaustive, and which of them are reachable. The arms
+/// don't have to be those of a `match` of the body, e.g. the pattern of a `let` can be checked on
+/// its own.
+pub fn check_match_arms(
+    db: &dyn HirDatabase,
+    owner: DefWithBodyId,
+    scrut_ty: &Ty,
+    arms: &[MatchCheckArm],
+) -> Result<MatchCheckReport, MatchCheckError> {
+    let _p = profile::span("check_match_arms");
+    if scrut_ty.is_unknown() {
+        return Err(MatchCheckError::UnknownType);
+    }
+    let (body, source_map) = db.body_with_source_map(owner);
+    let infer = db.infer(owner);
+    let pattern_arena = Arena::new();
+    let row_arena = Arena::new();
+    let cx = MatchCheckCtx::new(owner.module(db.upcast()), owner, db, &pattern_arena, &row_arena);
+
+    // The patterns of the matches, parameters and `let`s of the body are already lowered.
+    let lowered = db.lowered_patterns(owner);
+    let mut pat_ctxt = PatCtxt::new(db, owner, &infer, &body);
+    let m_arms = arms
+        .iter()
+        .map(|arm| {
+            if !arm_matches_scrutinee(db, owner, &infer, &body, scrut_ty, arm.pat) {
+                return Err(MatchCheckError::InvalidPattern(arm.pat));
+            }
+            let (pat, has_errors) = match lowered.pats.get(&arm.pat) {
+                Some((pat, errors)) => {
+                    (Cow::Borrowed(pat), errors.iter().any(PatternError::lowers_to_error))
+                }
+                None => {
+                    let pat = pat_ctxt.lower_pattern(arm.pat);
+                    let has_errors = pat_ctxt.errors.drain(..).any(|error| error.lowers_to_error());
+                    (Cow::Owned(pat), has_errors)
+                }
+            };
+            // Arity mismatches and unknown fields don't prevent checking, but patterns lowered as
+            // errors cover nothing, so their own reachability would be meaningless.
+            if has_errors {
+                return Err(MatchCheckError::InvalidPattern(arm.pat));
+            }
+            let pat = &*cx.pattern_arena.alloc(DeconstructedPat::from_pat(&cx, &pat));
+            Ok(match_check::MatchArm { pat, guard: arm.guard })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let report = cx
+        .compute_match_usefulness(&m_arms, scrut_ty)
+        .map_err(|TooComplex| MatchCheckError::TooComplex)?;
+    let self_enum = self_enum(db, owner);
+    let missing_patterns = report
+        .non_exhaustiveness_witnesses
+        .iter()
+        .flat_map(|witness| witness.expand_or_pats(cx.pattern_arena))
+        .map(|witness| WitnessPat::new(witness.to_pat(&cx), self_enum))
+        .collect();
+    let arms = report
+        .arm_usefulness
+        .into_iter()
+        .map(|(_, reachability)| match reachability {
+            Reachability::Reachable(alternatives) => {
+                let unreachable_alternatives = alternatives
+                    .into_iter()
+                    .filter_map(|pat| {
+                        let InFile { file_id, value } = source_map.pat_syntax(pat).ok()?;
+                        Some(InFile::new(file_id, value.left()?))
+                    })
+                    .collect();
+                ArmReachability::Reachable { unreachable_alternatives }
+            }
+            Reachability::Unreachable => ArmReachability::Unreachable,
+        })
+        .collect();
+    Ok(MatchCheckReport { missing_patterns, arms, is_approximate: report.is_approximate })
+}
+
+/// Whether `pat`, a pattern of the body of `owner`, is refutable, i.e. doesn't match all the
+/// values of its type, as checked by [`check_match_arms`].
+pub fn is_refutable(
+    db: &dyn HirDatabase,
+    owner: DefWithBodyId,
+    pat: PatId,
+) -> Result<bool, MatchCheckError> {
+    let infer = db.infer(owner);
+    let Some(ty) = infer.type_of_pat.get(pat) else {
+        return Err(MatchCheckError::UnknownType);
+    };
+    let report = check_match_arms(db, owner, ty, &[MatchCheckArm { pat, guard: None }])?;
+    Ok(!report.missing_patterns.is_empty())
+}
+
+pub(crate) fn body_validation_diagnostics_query(
+    db: &dyn HirDatabase,
+    owner: DefWithBodyId,
+) -> Arc<[BodyValidationDiagnostic]> {
+    BodyValidationDiagnostic::collect(db, owner).into()
+}
+
 struct ExprValidator {
     owner: DefWithBodyId,
     infer: Arc<InferenceResult>,
     pub(super) diagnostics: Vec<BodyValidationDiagnostic>,
+    unreachable_patterns: Vec<UnreachablePattern>,
 }
 
 impl ExprValidator {
     fn new(owner: DefWithBodyId, infer: Arc<InferenceResult>) -> ExprValidator {
-        ExprValidator { owner, infer, diagnostics: Vec::new() }
+        ExprValidator { owner, infer, diagnostics: Vec::new(), unreachable_patterns: Vec::new() }
     }
 
     fn validate_body(&mut self, db: &dyn HirDatabase) {
         let body = db.body(self.owner);
         let mut filter_map_next_checker = None;
 
+        self.validate_match_patterns(db, &body);
+
         for (id, expr) in body.exprs.iter() {
             if let Some((variant, missed_fields, true)) =
                 record_literal_missing_fields(db, &self.infer, id, expr)
@@ -83,9 +331,6 @@ fn validate_body(&mut self, db: &dyn HirDatabase) {
             }
 
             match expr {
-                Expr::Match { expr, arms } => {
-                    self.validate_match(id, *expr, arms, db);
-                }
                 Expr::Call { .. } | Expr::MethodCall { .. } => {
                     self.validate_call(db, id, expr, &mut filter_map_next_checker);
                 }
@@ -145,56 +390,66 @@ fn validate_call(
         };
     }
 
+    /// Runs all the checks on the patterns of the `match` expressions of the body: the patterns
+    /// are lowered once for the whole body, and then each `match` is checked for exhaustiveness
+    /// in a single traversal. The errors in the patterns of parameters and `let`s are reported
+    /// too.
+    fn validate_match_patterns(&mut self, db: &dyn HirDatabase, body: &Body) {
+        if is_match_check_skipped(db, self.owner) {
+            return;
+        }
+        let _p = profile::span("validate_match_patterns");
+        let lowered_patterns = db.lowered_patterns(self.owner);
+
+        for &match_expr in &lowered_patterns.matches {
+            self.validate_match(db, body, &lowered_patterns, match_expr);
+        }
+        for pat in &lowered_patterns.let_pats {
+            self.report_pattern_errors(db, &lowered_patterns.pats[pat].1);
+        }
+    }
+
     fn validate_match(
         &mut self,
-        match_expr: ExprId,
-        scrutinee_expr: ExprId,
-        arms: &[MatchArm],
         db: &dyn HirDatabase,
+        body: &Body,
+        lowered_patterns: &LoweredPatterns,
+        match_expr: ExprId,
     ) {
-        let body = db.body(self.owner);
+        let Expr::Match { expr: scrutinee_expr, arms } = &body[match_expr] else { return };
 
-        let scrut_ty = &self.infer[scrutinee_expr];
+        // Arms are checked against the scrutinee as it is after coercions, e.g. `&[T]` rather
+        // than `&Vec<T>`.
+        let scrut_ty = self.infer.type_of_expr_with_adjust(*scrutinee_expr).clone();
+        let scrut_ty = &scrut_ty;
         if scrut_ty.is_unknown() {
             return;
         }
 
         let pattern_arena = Arena::new();
-        let cx = MatchCheckCtx::new(self.owner.module(db.upcast()), self.owner, db, &pattern_arena);
+        let row_arena = Arena::new();
+        let cx = MatchCheckCtx::new(
+            self.owner.module(db.upcast()),
+            self.owner,
+            db,
+            &pattern_arena,
+            &row_arena,
+        );
 
         let mut m_arms = Vec::with_capacity(arms.len());
-        let mut has_lowering_errors = false;
+        // Whether the pattern of each arm has parts that failed to lower.
+        let mut arm_has_errors = Vec::with_capacity(arms.len());
         for arm in arms {
-            if let Some(pat_ty) = self.infer.type_of_pat.get(arm.pat) {
-                // We only include patterns whose type matches the type
-                // of the scrutinee expression. If we had an InvalidMatchArmPattern
-                // diagnostic or similar we could raise that in an else
-                // block here.
-                //
-                // When comparing the types, we also have to consider that rustc
-                // will automatically de-reference the scrutinee expression type if
-                // necessary.
-                //
-                // FIXME we should use the type checker for this.
-                if (pat_ty == scrut_ty
-                    || scrut_ty
-                        .as_reference()
-                        .map(|(match_expr_ty, ..)| match_expr_ty == pat_ty)
-                        .unwrap_or(false))
-                    && types_of_subpatterns_do_match(arm.pat, &body, &self.infer)
-                {
-                    // If we had a NotUsefulMatchArm diagnostic, we could
-                    // check the usefulness of each pattern as we added it
-                    // to the matrix here.
-                    let m_arm = match_check::MatchArm {
-                        pat: self.lower_pattern(&cx, arm.pat, db, &body, &mut has_lowering_errors),
-                        has_guard: arm.guard.is_some(),
-                    };
-                    m_arms.push(m_arm);
-                    if !has_lowering_errors {
-                        continue;
-                    }
-                }
+            // If we had an InvalidMatchArmPattern diagnostic or similar we could raise that when
+            // the pattern doesn't match the scrutinee.
+            if arm_matches_scrutinee(db, self.owner, &self.infer, body, scrut_ty, arm.pat) {
+                // If we had a NotUsefulMatchArm diagnostic, we could
+                // check the usefulness of each pattern as we added it
+                // to the matrix here.
+                let (pat, has_errors) = self.lower_pattern(&cx, lowered_patterns, arm.pat);
+                m_arms.push(match_check::MatchArm { pat, guard: arm.guard });
+                arm_has_errors.push(has_errors);
+                continue;
             }
 
             // If we can't resolve the type of a pattern, or the pattern type doesn't
@@ -205,10 +460,53 @@ fn validate_match(
             return;
         }
 
-        let report = compute_match_usefulness(&cx, &m_arms, scrut_ty);
+        let report = match cx.compute_match_usefulness(&m_arms, scrut_ty) {
+            Ok(report) => report,
+            Err(TooComplex) => {
+                self.diagnostics
+                    .push(BodyValidationDiagnostic::MatchCheckTooComplex { match_expr });
+                return;
+            }
+        };
+
+        if let Some(stats) = &report.stats {
+            tracing::info!("match check stats for {match_expr:?} in {:?}: {stats:?}", self.owner);
+        }
+
+        if report.is_approximate {
+            // Some arms were checked as covering more than they do. This can hide missing arms,
+            // but the witnesses we found are still genuine.
+            self.diagnostics.push(BodyValidationDiagnostic::MatchCheckApproximated { match_expr });
+        }
+
+        for ((arm, reachability), has_errors) in report.arm_usefulness.iter().zip(arm_has_errors) {
+            match reachability {
+                // Patterns that failed to lower cover nothing, so they are never useful, but
+                // that's not worth reporting on top of the errors about them.
+                Reachability::Unreachable if has_errors => {
+                    cov_mark::hit!(match_check_error_arm_not_reported);
+                }
+                Reachability::Unreachable => self.unreachable_patterns.extend(
+                    arm.pat
+                        .id()
+                        .map(|pat| UnreachablePattern { pat, kind: UnreachablePatternKind::Arm }),
+                ),
+                Reachability::Reachable(alternatives) => {
+                    self.unreachable_patterns.extend(alternatives.iter().map(|&pat| {
+                        UnreachablePattern { pat, kind: UnreachablePatternKind::OrAlternative }
+                    }))
+                }
+            }
+        }
 
-        // FIXME Report unreacheble arms
-        // https://github.com/rust-lang/rust/blob/f31622a50/compiler/rustc_mir_build/src/thir/pattern/check_match.rs#L200
+        for range in report.overlapping_range_endpoints {
+            let overlaps =
+                range.overlaps.iter().map(|(_, value)| value.display(db).to_string()).unique();
+            self.diagnostics.push(BodyValidationDiagnostic::OverlappingRangeEndpoints {
+                pat: range.pat,
+                overlaps: overlaps.collect(),
+            });
+        }
 
         let witnesses = report.non_exhaustiveness_witnesses;
         if !witnesses.is_empty() {
@@ -220,20 +518,46 @@ fn validate_match(
     }
 
     fn lower_pattern<'p>(
-        &self,
+        &mut self,
         cx: &MatchCheckCtx<'_, 'p>,
+        lowered_patterns: &LoweredPatterns,
         pat: PatId,
-        db: &dyn HirDatabase,
-        body: &Body,
-        have_errors: &mut bool,
-    ) -> &'p DeconstructedPat<'p> {
-        let mut patcx = match_check::PatCtxt::new(db, &self.infer, body);
-        let pattern = patcx.lower_pattern(pat);
-        let pattern = cx.pattern_arena.alloc(DeconstructedPat::from_pat(cx, &pattern));
-        if !patcx.errors.is_empty() {
-            *have_errors = true;
+    ) -> (&'p DeconstructedPat<'p>, bool) {
+        // All the arms accepted by `arm_matches_scrutinee` are lowered.
+        let (pattern, errors) = &lowered_patterns.pats[&pat];
+        let pattern = cx.pattern_arena.alloc(DeconstructedPat::from_pat(cx, pattern));
+        self.report_pattern_errors(cx.db, errors);
+        (pattern, errors.iter().any(PatternError::lowers_to_error))
+    }
+
+    fn report_pattern_errors(&mut self, db: &dyn HirDatabase, errors: &[PatternError]) {
+        for error in errors.iter().cloned() {
+            match error {
+                // Arity mismatches and unknown fields are lowered with wildcards in place of the
+                // offending fields, so the match can still be checked.
+                PatternError::MismatchedArgCount { pat, expected, found } => self.diagnostics.push(
+                    BodyValidationDiagnostic::MismatchedTupleStructPatArgCount {
+                        pat,
+                        expected,
+                        found,
+                    },
+                ),
+                PatternError::NoSuchFields { pat, fields } => self
+                    .diagnostics
+                    .push(BodyValidationDiagnostic::RecordPatNoSuchFields { pat, fields }),
+                // Out-of-range literals and empty ranges are lowered as opaque patterns, which
+                // don't prevent checking the other arms.
+                PatternError::LiteralOutOfRange | PatternError::EmptyRange => {}
+                PatternError::FloatLiteral { expr } => {
+                    if !is_lint_allowed(db, self.owner, FLOAT_LITERAL_PATTERN_LINT) {
+                        self.diagnostics
+                            .push(BodyValidationDiagnostic::FloatLiteralPattern { literal: expr });
+                    }
+                }
+                // The other patterns are lowered as errors, which cover nothing.
+                _ => {}
+            }
         }
-        pattern
     }
 }
 
@@ -356,6 +680,189 @@ pub fn record_pattern_missing_fields(
     Some((variant_def, missed_fields, exhaustive))
 }
 
+pub(crate) fn lowered_patterns_query(
+    db: &dyn HirDatabase,
+    owner: DefWithBodyId,
+) -> Arc<LoweredPatterns> {
+    let _p = profile::span("lowered_patterns_query").detail(|| format!("{owner:?}"));
+    let body = db.body(owner);
+    let infer = db.infer(owner);
+    let mut lowered = LoweredPatterns::default();
+    let mut lower = |pat: PatId| {
+        let mut patcx = match_check::PatCtxt::new(db, owner, &infer, &body);
+        let pattern = patcx.lower_pattern(pat);
+        lowered.pats.insert(pat, (pattern, patcx.errors));
+    };
+    let mut let_pats = body.params.clone();
+    for (id, expr) in body.exprs.iter() {
+        match expr {
+            Expr::Match { expr: scrutinee_expr, arms } => {
+                lowered.matches.push(id);
+                let scrut_ty = infer.type_of_expr_with_adjust(*scrutinee_expr);
+                for arm in arms.iter() {
+                    if arm_matches_scrutinee(db, owner, &infer, &body, scrut_ty, arm.pat) {
+                        lower(arm.pat);
+                    }
+                }
+            }
+            Expr::Let { pat, .. } => let_pats.push(*pat),
+            Expr::Closure { args, .. } => let_pats.extend(args.iter().copied()),
+            Expr::Block { statements, .. }
+            | Expr::TryBlock { statements, .. }
+            | Expr::Async { statements, .. }
+            | Expr::Const { statements, .. }
+            | Expr::Unsafe { statements, .. } => {
+                let_pats.extend(statements.iter().filter_map(|stmt| match stmt {
+                    Statement::Let { pat, .. } => Some(*pat),
+                    _ => None,
+                }));
+            }
+            _ => {}
+        }
+    }
+    // Patterns that don't have the type of what they bind aren't lowered, like mismatched arms.
+    let_pats.retain(|&pat| {
+        infer.type_of_pat.get(pat).is_some() && types_of_subpatterns_do_match(pat, &body, &infer)
+    });
+    for &pat in &let_pats {
+        lower(pat);
+    }
+    lowered.let_pats = let_pats;
+    Arc::new(lowered)
+}
+
+/// Renders the patterns of the arms of `match_expr` as they were lowered for match checking, with
+/// the type of each node and the errors found while lowering them. For debugging.
+pub fn pretty_print_match_patterns(
+    db: &dyn HirDatabase,
+    owner: DefWithBodyId,
+    match_expr: ExprId,
+) -> String {
+    let body = db.body(owner);
+    let Expr::Match { arms, .. } = &body[match_expr] else {
+        return "not a match expression".to_owned();
+    };
+    let lowered = db.lowered_patterns(owner);
+    let mut buf = String::new();
+    if is_match_check_skipped(db, owner) {
+        buf.push_str("match checking is disabled by `#[rust_analyzer::skip_match_check]`\n");
+    }
+    for (idx, arm) in arms.iter().enumerate() {
+        format_to!(buf, "arm {idx}{}", if arm.guard.is_some() { " (guarded)" } else { "" });
+        match lowered.pats.get(&arm.pat) {
+            Some((pat, errors)) => {
+                format_to!(buf, ": {}\n", pat.display(db));
+                pat.pretty_print(db, "", 4, &mut buf);
+                if !errors.is_empty() {
+                    format_to!(buf, "    errors: {errors:?}\n");
+                }
+            }
+            None => buf.push_str(": not lowered, its type doesn't match the scrutinee's\n"),
+        }
+    }
+    buf
+}
+
+/// The patterns of the arms to add to `match_expr` to make it exhaustive, with bindings named
+/// after the fields, e.g. `Variant { id, name }` or `Ok(value)`. `None` if the match can't be
+/// checked, is too complex to check or misses too many arms. This checks the match again for each
+/// round of arms added, so it's only computed when the arms are actually added rather than with
+/// the `missing-match-arm` diagnostic.
+pub fn fill_match_arms(
+    db: &dyn HirDatabase,
+    owner: DefWithBodyId,
+    match_expr: ExprId,
+) -> Option<Vec<String>> {
+    let body = db.body(owner);
+    let Expr::Match { expr: scrutinee_expr, arms } = &body[match_expr] else { return None };
+    let infer = db.infer(owner);
+    let scrut_ty = infer.type_of_expr_with_adjust(*scrutinee_expr);
+    if scrut_ty.is_unknown() {
+        return None;
+    }
+    let lowered = db.lowered_patterns(owner);
+    let pattern_arena = Arena::new();
+    let row_arena = Arena::new();
+    let cx = MatchCheckCtx::new(owner.module(db.upcast()), owner, db, &pattern_arena, &row_arena);
+    // Like `validate_match`, this gives up on matches with arms whose type doesn't match the
+    // scrutinee's, which aren't lowered.
+    let m_arms = arms
+        .iter()
+        .map(|arm| {
+            let (pat, _) = lowered.pats.get(&arm.pat)?;
+            let pat = &*cx.pattern_arena.alloc(DeconstructedPat::from_pat(&cx, pat));
+            Some(match_check::MatchArm { pat, guard: arm.guard })
+        })
+        .collect::<Option<Vec<_>>>()?;
+    let report = cx.compute_match_usefulness(&m_arms, scrut_ty).ok()?;
+    fill_arms(&cx, &m_arms, scrut_ty, &report.non_exhaustiveness_witnesses)
+}
+
+/// The decision tree `match_expr` can be compiled to, exported in `format` for visualization. See
+/// [`match_check::decision_tree`].
+pub fn match_decision_tree(
+    db: &dyn HirDatabase,
+    owner: DefWithBodyId,
+    match_expr: ExprId,
+    format: DecisionTreeFormat,
+) -> String {
+    let body = db.body(owner);
+    let Expr::Match { expr: scrutinee_expr, arms } = &body[match_expr] else {
+        return "not a match expression".to_owned();
+    };
+    let infer = db.infer(owner);
+    if infer.type_of_expr_with_adjust(*scrutinee_expr).is_unknown() {
+        return "the type of the scrutinee is unknown".to_owned();
+    }
+    let lowered = db.lowered_patterns(owner);
+    let pattern_arena = Arena::new();
+    let row_arena = Arena::new();
+    let cx = MatchCheckCtx::new(owner.module(db.upcast()), owner, db, &pattern_arena, &row_arena);
+    // Arms whose type doesn't match the scrutinee's aren't lowered, and can't be reached.
+    let arms: Vec<_> = arms
+        .iter()
+        .enumerate()
+        .filter_map(|(index, arm)| {
+            let (pat, _) = lowered.pats.get(&arm.pat)?;
+            let pat = &*cx.pattern_arena.alloc(DeconstructedPat::from_pat(&cx, pat));
+            Some(DecisionTreeArm { index, pat, has_guard: arm.guard.is_some() })
+        })
+        .collect();
+    match DecisionTree::build(&cx, &arms) {
+        Some(tree) => tree.render(format),
+        None => "the decision tree is too large".to_owned(),
+    }
+}
+
+/// Whether the pattern of a match arm has the type of the scrutinee, in which case it can be
+/// lowered and checked.
+///
+/// When comparing the types, we also have to consider that rustc will automatically
+/// de-reference the scrutinee expression type if necessary.
+///
+/// FIXME we should use the type checker for this.
+fn arm_matches_scrutinee(
+    db: &dyn HirDatabase,
+    owner: DefWithBodyId,
+    infer: &InferenceResult,
+    body: &Body,
+    scrut_ty: &Ty,
+    pat: PatId,
+) -> bool {
+    let Some(pat_ty) = infer.type_of_pat.get(pat) else { return false };
+    // Patterns on an opaque type this body defines are typed with its hidden type.
+    let revealed_ty = |ty: &Ty| reveal_opaque_ty(db, owner, ty.clone());
+    (pat_ty == scrut_ty
+        || *pat_ty == revealed_ty(scrut_ty)
+        || scrut_ty
+            .as_reference()
+            .map(|(match_expr_ty, ..)| {
+                match_expr_ty == pat_ty || *pat_ty == revealed_ty(match_expr_ty)
+            })
+            .unwrap_or(false))
+        && types_of_subpatterns_do_match(pat, body, infer)
+}
+
 fn types_of_subpatterns_do_match(pat: PatId, body: &Body, infer: &InferenceResult) -> bool {
     fn walk(pat: PatId, body: &Body, infer: &InferenceResult, has_type_mismatches: &mut bool) {
         match infer.type_mismatch_for_pat(pat) {
@@ -371,41 +878,157 @@ fn walk(pat: PatId, body: &Body, infer: &InferenceResult, has_type_mismatches: &
     !has_type_mismatches
 }
 
-fn missing_match_arms<'p>(
+/// Whether match checking was turned off for `owner` with `#[rust_analyzer::skip_match_check]`,
+/// either on the item itself, on its impl or trait, or on one of its enclosing modules.
+fn is_match_check_skipped(db: &dyn HirDatabase, owner: DefWithBodyId) -> bool {
+    any_enclosing_attrs(db, owner, |attrs| attrs.has_skip_match_check())
+}
+
+/// Whether the lint `lint` is allowed in `owner`, like `is_match_check_skipped`.
+fn is_lint_allowed(db: &dyn HirDatabase, owner: DefWithBodyId, lint: &str) -> bool {
+    any_enclosing_attrs(db, owner, |attrs| attrs.allows_lint(lint))
+}
+
+/// Whether `f` holds for the attributes of `owner`, of its impl, trait or enum, or of one of its
+/// enclosing modules.
+fn any_enclosing_attrs(
+    db: &dyn HirDatabase,
+    owner: DefWithBodyId,
+    f: impl Fn(&Attrs) -> bool,
+) -> bool {
+    let (item, container): (AttrDefId, Option<AttrDefId>) = match owner {
+        DefWithBodyId::FunctionId(it) => (it.into(), Some(it.lookup(db.upcast()).container.into())),
+        DefWithBodyId::ConstId(it) => (it.into(), Some(it.lookup(db.upcast()).container.into())),
+        DefWithBodyId::StaticId(it) => (it.into(), None),
+        DefWithBodyId::VariantId(it) => (it.into(), Some(AdtId::from(it.parent).into())),
+    };
+    if iter::once(item).chain(container).any(|def| f(&db.attrs(def))) {
+        return true;
+    }
+    iter::successors(Some(owner.module(db.upcast())), |module| {
+        module.containing_module(db.upcast())
+    })
+    .any(|module| f(&db.attrs(module.into())))
+}
+
+/// The enum that `Self` refers to in `owner`, if `owner` is an item of an inherent or trait impl
+/// for an enum.
+fn self_enum(db: &dyn HirDatabase, owner: DefWithBodyId) -> Option<EnumId> {
+    let container = match owner {
+        DefWithBodyId::FunctionId(it) => it.lookup(db.upcast()).container,
+        DefWithBodyId::ConstId(it) => it.lookup(db.upcast()).container,
+        _ => return None,
+    };
+    let ItemContainerId::ImplId(impl_id) = container else { return None };
+    match db.impl_self_ty(impl_id).skip_binders().as_adt()? {
+        (AdtId::EnumId(enum_id), _) => Some(enum_id),
+        _ => None,
+    }
+}
+
+pub(super) fn missing_match_arms<'p>(
     cx: &MatchCheckCtx<'_, 'p>,
     scrut_ty: &Ty,
     witnesses: Vec<DeconstructedPat<'p>>,
     arms: &[MatchArm],
 ) -> String {
-    struct DisplayWitness<'a, 'p>(&'a DeconstructedPat<'p>, &'a MatchCheckCtx<'a, 'p>);
+    let _p =
+        profile::span("missing_match_arms").detail(|| format!("{} witnesses", witnesses.len()));
+    struct DisplayWitness<'a, 'p>(
+        &'a DeconstructedPat<'p>,
+        &'a MatchCheckCtx<'a, 'p>,
+        Option<EnumId>,
+    );
     impl fmt::Display for DisplayWitness<'_, '_> {
         fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-            let DisplayWitness(witness, cx) = *self;
+            let DisplayWitness(witness, cx, self_enum) = *self;
             let pat = witness.to_pat(cx);
-            write!(f, "{}", pat.display(cx.db))
+            let res = write!(f, "{}", pat.display_with_self_enum(self_enum).display(cx.db));
+            res
         }
     }
 
-    let non_empty_enum = match scrut_ty.as_adt() {
+    let non_empty_enum = match cx.normalize(scrut_ty.clone()).as_adt() {
         Some((AdtId::EnumId(e), _)) => !cx.db.enum_data(e).variants.is_empty(),
         _ => false,
     };
     if arms.is_empty() && !non_empty_enum {
         format!("type `{}` is non-empty", scrut_ty.display(cx.db))
     } else {
-        let pat_display = |witness| DisplayWitness(witness, cx);
+        let self_enum = self_enum(cx.db, cx.body);
+        let pat_display = |witness| DisplayWitness(witness, cx, self_enum);
         const LIMIT: usize = 3;
+        const GROUPING_LIMIT: usize = 64;
+        // The witnesses may be or-patterns standing for many witnesses each. Only expand those we
+        // display, unless there are few enough to group those of tuples by their first field.
+        let count = witnesses.iter().map(|w| w.or_expansion_count()).fold(0, usize::saturating_add);
+        let (witnesses, count) = if count <= GROUPING_LIMIT {
+            let witnesses =
+                witnesses.iter().flat_map(|w| w.expand_or_pats(cx.pattern_arena)).collect();
+            let witnesses = DeconstructedPat::group_by_first_field(cx.pattern_arena, witnesses);
+            let count = witnesses.len();
+            (witnesses, count)
+        } else {
+            (
+                witnesses
+                    .iter()
+                    .flat_map(|w| w.expand_or_pats(cx.pattern_arena))
+                    .take(LIMIT + 1)
+                    .collect(),
+                count,
+            )
+        };
         match &*witnesses {
-            [witness] => format!("`{}` not covered", pat_display(witness)),
+            [witness] => match witness.min_slice_len() {
+                Some(len) if len > 0 => format!(
+                    "`{}` not covered: slices of length {len} or more",
+                    pat_display(witness)
+                ),
+                _ => format!("`{}` not covered", pat_display(witness)),
+            },
             [head @ .., tail] if head.len() < LIMIT => {
                 let head = head.iter().map(pat_display);
                 format!("`{}` and `{}` not covered", head.format("`, `"), pat_display(tail))
             }
             _ => {
-                let (head, tail) = witnesses.split_at(LIMIT);
-                let head = head.iter().map(pat_display);
-                format!("`{}` and {} more not covered", head.format("`, `"), tail.len())
+                let head = witnesses[..LIMIT].iter().map(pat_display);
+                format!("`{}` and {} more not covered", head.format("`, `"), count - LIMIT)
             }
         }
     }
 }
+
+/// Displays the patterns of the arms to add to `arms` to make the match exhaustive, with the
+/// wildcards in fields replaced by bindings, or returns `None` if there are too many arms to add
+/// or checking them is too complex. When some constructors are missing from a column, the
+/// `witnesses` only list those: `A | B` but not `C(_, false)` for `match x { C(_, true) => {} }`.
+/// So the match is checked again with the arms for the witnesses added, until it is exhaustive.
+fn fill_arms<'p>(
+    cx: &MatchCheckCtx<'_, 'p>,
+    arms: &[match_check::MatchArm<'p>],
+    scrut_ty: &Ty,
+    witnesses: &[DeconstructedPat<'p>],
+) -> Option<Vec<String>> {
+    const LIMIT: usize = 64;
+    let self_enum = self_enum(cx.db, cx.body);
+    let mut arms = arms.to_vec();
+    let mut fill_arms = Vec::new();
+    let mut remaining;
+    let mut witnesses = witnesses;
+    while !witnesses.is_empty() {
+        let count = witnesses
+            .iter()
+            .map(|w| w.or_expansion_count())
+            .fold(fill_arms.len(), usize::saturating_add);
+        if count > LIMIT {
+            return None;
+        }
+        for witness in witnesses.iter().flat_map(|w| w.expand_or_pats(cx.pattern_arena)) {
+            fill_arms.push(witness.to_pat(cx).display_as_arm(self_enum).display(cx.db).to_string());
+            arms.push(match_check::MatchArm { pat: cx.pattern_arena.alloc(witness), guard: None });
+        }
+        remaining = cx.compute_match_usefulness(&arms, scrut_ty).ok()?.non_exhaustiveness_witnesses;
+        witnesses = &remaining;
+    }
+    Some(fill_arms)
+}
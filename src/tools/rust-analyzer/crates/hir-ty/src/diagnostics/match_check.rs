@@ -7,57 +7,205 @@
 
 mod pat_util;
 
+pub(crate) mod decision_tree;
 pub(crate) mod deconstruct_pat;
+pub(crate) mod pat_analysis;
+pub(crate) mod taken_arm;
 pub(crate) mod usefulness;
 
-use chalk_ir::Mutability;
+use std::cell::{Cell, RefCell};
+
+use chalk_ir::{IntTy, Mutability, Scalar, UintTy};
 use hir_def::{
-    adt::VariantData, body::Body, expr::PatId, AdtId, EnumVariantId, LocalFieldId, VariantId,
+    adt::VariantData,
+    body::Body,
+    expr::{ExprId, Literal, PatId, RangeOp},
+    find_path,
+    item_scope::ItemInNs,
+    resolver::{HasResolver, ValueNs},
+    AdtId, AssocItemId, ConstId, DefWithBodyId, EnumId, EnumVariantId, LocalFieldId, ModuleId,
+    VariantId,
 };
 use hir_expand::name::Name;
-use stdx::{always, never};
+use rustc_hash::{FxHashMap, FxHashSet};
+use stdx::{always, format_to, never, to_lower_snake_case};
+use syntax::SyntaxKind;
 
 use crate::{
     db::HirDatabase,
-    display::{HirDisplay, HirDisplayError, HirFormatter},
+    display::{DisplaySourceCodeError, HirDisplay, HirDisplayError, HirFormatter},
     infer::BindingMode,
     lang_items::is_box,
-    InferenceResult, Interner, Substitution, Ty, TyExt, TyKind,
+    mir::pad16,
+    primitive, ConstScalar, InferenceResult, Interner, Substitution, Ty, TyExt, TyKind,
 };
 
 use self::pat_util::EnumerateAndAdjustIterator;
 
 pub(crate) use self::usefulness::MatchArm;
 
-#[derive(Clone, Debug)]
+/// Runs `f`, first growing the stack if little of it is left. Patterns are walked recursively
+/// all through match checking, and `is_useful` recurses once per column, so huge or deeply nested
+/// patterns would otherwise overflow the stack. Like rustc's `ensure_sufficient_stack`.
+pub(crate) fn ensure_sufficient_stack<R>(f: impl FnOnce() -> R) -> R {
+    const RED_ZONE: usize = 100 * 1024;
+    const STACK_PER_RECURSION: usize = 1024 * 1024;
+    stacker::maybe_grow(RED_ZONE, STACK_PER_RECURSION, f)
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub(crate) enum PatternError {
     Unimplemented,
     UnexpectedType,
     UnresolvedVariant,
-    MissingField,
-    ExtraFields,
+    /// A record pattern naming fields its variant doesn't have. The pattern is still lowered, with
+    /// the unknown fields left out.
+    NoSuchFields {
+        pat: PatId,
+        fields: Vec<Name>,
+    },
+    /// A tuple or tuple struct pattern with a number of subpatterns that doesn't match the arity
+    /// of its type. The pattern is still lowered, with its fields filled up with wildcards.
+    MismatchedArgCount {
+        pat: PatId,
+        expected: usize,
+        found: usize,
+    },
+    /// An integer literal pattern whose value doesn't fit in its type, e.g. `300` for a `u8`. The
+    /// pattern is lowered as an opaque one, which covers no value we know of, so the rest of the
+    /// match can still be checked.
+    LiteralOutOfRange,
+    /// A range pattern that contains no value, like `5..=1` or `0..0`. It is lowered as an opaque
+    /// pattern, like an out-of-range literal.
+    EmptyRange,
+    /// A floating-point literal used as a pattern or as the endpoint of a range pattern, which is
+    /// linted against. Matching on floats can't be checked for exhaustiveness anyway, so the
+    /// pattern is lowered as an opaque one.
+    FloatLiteral {
+        expr: ExprId,
+    },
+}
+
+impl PatternError {
+    /// Whether the pattern with this error was lowered as a [`PatKind::Error`], which covers
+    /// nothing: the arm can't be reported as unreachable.
+    pub(crate) fn lowers_to_error(&self) -> bool {
+        matches!(
+            self,
+            PatternError::Unimplemented
+                | PatternError::UnexpectedType
+                | PatternError::UnresolvedVariant
+        )
+    }
 }
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub(crate) struct FieldPat {
     pub(crate) field: LocalFieldId,
     pub(crate) pattern: Pat,
 }
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub(crate) struct Pat {
     pub(crate) ty: Ty,
     pub(crate) kind: Box<PatKind>,
+    /// The pattern of the body this was lowered from. `None` for the patterns built by match
+    /// checking, and for the dereferences added by match ergonomics around a lowered pattern.
+    pub(crate) id: Option<PatId>,
+}
+
+impl Pat {
+    pub(crate) fn is_wildcard(&self) -> bool {
+        matches!(*self.kind, PatKind::Binding { subpattern: None, .. } | PatKind::Wild)
+    }
+
+    /// Writes the tree of the pattern with the type of each node, one node per line, for
+    /// debugging. `label` says where the pattern is in its parent.
+    pub(crate) fn pretty_print(
+        &self,
+        db: &dyn HirDatabase,
+        label: &str,
+        indent: usize,
+        buf: &mut String,
+    ) {
+        let node = match &*self.kind {
+            PatKind::Wild => "_".to_owned(),
+            PatKind::Error => "<error>".to_owned(),
+            PatKind::Opaque => "<opaque>".to_owned(),
+            PatKind::Binding { name, mode, .. } => format!("binding {name} ({mode:?})"),
+            PatKind::Variant { enum_variant, .. } => format!(
+                "variant {}",
+                db.enum_data(enum_variant.parent).variants[enum_variant.local_id].name
+            ),
+            PatKind::Leaf { .. } => "leaf".to_owned(),
+            PatKind::Deref { .. } => "deref".to_owned(),
+            PatKind::LiteralBool { value } => value.to_string(),
+            PatKind::Str { value } => format!("{value:?}"),
+            PatKind::Range { lo, hi } => format!("range {lo:#x}..={hi:#x}"),
+            PatKind::Slice { .. } => "slice".to_owned(),
+            PatKind::Or { .. } => "or".to_owned(),
+        };
+        format_to!(buf, "{:indent$}{label}{node}: {}\n", "", self.ty.display(db));
+
+        let indent = indent + 4;
+        match &*self.kind {
+            PatKind::Wild
+            | PatKind::Error
+            | PatKind::Opaque
+            | PatKind::LiteralBool { .. }
+            | PatKind::Str { .. }
+            | PatKind::Range { .. } => {}
+            PatKind::Binding { subpattern, .. } => {
+                if let Some(subpattern) = subpattern {
+                    subpattern.pretty_print(db, "@ ", indent, buf);
+                }
+            }
+            PatKind::Variant { subpatterns, .. } | PatKind::Leaf { subpatterns } => {
+                for FieldPat { field, pattern } in subpatterns {
+                    let label = format!(".{}: ", u32::from(field.into_raw()));
+                    pattern.pretty_print(db, &label, indent, buf);
+                }
+            }
+            PatKind::Deref { subpattern } => subpattern.pretty_print(db, "", indent, buf),
+            PatKind::Slice { prefix, slice, suffix } => {
+                for pat in prefix {
+                    pat.pretty_print(db, "", indent, buf);
+                }
+                if let Some(slice) = slice {
+                    slice.pretty_print(db, "..: ", indent, buf);
+                }
+                for pat in suffix {
+                    pat.pretty_print(db, "", indent, buf);
+                }
+            }
+            PatKind::Or { pats } => {
+                for pat in pats {
+                    pat.pretty_print(db, "| ", indent, buf);
+                }
+            }
+        }
+    }
 }
 
 /// Close relative to `rustc_mir_build::thir::pattern::PatKind`
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub(crate) enum PatKind {
     Wild,
 
+    /// A pattern that failed to lower, e.g. because of an unresolved path or an unsupported
+    /// literal. Like [`PatKind::Opaque`], it covers nothing, so the arms after it are still
+    /// checked, but it is never reported as unreachable itself.
+    Error,
+
+    /// A pattern matching values the algorithm can't see, like an out-of-range literal or a
+    /// float literal. It covers no other pattern, so the rest of the match is still checked.
+    Opaque,
+
     /// `x`, `ref x`, `x @ P`, etc.
     Binding {
         name: Name,
+        /// The binding mode, including modes inferred by match ergonomics.
+        mode: BindingMode,
         subpattern: Option<Pat>,
     },
 
@@ -80,11 +228,34 @@ pub(crate) enum PatKind {
         subpattern: Pat,
     },
 
-    // FIXME: for now, only bool literals are implemented
+    // FIXME: for now, only bool, integer and string literals are implemented
     LiteralBool {
         value: bool,
     },
 
+    /// A string, the value of a string literal or of a `&str` constant. Its type is `str`: like
+    /// in rustc, `&str` patterns are lowered as a `Deref` pattern of a `Str` pattern.
+    Str {
+        value: Box<str>,
+    },
+
+    /// An inclusive range of integers, like the literal `-1`, which is `-1..=-1`. The endpoints
+    /// are the bits of the values in two's complement, truncated to the width of the type, so
+    /// `-1i8` is `0xff`.
+    Range {
+        lo: u128,
+        hi: u128,
+    },
+
+    /// Matches against a slice or an array, checking the length and extracting elements.
+    /// Irrefutable when there is a slice pattern and both `prefix` and `suffix` are empty,
+    /// e.g., `&[ref xs @ ..]`.
+    Slice {
+        prefix: Vec<Pat>,
+        slice: Option<Pat>,
+        suffix: Vec<Pat>,
+    },
+
     /// An or-pattern, e.g. `p | q`.
     /// Invariant: `pats.len() >= 2`.
     Or {
@@ -92,16 +263,37 @@ pub(crate) enum PatKind {
     },
 }
 
+/// The patterns of the arms of the `match` expressions of a body, and the ones of its parameters
+/// and `let`s, lowered with [`PatCtxt`] along with the errors found while lowering them. See
+/// `HirDatabase::lowered_patterns`.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct LoweredPatterns {
+    pub(crate) pats: FxHashMap<PatId, (Pat, Vec<PatternError>)>,
+    /// The `match` expressions of the body, whose arms are in `pats` if they have the type of
+    /// their scrutinee.
+    pub(crate) matches: Vec<ExprId>,
+    /// The patterns of the parameters, closure parameters, `let` statements and `let`
+    /// expressions, which aren't checked for exhaustiveness but can have the same errors as the
+    /// arms.
+    pub(crate) let_pats: Vec<PatId>,
+}
+
 pub(crate) struct PatCtxt<'a> {
     db: &'a dyn HirDatabase,
+    owner: DefWithBodyId,
     infer: &'a InferenceResult,
     body: &'a Body,
     pub(crate) errors: Vec<PatternError>,
 }
 
 impl<'a> PatCtxt<'a> {
-    pub(crate) fn new(db: &'a dyn HirDatabase, infer: &'a InferenceResult, body: &'a Body) -> Self {
-        Self { db, infer, body, errors: Vec::new() }
+    pub(crate) fn new(
+        db: &'a dyn HirDatabase,
+        owner: DefWithBodyId,
+        infer: &'a InferenceResult,
+        body: &'a Body,
+    ) -> Self {
+        Self { db, owner, infer, body, errors: Vec::new() }
     }
 
     pub(crate) fn lower_pattern(&mut self, pat: PatId) -> Pat {
@@ -110,14 +302,22 @@ pub(crate) fn lower_pattern(&mut self, pat: PatId) -> Pat {
         // in a manner of `match_ergonomics_issue_9095` test.
         // Pattern adjustment is part of RFC 2005-match-ergonomics.
         // More info https://github.com/rust-lang/rust/issues/42640#issuecomment-313535089
-        let unadjusted_pat = self.lower_pattern_unadjusted(pat);
-        self.infer.pat_adjustments.get(&pat).map(|it| &**it).unwrap_or_default().iter().rev().fold(
-            unadjusted_pat,
-            |subpattern, ref_ty| Pat {
+        let unadjusted_pat = ensure_sufficient_stack(|| self.lower_pattern_unadjusted(pat));
+        let mut lowered = self
+            .infer
+            .pat_adjustments
+            .get(&pat)
+            .map(|it| &**it)
+            .unwrap_or_default()
+            .iter()
+            .rev()
+            .fold(unadjusted_pat, |subpattern, ref_ty| Pat {
                 ty: ref_ty.clone(),
                 kind: Box::new(PatKind::Deref { subpattern }),
-            },
-        )
+                id: None,
+            });
+        lowered.id = Some(pat);
+        lowered
     }
 
     fn lower_pattern_unadjusted(&mut self, pat: PatId) -> Pat {
@@ -127,7 +327,10 @@ fn lower_pattern_unadjusted(&mut self, pat: PatId) -> Pat {
         let kind = match self.body[pat] {
             hir_def::expr::Pat::Wild => PatKind::Wild,
 
-            hir_def::expr::Pat::Lit(expr) => self.lower_lit(expr),
+            hir_def::expr::Pat::Lit(expr) => {
+                ty = self.pattern_type_base(ty);
+                self.lower_lit(expr, ty)
+            }
 
             hir_def::expr::Pat::Path(ref path) => {
                 return self.lower_path(pat, path);
@@ -139,10 +342,10 @@ fn lower_pattern_unadjusted(&mut self, pat: PatId) -> Pat {
                     _ => {
                         never!("unexpected type for tuple pattern: {:?}", ty);
                         self.errors.push(PatternError::UnexpectedType);
-                        return Pat { ty: ty.clone(), kind: PatKind::Wild.into() };
+                        return Pat { ty: ty.clone(), kind: PatKind::Error.into(), id: None };
                     }
                 };
-                let subpatterns = self.lower_tuple_subpats(args, arity, ellipsis);
+                let subpatterns = self.lower_tuple_subpats(pat, args, arity, ellipsis);
                 PatKind::Leaf { subpatterns }
             }
 
@@ -154,63 +357,104 @@ fn lower_pattern_unadjusted(&mut self, pat: PatId) -> Pat {
                     (BindingMode::Ref(_), _) => {
                         never!("`ref {}` has wrong type {:?}", name, ty);
                         self.errors.push(PatternError::UnexpectedType);
-                        return Pat { ty: ty.clone(), kind: PatKind::Wild.into() };
+                        return Pat { ty: ty.clone(), kind: PatKind::Error.into(), id: None };
                     }
                     _ => (),
                 }
-                PatKind::Binding { name: name.clone(), subpattern: self.lower_opt_pattern(subpat) }
+                PatKind::Binding {
+                    name: name.clone(),
+                    mode: bm,
+                    subpattern: self.lower_opt_pattern(subpat),
+                }
             }
 
             hir_def::expr::Pat::TupleStruct { ref args, ellipsis, .. } if variant.is_some() => {
                 let expected_len = variant.unwrap().variant_data(self.db.upcast()).fields().len();
-                let subpatterns = self.lower_tuple_subpats(args, expected_len, ellipsis);
+                let subpatterns = self.lower_tuple_subpats(pat, args, expected_len, ellipsis);
                 self.lower_variant_or_leaf(pat, ty, subpatterns)
             }
 
             hir_def::expr::Pat::Record { ref args, .. } if variant.is_some() => {
                 let variant_data = variant.unwrap().variant_data(self.db.upcast());
+                let mut unknown_fields = Vec::new();
                 let subpatterns = args
                     .iter()
-                    .map(|field| {
+                    .filter_map(|field| {
                         // XXX(iDawer): field lookup is inefficient
-                        variant_data.field(&field.name).map(|lfield_id| FieldPat {
-                            field: lfield_id,
-                            pattern: self.lower_pattern(field.pat),
-                        })
+                        match variant_data.field(&field.name) {
+                            Some(lfield_id) => Some(FieldPat {
+                                field: lfield_id,
+                                pattern: self.lower_pattern(field.pat),
+                            }),
+                            None => {
+                                unknown_fields.push(field.name.clone());
+                                None
+                            }
+                        }
                     })
                     .collect();
-                match subpatterns {
-                    Some(subpatterns) => self.lower_variant_or_leaf(pat, ty, subpatterns),
-                    None => {
-                        self.errors.push(PatternError::MissingField);
-                        PatKind::Wild
-                    }
+                if !unknown_fields.is_empty() {
+                    self.errors.push(PatternError::NoSuchFields { pat, fields: unknown_fields });
                 }
+                // Fields omitted without `..` are filled with wildcards when deconstructing the
+                // pattern, and are reported separately as `RecordMissingFields`.
+                self.lower_variant_or_leaf(pat, ty, subpatterns)
             }
             hir_def::expr::Pat::TupleStruct { .. } | hir_def::expr::Pat::Record { .. } => {
                 self.errors.push(PatternError::UnresolvedVariant);
-                PatKind::Wild
+                PatKind::Error
+            }
+
+            hir_def::expr::Pat::Range { start, end, range_type } => {
+                ty = self.pattern_type_base(ty);
+                self.lower_range(start, end, range_type, ty)
             }
 
             hir_def::expr::Pat::Or(ref pats) => PatKind::Or { pats: self.lower_patterns(pats) },
 
+            hir_def::expr::Pat::Slice { ref prefix, slice, ref suffix } => {
+                if !matches!(ty.kind(Interner), TyKind::Array(..) | TyKind::Slice(..)) {
+                    never!("unexpected type for slice pattern: {:?}", ty);
+                    self.errors.push(PatternError::UnexpectedType);
+                    return Pat { ty: ty.clone(), kind: PatKind::Error.into(), id: None };
+                }
+                // The middle subpattern only binds the elements `..` skips over, so it never
+                // affects exhaustiveness.
+                let slice =
+                    slice.map(|_| Pat { ty: ty.clone(), kind: PatKind::Wild.into(), id: None });
+                PatKind::Slice {
+                    prefix: self.lower_patterns(prefix),
+                    slice,
+                    suffix: self.lower_patterns(suffix),
+                }
+            }
+
             _ => {
                 self.errors.push(PatternError::Unimplemented);
-                PatKind::Wild
+                PatKind::Error
             }
         };
 
-        Pat { ty: ty.clone(), kind: Box::new(kind) }
+        Pat { ty: ty.clone(), kind: Box::new(kind), id: None }
     }
 
     fn lower_tuple_subpats(
         &mut self,
+        pat: PatId,
         pats: &[PatId],
         expected_len: usize,
         ellipsis: Option<usize>,
     ) -> Vec<FieldPat> {
+        if pats.len() > expected_len || (ellipsis.is_none() && pats.len() < expected_len) {
+            self.errors.push(PatternError::MismatchedArgCount {
+                pat,
+                expected: expected_len,
+                found: pats.len(),
+            });
+        }
         if pats.len() > expected_len {
-            self.errors.push(PatternError::ExtraFields);
+            // We can't tell which subpatterns were meant for which fields, so all of them are
+            // treated as wildcards.
             return Vec::new();
         }
 
@@ -249,7 +493,7 @@ fn lower_variant_or_leaf(
                                 ty
                             );
                             self.errors.push(PatternError::UnexpectedType);
-                            return PatKind::Wild;
+                            return PatKind::Error;
                         }
                     };
                     PatKind::Variant { substs, enum_variant, subpatterns }
@@ -259,48 +503,471 @@ fn lower_variant_or_leaf(
             }
             None => {
                 self.errors.push(PatternError::UnresolvedVariant);
-                PatKind::Wild
+                PatKind::Error
             }
         };
         kind
     }
 
-    fn lower_path(&mut self, pat: PatId, _path: &hir_def::path::Path) -> Pat {
+    fn lower_path(&mut self, pat: PatId, path: &hir_def::path::Path) -> Pat {
         let ty = &self.infer[pat];
 
-        let pat_from_kind = |kind| Pat { ty: ty.clone(), kind: Box::new(kind) };
+        let pat_from_kind = |kind| Pat { ty: ty.clone(), kind: Box::new(kind), id: None };
 
-        match self.infer.variant_resolution_for_pat(pat) {
-            Some(_) => pat_from_kind(self.lower_variant_or_leaf(pat, ty, Vec::new())),
+        if self.infer.variant_resolution_for_pat(pat).is_some() {
+            return pat_from_kind(self.lower_variant_or_leaf(pat, ty, Vec::new()));
+        }
+        let kind = match self.resolve_const(pat, path) {
+            // FIXME: lower the constants of other types
+            Some(konst) => match str_pointee(ty).zip(const_str_value(self.db, konst)) {
+                Some((str_ty, value)) => deref_str_pat(str_ty, value),
+                None => {
+                    self.errors.push(PatternError::Unimplemented);
+                    PatKind::Error
+                }
+            },
             None => {
                 self.errors.push(PatternError::UnresolvedVariant);
-                pat_from_kind(PatKind::Wild)
+                PatKind::Error
             }
+        };
+        pat_from_kind(kind)
+    }
+
+    /// The constant a path pattern refers to. Like inference, this resolves the path in the scope
+    /// of the body rather than in the one of the pattern.
+    fn resolve_const(&self, pat: PatId, path: &hir_def::path::Path) -> Option<ConstId> {
+        match self.infer.assoc_resolutions_for_pat(pat) {
+            Some((AssocItemId::ConstId(it), _)) => Some(it),
+            Some(_) => None,
+            None => match self
+                .owner
+                .resolver(self.db.upcast())
+                .resolve_path_in_value_ns_fully(self.db.upcast(), path.mod_path())?
+            {
+                ValueNs::ConstId(it) => Some(it),
+                _ => None,
+            },
         }
     }
 
-    fn lower_lit(&mut self, expr: hir_def::expr::ExprId) -> PatKind {
+    /// The base type of `ty` if it is a pattern type like `u32 is 1..`, else `ty`. Literals and
+    /// ranges matching a pattern type are lowered with its base type, so that their values can
+    /// be read without a database; the pattern type only restricts the constructors of the
+    /// column, see `MatchCheckCtx::ctors_for_ty`.
+    fn pattern_type_base<'t>(&self, ty: &'t Ty) -> &'t Ty {
+        ty.as_pattern_type(self.db).map_or(ty, |(base, _)| base)
+    }
+
+    fn lower_lit(&mut self, expr: hir_def::expr::ExprId, ty: &Ty) -> PatKind {
         use hir_def::expr::{Expr, Literal::Bool};
 
-        match self.body[expr] {
-            Expr::Literal(Bool(value)) => PatKind::LiteralBool { value },
+        match &self.body[expr] {
+            &Expr::Literal(Bool(value)) => PatKind::LiteralBool { value },
+            Expr::Literal(Literal::Float(..)) => {
+                self.errors.push(PatternError::FloatLiteral { expr });
+                PatKind::Opaque
+            }
+            Expr::Literal(Literal::String(value)) => match str_pointee(ty) {
+                Some(str_ty) => deref_str_pat(str_ty, value.clone()),
+                None => {
+                    self.errors.push(PatternError::Unimplemented);
+                    PatKind::Error
+                }
+            },
+            Expr::Literal(lit @ (Literal::Int(..) | Literal::Uint(..))) => {
+                match IntWidth::of(ty).map(|width| width.literal_bits(lit)) {
+                    Some(Some(bits)) => PatKind::Range { lo: bits, hi: bits },
+                    Some(None) => {
+                        self.errors.push(PatternError::LiteralOutOfRange);
+                        PatKind::Opaque
+                    }
+                    // Literals of other types are type mismatches, which are reported on their own.
+                    None => {
+                        self.errors.push(PatternError::Unimplemented);
+                        PatKind::Error
+                    }
+                }
+            }
             _ => {
                 self.errors.push(PatternError::Unimplemented);
-                PatKind::Wild
+                PatKind::Error
+            }
+        }
+    }
+
+    /// Lowers an integer range pattern whose endpoints are literals. A missing endpoint is the
+    /// smallest or the largest value of the type.
+    fn lower_range(
+        &mut self,
+        start: Option<ExprId>,
+        end: Option<ExprId>,
+        range_type: RangeOp,
+        ty: &Ty,
+    ) -> PatKind {
+        use hir_def::expr::Expr;
+
+        if let TyKind::Scalar(Scalar::Float(_)) = ty.kind(Interner) {
+            let mut kind = PatKind::Opaque;
+            for endpoint in start.into_iter().chain(end) {
+                match self.body[endpoint] {
+                    Expr::Literal(Literal::Float(..)) => {
+                        self.errors.push(PatternError::FloatLiteral { expr: endpoint })
+                    }
+                    _ => {
+                        self.errors.push(PatternError::Unimplemented);
+                        kind = PatKind::Error;
+                    }
+                }
+            }
+            return kind;
+        }
+        let Some(width) = IntWidth::of(ty) else {
+            self.errors.push(PatternError::Unimplemented);
+            return PatKind::Error;
+        };
+        let bits = |endpoint: Option<ExprId>, default| {
+            let Some(endpoint) = endpoint else { return Ok(default) };
+            match &self.body[endpoint] {
+                Expr::Literal(lit @ (Literal::Int(..) | Literal::Uint(..))) => {
+                    width.literal_bits(lit).ok_or(PatternError::LiteralOutOfRange)
+                }
+                // FIXME: evaluate the constants used as endpoints
+                _ => Err(PatternError::Unimplemented),
+            }
+        };
+        let range = bits(start, width.min_bits()).and_then(|lo| {
+            let hi = bits(end, width.max_bits())?;
+            // Compare the values in the biased encoding, where the smallest value is `0`.
+            let bias = width.min_bits();
+            let (lo, hi) = (lo ^ bias, hi ^ bias);
+            let hi = match range_type {
+                RangeOp::Exclusive if end.is_some() => hi.checked_sub(1),
+                _ => Some(hi),
+            };
+            match hi {
+                Some(hi) if lo <= hi => Ok(PatKind::Range { lo: lo ^ bias, hi: hi ^ bias }),
+                _ => Err(PatternError::EmptyRange),
+            }
+        });
+        range.unwrap_or_else(|error| {
+            let kind = if error.lowers_to_error() { PatKind::Error } else { PatKind::Opaque };
+            self.errors.push(error);
+            kind
+        })
+    }
+}
+
+/// The `str` that `ty` refers to, if it is `&str`.
+fn str_pointee(ty: &Ty) -> Option<&Ty> {
+    match ty.kind(Interner) {
+        TyKind::Ref(_, _, pointee) if matches!(pointee.kind(Interner), TyKind::Str) => {
+            Some(pointee)
+        }
+        _ => None,
+    }
+}
+
+/// The pattern `&"value"`, for a string literal or constant of type `&str`.
+fn deref_str_pat(str_ty: &Ty, value: Box<str>) -> PatKind {
+    let subpattern = Pat { ty: str_ty.clone(), kind: Box::new(PatKind::Str { value }), id: None };
+    PatKind::Deref { subpattern }
+}
+
+/// The value of `konst`, a constant of type `&str`, or `None` if it can't be evaluated.
+pub(super) fn const_str_value(db: &dyn HirDatabase, konst: ConstId) -> Option<Box<str>> {
+    let konst = db.const_eval(konst).ok()?;
+    let chalk_ir::ConstValue::Concrete(c) = &konst.data(Interner).value else { return None };
+    let ConstScalar::Bytes(bytes, memory_map) = &c.interned else { return None };
+    // A `&str` is a pointer into the memory map followed by the length of the string.
+    let (addr, len) = bytes.split_at(bytes.len() / 2);
+    let addr = usize::try_from(u128::from_le_bytes(pad16(addr, false))).ok()?;
+    let len = usize::try_from(u128::from_le_bytes(pad16(len, false))).ok()?;
+    let bytes = memory_map.0.get(&addr)?.get(..len)?;
+    std::str::from_utf8(bytes).ok().map(Box::from)
+}
+
+/// The width and signedness of an integer type. `isize` and `usize` are taken to be 64 bits wide,
+/// the widest pointer size: their actual size depends on the target, so we never list their
+/// values, but a literal that doesn't fit in 64 bits is an error on any target.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct IntWidth {
+    pub(crate) bits: u32,
+    pub(crate) signed: bool,
+}
+
+impl IntWidth {
+    pub(crate) fn of(ty: &Ty) -> Option<IntWidth> {
+        let (bits, signed) = match ty.kind(Interner) {
+            TyKind::Scalar(Scalar::Int(int_ty)) => {
+                let bits = match int_ty {
+                    IntTy::I8 => 8,
+                    IntTy::I16 => 16,
+                    IntTy::I32 => 32,
+                    IntTy::I64 | IntTy::Isize => 64,
+                    IntTy::I128 => 128,
+                };
+                (bits, true)
+            }
+            TyKind::Scalar(Scalar::Uint(uint_ty)) => {
+                let bits = match uint_ty {
+                    UintTy::U8 => 8,
+                    UintTy::U16 => 16,
+                    UintTy::U32 => 32,
+                    UintTy::U64 | UintTy::Usize => 64,
+                    UintTy::U128 => 128,
+                };
+                (bits, false)
             }
+            _ => return None,
+        };
+        Some(IntWidth { bits, signed })
+    }
+
+    /// The bits of all the values of the type, e.g. `0xff` for `u8` and `i8`.
+    pub(crate) fn mask(self) -> u128 {
+        u128::MAX >> (128 - self.bits)
+    }
+
+    /// The bits of the smallest value of the type, e.g. `0x80` for `i8::MIN`.
+    pub(crate) fn min_bits(self) -> u128 {
+        if self.signed {
+            1 << (self.bits - 1)
+        } else {
+            0
+        }
+    }
+
+    /// The bits of the largest value of the type, e.g. `0x7f` for `i8::MAX`.
+    pub(crate) fn max_bits(self) -> u128 {
+        if self.signed {
+            self.mask() >> 1
+        } else {
+            self.mask()
+        }
+    }
+
+    /// The bits of the value of an integer literal, or `None` if the value doesn't fit.
+    pub(crate) fn literal_bits(self, lit: &Literal) -> Option<u128> {
+        let (min, max) = if self.signed {
+            (i128::MIN >> (128 - self.bits), self.max_bits())
+        } else {
+            (0, self.max_bits())
+        };
+        let (bits, fits) = match *lit {
+            Literal::Int(value, _) => {
+                (value as u128, min <= value && (value < 0 || value as u128 <= max))
+            }
+            Literal::Uint(value, _) => (value, value <= max),
+            _ => return None,
+        };
+        fits.then_some(bits & self.mask())
+    }
+
+    /// Writes the value with the given bits like rustc writes it in patterns: `i8::MIN`,
+    /// `-1_i8`, `u8::MAX`.
+    fn fmt_value(
+        self,
+        f: &mut HirFormatter<'_>,
+        ty: &Ty,
+        bits: u128,
+    ) -> Result<(), HirDisplayError> {
+        let name = match ty.kind(Interner) {
+            TyKind::Scalar(Scalar::Int(int_ty)) => primitive::int_ty_to_string(*int_ty),
+            TyKind::Scalar(Scalar::Uint(uint_ty)) => primitive::uint_ty_to_string(*uint_ty),
+            _ => return write!(f, "{bits}"),
+        };
+        if self.signed && bits == self.min_bits() {
+            write!(f, "{name}::MIN")
+        } else if bits == self.max_bits() {
+            write!(f, "{name}::MAX")
+        } else {
+            self.fmt_number(f, bits)?;
+            write!(f, "_{name}")
+        }
+    }
+
+    /// Writes the value with the given bits as a plain number, e.g. `-1` or `255`.
+    pub(crate) fn fmt_number(
+        self,
+        f: &mut HirFormatter<'_>,
+        bits: u128,
+    ) -> Result<(), HirDisplayError> {
+        if self.signed {
+            // Sign-extend the value from the width of the type.
+            let shift = 128 - self.bits;
+            write!(f, "{}", ((bits << shift) as i128) >> shift)
+        } else {
+            write!(f, "{bits}")
         }
     }
 }
 
 impl HirDisplay for Pat {
     fn hir_fmt(&self, f: &mut HirFormatter<'_>) -> Result<(), HirDisplayError> {
+        self.fmt_with(f, &PatDisplayOptions { self_enum: None, bindings: None, placeholders: None })
+    }
+}
+
+/// A pattern found by match checking, like a pattern that no arm of a `match` covers, which can
+/// be displayed for diagnostics or turned into source code for assists.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WitnessPat {
+    pat: Pat,
+    /// The enum whose variants are written `Self::Variant`, if the pattern is in its `impl`.
+    self_enum: Option<EnumId>,
+}
+
+/// How [`WitnessPat::to_source`] writes a pattern.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PatSourceOptions {
+    /// Write the wildcards in fields as bindings named after the fields, e.g.
+    /// `Variant { id, name }` or `Ok(value)`, to use the pattern as the pattern of a new arm.
+    pub bind_fields: bool,
+    /// Write wildcards and bindings as numbered snippet placeholders, e.g. `Some(${1:_})`.
+    pub placeholders: bool,
+}
+
+impl WitnessPat {
+    pub(crate) fn new(pat: Pat, self_enum: Option<EnumId>) -> WitnessPat {
+        WitnessPat { pat, self_enum }
+    }
+
+    /// Writes the pattern as source code in `module`: paths are qualified as needed to resolve
+    /// there, and or-patterns are parenthesized where they would otherwise not parse, e.g.
+    /// `&(A | B)`. Fails if an item of the pattern can't be named from `module`.
+    pub fn to_source(
+        &self,
+        db: &dyn HirDatabase,
+        module: ModuleId,
+        options: PatSourceOptions,
+    ) -> Result<String, DisplaySourceCodeError> {
+        let options = PatDisplayOptions {
+            self_enum: self.self_enum,
+            bindings: options.bind_fields.then(RefCell::default),
+            placeholders: options.placeholders.then(Cell::default),
+        };
+        WriteWith(|f| self.pat.fmt_with(f, &options)).display_source_code(db, module)
+    }
+}
+
+impl HirDisplay for WitnessPat {
+    fn hir_fmt(&self, f: &mut HirFormatter<'_>) -> Result<(), HirDisplayError> {
+        self.pat.display_with_self_enum(self.self_enum).hir_fmt(f)
+    }
+}
+
+/// How to display a pattern.
+struct PatDisplayOptions {
+    /// Variants of this enum are prefixed by `Self::`.
+    self_enum: Option<EnumId>,
+    /// The names of the bindings displayed so far, when wildcards in fields are displayed as
+    /// bindings.
+    bindings: Option<RefCell<FxHashSet<String>>>,
+    /// The number of the last snippet placeholder written, when wildcards and bindings are written
+    /// as placeholders.
+    placeholders: Option<Cell<usize>>,
+}
+
+impl PatDisplayOptions {
+    /// Returns the binding to display instead of `pat`, if it is a wildcard in a field and such
+    /// wildcards are displayed as bindings. Bindings are named after the `field` if it has a name,
+    /// and after the type of `pat` otherwise.
+    fn binding_for(&self, db: &dyn HirDatabase, pat: &Pat, field: Option<&Name>) -> Option<String> {
+        let bindings = self.bindings.as_ref()?;
+        if !matches!(*pat.kind, PatKind::Wild) {
+            return None;
+        }
+        let base = match field {
+            Some(field) => field.to_string(),
+            None => binding_name_for_ty(db, &pat.ty),
+        };
+        let mut bindings = bindings.borrow_mut();
+        let mut name = base.clone();
+        let mut suffix = 1;
+        while !bindings.insert(name.clone()) {
+            name = format!("{base}{suffix}");
+            suffix += 1;
+        }
+        Some(name)
+    }
+
+    /// Writes `text`, as the next snippet placeholder if placeholders are written.
+    fn write_placeholder(
+        &self,
+        f: &mut HirFormatter<'_>,
+        text: &str,
+    ) -> Result<(), HirDisplayError> {
+        match &self.placeholders {
+            Some(last) => {
+                last.set(last.get() + 1);
+                write!(f, "${{{}:{text}}}", last.get())
+            }
+            None => write!(f, "{text}"),
+        }
+    }
+}
+
+/// `foo_bar` for a value of type `FooBar` or `&FooBar`, and `value` for other types.
+fn binding_name_for_ty(db: &dyn HirDatabase, ty: &Ty) -> String {
+    let adt_name = match ty.strip_references().as_adt() {
+        Some((AdtId::StructId(id), _)) => db.struct_data(id).name.clone(),
+        Some((AdtId::EnumId(id), _)) => db.enum_data(id).name.clone(),
+        Some((AdtId::UnionId(id), _)) => db.union_data(id).name.clone(),
+        None => return "value".to_owned(),
+    };
+    let name = to_lower_snake_case(&adt_name.to_smol_str());
+    // E.g. `Box`.
+    if SyntaxKind::from_keyword(&name).is_some() {
+        return "value".to_owned();
+    }
+    name
+}
+
+impl Pat {
+    /// Displays the pattern as it would be written inside an `impl` of `self_enum`, i.e. with
+    /// variants of that enum prefixed by `Self::`.
+    pub(crate) fn display_with_self_enum(&self, self_enum: Option<EnumId>) -> impl HirDisplay + '_ {
+        WriteWith(move |f| {
+            self.fmt_with(f, &PatDisplayOptions { self_enum, bindings: None, placeholders: None })
+        })
+    }
+
+    /// Displays the pattern as the pattern of a new arm: like [`Pat::display_with_self_enum`], but
+    /// with the wildcards in fields replaced by bindings named after the fields, e.g.
+    /// `Variant { id, name }` or `Ok(value)`.
+    pub(crate) fn display_as_arm(&self, self_enum: Option<EnumId>) -> impl HirDisplay + '_ {
+        WriteWith(move |f| {
+            let options = PatDisplayOptions {
+                self_enum,
+                bindings: Some(RefCell::default()),
+                placeholders: None,
+            };
+            self.fmt_with(f, &options)
+        })
+    }
+
+    fn display_with<'a>(&'a self, options: &'a PatDisplayOptions) -> impl HirDisplay + 'a {
+        WriteWith(move |f| self.fmt_with(f, options))
+    }
+
+    fn fmt_with(
+        &self,
+        f: &mut HirFormatter<'_>,
+        options: &PatDisplayOptions,
+    ) -> Result<(), HirDisplayError> {
         match &*self.kind {
-            PatKind::Wild => write!(f, "_"),
-            PatKind::Binding { name, subpattern } => {
+            PatKind::Wild | PatKind::Error | PatKind::Opaque => options.write_placeholder(f, "_"),
+            PatKind::Binding { name, mode, subpattern } => {
+                match mode {
+                    BindingMode::Move => {}
+                    BindingMode::Ref(Mutability::Not) => write!(f, "ref ")?,
+                    BindingMode::Ref(Mutability::Mut) => write!(f, "ref mut ")?,
+                }
                 write!(f, "{name}")?;
                 if let Some(subpattern) = subpattern {
                     write!(f, " @ ")?;
-                    subpattern.hir_fmt(f)?;
+                    subpattern.fmt_operand_with(f, options)?;
                 }
                 Ok(())
             }
@@ -315,14 +982,7 @@ fn hir_fmt(&self, f: &mut HirFormatter<'_>) -> Result<(), HirDisplayError> {
                 };
 
                 if let Some(variant) = variant {
-                    match variant {
-                        VariantId::EnumVariantId(v) => {
-                            let data = f.db.enum_data(v.parent);
-                            write!(f, "{}", data.variants[v.local_id].name)?;
-                        }
-                        VariantId::StructId(s) => write!(f, "{}", f.db.struct_data(s).name)?,
-                        VariantId::UnionId(u) => write!(f, "{}", f.db.union_data(u).name)?,
-                    };
+                    write_variant_path(f, variant, options)?;
 
                     let variant_data = variant.variant_data(f.db.upcast());
                     if let VariantData::Record(rec_fields) = &*variant_data {
@@ -331,12 +991,29 @@ fn hir_fmt(&self, f: &mut HirFormatter<'_>) -> Result<(), HirDisplayError> {
                         let mut printed = 0;
                         let subpats = subpatterns
                             .iter()
-                            .filter(|p| !matches!(*p.pattern.kind, PatKind::Wild))
+                            .filter(|p| {
+                                options.bindings.is_some()
+                                    || !matches!(*p.pattern.kind, PatKind::Wild)
+                            })
                             .map(|p| {
                                 printed += 1;
                                 WriteWith(move |f| {
-                                    write!(f, "{}: ", rec_fields[p.field].name)?;
-                                    p.pattern.hir_fmt(f)
+                                    let field = &rec_fields[p.field].name;
+                                    match options.binding_for(f.db, &p.pattern, Some(field)) {
+                                        // Shorthand fields can't be renamed in place, so they
+                                        // aren't placeholders.
+                                        Some(binding) if binding == field.to_string() => {
+                                            write!(f, "{binding}")
+                                        }
+                                        Some(binding) => {
+                                            write!(f, "{field}: ")?;
+                                            options.write_placeholder(f, &binding)
+                                        }
+                                        None => {
+                                            write!(f, "{field}: ")?;
+                                            p.pattern.fmt_with(f, options)
+                                        }
+                                    }
                                 })
                             });
                         f.write_joined(subpats, ", ")?;
@@ -356,15 +1033,14 @@ fn hir_fmt(&self, f: &mut HirFormatter<'_>) -> Result<(), HirDisplayError> {
                     let subpats = (0..num_fields).map(|i| {
                         WriteWith(move |f| {
                             let fid = LocalFieldId::from_raw((i as u32).into());
-                            if let Some(p) = subpatterns.get(i) {
-                                if p.field == fid {
-                                    return p.pattern.hir_fmt(f);
-                                }
-                            }
-                            if let Some(p) = subpatterns.iter().find(|p| p.field == fid) {
-                                p.pattern.hir_fmt(f)
-                            } else {
-                                write!(f, "_")
+                            let p = match subpatterns.get(i) {
+                                Some(p) if p.field == fid => Some(p),
+                                _ => subpatterns.iter().find(|p| p.field == fid),
+                            };
+                            let Some(p) = p else { return options.write_placeholder(f, "_") };
+                            match options.binding_for(f.db, &p.pattern, None) {
+                                Some(binding) => options.write_placeholder(f, &binding),
+                                None => p.pattern.fmt_with(f, options),
                             }
                         })
                     });
@@ -380,17 +1056,99 @@ fn hir_fmt(&self, f: &mut HirFormatter<'_>) -> Result<(), HirDisplayError> {
             PatKind::Deref { subpattern } => {
                 match self.ty.kind(Interner) {
                     TyKind::Adt(adt, _) if is_box(adt.0, f.db) => write!(f, "box ")?,
+                    // `&str` patterns are written as string literals.
+                    TyKind::Ref(..) if matches!(*subpattern.kind, PatKind::Str { .. }) => {}
                     &TyKind::Ref(mutbl, ..) => {
                         write!(f, "&{}", if mutbl == Mutability::Mut { "mut " } else { "" })?
                     }
                     _ => never!("{:?} is a bad Deref pattern type", self.ty),
                 }
-                subpattern.hir_fmt(f)
+                subpattern.fmt_operand_with(f, options)
             }
             PatKind::LiteralBool { value } => write!(f, "{value}"),
-            PatKind::Or { pats } => f.write_joined(pats.iter(), " | "),
+            PatKind::Str { value } => write!(f, "{value:?}"),
+            &PatKind::Range { lo, hi } => {
+                let Some(width) = IntWidth::of(&self.ty) else {
+                    never!("{:?} is a bad range pattern type", self.ty);
+                    return write!(f, "_");
+                };
+                width.fmt_value(f, &self.ty, lo)?;
+                if lo != hi {
+                    write!(f, "..=")?;
+                    width.fmt_value(f, &self.ty, hi)?;
+                }
+                Ok(())
+            }
+            PatKind::Slice { prefix, slice, suffix } => {
+                write!(f, "[")?;
+                f.write_joined(prefix.iter().map(|p| p.display_with(options)), ", ")?;
+                if slice.is_some() {
+                    if !prefix.is_empty() {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "..")?;
+                    if !suffix.is_empty() {
+                        write!(f, ", ")?;
+                    }
+                } else if !prefix.is_empty() && !suffix.is_empty() {
+                    write!(f, ", ")?;
+                }
+                f.write_joined(suffix.iter().map(|p| p.display_with(options)), ", ")?;
+                write!(f, "]")
+            }
+            PatKind::Or { pats } => {
+                f.write_joined(pats.iter().map(|p| p.display_with(options)), " | ")
+            }
         }
     }
+
+    /// Writes the pattern as the operand of `&`, `box` or `@`, which binds tighter than `|`.
+    fn fmt_operand_with(
+        &self,
+        f: &mut HirFormatter<'_>,
+        options: &PatDisplayOptions,
+    ) -> Result<(), HirDisplayError> {
+        if !matches!(*self.kind, PatKind::Or { .. }) {
+            return self.fmt_with(f, options);
+        }
+        write!(f, "(")?;
+        self.fmt_with(f, options)?;
+        write!(f, ")")
+    }
+}
+
+/// Writes the path of a struct, union or enum variant: its name for diagnostics, and a path
+/// that resolves in the target module for source code.
+fn write_variant_path(
+    f: &mut HirFormatter<'_>,
+    variant: VariantId,
+    options: &PatDisplayOptions,
+) -> Result<(), HirDisplayError> {
+    if let VariantId::EnumVariantId(v) = variant {
+        if options.self_enum == Some(v.parent) {
+            return write!(f, "Self::{}", f.db.enum_data(v.parent).variants[v.local_id].name);
+        }
+    }
+    if let Some(module) = f.source_code_module() {
+        let item = match variant {
+            VariantId::EnumVariantId(v) => ItemInNs::Types(v.into()),
+            VariantId::StructId(s) => ItemInNs::Types(AdtId::from(s).into()),
+            VariantId::UnionId(u) => ItemInNs::Types(AdtId::from(u).into()),
+        };
+        return match find_path::find_path(f.db.upcast(), item, module, false) {
+            Some(path) => write!(f, "{path}"),
+            None => {
+                Err(HirDisplayError::DisplaySourceCodeError(DisplaySourceCodeError::PathNotFound))
+            }
+        };
+    }
+    match variant {
+        VariantId::EnumVariantId(v) => {
+            write!(f, "{}", f.db.enum_data(v.parent).variants[v.local_id].name)
+        }
+        VariantId::StructId(s) => write!(f, "{}", f.db.struct_data(s).name),
+        VariantId::UnionId(u) => write!(f, "{}", f.db.union_data(u).name),
+    }
 }
 
 struct WriteWith<F>(F)
@@ -469,7 +1227,7 @@ fn fold_with<F: PatternFolder>(&self, folder: &mut F) -> Self {
     }
 
     fn super_fold_with<F: PatternFolder>(&self, folder: &mut F) -> Self {
-        Pat { ty: self.ty.fold_with(folder), kind: self.kind.fold_with(folder) }
+        Pat { ty: self.ty.fold_with(folder), kind: self.kind.fold_with(folder), id: self.id }
     }
 }
 
@@ -481,9 +1239,13 @@ fn fold_with<F: PatternFolder>(&self, folder: &mut F) -> Self {
     fn super_fold_with<F: PatternFolder>(&self, folder: &mut F) -> Self {
         match self {
             PatKind::Wild => PatKind::Wild,
-            PatKind::Binding { name, subpattern } => {
-                PatKind::Binding { name: name.clone(), subpattern: subpattern.fold_with(folder) }
-            }
+            PatKind::Error => PatKind::Error,
+            PatKind::Opaque => PatKind::Opaque,
+            PatKind::Binding { name, mode, subpattern } => PatKind::Binding {
+                name: name.clone(),
+                mode: *mode,
+                subpattern: subpattern.fold_with(folder),
+            },
             PatKind::Variant { substs, enum_variant, subpatterns } => PatKind::Variant {
                 substs: substs.fold_with(folder),
                 enum_variant: enum_variant.fold_with(folder),
@@ -496,7 +1258,17 @@ fn super_fold_with<F: PatternFolder>(&self, folder: &mut F) -> Self {
                 PatKind::Deref { subpattern: subpattern.fold_with(folder) }
             }
             &PatKind::LiteralBool { value } => PatKind::LiteralBool { value },
+            PatKind::Str { value } => PatKind::Str { value: value.clone() },
+            &PatKind::Range { lo, hi } => PatKind::Range { lo, hi },
+            PatKind::Slice { prefix, slice, suffix } => PatKind::Slice {
+                prefix: prefix.fold_with(folder),
+                slice: slice.fold_with(folder),
+                suffix: suffix.fold_with(folder),
+            },
             PatKind::Or { pats } => PatKind::Or { pats: pats.fold_with(folder) },
         }
     }
 }
+
+#[cfg(test)]
+mod tests;
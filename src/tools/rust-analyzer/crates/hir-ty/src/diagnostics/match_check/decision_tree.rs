@@ -0,0 +1,263 @@
+//! Exports a `match` as a decision tree, for visualization: each inner node tests the constructor
+//! of one place of the scrutinee, e.g. `scrutinee.0` or `*scrutinee`, and each leaf is the arm run
+//! for the values reaching it, or a failure if there is none.
+//!
+//! The tree is built like the matrices of [`super::usefulness`]: the first column that the first
+//! row doesn't match with a wildcard is tested, the wildcard is split against the constructors of
+//! that column, and the matrix is specialized with each of the resulting constructors. The order
+//! of the tests is the simplest one, not the one rustc would pick.
+
+use stdx::format_to;
+
+use crate::{display::HirDisplay, Ty};
+
+use super::{
+    deconstruct_pat::{Constructor, DeconstructedPat, SplitWildcard},
+    pat_analysis::MatchCheckCtx,
+    usefulness::{PatCtxt, TypeCx, UsefulnessCtxt},
+};
+
+/// The number of nodes past which we give up building a tree, since splitting every column of a
+/// large tuple can make the tree exponentially large.
+const NODE_LIMIT: usize = 10_000;
+
+/// How a decision tree is exported.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DecisionTreeFormat {
+    /// A Graphviz `digraph`.
+    Dot,
+    /// A JSON object per node, with a `kind` of `switch`, `arm` or `fail`.
+    Json,
+}
+
+/// An arm of the match the tree is built for.
+pub(crate) struct DecisionTreeArm<'p> {
+    /// The index of the arm in the match, counting arms that couldn't be lowered.
+    pub(crate) index: usize,
+    pub(crate) pat: &'p DeconstructedPat<'p>,
+    pub(crate) has_guard: bool,
+}
+
+#[derive(Debug)]
+pub(crate) enum DecisionTree {
+    /// Tests the constructor of the value at `place`, of type `ty`.
+    Switch { place: String, ty: String, branches: Vec<(String, DecisionTree)> },
+    /// Runs the arm with index `arm`, or continues with `otherwise` if the arm has a guard and it
+    /// fails.
+    Arm { arm: usize, otherwise: Option<Box<DecisionTree>> },
+    /// No arm matches the values reaching this node, i.e. the match isn't exhaustive.
+    Fail,
+}
+
+/// A column of the matrix: the place of the scrutinee whose value it matches.
+#[derive(Clone)]
+struct Place {
+    path: String,
+    is_top_level: bool,
+}
+
+struct Row<'p> {
+    pats: Vec<&'p DeconstructedPat<'p>>,
+    arm: usize,
+    has_guard: bool,
+}
+
+impl DecisionTree {
+    /// Builds the tree for `arms`, or returns `None` if it has more than [`NODE_LIMIT`] nodes.
+    pub(crate) fn build<'p>(
+        cx: &MatchCheckCtx<'_, 'p>,
+        arms: &[DecisionTreeArm<'p>],
+    ) -> Option<DecisionTree> {
+        let place = Place { path: "scrutinee".to_owned(), is_top_level: true };
+        let rows = arms
+            .iter()
+            .map(|arm| Row { pats: vec![arm.pat], arm: arm.index, has_guard: arm.has_guard })
+            .collect();
+        let mut nodes_left = NODE_LIMIT;
+        build_node(cx, &cx.usefulness_cx(), vec![place], rows, &mut nodes_left)
+    }
+
+    pub(crate) fn render(&self, format: DecisionTreeFormat) -> String {
+        let mut buf = String::new();
+        match format {
+            DecisionTreeFormat::Dot => {
+                buf.push_str("digraph match {\n");
+                self.render_dot(&mut 0, &mut buf);
+                buf.push_str("}\n");
+            }
+            DecisionTreeFormat::Json => {
+                self.render_json(&mut buf);
+                buf.push('\n');
+            }
+        }
+        buf
+    }
+
+    /// Renders the subtree starting at this node as DOT statements, numbering the nodes in
+    /// preorder from `next_id`, and returns the id of this node.
+    fn render_dot(&self, next_id: &mut usize, buf: &mut String) -> usize {
+        let id = *next_id;
+        *next_id += 1;
+        match self {
+            DecisionTree::Switch { place, ty, branches } => {
+                format_to!(buf, "    n{id} [label={}];\n", quote(&format!("{place}: {ty}")));
+                for (ctor, node) in branches {
+                    let child = node.render_dot(next_id, buf);
+                    format_to!(buf, "    n{id} -> n{child} [label={}];\n", quote(ctor));
+                }
+            }
+            DecisionTree::Arm { arm, otherwise } => {
+                let guard = if otherwise.is_some() { " if guard" } else { "" };
+                format_to!(buf, "    n{id} [label=\"arm {arm}{guard}\", shape=box];\n");
+                if let Some(node) = otherwise {
+                    let child = node.render_dot(next_id, buf);
+                    format_to!(
+                        buf,
+                        "    n{id} -> n{child} [label=\"guard fails\", style=dashed];\n"
+                    );
+                }
+            }
+            DecisionTree::Fail => {
+                format_to!(buf, "    n{id} [label=\"not covered\", shape=box, style=dashed];\n");
+            }
+        }
+        id
+    }
+
+    fn render_json(&self, buf: &mut String) {
+        match self {
+            DecisionTree::Switch { place, ty, branches } => {
+                format_to!(
+                    buf,
+                    "{{\"kind\":\"switch\",\"place\":{},\"ty\":{},\"branches\":[",
+                    quote(place),
+                    quote(ty)
+                );
+                for (idx, (ctor, node)) in branches.iter().enumerate() {
+                    if idx != 0 {
+                        buf.push(',');
+                    }
+                    format_to!(buf, "{{\"ctor\":{},\"node\":", quote(ctor));
+                    node.render_json(buf);
+                    buf.push('}');
+                }
+                buf.push_str("]}");
+            }
+            DecisionTree::Arm { arm, otherwise } => {
+                format_to!(buf, "{{\"kind\":\"arm\",\"arm\":{arm},\"otherwise\":");
+                match otherwise {
+                    Some(node) => node.render_json(buf),
+                    None => buf.push_str("null"),
+                }
+                buf.push('}');
+            }
+            DecisionTree::Fail => buf.push_str("{\"kind\":\"fail\"}"),
+        }
+    }
+}
+
+fn build_node<'p>(
+    cx: &MatchCheckCtx<'_, 'p>,
+    ucx: &UsefulnessCtxt<'_, 'p>,
+    mut places: Vec<Place>,
+    mut rows: Vec<Row<'p>>,
+    nodes_left: &mut usize,
+) -> Option<DecisionTree> {
+    *nodes_left = nodes_left.checked_sub(1)?;
+    let Some(first) = rows.first() else { return Some(DecisionTree::Fail) };
+    let Some(col) = first.pats.iter().position(|pat| !pat.ctor().is_wildcard()) else {
+        // The first row matches everything reaching this node.
+        let first = rows.remove(0);
+        let otherwise = if first.has_guard {
+            Some(Box::new(build_node(cx, ucx, places, rows, nodes_left)?))
+        } else {
+            None
+        };
+        return Some(DecisionTree::Arm { arm: first.arm, otherwise });
+    };
+
+    places.swap(0, col);
+    let mut expanded = Vec::with_capacity(rows.len());
+    for mut row in rows {
+        row.pats.swap(0, col);
+        expand_or_head(row, &mut expanded);
+    }
+    let rows = expanded;
+
+    let ty: Ty = rows[0].pats[0].ty().clone();
+    let pcx = PatCtxt {
+        cx: ucx,
+        ty: &ty,
+        is_top_level: places[0].is_top_level,
+        is_non_exhaustive: cx.is_foreign_non_exhaustive_enum(&ty),
+    };
+    let mut split_wildcard = SplitWildcard::new(pcx);
+    split_wildcard.split(pcx, rows.iter().map(|row| row.pats[0].ctor()));
+    let mut ctors: Vec<_> = split_wildcard.iter_present(pcx).cloned().collect();
+    if split_wildcard.iter_missing(pcx).next().is_some() {
+        // The constructors no row tests are only matched by the wildcard rows, so they are
+        // grouped in a single `_` branch.
+        ctors.push(Constructor::Wildcard);
+    }
+
+    let mut branches = Vec::with_capacity(ctors.len());
+    for ctor in ctors {
+        let mut sub_places: Vec<_> = ctor
+            .field_places(cx, &ty, &places[0].path)
+            .into_iter()
+            .map(|path| Place { path, is_top_level: false })
+            .collect();
+        sub_places.extend(places[1..].iter().cloned());
+        // Rows headed by constants we couldn't evaluate are covered by no constructor, and left
+        // out.
+        let sub_rows = rows
+            .iter()
+            .filter(|row| ctor.is_covered_by(pcx, row.pats[0].ctor()))
+            .map(|row| {
+                let mut pats: Vec<_> = row.pats[0].specialize(ucx, &ctor).into_iter().collect();
+                pats.extend_from_slice(&row.pats[1..]);
+                Row { pats, arm: row.arm, has_guard: row.has_guard }
+            })
+            .collect();
+        let node = build_node(cx, ucx, sub_places, sub_rows, nodes_left)?;
+        branches.push((ctor.display(pcx).to_string(), node));
+    }
+
+    // Testing a place that can only take one constructor, like a tuple, isn't a decision.
+    if branches.len() == 1 {
+        return branches.pop().map(|(_, node)| node);
+    }
+    let Place { path, .. } = places.swap_remove(0);
+    Some(DecisionTree::Switch { place: path, ty: ty.display(cx.db).to_string(), branches })
+}
+
+/// Pushes `row`, or a row per alternative if it starts with an or-pattern, to `rows`.
+fn expand_or_head<'p>(row: Row<'p>, rows: &mut Vec<Row<'p>>) {
+    let head = row.pats[0];
+    if !head.is_or_pat() {
+        rows.push(row);
+        return;
+    }
+    for alt in head.iter_fields() {
+        let mut pats = row.pats.clone();
+        pats[0] = alt;
+        expand_or_head(Row { pats, arm: row.arm, has_guard: row.has_guard }, rows);
+    }
+}
+
+/// Quotes `text` as a string literal of DOT or JSON.
+fn quote(text: &str) -> String {
+    let mut buf = String::with_capacity(text.len() + 2);
+    buf.push('"');
+    for c in text.chars() {
+        match c {
+            '"' => buf.push_str("\\\""),
+            '\\' => buf.push_str("\\\\"),
+            '\n' => buf.push_str("\\n"),
+            c if c.is_control() => format_to!(buf, "\\u{:04x}", c as u32),
+            c => buf.push(c),
+        }
+    }
+    buf.push('"');
+    buf
+}
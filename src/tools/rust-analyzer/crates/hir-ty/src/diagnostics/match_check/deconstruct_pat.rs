@@ -44,47 +44,36 @@
 use std::{
     cell::Cell,
     cmp::{max, min},
+    fmt,
     iter::once,
     ops::RangeInclusive,
+    ptr,
 };
 
-use hir_def::{EnumVariantId, HasModule, LocalFieldId, VariantId};
+use chalk_ir::Mutability;
+use hir_def::{expr::PatId, EnumVariantId, LocalFieldId, VariantId};
+use hir_expand::name::Name;
+use itertools::Itertools;
 use smallvec::{smallvec, SmallVec};
 use stdx::never;
+use typed_arena::Arena;
 
 use crate::{
-    infer::normalize, inhabitedness::is_enum_variant_uninhabited_from, AdtId, Interner, Scalar, Ty,
-    TyExt, TyKind,
+    consteval::try_const_usize, infer::BindingMode, Const, Interner, Scalar, Ty, TyExt, TyKind,
 };
 
 use super::{
-    is_box,
-    usefulness::{helper::Captures, MatchCheckCtx, PatCtxt},
-    FieldPat, Pat, PatKind,
+    ensure_sufficient_stack, is_box,
+    pat_analysis::MatchCheckCtx,
+    usefulness::{helper::Captures, PatCtxt, UsefulnessCtxt},
+    FieldPat, IntWidth, Pat, PatKind,
 };
 
-use self::Constructor::*;
-
-/// Recursively expand this pattern into its subpatterns. Only useful for or-patterns.
-fn expand_or_pat(pat: &Pat) -> Vec<&Pat> {
-    fn expand<'p>(pat: &'p Pat, vec: &mut Vec<&'p Pat>) {
-        if let PatKind::Or { pats } = pat.kind.as_ref() {
-            for pat in pats {
-                expand(pat, vec);
-            }
-        } else {
-            vec.push(pat)
-        }
-    }
-
-    let mut pats = Vec::new();
-    expand(pat, &mut pats);
-    pats
-}
+use self::{Constructor::*, SliceKind::*};
 
 /// [Constructor] uses this in umimplemented variants.
 /// It allows porting match expressions from upstream algorithm without losing semantics.
-#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
 pub(super) enum Void {}
 
 /// An inclusive interval, used for precise integer exhaustiveness checking.
@@ -97,7 +86,7 @@ pub(super) enum Void {}
 ///
 /// `IntRange` is never used to encode an empty range or a "range" that wraps
 /// around the (offset) space: i.e., `range.lo <= range.hi`.
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub(super) struct IntRange {
     range: RangeInclusive<u128>,
 }
@@ -120,7 +109,7 @@ fn boundaries(&self) -> (u128, u128) {
     }
 
     #[inline]
-    fn from_bool(value: bool) -> IntRange {
+    pub(super) fn from_bool(value: bool) -> IntRange {
         let val = value as u128;
         IntRange { range: val..=val }
     }
@@ -133,11 +122,27 @@ fn from_range(lo: u128, hi: u128, scalar_ty: Scalar) -> IntRange {
         }
     }
 
+    /// The range of the values of an integer type from `lo` to `hi` inclusive, given by their
+    /// bits like in [`PatKind::Range`]. `lo` must not be above `hi` as values of the type.
+    #[inline]
+    pub(super) fn from_bits(width: IntWidth, lo: u128, hi: u128) -> IntRange {
+        let bias = signed_bias(width);
+        IntRange { range: (lo ^ bias)..=(hi ^ bias) }
+    }
+
     fn is_subrange(&self, other: &Self) -> bool {
         other.range.start() <= self.range.start() && self.range.end() <= other.range.end()
     }
 
-    fn intersection(&self, other: &Self) -> Option<Self> {
+    /// Whether `self` and `other` only share an endpoint, like `0..=5` and `5..=10`, which is
+    /// likely a mistake. A range containing the other or a single value doesn't count.
+    fn suspicious_intersection(&self, other: &Self) -> bool {
+        let (lo, hi) = self.boundaries();
+        let (other_lo, other_hi) = other.boundaries();
+        (lo == other_hi || hi == other_lo) && !self.is_singleton() && !other.is_singleton()
+    }
+
+    pub(super) fn intersection(&self, other: &Self) -> Option<Self> {
         let (lo, hi) = self.boundaries();
         let (other_lo, other_hi) = other.boundaries();
         if lo <= other_hi && other_lo <= hi {
@@ -147,7 +152,17 @@ fn intersection(&self, other: &Self) -> Option<Self> {
         }
     }
 
-    fn to_pat(&self, _cx: &MatchCheckCtx<'_, '_>, ty: Ty) -> Pat {
+    fn to_pat(&self, ty: Ty) -> Pat {
+        if let Some(width) = IntWidth::of(&ty) {
+            let (lo, hi) = self.boundaries();
+            let kind = if (lo, hi) == (0, width.mask()) {
+                PatKind::Wild
+            } else {
+                let bias = signed_bias(width);
+                PatKind::Range { lo: lo ^ bias, hi: hi ^ bias }
+            };
+            return Pat { ty, kind: kind.into(), id: None };
+        }
         match ty.kind(Interner) {
             TyKind::Scalar(Scalar::Bool) => {
                 let kind = match self.boundaries() {
@@ -159,7 +174,7 @@ fn to_pat(&self, _cx: &MatchCheckCtx<'_, '_>, ty: Ty) -> Pat {
                         PatKind::Wild
                     }
                 };
-                Pat { ty, kind: kind.into() }
+                Pat { ty, kind: kind.into(), id: None }
             }
             _ => unimplemented!(),
         }
@@ -178,6 +193,14 @@ fn is_covered_by(&self, other: &Self) -> bool {
     }
 }
 
+/// The offset that maps the values of an integer type onto `0..=mask` in order: flipping the sign
+/// bit of a signed value moves `MIN` to `0` and `MAX` to `mask`, e.g. `-128i8` (`0x80`) to `0x00`,
+/// `-1i8` (`0xff`) to `0x7f` and `127i8` (`0x7f`) to `0xff`. Unsigned values are already in order.
+#[inline]
+fn signed_bias(width: IntWidth) -> u128 {
+    width.min_bits()
+}
+
 /// Represents a border between 2 integers. Because the intervals spanning borders must be able to
 /// cover every integer, we need to be able to represent 2^128 + 1 such borders.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
@@ -273,20 +296,195 @@ fn iter(&self) -> impl Iterator<Item = IntRange> + '_ {
     }
 }
 
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub(super) enum SliceKind {
+    /// Patterns of length `n` (`[x, y]`).
+    FixedLen(usize),
+    /// Patterns using the `..` notation (`[x, .., y]`).
+    /// Captures any array constructor of `length >= i + j`.
+    /// In the case where `array_len` is `Some(_)`,
+    /// this indicates that we only care about the first `i` and the last `j` values of the array,
+    /// and everything in between is a wildcard `_`.
+    VarLen(usize, usize),
+}
+
+impl SliceKind {
+    fn arity(self) -> usize {
+        match self {
+            FixedLen(length) => length,
+            VarLen(prefix, suffix) => prefix + suffix,
+        }
+    }
+
+    /// Whether this pattern includes patterns of length `other_len`.
+    fn covers_length(self, other_len: usize) -> bool {
+        match self {
+            FixedLen(len) => len == other_len,
+            VarLen(prefix, suffix) => prefix + suffix <= other_len,
+        }
+    }
+}
+
 /// A constructor for array and slice patterns.
-#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
 pub(super) struct Slice {
-    _unimplemented: Void,
+    /// `None` if the matched value is a slice, or an array whose length can't be evaluated (e.g.
+    /// a generic const), `Some(n)` if it is an array of size `n`.
+    array_len: Option<usize>,
+    /// The kind of pattern it is: fixed-length `[x, y]` or variable length `[x, .., y]`.
+    kind: SliceKind,
 }
 
 impl Slice {
-    fn arity(self) -> usize {
-        match self._unimplemented {}
+    pub(super) fn new(array_len: Option<usize>, kind: SliceKind) -> Self {
+        let kind = match (array_len, kind) {
+            // If the middle `..` is empty, we effectively have a fixed-length pattern.
+            (Some(len), VarLen(prefix, suffix)) if prefix + suffix >= len => FixedLen(len),
+            _ => kind,
+        };
+        Slice { array_len, kind }
+    }
+
+    pub(super) fn arity(self) -> usize {
+        self.kind.arity()
     }
 
     /// See `Constructor::is_covered_by`
-    fn is_covered_by(self, _other: Self) -> bool {
-        match self._unimplemented {}
+    fn is_covered_by(self, other: Self) -> bool {
+        other.kind.covers_length(self.arity())
+    }
+}
+
+/// This computes constructor splitting for variable-length slices, as explained at the top of the
+/// file.
+///
+/// A slice pattern `[x, .., y]` behaves like the infinite or-pattern `[x, y] | [x, _, y] | [x, _,
+/// _, y] | ...`. The corresponding value constructors are fixed-length array constructors above a
+/// given minimum length. We obviously can't list this infinitude of constructors. Thankfully,
+/// it turns out that for each finite set of slice patterns, all sufficiently large array lengths
+/// are equivalent.
+///
+/// Let's look at an example, where we are trying to split the last pattern:
+/// ```
+/// match x {
+///     [true, true, ..] => {}
+///     [.., false, false] => {}
+///     [..] => {}
+/// }
+/// ```
+/// Here are the results of specialization for the first few lengths:
+/// ```
+/// // length 0
+/// [] => {}
+/// // length 1
+/// [_] => {} // `_` can be either `true` or `false`
+/// // length 2
+/// [true, true] => {}
+/// [false, false] => {}
+/// [_, _] => {}
+/// // length 3
+/// [true, true,  _    ] => {}
+/// [_,    false, false] => {}
+/// [_,    _,     _    ] => {}
+/// // length 4
+/// [true, true, _,     _    ] => {}
+/// [_,    _,    false, false] => {}
+/// [_,    _,    _,     _    ] => {}
+/// // length 5
+/// [true, true, _, _,     _    ] => {}
+/// [_,    _,    _, false, false] => {}
+/// [_,    _,    _, _,     _    ] => {}
+/// ```
+///
+/// If we went above length 5, we would simply be inserting more columns full of wildcards in the
+/// middle. This means that the set of witnesses for length `l >= 5` if equivalent to the set for
+/// any other `l' >= 5`: simply add or remove wildcards in the middle to convert between them.
+///
+/// This applies to any set of slice patterns: there will be a length `L` above which all lengths
+/// behave the same. This is exactly what we need for constructor splitting. Therefore a
+/// variable-length slice can be split into a variable-length slice of minimal length `L`, and many
+/// fixed-length slices of lengths `< L`.
+///
+/// For each variable-length pattern `p` with a prefix of length `plₚ` and suffix of length `slₚ`,
+/// only the first `plₚ` and the last `slₚ` elements are examined. Therefore, as long as `L` is
+/// positive (to avoid concerns about empty types), all elements after the maximum prefix length
+/// and before the maximum suffix length are not examined by any variable-length pattern, and
+/// therefore can be added/removed without affecting them - creating equivalent patterns from any
+/// sufficiently-large length.
+///
+/// Of course, if fixed-length patterns exist, we must be sure that our length is large enough to
+/// miss them all, so we can pick `L = max(max(FIXED_LEN)+1, max(PREFIX_LEN) + max(SUFFIX_LEN))`
+///
+/// `max_slice` below will be made to have arity `L`.
+#[derive(Debug)]
+struct SplitVarLenSlice {
+    /// If the type is an array, this is its size.
+    array_len: Option<usize>,
+    /// The arity of the input slice.
+    arity: usize,
+    /// The smallest slice bigger than any slice seen. `max_slice.arity()` is the length `L`
+    /// described above.
+    max_slice: SliceKind,
+}
+
+impl SplitVarLenSlice {
+    fn new(prefix: usize, suffix: usize, array_len: Option<usize>) -> Self {
+        SplitVarLenSlice { array_len, arity: prefix + suffix, max_slice: VarLen(prefix, suffix) }
+    }
+
+    /// Pass a set of slices relative to which to split this one.
+    fn split(&mut self, slices: impl Iterator<Item = SliceKind>) {
+        let (mut max_prefix_len, mut max_suffix_len) = match self.max_slice {
+            VarLen(prefix, suffix) => (prefix, suffix),
+            FixedLen(_) => return,
+        };
+        // We grow `self.max_slice` to be larger than all slices encountered, as described above.
+        // For diagnostics, we keep the prefix and suffix lengths separate, but grow them so that
+        // `L = max_prefix_len + max_suffix_len`.
+        let mut max_fixed_len = 0;
+        for slice in slices {
+            match slice {
+                FixedLen(len) => {
+                    max_fixed_len = max(max_fixed_len, len);
+                }
+                VarLen(prefix, suffix) => {
+                    max_prefix_len = max(max_prefix_len, prefix);
+                    max_suffix_len = max(max_suffix_len, suffix);
+                }
+            }
+        }
+        // We want `L = max(L, max_fixed_len + 1)`, modulo the fact that we keep prefix and
+        // suffix separate.
+        if max_fixed_len + 1 >= max_prefix_len + max_suffix_len {
+            // The subtraction can't overflow thanks to the above check.
+            // The new `max_prefix_len` is larger than its previous value.
+            max_prefix_len = max_fixed_len + 1 - max_suffix_len;
+        }
+        self.max_slice = VarLen(max_prefix_len, max_suffix_len);
+
+        // We cap the arity of `max_slice` at the array size.
+        match self.array_len {
+            Some(len) if self.max_slice.arity() >= len => self.max_slice = FixedLen(len),
+            _ => {}
+        }
+    }
+
+    /// Iterate over the partition of this slice.
+    fn iter(&self) -> impl Iterator<Item = Slice> + '_ {
+        let smaller_lengths = match self.array_len {
+            // The only admissible fixed-length slice is one of the array size. Whether `max_slice`
+            // is fixed-length or variable-length, it will be the only relevant slice to output
+            // here.
+            Some(_) => 0..0, // empty range
+            // We cover all arities in the range `(self.arity..infinity)`. We split that range into
+            // two: lengths smaller than `max_slice.arity()` are treated independently as
+            // fixed-lengths slices, and lengths above are captured by `max_slice`.
+            None => self.arity..self.max_slice.arity(),
+        };
+        smaller_lengths
+            .map(FixedLen)
+            .chain(once(self.max_slice))
+            .map(move |kind| Slice::new(self.array_len, kind))
     }
 }
 
@@ -298,7 +496,7 @@ fn is_covered_by(self, _other: Self) -> bool {
 /// constructor. `Constructor::apply` reconstructs the pattern from a pair of `Constructor` and
 /// `Fields`.
 #[allow(dead_code)]
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub(super) enum Constructor {
     /// The constructor for patterns that have a single constructor, like tuples, struct patterns
     /// and fixed-length arrays.
@@ -309,8 +507,9 @@ pub(super) enum Constructor {
     IntRange(IntRange),
     /// Ranges of floating-point literal values (`2.0..=5.2`).
     FloatRange(Void),
-    /// String literals. Strings are not quite the same as `&[u8]` so we treat them separately.
-    Str(Void),
+    /// String literals and `&str` constants, as patterns of type `str`. Strings are not quite the
+    /// same as `&[u8]` so we treat them separately.
+    Str(Box<str>),
     /// Array and slice patterns.
     Slice(Slice),
     /// Constants that must not be matched structurally. They are treated as black
@@ -361,7 +560,7 @@ pub(super) fn is_doc_hidden_variant(&self, _pcx: PatCtxt<'_, '_>) -> bool {
         false //FIXME: implement this
     }
 
-    fn variant_id_for_adt(&self, adt: hir_def::AdtId) -> VariantId {
+    pub(super) fn variant_id_for_adt(&self, adt: hir_def::AdtId) -> VariantId {
         match *self {
             Variant(id) => id.into(),
             Single => {
@@ -376,40 +575,45 @@ fn variant_id_for_adt(&self, adt: hir_def::AdtId) -> VariantId {
         }
     }
 
-    /// The number of fields for this constructor. This must be kept in sync with
-    /// `Fields::wildcards`.
+    /// The number of fields for this constructor, i.e. of the wildcards of
+    /// [`Fields::wildcards`].
     pub(super) fn arity(&self, pcx: PatCtxt<'_, '_>) -> usize {
+        Fields::wildcards(pcx.cx, pcx.ty, self).fields.len()
+    }
+
+    /// Paths to the fields of this constructor from the value at `place`, e.g. `place.0`, `*place`
+    /// or `place[1]`, in the order of `Fields::wildcards`. Used to label the decision trees of
+    /// matches.
+    pub(super) fn field_places(
+        &self,
+        cx: &MatchCheckCtx<'_, '_>,
+        ty: &Ty,
+        place: &str,
+    ) -> Vec<String> {
         match self {
-            Single | Variant(_) => match *pcx.ty.kind(Interner) {
-                TyKind::Tuple(arity, ..) => arity,
-                TyKind::Ref(..) => 1,
-                TyKind::Adt(adt, ..) => {
-                    if is_box(adt.0, pcx.cx.db) {
-                        // The only legal patterns of type `Box` (outside `std`) are `_` and box
-                        // patterns. If we're here we can assume this is a box pattern.
-                        1
-                    } else {
-                        let variant = self.variant_id_for_adt(adt.0);
-                        Fields::list_variant_nonhidden_fields(pcx.cx, pcx.ty, variant).count()
-                    }
+            Single | Variant(_) => match *ty.kind(Interner) {
+                TyKind::Tuple(arity, ..) => {
+                    (0..arity).map(|idx| format!("{place}.{idx}")).collect()
                 }
-                _ => {
-                    never!("Unexpected type for `Single` constructor: {:?}", pcx.ty);
-                    0
+                TyKind::Ref(..) => vec![format!("*{place}")],
+                TyKind::Adt(adt, ..) if is_box(adt.0, cx.db) => vec![format!("*{place}")],
+                TyKind::Adt(adt, ..) => {
+                    let variant = self.variant_id_for_adt(adt.0);
+                    let variant_data = variant.variant_data(cx.db.upcast());
+                    cx.list_variant_nonhidden_fields(ty, variant)
+                        .map(|(fid, _)| format!("{place}.{}", variant_data.fields()[fid].name))
+                        .collect()
                 }
+                _ => Vec::new(),
             },
-            Slice(slice) => slice.arity(),
-            Str(..)
-            | FloatRange(..)
-            | IntRange(..)
-            | NonExhaustive
-            | Opaque
-            | Missing { .. }
-            | Wildcard => 0,
-            Or => {
-                never!("The `Or` constructor doesn't have a fixed arity");
-                0
-            }
+            Slice(slice) => match slice.kind {
+                FixedLen(len) => (0..len).map(|idx| format!("{place}[{idx}]")).collect(),
+                VarLen(prefix, suffix) => (0..prefix)
+                    .map(|idx| format!("{place}[{idx}]"))
+                    .chain((1..=suffix).rev().map(|idx| format!("{place}[len - {idx}]")))
+                    .collect(),
+            },
+            _ => Vec::new(),
         }
     }
 
@@ -426,16 +630,48 @@ pub(super) fn arity(&self, pcx: PatCtxt<'_, '_>) -> usize {
     /// This function may discard some irrelevant constructors if this preserves behavior and
     /// diagnostics. Eg. for the `_` case, we ignore the constructors already present in the
     /// matrix, unless all of them are.
+    ///
+    /// Splits of ranges and slices are memoized per column type and set of seen constructors,
+    /// since the same column tends to recur, e.g. once per alternative of an expanded or-pattern.
+    /// Wildcards are split by `is_useful` directly, which keeps the [`SplitWildcard`] around.
     pub(super) fn split<'a>(
         &self,
         pcx: PatCtxt<'_, '_>,
         ctors: impl Iterator<Item = &'a Constructor> + Clone,
+    ) -> SmallVec<[Self; 1]> {
+        let is_cacheable = match self {
+            IntRange(ctor_range) => !ctor_range.is_singleton(),
+            Slice(slice) => matches!(slice.kind, VarLen(..)),
+            _ => false,
+        };
+        if !is_cacheable {
+            return self.split_uncached(pcx, ctors);
+        }
+        let key = SplitKey {
+            ty: pcx.ty.clone(),
+            is_top_level: pcx.is_top_level,
+            ctor: self.clone(),
+            // Wildcards in the column don't influence splitting.
+            seen: ctors.clone().filter(|c| !c.is_wildcard()).cloned().collect(),
+        };
+        if let Some(split) = pcx.cx.split_cache.borrow().get(&key) {
+            return split.clone();
+        }
+        let split = self.split_uncached(pcx, ctors);
+        pcx.cx.split_cache.borrow_mut().insert(key, split.clone());
+        split
+    }
+
+    fn split_uncached<'a>(
+        &self,
+        pcx: PatCtxt<'_, '_>,
+        ctors: impl Iterator<Item = &'a Constructor> + Clone,
     ) -> SmallVec<[Self; 1]> {
         match self {
             Wildcard => {
                 let mut split_wildcard = SplitWildcard::new(pcx);
                 split_wildcard.split(pcx, ctors);
-                split_wildcard.into_ctors(pcx)
+                split_wildcard.to_ctors(pcx)
             }
             // Fast-track if the range is trivial. In particular, we don't do the overlapping
             // ranges check.
@@ -445,7 +681,12 @@ pub(super) fn split<'a>(
                 split_range.split(int_ranges.cloned());
                 split_range.iter().map(IntRange).collect()
             }
-            Slice(slice) => match slice._unimplemented {},
+            &Slice(Slice { kind: VarLen(self_prefix, self_suffix), array_len }) => {
+                let mut split_self = SplitVarLenSlice::new(self_prefix, self_suffix, array_len);
+                let slices = ctors.filter_map(|c| c.as_slice()).map(|s| s.kind);
+                split_self.split(slices);
+                split_self.iter().map(Slice).collect()
+            }
             // Any other constructor can be used unchanged.
             _ => smallvec![self.clone()],
         }
@@ -469,7 +710,7 @@ pub(super) fn is_covered_by(&self, _pcx: PatCtxt<'_, '_>, other: &Self) -> bool
 
             (IntRange(self_range), IntRange(other_range)) => self_range.is_covered_by(other_range),
             (FloatRange(void), FloatRange(..)) => match *void {},
-            (Str(void), Str(..)) => match *void {},
+            (Str(self_value), Str(other_value)) => self_value == other_value,
             (Slice(self_slice), Slice(other_slice)) => self_slice.is_covered_by(*other_slice),
 
             // We are trying to inspect an opaque constant. Thus we skip the row.
@@ -514,6 +755,45 @@ fn is_covered_by_any(&self, _pcx: PatCtxt<'_, '_>, used_ctors: &[Constructor]) -
             }
         }
     }
+
+    /// Renders the constructor as the pattern that matches everything starting with it, e.g.
+    /// `Some(_)`, `3..=7` or `[_, ..]`, for diagnostics and tracing. `pcx.ty` must be the type the
+    /// constructor belongs to.
+    pub(super) fn display<'a, 'p>(&'a self, pcx: PatCtxt<'a, 'p>) -> DisplayConstructor<'a, 'p> {
+        DisplayConstructor { ctor: self, pcx }
+    }
+}
+
+/// See [`Constructor::display`].
+pub(super) struct DisplayConstructor<'a, 'p> {
+    ctor: &'a Constructor,
+    pcx: PatCtxt<'a, 'p>,
+}
+
+impl fmt::Display for DisplayConstructor<'_, '_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let DisplayConstructor { ctor, pcx } = *self;
+        match ctor {
+            Wildcard | NonExhaustive => f.write_str("_"),
+            Missing { .. } => f.write_str("missing"),
+            Opaque => f.write_str("<opaque constant>"),
+            Or => f.write_str("<or-pattern>"),
+            _ => {
+                let pat = DeconstructedPat::wild_from_ctor(pcx, ctor.clone());
+                f.write_str(&pcx.cx.tcx.display_pat(&pat))
+            }
+        }
+    }
+}
+
+/// Identifies a call to [`Constructor::split`]: the constructor to split, the column it's in and
+/// the constructors seen in that column.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub(super) struct SplitKey {
+    ty: Ty,
+    is_top_level: bool,
+    ctor: Constructor,
+    seen: Vec<Constructor>,
 }
 
 /// A wildcard constructor that we split relative to the constructors in the matrix, as explained
@@ -535,94 +815,71 @@ fn is_covered_by_any(&self, _pcx: PatCtxt<'_, '_>, used_ctors: &[Constructor]) -
 pub(super) struct SplitWildcard {
     /// Constructors seen in the matrix.
     matrix_ctors: Vec<Constructor>,
+    /// For enums, the variants seen in the matrix, which are then left out of `matrix_ctors`: it
+    /// would be slow to search for enums with hundreds of variants.
+    seen_variants: Option<VariantSet>,
     /// All the constructors for this type
     all_ctors: SmallVec<[Constructor; 1]>,
 }
 
-impl SplitWildcard {
-    pub(super) fn new(pcx: PatCtxt<'_, '_>) -> Self {
-        let cx = pcx.cx;
-        let make_range = |start, end, scalar| IntRange(IntRange::from_range(start, end, scalar));
-
-        // Unhandled types are treated as non-exhaustive. Being explicit here instead of falling
-        // to catchall arm to ease further implementation.
-        let unhandled = || smallvec![NonExhaustive];
-
-        // This determines the set of all possible constructors for the type `pcx.ty`. For numbers,
-        // arrays and slices we use ranges and variable-length slices when appropriate.
-        //
-        // If the `exhaustive_patterns` feature is enabled, we make sure to omit constructors that
-        // are statically impossible. E.g., for `Option<!>`, we do not include `Some(_)` in the
-        // returned list of constructors.
-        // Invariant: this is empty if and only if the type is uninhabited (as determined by
-        // `cx.is_uninhabited()`).
-        let all_ctors = match pcx.ty.kind(Interner) {
-            TyKind::Scalar(Scalar::Bool) => smallvec![make_range(0, 1, Scalar::Bool)],
-            // TyKind::Array(..) if ... => unhandled(),
-            TyKind::Array(..) | TyKind::Slice(..) => unhandled(),
-            TyKind::Adt(AdtId(hir_def::AdtId::EnumId(enum_id)), subst) => {
-                let enum_data = cx.db.enum_data(*enum_id);
-
-                // If the enum is declared as `#[non_exhaustive]`, we treat it as if it had an
-                // additional "unknown" constructor.
-                // There is no point in enumerating all possible variants, because the user can't
-                // actually match against them all themselves. So we always return only the fictitious
-                // constructor.
-                // E.g., in an example like:
-                //
-                // ```
-                //     let err: io::ErrorKind = ...;
-                //     match err {
-                //         io::ErrorKind::NotFound => {},
-                //     }
-                // ```
-                //
-                // we don't want to show every possible IO error, but instead have only `_` as the
-                // witness.
-                let is_declared_nonexhaustive = cx.is_foreign_non_exhaustive_enum(pcx.ty);
-
-                let is_exhaustive_pat_feature = cx.feature_exhaustive_patterns();
-
-                // If `exhaustive_patterns` is disabled and our scrutinee is an empty enum, we treat it
-                // as though it had an "unknown" constructor to avoid exposing its emptiness. The
-                // exception is if the pattern is at the top level, because we want empty matches to be
-                // considered exhaustive.
-                let is_secretly_empty = enum_data.variants.is_empty()
-                    && !is_exhaustive_pat_feature
-                    && !pcx.is_top_level;
-
-                let mut ctors: SmallVec<[_; 1]> = enum_data
-                    .variants
-                    .iter()
-                    .map(|(local_id, _)| EnumVariantId { parent: *enum_id, local_id })
-                    .filter(|&variant| {
-                        // If `exhaustive_patterns` is enabled, we exclude variants known to be
-                        // uninhabited.
-                        let is_uninhabited = is_exhaustive_pat_feature
-                            && is_enum_variant_uninhabited_from(variant, subst, cx.module, cx.db);
-                        !is_uninhabited
-                    })
-                    .map(Variant)
-                    .collect();
-
-                if is_secretly_empty || is_declared_nonexhaustive {
+/// The set of all the constructors of a type, which is what a wildcard of that type stands for.
+/// [`SplitWildcard`] splits it relative to the constructors found in a column of the matrix. It
+/// is given by [`TypeCx::ctors_for_ty`](super::usefulness::TypeCx::ctors_for_ty).
+#[derive(Debug)]
+pub(super) enum ConstructorSet {
+    /// The type has a single constructor, e.g. structs, tuples and references.
+    Single,
+    /// The constructors are the listed variants of an enum with `variant_count` variants, leaving
+    /// out those known to be uninhabited. If `non_exhaustive` is set, there is an additional
+    /// constructor that can't be matched other than by a wildcard, e.g. for `#[non_exhaustive]`
+    /// enums from other crates.
+    Variants { variants: Vec<EnumVariantId>, variant_count: usize, non_exhaustive: bool },
+    /// `bool`, whose constructors are `false` and `true`.
+    Bool,
+    /// An integer type of a fixed size, whose constructors are ranges of its values. This is the
+    /// range of all of them, encoded as explained on [`IntRange`].
+    Integers(IntRange),
+    /// Slices of any length allowed by this `Slice`, i.e. arrays, and slices that aren't known
+    /// to be empty.
+    Slice(Slice),
+    /// The type is uninhabited, e.g. `!` or an array of a nonzero number of uninhabited elements.
+    NoConstructors,
+    /// The constructors can't be listed, so only a wildcard matches them all, e.g. `str`, floats,
+    /// `isize` and `usize`, whose range depends on the target, and, for now, `char`.
+    Unlistable,
+}
+
+impl ConstructorSet {
+    /// The constructors of the set, using ranges and variable-length slices where appropriate.
+    /// Invariant: this is empty if and only if the type is uninhabited (as determined by
+    /// the [`TypeCx`](super::usefulness::TypeCx)).
+    fn all_ctors(&self) -> SmallVec<[Constructor; 1]> {
+        match self {
+            ConstructorSet::Single => smallvec![Single],
+            ConstructorSet::Variants { variants, non_exhaustive, .. } => {
+                let mut ctors: SmallVec<[_; 1]> = variants.iter().copied().map(Variant).collect();
+                if *non_exhaustive {
                     ctors.push(NonExhaustive);
                 }
                 ctors
             }
-            TyKind::Scalar(Scalar::Char) => unhandled(),
-            TyKind::Scalar(Scalar::Int(..) | Scalar::Uint(..)) => unhandled(),
-            TyKind::Never if !cx.feature_exhaustive_patterns() && !pcx.is_top_level => {
-                smallvec![NonExhaustive]
-            }
-            TyKind::Never => SmallVec::new(),
-            _ if cx.is_uninhabited(pcx.ty) => SmallVec::new(),
-            TyKind::Adt(..) | TyKind::Tuple(..) | TyKind::Ref(..) => smallvec![Single],
-            // This type is one for which we cannot list constructors, like `str` or `f64`.
-            _ => smallvec![NonExhaustive],
-        };
+            ConstructorSet::Bool => smallvec![IntRange(IntRange::from_range(0, 1, Scalar::Bool))],
+            ConstructorSet::Integers(range) => smallvec![IntRange(range.clone())],
+            ConstructorSet::Slice(slice) => smallvec![Slice(*slice)],
+            ConstructorSet::NoConstructors => SmallVec::new(),
+            ConstructorSet::Unlistable => smallvec![NonExhaustive],
+        }
+    }
+}
 
-        SplitWildcard { matrix_ctors: Vec::new(), all_ctors }
+impl SplitWildcard {
+    pub(super) fn new(pcx: PatCtxt<'_, '_>) -> Self {
+        let ctor_set = pcx.cx.tcx.ctors_for_ty(pcx.ty, pcx.is_top_level);
+        let seen_variants = match ctor_set {
+            ConstructorSet::Variants { variant_count, .. } => Some(VariantSet::new(variant_count)),
+            _ => None,
+        };
+        SplitWildcard { matrix_ctors: Vec::new(), seen_variants, all_ctors: ctor_set.all_ctors() }
     }
 
     /// Pass a set of constructors relative to which to split this one. Don't call twice, it won't
@@ -632,6 +889,17 @@ pub(super) fn split<'a>(
         pcx: PatCtxt<'_, '_>,
         ctors: impl Iterator<Item = &'a Constructor> + Clone,
     ) {
+        if let Some(seen_variants) = &mut self.seen_variants {
+            // Variants don't split any further, so we only need to record which ones are present.
+            for ctor in ctors {
+                match ctor {
+                    Variant(id) => seen_variants.insert(*id),
+                    Wildcard => {}
+                    _ => self.matrix_ctors.push(ctor.clone()),
+                }
+            }
+            return;
+        }
         // Since `all_ctors` never contains wildcards, this won't recurse further.
         self.all_ctors =
             self.all_ctors.iter().flat_map(|ctor| ctor.split(pcx, ctors.clone())).collect();
@@ -648,12 +916,33 @@ pub(super) fn iter_missing<'a, 'p>(
         &'a self,
         pcx: PatCtxt<'a, 'p>,
     ) -> impl Iterator<Item = &'a Constructor> + Captures<'p> {
-        self.all_ctors.iter().filter(move |ctor| !ctor.is_covered_by_any(pcx, &self.matrix_ctors))
+        self.all_ctors.iter().filter(move |ctor| match (ctor, &self.seen_variants) {
+            (Variant(id), Some(seen_variants)) => !seen_variants.contains(*id),
+            _ => !ctor.is_covered_by_any(pcx, &self.matrix_ctors),
+        })
+    }
+
+    /// Iterate over the constructors for this type that are present in the matrix, the complement
+    /// of [`Self::iter_missing`].
+    pub(super) fn iter_present<'a, 'p>(
+        &'a self,
+        pcx: PatCtxt<'a, 'p>,
+    ) -> impl Iterator<Item = &'a Constructor> + Captures<'p> {
+        self.all_ctors.iter().filter(move |ctor| match (ctor, &self.seen_variants) {
+            (Variant(id), Some(seen_variants)) => seen_variants.contains(*id),
+            _ => ctor.is_covered_by_any(pcx, &self.matrix_ctors),
+        })
+    }
+
+    /// Whether the matrix has any constructor other than wildcards.
+    fn any_seen(&self) -> bool {
+        !self.matrix_ctors.is_empty()
+            || self.seen_variants.as_ref().map_or(false, |seen_variants| !seen_variants.is_empty())
     }
 
     /// Return the set of constructors resulting from splitting the wildcard. As explained at the
     /// top of the file, if any constructors are missing we can ignore the present ones.
-    fn into_ctors(self, pcx: PatCtxt<'_, '_>) -> SmallVec<[Constructor; 1]> {
+    pub(super) fn to_ctors(&self, pcx: PatCtxt<'_, '_>) -> SmallVec<[Constructor; 1]> {
         if self.any_missing(pcx) {
             // Some constructors are missing, thus we can specialize with the special `Missing`
             // constructor, which stands for those constructors that are not seen in the matrix,
@@ -683,7 +972,7 @@ pub(super) fn iter_missing<'a, 'p>(
             // The exception is: if we are at the top-level, for example in an empty match, we
             // sometimes prefer reporting the list of constructors instead of just `_`.
             let report_when_all_missing = pcx.is_top_level && !IntRange::is_integral(pcx.ty);
-            let ctor = if !self.matrix_ctors.is_empty() || report_when_all_missing {
+            let ctor = if self.any_seen() || report_when_all_missing {
                 if pcx.is_non_exhaustive {
                     Missing {
                         nonexhaustive_enum_missing_real_variants: self
@@ -700,7 +989,123 @@ pub(super) fn iter_missing<'a, 'p>(
         }
 
         // All the constructors are present in the matrix, so we just go through them all.
-        self.all_ctors
+        self.all_ctors.clone()
+    }
+}
+
+/// A set of variants of an enum, as a bitset indexed by the position of the variants in the enum.
+#[derive(Debug)]
+struct VariantSet {
+    words: SmallVec<[u64; 1]>,
+}
+
+impl VariantSet {
+    const WORD_BITS: usize = u64::BITS as usize;
+
+    fn new(variant_count: usize) -> Self {
+        VariantSet { words: smallvec![0; (variant_count + Self::WORD_BITS - 1) / Self::WORD_BITS] }
+    }
+
+    fn index(id: EnumVariantId) -> (usize, u64) {
+        let idx = u32::from(id.local_id.into_raw()) as usize;
+        (idx / Self::WORD_BITS, 1 << (idx % Self::WORD_BITS))
+    }
+
+    fn insert(&mut self, id: EnumVariantId) {
+        let (word, bit) = Self::index(id);
+        self.words[word] |= bit;
+    }
+
+    fn contains(&self, id: EnumVariantId) -> bool {
+        let (word, bit) = Self::index(id);
+        self.words[word] & bit != 0
+    }
+
+    fn is_empty(&self) -> bool {
+        self.words.iter().all(|&word| word == 0)
+    }
+}
+
+/// The constructors of a `bool` or of an enum whose variants have no fields, when there are few
+/// enough of them for a set of them to fit in the bits of a `u64`. Bit `i` stands for `ctors[i]`.
+///
+/// Matches on such types are very common and need none of the machinery of `is_useful`: a pattern
+/// is just the set of values it matches, so we check them with a few bitwise operations instead.
+pub(super) struct FieldlessCtors {
+    ctors: SmallVec<[Constructor; 2]>,
+}
+
+impl FieldlessCtors {
+    pub(super) fn new(pcx: PatCtxt<'_, '_>) -> Option<Self> {
+        if pcx.is_non_exhaustive {
+            return None;
+        }
+        let ctors: SmallVec<[Constructor; 2]> =
+            match pcx.cx.tcx.ctors_for_ty(pcx.ty, pcx.is_top_level) {
+                ConstructorSet::Bool => smallvec![
+                    IntRange(IntRange::from_bool(false)),
+                    IntRange(IntRange::from_bool(true))
+                ],
+                // Fieldless variants are never uninhabited, so if any variant was left out, it
+                // has fields.
+                ConstructorSet::Variants { variants, variant_count, non_exhaustive: false }
+                    if !variants.is_empty()
+                        && variants.len() == variant_count
+                        && variant_count <= u64::BITS as usize =>
+                {
+                    variants.into_iter().map(Variant).collect()
+                }
+                _ => return None,
+            };
+        if ctors.iter().any(|c| c.arity(pcx) != 0) {
+            return None;
+        }
+        // Hidden variants change which witnesses we report, leave them to the general algorithm.
+        if ctors.iter().any(|c| c.is_doc_hidden_variant(pcx) || c.is_unstable_variant(pcx)) {
+            return None;
+        }
+        Some(FieldlessCtors { ctors })
+    }
+
+    fn all(&self) -> u64 {
+        u64::MAX >> (u64::BITS as usize - self.ctors.len())
+    }
+
+    /// The set of values matched by `pat`, or `None` if `pat` isn't made only of the constructors
+    /// of this type, e.g. because it mentions an opaque constant.
+    pub(super) fn mask(&self, pcx: PatCtxt<'_, '_>, pat: &DeconstructedPat<'_>) -> Option<u64> {
+        match pat.ctor() {
+            Wildcard => Some(self.all()),
+            Or => pat.iter_fields().try_fold(0, |mask, alt| Some(mask | self.mask(pcx, alt)?)),
+            ctor @ (Variant(_) | IntRange(_)) => Some(
+                self.ctors
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, c)| c.is_covered_by(pcx, ctor))
+                    .fold(0, |mask, (i, _)| mask | 1 << i),
+            ),
+            _ => None,
+        }
+    }
+
+    /// The patterns not matched by any pattern in `covered`, reported the same way `is_useful`
+    /// would report them.
+    pub(super) fn witnesses<'p>(
+        &self,
+        pcx: PatCtxt<'_, 'p>,
+        covered: u64,
+    ) -> Vec<DeconstructedPat<'p>> {
+        // See `SplitWildcard::to_ctors`: when no constructor of an integral type was mentioned, we
+        // only report `_`.
+        if covered == 0 && IntRange::is_integral(pcx.ty) {
+            return vec![DeconstructedPat::wildcard(pcx.ty.clone())];
+        }
+        self.ctors
+            .iter()
+            .enumerate()
+            .filter(|&(i, _)| covered & 1 << i == 0)
+            .map(|(_, ctor)| DeconstructedPat::wild_from_ctor(pcx, ctor.clone()))
+            .collect()
     }
 }
 
@@ -735,101 +1140,36 @@ fn empty() -> Self {
         Fields { fields: &[] }
     }
 
-    fn singleton(cx: &MatchCheckCtx<'_, 'p>, field: DeconstructedPat<'p>) -> Self {
-        let field = cx.pattern_arena.alloc(field);
+    fn singleton(arena: &'p Arena<DeconstructedPat<'p>>, field: DeconstructedPat<'p>) -> Self {
+        let field = arena.alloc(field);
         Fields { fields: std::slice::from_ref(field) }
     }
 
     pub(super) fn from_iter(
-        cx: &MatchCheckCtx<'_, 'p>,
+        arena: &'p Arena<DeconstructedPat<'p>>,
         fields: impl IntoIterator<Item = DeconstructedPat<'p>>,
     ) -> Self {
-        let fields: &[_] = cx.pattern_arena.alloc_extend(fields);
+        let fields: &[_] = arena.alloc_extend(fields);
         Fields { fields }
     }
 
-    fn wildcards_from_tys(cx: &MatchCheckCtx<'_, 'p>, tys: impl IntoIterator<Item = Ty>) -> Self {
-        Fields::from_iter(cx, tys.into_iter().map(DeconstructedPat::wildcard))
-    }
-
-    // In the cases of either a `#[non_exhaustive]` field list or a non-public field, we hide
-    // uninhabited fields in order not to reveal the uninhabitedness of the whole variant.
-    // This lists the fields we keep along with their types.
-    fn list_variant_nonhidden_fields<'a>(
-        cx: &'a MatchCheckCtx<'a, 'p>,
-        ty: &'a Ty,
-        variant: VariantId,
-    ) -> impl Iterator<Item = (LocalFieldId, Ty)> + Captures<'a> + Captures<'p> {
-        let (adt, substs) = ty.as_adt().unwrap();
-
-        let adt_is_local = variant.module(cx.db.upcast()).krate() == cx.module.krate();
-        // Whether we must not match the fields of this variant exhaustively.
-        let is_non_exhaustive = is_field_list_non_exhaustive(variant, cx) && !adt_is_local;
-
-        let visibility = cx.db.field_visibilities(variant);
-        let field_ty = cx.db.field_types(variant);
-        let fields_len = variant.variant_data(cx.db.upcast()).fields().len() as u32;
-
-        (0..fields_len).map(|idx| LocalFieldId::from_raw(idx.into())).filter_map(move |fid| {
-            let ty = field_ty[fid].clone().substitute(Interner, substs);
-            let ty = normalize(cx.db, cx.body, ty);
-            let is_visible = matches!(adt, hir_def::AdtId::EnumId(..))
-                || visibility[fid].is_visible_from(cx.db.upcast(), cx.module);
-            let is_uninhabited = cx.is_uninhabited(&ty);
-
-            if is_uninhabited && (!is_visible || is_non_exhaustive) {
-                None
-            } else {
-                Some((fid, ty))
-            }
-        })
-    }
-
-    /// Creates a new list of wildcard fields for a given constructor. The result must have a
-    /// length of `constructor.arity()`.
+    /// Creates a new list of wildcard fields for a given constructor, with the types given by
+    /// [`TypeCx::ctor_sub_tys`](super::usefulness::TypeCx::ctor_sub_tys). Lists are interned per
+    /// type and constructor, so repeated specializations of the same column share their
+    /// wildcards.
     pub(crate) fn wildcards(
-        cx: &MatchCheckCtx<'_, 'p>,
+        cx: &UsefulnessCtxt<'_, 'p>,
         ty: &Ty,
         constructor: &Constructor,
     ) -> Self {
-        let ret = match constructor {
-            Single | Variant(_) => match ty.kind(Interner) {
-                TyKind::Tuple(_, substs) => {
-                    let tys = substs.iter(Interner).map(|ty| ty.assert_ty_ref(Interner));
-                    Fields::wildcards_from_tys(cx, tys.cloned())
-                }
-                TyKind::Ref(.., rty) => Fields::wildcards_from_tys(cx, once(rty.clone())),
-                &TyKind::Adt(AdtId(adt), ref substs) => {
-                    if is_box(adt, cx.db) {
-                        // The only legal patterns of type `Box` (outside `std`) are `_` and box
-                        // patterns. If we're here we can assume this is a box pattern.
-                        let subst_ty = substs.at(Interner, 0).assert_ty_ref(Interner).clone();
-                        Fields::wildcards_from_tys(cx, once(subst_ty))
-                    } else {
-                        let variant = constructor.variant_id_for_adt(adt);
-                        let tys = Fields::list_variant_nonhidden_fields(cx, ty, variant)
-                            .map(|(_, ty)| ty);
-                        Fields::wildcards_from_tys(cx, tys)
-                    }
-                }
-                ty_kind => {
-                    never!("Unexpected type for `Single` constructor: {:?}", ty_kind);
-                    Fields::wildcards_from_tys(cx, once(ty.clone()))
-                }
-            },
-            Slice(slice) => match slice._unimplemented {},
-            Str(..)
-            | FloatRange(..)
-            | IntRange(..)
-            | NonExhaustive
-            | Opaque
-            | Missing { .. }
-            | Wildcard => Fields::empty(),
-            Or => {
-                never!("called `Fields::wildcards` on an `Or` ctor");
-                Fields::empty()
-            }
-        };
+        let key = (ty.clone(), constructor.clone());
+        if let Some(&fields) = cx.wildcard_fields.borrow().get(&key) {
+            return fields;
+        }
+        let tys = cx.tcx.ctor_sub_tys(constructor, ty);
+        let ret =
+            Fields::from_iter(cx.pattern_arena, tys.into_iter().map(DeconstructedPat::wildcard));
+        cx.wildcard_fields.borrow_mut().insert(key, ret);
         ret
     }
 
@@ -850,6 +1190,11 @@ pub(crate) struct DeconstructedPat<'p> {
     ctor: Constructor,
     fields: Fields<'p>,
     ty: Ty,
+    /// The name and binding mode the user bound this pattern with, e.g. `x` in `x` or
+    /// `ref x @ Some(_)`.
+    binding: Option<(Name, BindingMode)>,
+    /// The pattern of the body this was lowered from, see [`Pat::id`].
+    id: Option<PatId>,
     reachable: Cell<bool>,
 }
 
@@ -859,7 +1204,7 @@ pub(super) fn wildcard(ty: Ty) -> Self {
     }
 
     pub(super) fn new(ctor: Constructor, fields: Fields<'p>, ty: Ty) -> Self {
-        DeconstructedPat { ctor, fields, ty, reachable: Cell::new(false) }
+        DeconstructedPat { ctor, fields, ty, binding: None, id: None, reachable: Cell::new(false) }
     }
 
     /// Construct a pattern that matches everything that starts with this constructor.
@@ -873,25 +1218,41 @@ pub(super) fn wild_from_ctor(pcx: PatCtxt<'_, 'p>, ctor: Constructor) -> Self {
     /// Clone this value. This method emphasizes that cloning loses reachability information and
     /// should be done carefully.
     pub(super) fn clone_and_forget_reachability(&self) -> Self {
-        DeconstructedPat::new(self.ctor.clone(), self.fields, self.ty.clone())
+        let mut pat = DeconstructedPat::new(self.ctor.clone(), self.fields, self.ty.clone());
+        pat.binding = self.binding.clone();
+        pat.id = self.id;
+        pat
     }
 
     pub(crate) fn from_pat(cx: &MatchCheckCtx<'_, 'p>, pat: &Pat) -> Self {
-        let mkpat = |pat| DeconstructedPat::from_pat(cx, pat);
+        let mkpat = |pat| ensure_sufficient_stack(|| DeconstructedPat::from_pat(cx, pat));
+        let ty = cx.normalize(pat.ty.clone());
+        let id = pat.id;
         let ctor;
         let fields;
+        let mut binding = None;
         match pat.kind.as_ref() {
-            PatKind::Binding { subpattern: Some(subpat), .. } => return mkpat(subpat),
-            PatKind::Binding { subpattern: None, .. } | PatKind::Wild => {
+            PatKind::Binding { name, mode, subpattern: Some(subpat) } => {
+                let mut pat = mkpat(subpat);
+                pat.binding = Some((name.clone(), *mode));
+                pat.id = id.or(pat.id);
+                return pat;
+            }
+            PatKind::Binding { name, mode, subpattern: None } => {
+                ctor = Wildcard;
+                fields = Fields::empty();
+                binding = Some((name.clone(), *mode));
+            }
+            PatKind::Wild => {
                 ctor = Wildcard;
                 fields = Fields::empty();
             }
             PatKind::Deref { subpattern } => {
                 ctor = Single;
-                fields = Fields::singleton(cx, mkpat(subpattern));
+                fields = Fields::singleton(cx.pattern_arena, mkpat(subpattern));
             }
             PatKind::Leaf { subpatterns } | PatKind::Variant { subpatterns, .. } => {
-                match pat.ty.kind(Interner) {
+                match ty.kind(Interner) {
                     TyKind::Tuple(_, substs) => {
                         ctor = Single;
                         let mut wilds: SmallVec<[_; 2]> = substs
@@ -903,7 +1264,7 @@ pub(crate) fn from_pat(cx: &MatchCheckCtx<'_, 'p>, pat: &Pat) -> Self {
                             let idx: u32 = pat.field.into_raw().into();
                             wilds[idx as usize] = mkpat(&pat.pattern);
                         }
-                        fields = Fields::from_iter(cx, wilds)
+                        fields = Fields::from_iter(cx.pattern_arena, wilds)
                     }
                     TyKind::Adt(adt, substs) if is_box(adt.0, cx.db) => {
                         // The only legal patterns of type `Box` (outside `std`) are `_` and box
@@ -927,7 +1288,7 @@ pub(crate) fn from_pat(cx: &MatchCheckCtx<'_, 'p>, pat: &Pat) -> Self {
                             DeconstructedPat::wildcard(ty)
                         };
                         ctor = Single;
-                        fields = Fields::singleton(cx, field)
+                        fields = Fields::singleton(cx.pattern_arena, field)
                     }
                     &TyKind::Adt(adt, _) => {
                         ctor = match pat.kind.as_ref() {
@@ -942,13 +1303,13 @@ pub(crate) fn from_pat(cx: &MatchCheckCtx<'_, 'p>, pat: &Pat) -> Self {
                         let fields_len = variant.variant_data(cx.db.upcast()).fields().len();
                         // For each field in the variant, we store the relevant index into `self.fields` if any.
                         let mut field_id_to_id: Vec<Option<usize>> = vec![None; fields_len];
-                        let tys = Fields::list_variant_nonhidden_fields(cx, &pat.ty, variant)
-                            .enumerate()
-                            .map(|(i, (fid, ty))| {
+                        let tys = cx.list_variant_nonhidden_fields(&ty, variant).enumerate().map(
+                            |(i, (fid, ty))| {
                                 let field_idx: u32 = fid.into_raw().into();
                                 field_id_to_id[field_idx as usize] = Some(i);
                                 ty
-                            });
+                            },
+                        );
                         let mut wilds: SmallVec<[_; 2]> =
                             tys.map(DeconstructedPat::wildcard).collect();
                         for pat in subpatterns {
@@ -957,29 +1318,77 @@ pub(crate) fn from_pat(cx: &MatchCheckCtx<'_, 'p>, pat: &Pat) -> Self {
                                 wilds[i] = mkpat(&pat.pattern);
                             }
                         }
-                        fields = Fields::from_iter(cx, wilds);
+                        fields = Fields::from_iter(cx.pattern_arena, wilds);
                     }
                     _ => {
-                        never!("pattern has unexpected type: pat: {:?}, ty: {:?}", pat, &pat.ty);
-                        ctor = Wildcard;
+                        never!("pattern has unexpected type: pat: {:?}, ty: {:?}", pat, &ty);
+                        ctor = Opaque;
                         fields = Fields::empty();
                     }
                 }
             }
+            PatKind::Error => {
+                // A pattern that failed to lower covers nothing, like an opaque constant, so it
+                // doesn't hide the arms after it. It has no id, so it isn't reported as
+                // unreachable either.
+                return DeconstructedPat::new(Opaque, Fields::empty(), ty);
+            }
+            PatKind::Opaque => {
+                ctor = Opaque;
+                fields = Fields::empty();
+            }
+            PatKind::Slice { prefix, slice, suffix } => {
+                let array_len = match ty.kind(Interner) {
+                    TyKind::Array(_, len) => array_len(len),
+                    TyKind::Slice(_) => None,
+                    _ => {
+                        never!("bad ty {:?} for slice pattern", ty);
+                        None
+                    }
+                };
+                let kind = if slice.is_some() {
+                    VarLen(prefix.len(), suffix.len())
+                } else {
+                    FixedLen(prefix.len() + suffix.len())
+                };
+                ctor = Slice(Slice::new(array_len, kind));
+                let pats: SmallVec<[_; 2]> = prefix.iter().chain(suffix).map(mkpat).collect();
+                fields = Fields::from_iter(cx.pattern_arena, pats);
+            }
             &PatKind::LiteralBool { value } => {
                 ctor = IntRange(IntRange::from_bool(value));
                 fields = Fields::empty();
             }
-            PatKind::Or { .. } => {
+            &PatKind::Range { lo, hi } => {
+                ctor = match IntWidth::of(&ty) {
+                    Some(width) => IntRange(IntRange::from_bits(width, lo, hi)),
+                    None => {
+                        never!("bad ty {:?} for range pattern", ty);
+                        Wildcard
+                    }
+                };
+                fields = Fields::empty();
+            }
+            PatKind::Str { value } => {
+                ctor = Str(value.clone());
+                fields = Fields::empty();
+            }
+            PatKind::Or { pats } => {
+                // Nested or-patterns like `1 | (2 | 3)` are kept as they are written rather than
+                // flattened, so that an inner or-pattern whose alternatives are all unreachable
+                // is reported as a whole.
                 ctor = Or;
-                let pats: SmallVec<[_; 2]> = expand_or_pat(pat).into_iter().map(mkpat).collect();
-                fields = Fields::from_iter(cx, pats)
+                let pats: SmallVec<[_; 2]> = pats.iter().map(mkpat).collect();
+                fields = Fields::from_iter(cx.pattern_arena, pats)
             }
         }
-        DeconstructedPat::new(ctor, fields, pat.ty.clone())
+        let mut pat = DeconstructedPat::new(ctor, fields, ty);
+        pat.binding = binding;
+        pat.id = id;
+        pat
     }
 
-    pub(crate) fn to_pat(&self, cx: &MatchCheckCtx<'_, 'p>) -> Pat {
+    pub(crate) fn to_pat(&self, cx: &MatchCheckCtx<'_, '_>) -> Pat {
         let mut subpatterns = self.iter_fields().map(|p| p.to_pat(cx));
         let pat = match &self.ctor {
             Single | Variant(_) => match self.ty.kind(Interner) {
@@ -1000,7 +1409,8 @@ pub(crate) fn to_pat(&self, cx: &MatchCheckCtx<'_, 'p>) -> Pat {
                 }
                 TyKind::Adt(adt, substs) => {
                     let variant = self.ctor.variant_id_for_adt(adt.0);
-                    let subpatterns = Fields::list_variant_nonhidden_fields(cx, self.ty(), variant)
+                    let subpatterns = cx
+                        .list_variant_nonhidden_fields(self.ty(), variant)
                         .zip(subpatterns)
                         .map(|((field, _ty), pattern)| FieldPat { field, pattern })
                         .collect();
@@ -1011,20 +1421,46 @@ pub(crate) fn to_pat(&self, cx: &MatchCheckCtx<'_, 'p>) -> Pat {
                         PatKind::Leaf { subpatterns }
                     }
                 }
-                // Note: given the expansion of `&str` patterns done in `expand_pattern`, we should
-                // be careful to reconstruct the correct constant pattern here. However a string
-                // literal pattern will never be reported as a non-exhaustiveness witness, so we
-                // ignore this issue.
+                // `&str` patterns are `Deref` patterns of `Str` ones, see `PatKind::Str`, so this
+                // also reconstructs string literal patterns.
                 TyKind::Ref(..) => PatKind::Deref { subpattern: subpatterns.next().unwrap() },
                 _ => {
                     never!("unexpected ctor for type {:?} {:?}", self.ctor, self.ty);
                     PatKind::Wild
                 }
             },
-            &Slice(slice) => match slice._unimplemented {},
-            &Str(void) => match void {},
+            Slice(slice) => match slice.kind {
+                FixedLen(_) => {
+                    PatKind::Slice { prefix: subpatterns.collect(), slice: None, suffix: vec![] }
+                }
+                VarLen(prefix, _) => {
+                    let mut prefix: Vec<_> = subpatterns.by_ref().take(prefix).collect();
+                    if slice.array_len.is_some() {
+                        // Improves diagnostics a bit: if the type is a known-size array, instead
+                        // of reporting `[x, _, .., _, y]`, we prefer to report `[x, .., y]`.
+                        // This is incorrect if the size is not known, since `[_, ..]` captures
+                        // arrays of lengths `>= 1` whereas `[..]` captures any length.
+                        while !prefix.is_empty() && prefix.last().unwrap().is_wildcard() {
+                            prefix.pop();
+                        }
+                    }
+                    let suffix: Vec<_> = if slice.array_len.is_some() {
+                        // Same as above.
+                        subpatterns.skip_while(Pat::is_wildcard).collect()
+                    } else {
+                        subpatterns.collect()
+                    };
+                    let wild = Pat { ty: self.ty.clone(), kind: Box::new(PatKind::Wild), id: None };
+                    PatKind::Slice { prefix, slice: Some(wild), suffix }
+                }
+            },
+            Str(value) => PatKind::Str { value: value.clone() },
             &FloatRange(void) => match void {},
-            IntRange(range) => return range.to_pat(cx, self.ty.clone()),
+            IntRange(range) => {
+                // The values of a pattern type are written as values of its base type.
+                let ty = self.ty.as_pattern_type(cx.db).map_or(&self.ty, |(base, _)| base);
+                return range.to_pat(ty.clone());
+            }
             Wildcard | NonExhaustive => PatKind::Wild,
             Missing { .. } => {
                 never!(
@@ -1033,18 +1469,150 @@ pub(crate) fn to_pat(&self, cx: &MatchCheckCtx<'_, 'p>) -> Pat {
                 );
                 PatKind::Wild
             }
-            Opaque | Or => {
+            // Witnesses are grouped into or-patterns for display, see `group_by_first_field`.
+            Or => PatKind::Or { pats: subpatterns.collect() },
+            Opaque => {
                 never!("can't convert to pattern: {:?}", self.ctor);
                 PatKind::Wild
             }
         };
-        Pat { ty: self.ty.clone(), kind: Box::new(pat) }
+        let pat = match &self.binding {
+            Some((name, mode)) if matches!(pat, PatKind::Wild) => {
+                PatKind::Binding { name: name.clone(), mode: *mode, subpattern: None }
+            }
+            Some((name, mode)) => PatKind::Binding {
+                name: name.clone(),
+                mode: *mode,
+                subpattern: Some(Pat { ty: self.ty.clone(), kind: Box::new(pat), id: None }),
+            },
+            None => pat,
+        };
+        Pat { ty: self.ty.clone(), kind: Box::new(pat), id: None }
     }
 
     pub(super) fn is_or_pat(&self) -> bool {
         matches!(self.ctor, Or)
     }
 
+    /// The number of rows this pattern gives once all its or-patterns are expanded, saturating at
+    /// `usize::MAX`.
+    pub(crate) fn or_expansion_count(&self) -> usize {
+        let counts = self.iter_fields().map(|p| p.or_expansion_count());
+        if self.is_or_pat() {
+            counts.fold(0, usize::saturating_add)
+        } else {
+            counts.fold(1, usize::saturating_mul)
+        }
+    }
+
+    /// The patterns `self` stands for once its or-patterns are expanded, e.g. `(A | B, C | D)`
+    /// gives `(A, C)`, `(A, D)`, `(B, C)` and `(B, D)`. The patterns are built as the iterator is
+    /// consumed, so taking only the first few of them is cheap.
+    pub(crate) fn expand_or_pats<'a>(
+        &'a self,
+        arena: &'p Arena<DeconstructedPat<'p>>,
+    ) -> Box<dyn Iterator<Item = DeconstructedPat<'p>> + 'a> {
+        if self.is_or_pat() {
+            return Box::new(self.iter_fields().flat_map(move |alt| alt.expand_or_pats(arena)));
+        }
+        if self.or_expansion_count() == 1 {
+            return Box::new(once(self.clone_and_forget_reachability()));
+        }
+        let fields = self.iter_fields().map(|field| {
+            field.expand_or_pats(arena).map(|pat| &*arena.alloc(pat)).collect::<Vec<_>>()
+        });
+        Box::new(fields.multi_cartesian_product().map(move |fields| {
+            let fields = fields.into_iter().map(DeconstructedPat::clone_and_forget_reachability);
+            DeconstructedPat::new(
+                self.ctor.clone(),
+                Fields::from_iter(arena, fields),
+                self.ty.clone(),
+            )
+        }))
+    }
+
+    /// A pattern like `self` where each or-pattern is replaced with a wildcard, i.e. a pattern that
+    /// matches at least the values `self` matches but has no alternatives to expand.
+    pub(super) fn without_or_pats(
+        &self,
+        arena: &'p Arena<DeconstructedPat<'p>>,
+    ) -> DeconstructedPat<'p> {
+        if self.is_or_pat() {
+            return DeconstructedPat::wildcard(self.ty.clone());
+        }
+        let fields: SmallVec<[_; 2]> =
+            self.iter_fields().map(|p| p.without_or_pats(arena)).collect();
+        let fields = Fields::from_iter(arena, fields);
+        DeconstructedPat::new(self.ctor.clone(), fields, self.ty.clone())
+    }
+
+    /// Groups the witnesses of a tuple type that start with the same pattern, so that missing
+    /// arms are reported per first field: `(A, X)` and `(A, Y)` are merged into `(A, X | Y)` when
+    /// the other fields are the same, and otherwise listed next to each other. `witnesses` must
+    /// not contain or-patterns.
+    pub(crate) fn group_by_first_field(
+        arena: &'p Arena<DeconstructedPat<'p>>,
+        witnesses: Vec<DeconstructedPat<'p>>,
+    ) -> Vec<DeconstructedPat<'p>> {
+        let first_field = |pat: &DeconstructedPat<'p>| match (&pat.ctor, pat.ty.kind(Interner)) {
+            (Single, TyKind::Tuple(arity, ..)) if *arity >= 2 => pat.iter_fields().next(),
+            _ => None,
+        };
+        let mut groups: Vec<Vec<DeconstructedPat<'p>>> = Vec::new();
+        for witness in witnesses {
+            let group = first_field(&witness).and_then(|head| {
+                groups.iter_mut().find(|group| {
+                    first_field(&group[0]).map_or(false, |other| other.is_same_as(head))
+                })
+            });
+            match group {
+                Some(group) => group.push(witness),
+                None => groups.push(vec![witness]),
+            }
+        }
+        groups
+            .into_iter()
+            .flat_map(|group| {
+                if group.len() == 1 {
+                    return group;
+                }
+                let first = &group[0];
+                let mut differing = (1..first.fields.fields.len()).filter(|&idx| {
+                    group
+                        .iter()
+                        .any(|it| !it.fields.fields[idx].is_same_as(&first.fields.fields[idx]))
+                });
+                let (Some(idx), None) = (differing.next(), differing.next()) else {
+                    return group;
+                };
+                let alternatives = Fields::from_iter(
+                    arena,
+                    group.iter().map(|it| it.fields.fields[idx].clone_and_forget_reachability()),
+                );
+                let mut fields: Vec<_> =
+                    first.iter_fields().map(|it| it.clone_and_forget_reachability()).collect();
+                fields[idx] = DeconstructedPat::new(Or, alternatives, fields[idx].ty.clone());
+                let fields = Fields::from_iter(arena, fields);
+                vec![DeconstructedPat::new(Single, fields, first.ty.clone())]
+            })
+            .collect()
+    }
+
+    /// The minimum length of the slices this witness stands for, if it is a pattern of a slice
+    /// type, possibly behind references, that doesn't constrain its elements: `[_, _, ..]`
+    /// stands for the slices of length 2 or more.
+    pub(crate) fn min_slice_len(&self) -> Option<usize> {
+        match (&self.ctor, self.ty.kind(Interner)) {
+            (Single, TyKind::Ref(..)) => self.iter_fields().next()?.min_slice_len(),
+            (Slice(Slice { kind: VarLen(prefix, suffix), .. }), TyKind::Slice(_))
+                if self.iter_fields().all(|field| field.ctor.is_wildcard()) =>
+            {
+                Some(prefix + suffix)
+            }
+            _ => None,
+        }
+    }
+
     pub(super) fn ctor(&self) -> &Constructor {
         &self.ctor
     }
@@ -1057,11 +1625,23 @@ pub(super) fn iter_fields<'a>(&'a self) -> impl Iterator<Item = &'p Deconstructe
         self.fields.iter_patterns()
     }
 
+    /// Whether `self` and `other` are the same pattern, regardless of reachability and binding
+    /// names.
+    pub(super) fn is_same_as(&self, other: &Self) -> bool {
+        self.ctor == other.ctor
+            && self.ty == other.ty
+            && self.fields.fields.len() == other.fields.fields.len()
+            && self
+                .iter_fields()
+                .zip(other.iter_fields())
+                .all(|(field, other)| ptr::eq(field, other) || field.is_same_as(other))
+    }
+
     /// Specialize this pattern with a constructor.
     /// `other_ctor` can be different from `self.ctor`, but must be covered by it.
     pub(super) fn specialize<'a>(
         &'a self,
-        cx: &MatchCheckCtx<'_, 'p>,
+        cx: &UsefulnessCtxt<'_, 'p>,
         other_ctor: &Constructor,
     ) -> SmallVec<[&'p DeconstructedPat<'p>; 2]> {
         match (&self.ctor, other_ctor) {
@@ -1072,27 +1652,156 @@ pub(super) fn specialize<'a>(
             (Slice(self_slice), Slice(other_slice))
                 if self_slice.arity() != other_slice.arity() =>
             {
-                match self_slice._unimplemented {}
+                // The only non-trivial case: two slices of different arity. `other_slice` is
+                // guaranteed to have a larger arity, so we fill the middle part with enough
+                // wildcards to reach the length of the new, larger slice.
+                match self_slice.kind {
+                    FixedLen(_) => {
+                        never!("{:?} doesn't cover {:?}", self_slice, other_slice);
+                        Fields::wildcards(cx, &self.ty, other_ctor).iter_patterns().collect()
+                    }
+                    VarLen(prefix, suffix) => {
+                        let inner_ty = match self.ty.kind(Interner) {
+                            TyKind::Slice(ty) | TyKind::Array(ty, _) => ty.clone(),
+                            _ => {
+                                never!("bad slice pattern {:?} {:?}", self.ctor, self.ty);
+                                return smallvec![];
+                            }
+                        };
+                        let prefix = &self.fields.fields[..prefix];
+                        let suffix = &self.fields.fields[self_slice.arity() - suffix..];
+                        let wildcard: &_ =
+                            cx.pattern_arena.alloc(DeconstructedPat::wildcard(inner_ty));
+                        let extra_wildcards = other_slice.arity() - self_slice.arity();
+                        let extra_wildcards = (0..extra_wildcards).map(|_| wildcard);
+                        prefix.iter().chain(extra_wildcards).chain(suffix).collect()
+                    }
+                }
             }
             _ => self.fields.iter_patterns().collect(),
         }
     }
 
     /// We keep track for each pattern if it was ever reachable during the analysis. This is used
-    /// with `unreachable_spans` to report unreachable subpatterns arising from or patterns.
+    /// with `unreachable_alternatives` to report unreachable subpatterns arising from or patterns.
     pub(super) fn set_reachable(&self) {
         self.reachable.set(true)
     }
     pub(super) fn is_reachable(&self) -> bool {
         self.reachable.get()
     }
+
+    pub(crate) fn id(&self) -> Option<PatId> {
+        self.id
+    }
+
+    /// The range patterns among `others` that share only an endpoint with this range pattern,
+    /// with the value they share.
+    pub(super) fn overlapping_range_endpoints(
+        &self,
+        others: impl Iterator<Item = &'p DeconstructedPat<'p>>,
+    ) -> Vec<(PatId, Pat)> {
+        let Some(range) = self.ctor.as_int_range() else { return Vec::new() };
+        others
+            .filter_map(|other| {
+                let other_range = other.ctor.as_int_range()?;
+                if !range.suspicious_intersection(other_range) {
+                    return None;
+                }
+                let overlap = range.intersection(other_range)?.to_pat(self.ty.clone());
+                Some((other.id?, overlap))
+            })
+            .collect()
+    }
+
+    /// The alternatives of the or-patterns in this pattern that were never found reachable, at any
+    /// depth. The alternatives of unreachable alternatives aren't listed.
+    pub(super) fn unreachable_alternatives(&self) -> Vec<PatId> {
+        fn collect(pat: &DeconstructedPat<'_>, acc: &mut Vec<PatId>) {
+            if pat.is_or_pat() {
+                for alt in pat.iter_fields() {
+                    if !alt.is_reachable() {
+                        acc.extend(alt.source_id());
+                    } else {
+                        collect(alt, acc);
+                    }
+                }
+            } else {
+                for field in pat.iter_fields() {
+                    collect(field, acc);
+                }
+            }
+        }
+        let mut acc = Vec::new();
+        collect(self, &mut acc);
+        acc
+    }
+
+    /// The pattern of the body this was lowered from, looking through the dereferences match
+    /// ergonomics adds around it, which have no pattern of their own.
+    fn source_id(&self) -> Option<PatId> {
+        self.id.or_else(|| match (&self.ctor, self.ty.kind(Interner)) {
+            (Single, TyKind::Ref(..)) => self.iter_fields().next()?.source_id(),
+            _ => None,
+        })
+    }
+}
+
+/// This is mostly copied from the `Pat` impl. This is best effort and not good enough for a
+/// `Display` impl: without the database we don't know the names of variants and fields, so a
+/// variant is shown as `#` followed by its index, e.g. `Some(_)` is `#1(_)`, and integer ranges
+/// are shown with their biased encoding.
+impl<'p> fmt::Debug for DeconstructedPat<'p> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.ctor {
+            Single | Variant(_) => match self.ty.kind(Interner) {
+                TyKind::Ref(mutbl, ..) => {
+                    let subpattern = self.iter_fields().next().unwrap();
+                    let prefix = if *mutbl == Mutability::Mut { "mut " } else { "" };
+                    write!(f, "&{prefix}{subpattern:?}")
+                }
+                TyKind::Adt(..) | TyKind::Tuple(..) => {
+                    if let Variant(variant) = &self.ctor {
+                        write!(f, "#{}", u32::from(variant.local_id.into_raw()))?;
+                    }
+                    write!(f, "({:?})", self.iter_fields().format(", "))
+                }
+                _ => write!(f, "_"),
+            },
+            Slice(slice) => {
+                let mut subpatterns = self.iter_fields();
+                write!(f, "[")?;
+                match slice.kind {
+                    FixedLen(_) => write!(f, "{:?}", subpatterns.format(", "))?,
+                    VarLen(prefix, _) => {
+                        let prefix: Vec<_> =
+                            subpatterns.by_ref().take(prefix).map(|p| format!("{p:?}")).collect();
+                        let suffix = subpatterns.map(|p| format!("{p:?}"));
+                        let pats = prefix.into_iter().chain(once("..".to_owned())).chain(suffix);
+                        write!(f, "{}", pats.format(", "))?
+                    }
+                }
+                write!(f, "]")
+            }
+            IntRange(range) => {
+                let (lo, hi) = range.boundaries();
+                if lo == hi {
+                    write!(f, "{lo}")
+                } else {
+                    write!(f, "{lo}..={hi}")
+                }
+            }
+            Str(value) => write!(f, "{value:?}"),
+            &FloatRange(void) => match void {},
+            Opaque => write!(f, "<constant pattern>"),
+            Wildcard | Missing { .. } | NonExhaustive => write!(f, "_"),
+            Or => write!(f, "{:?}", self.iter_fields().format(" | ")),
+        }
+    }
 }
 
-fn is_field_list_non_exhaustive(variant_id: VariantId, cx: &MatchCheckCtx<'_, '_>) -> bool {
-    let attr_def_id = match variant_id {
-        VariantId::EnumVariantId(id) => id.into(),
-        VariantId::StructId(id) => id.into(),
-        VariantId::UnionId(id) => id.into(),
-    };
-    cx.db.attrs(attr_def_id).by_key("non_exhaustive").exists()
+/// The length of an array type, if it is known. Arrays whose length is a generic const or can't be
+/// evaluated are checked as if they were slices.
+pub(super) fn array_len(len: &Const) -> Option<usize> {
+    try_const_usize(len).and_then(|len| usize::try_from(len).ok())
 }
@@ -0,0 +1,345 @@
+//! [`MatchCheckCtx`], the [`TypeCx`] of the types of a body: it answers the questions the
+//! usefulness algorithm asks about types, like which constructors a type has or what the types of
+//! their fields are, with the database. It also lowers patterns to and from
+//! [`DeconstructedPat`]s, see [`DeconstructedPat::from_pat`] and [`DeconstructedPat::to_pat`].
+
+use chalk_ir::{IntTy, UintTy};
+use hir_def::{AdtId, DefWithBodyId, EnumVariantId, HasModule, LocalFieldId, ModuleId, VariantId};
+use smallvec::{smallvec, SmallVec};
+use stdx::never;
+use typed_arena::Arena;
+
+use crate::{
+    db::HirDatabase,
+    display::HirDisplay,
+    infer::{normalize, reveal_opaque_ty},
+    inhabitedness::{is_enum_variant_uninhabited_from, is_ty_uninhabited_from},
+    Interner, Scalar, Ty, TyExt, TyKind,
+};
+
+use super::{
+    deconstruct_pat::{
+        array_len, Constructor, ConstructorSet, DeconstructedPat, IntRange, Slice, SliceKind,
+    },
+    is_box,
+    usefulness::{
+        compute_match_usefulness, helper::Captures, MatchArm, TooComplex, TypeCx, UsefulnessCtxt,
+        UsefulnessReport,
+    },
+    IntWidth,
+};
+
+/// The default for `#![pattern_complexity]`. The usefulness algorithm is exponential in the worst
+/// case, so we bound the work done on a single match rather than freeze analysis.
+const DEFAULT_COMPLEXITY_LIMIT: usize = 1_000_000;
+
+pub(crate) struct MatchCheckCtx<'a, 'p> {
+    pub(crate) module: ModuleId,
+    pub(crate) body: DefWithBodyId,
+    pub(crate) db: &'a dyn HirDatabase,
+    /// Lowered patterns from arms plus generated by the check.
+    pub(crate) pattern_arena: &'p Arena<DeconstructedPat<'p>>,
+    /// The rows of the matrices built during the check, see [`UsefulnessCtxt`].
+    row_arena: &'p Arena<&'p DeconstructedPat<'p>>,
+    exhaustive_patterns: bool,
+    complexity_limit: usize,
+}
+
+impl<'a, 'p> MatchCheckCtx<'a, 'p> {
+    pub(crate) fn new(
+        module: ModuleId,
+        body: DefWithBodyId,
+        db: &'a dyn HirDatabase,
+        pattern_arena: &'p Arena<DeconstructedPat<'p>>,
+        row_arena: &'p Arena<&'p DeconstructedPat<'p>>,
+    ) -> Self {
+        let def_map = db.crate_def_map(module.krate());
+        let exhaustive_patterns = def_map.is_unstable_feature_enabled("exhaustive_patterns");
+        let complexity_limit =
+            def_map.pattern_complexity().map_or(DEFAULT_COMPLEXITY_LIMIT, |limit| limit as usize);
+        Self { module, body, db, pattern_arena, row_arena, exhaustive_patterns, complexity_limit }
+    }
+
+    /// A context to run the usefulness algorithm on the patterns of this body in, with fresh
+    /// caches.
+    pub(super) fn usefulness_cx(&self) -> UsefulnessCtxt<'_, 'p> {
+        UsefulnessCtxt::new(self, self.pattern_arena, self.row_arena)
+    }
+
+    /// Computes whether a match on `scrut_ty` is exhaustive and which of its arms are reachable,
+    /// see [`compute_match_usefulness`].
+    pub(crate) fn compute_match_usefulness(
+        &self,
+        arms: &[MatchArm<'p>],
+        scrut_ty: &Ty,
+    ) -> Result<UsefulnessReport<'p>, TooComplex> {
+        // The scrutinee may be typed with a projection, e.g. `<T as Iterator>::Item`, which only
+        // resolves to an enum once normalized with the environment of the body.
+        let scrut_ty = self.normalize(scrut_ty.clone());
+        compute_match_usefulness(&self.usefulness_cx(), arms, &scrut_ty)
+    }
+
+    pub(super) fn is_uninhabited(&self, ty: &Ty) -> bool {
+        if self.feature_exhaustive_patterns() {
+            is_ty_uninhabited_from(ty, self.module, self.db)
+        } else {
+            false
+        }
+    }
+
+    // Rust's unstable feature described as "Allows exhaustive pattern matching on types that contain uninhabited types."
+    pub(super) fn feature_exhaustive_patterns(&self) -> bool {
+        self.exhaustive_patterns
+    }
+
+    /// Normalizes associated types in `ty` using the trait environment of the body being checked,
+    /// and reveals the opaque types this body defines. All the types of a column must be
+    /// normalized for constructors to be computed correctly.
+    pub(crate) fn normalize(&self, ty: Ty) -> Ty {
+        reveal_opaque_ty(self.db, self.body, normalize(self.db, self.body, ty))
+    }
+
+    // In the cases of either a `#[non_exhaustive]` field list or a non-public field, we hide
+    // uninhabited fields in order not to reveal the uninhabitedness of the whole variant.
+    // This lists the fields we keep along with their types.
+    pub(super) fn list_variant_nonhidden_fields<'b>(
+        &'b self,
+        ty: &'b Ty,
+        variant: VariantId,
+    ) -> impl Iterator<Item = (LocalFieldId, Ty)> + Captures<'a> + Captures<'b> + Captures<'p> {
+        let (adt, substs) = ty.as_adt().unwrap();
+
+        let adt_is_local = variant.module(self.db.upcast()).krate() == self.module.krate();
+        // Whether we must not match the fields of this variant exhaustively.
+        let is_non_exhaustive = self.is_field_list_non_exhaustive(variant) && !adt_is_local;
+
+        let visibility = self.db.field_visibilities(variant);
+        let field_ty = self.db.field_types(variant);
+        let fields_len = variant.variant_data(self.db.upcast()).fields().len() as u32;
+
+        (0..fields_len).map(|idx| LocalFieldId::from_raw(idx.into())).filter_map(move |fid| {
+            let ty = field_ty[fid].clone().substitute(Interner, substs);
+            let ty = self.normalize(ty);
+            let is_visible = matches!(adt, AdtId::EnumId(..))
+                || visibility[fid].is_visible_from(self.db.upcast(), self.module);
+            let is_uninhabited = self.is_uninhabited(&ty);
+
+            if is_uninhabited && (!is_visible || is_non_exhaustive) {
+                None
+            } else {
+                Some((fid, ty))
+            }
+        })
+    }
+
+    fn is_field_list_non_exhaustive(&self, variant_id: VariantId) -> bool {
+        let attr_def_id = match variant_id {
+            VariantId::EnumVariantId(id) => id.into(),
+            VariantId::StructId(id) => id.into(),
+            VariantId::UnionId(id) => id.into(),
+        };
+        self.db.attrs(attr_def_id).by_key("non_exhaustive").exists()
+    }
+}
+
+impl TypeCx for MatchCheckCtx<'_, '_> {
+    fn is_foreign_non_exhaustive_enum(&self, ty: &Ty) -> bool {
+        match ty.as_adt() {
+            Some((adt @ AdtId::EnumId(_), _)) => {
+                let has_non_exhaustive_attr =
+                    self.db.attrs(adt.into()).by_key("non_exhaustive").exists();
+                let is_local = adt.module(self.db.upcast()).krate() == self.module.krate();
+                has_non_exhaustive_attr && !is_local
+            }
+            _ => false,
+        }
+    }
+
+    /// If the `exhaustive_patterns` feature is enabled, we make sure to omit constructors that
+    /// are statically impossible. E.g., for `Option<!>`, we do not include `Some(_)` in the
+    /// returned list of constructors.
+    fn ctors_for_ty(&self, ty: &Ty, is_top_level: bool) -> ConstructorSet {
+        // A pattern type like `u32 is 1..` only has the values of its base type that its pattern
+        // matches.
+        if let Some((base, range)) = ty.as_pattern_type(self.db) {
+            return match (self.ctors_for_ty(base, is_top_level), IntWidth::of(base)) {
+                (ConstructorSet::Integers(all), Some(width)) => {
+                    match all.intersection(&IntRange::from_bits(width, range.lo, range.hi)) {
+                        Some(values) => ConstructorSet::Integers(values),
+                        None => ConstructorSet::NoConstructors,
+                    }
+                }
+                (ctors, _) => ctors,
+            };
+        }
+        match ty.kind(Interner) {
+            TyKind::Scalar(Scalar::Bool) => ConstructorSet::Bool,
+            TyKind::Array(sub_ty, len) if array_len(len).is_some() => {
+                let len = array_len(len).unwrap();
+                if len != 0 && self.is_uninhabited(sub_ty) {
+                    ConstructorSet::NoConstructors
+                } else {
+                    ConstructorSet::Slice(Slice::new(Some(len), SliceKind::VarLen(0, 0)))
+                }
+            }
+            // Treat arrays of a generic or otherwise unknown length like slices: only patterns
+            // covering slices of any length make them exhaustive.
+            TyKind::Array(sub_ty, _) | TyKind::Slice(sub_ty) => {
+                let kind = if self.is_uninhabited(sub_ty) {
+                    SliceKind::FixedLen(0)
+                } else {
+                    SliceKind::VarLen(0, 0)
+                };
+                ConstructorSet::Slice(Slice::new(None, kind))
+            }
+            TyKind::Adt(crate::AdtId(AdtId::EnumId(enum_id)), subst) => {
+                let enum_data = self.db.enum_data(*enum_id);
+
+                // If the enum is declared as `#[non_exhaustive]`, we treat it as if it had an
+                // additional "unknown" constructor.
+                // There is no point in enumerating all possible variants, because the user can't
+                // actually match against them all themselves. So we always return only the fictitious
+                // constructor.
+                // E.g., in an example like:
+                //
+                // ```
+                //     let err: io::ErrorKind = ...;
+                //     match err {
+                //         io::ErrorKind::NotFound => {},
+                //     }
+                // ```
+                //
+                // we don't want to show every possible IO error, but instead have only `_` as the
+                // witness.
+                //
+                // This takes precedence over emptiness, even at the top level and with
+                // `exhaustive_patterns`: a foreign `#[non_exhaustive] enum Never {}` may gain
+                // variants later, so matching it still requires a `_` arm, as in rustc. The
+                // inhabitedness analysis agrees and considers such enums opaquely inhabited.
+                let is_declared_nonexhaustive = self.is_foreign_non_exhaustive_enum(ty);
+
+                let is_exhaustive_pat_feature = self.feature_exhaustive_patterns();
+
+                // If `exhaustive_patterns` is disabled and our scrutinee is an empty enum, we treat it
+                // as though it had an "unknown" constructor to avoid exposing its emptiness. The
+                // exception is if the pattern is at the top level, because we want empty matches to be
+                // considered exhaustive.
+                let is_secretly_empty =
+                    enum_data.variants.is_empty() && !is_exhaustive_pat_feature && !is_top_level;
+
+                let variants = enum_data
+                    .variants
+                    .iter()
+                    .map(|(local_id, _)| EnumVariantId { parent: *enum_id, local_id })
+                    .filter(|&variant| {
+                        // If `exhaustive_patterns` is enabled, we exclude variants known to be
+                        // uninhabited.
+                        let is_uninhabited = is_exhaustive_pat_feature
+                            && is_enum_variant_uninhabited_from(
+                                variant,
+                                subst,
+                                self.module,
+                                self.db,
+                            );
+                        !is_uninhabited
+                    })
+                    .collect();
+
+                ConstructorSet::Variants {
+                    variants,
+                    variant_count: enum_data.variants.len(),
+                    non_exhaustive: is_secretly_empty || is_declared_nonexhaustive,
+                }
+            }
+            TyKind::Scalar(Scalar::Char) => ConstructorSet::Unlistable,
+            TyKind::Scalar(Scalar::Int(IntTy::Isize) | Scalar::Uint(UintTy::Usize)) => {
+                ConstructorSet::Unlistable
+            }
+            TyKind::Scalar(Scalar::Int(..) | Scalar::Uint(..)) => match IntWidth::of(ty) {
+                Some(width) => ConstructorSet::Integers(IntRange::from_bits(
+                    width,
+                    width.min_bits(),
+                    width.max_bits(),
+                )),
+                None => ConstructorSet::Unlistable,
+            },
+            TyKind::Never if !self.feature_exhaustive_patterns() && !is_top_level => {
+                ConstructorSet::Unlistable
+            }
+            TyKind::Never => ConstructorSet::NoConstructors,
+            _ if self.is_uninhabited(ty) => ConstructorSet::NoConstructors,
+            TyKind::Adt(..) | TyKind::Tuple(..) | TyKind::Ref(..) => ConstructorSet::Single,
+            // This type is one for which we cannot list constructors, like `str` or `f64`.
+            _ => ConstructorSet::Unlistable,
+        }
+    }
+
+    /// The field types are normalized here, so that `is_useful` can use the types of patterns
+    /// as they are.
+    fn ctor_sub_tys(&self, ctor: &Constructor, ty: &Ty) -> SmallVec<[Ty; 2]> {
+        use Constructor::*;
+        let tys: SmallVec<[Ty; 2]> = match ctor {
+            Single | Variant(_) => match ty.kind(Interner) {
+                TyKind::Tuple(_, substs) => {
+                    substs.iter(Interner).map(|ty| ty.assert_ty_ref(Interner).clone()).collect()
+                }
+                TyKind::Ref(.., rty) => smallvec![rty.clone()],
+                &TyKind::Adt(crate::AdtId(adt), ref substs) => {
+                    if is_box(adt, self.db) {
+                        // The only legal patterns of type `Box` (outside `std`) are `_` and box
+                        // patterns. If we're here we can assume this is a box pattern.
+                        smallvec![substs.at(Interner, 0).assert_ty_ref(Interner).clone()]
+                    } else {
+                        let variant = ctor.variant_id_for_adt(adt);
+                        // These are normalized already.
+                        return self
+                            .list_variant_nonhidden_fields(ty, variant)
+                            .map(|(_, ty)| ty)
+                            .collect();
+                    }
+                }
+                ty_kind => {
+                    never!("Unexpected type for `Single` constructor: {:?}", ty_kind);
+                    smallvec![ty.clone()]
+                }
+            },
+            Slice(slice) => match ty.kind(Interner) {
+                TyKind::Slice(ty) | TyKind::Array(ty, _) => {
+                    (0..slice.arity()).map(|_| ty.clone()).collect()
+                }
+                _ => {
+                    never!("bad slice pattern {:?} {:?}", ctor, ty);
+                    SmallVec::new()
+                }
+            },
+            Str(..)
+            | FloatRange(..)
+            | IntRange(..)
+            | NonExhaustive
+            | Opaque
+            | Missing { .. }
+            | Wildcard => SmallVec::new(),
+            Or => {
+                never!("called `ctor_sub_tys` on an `Or` ctor");
+                SmallVec::new()
+            }
+        };
+        tys.into_iter().map(|ty| self.normalize(ty)).collect()
+    }
+
+    fn complexity_limit(&self) -> usize {
+        self.complexity_limit
+    }
+
+    fn unwind_if_cancelled(&self) {
+        self.db.unwind_if_cancelled();
+    }
+
+    fn display_pat(&self, pat: &DeconstructedPat<'_>) -> String {
+        pat.to_pat(self).display(self.db).to_string()
+    }
+
+    fn display_ty(&self, ty: &Ty) -> String {
+        ty.display(self.db).to_string()
+    }
+}
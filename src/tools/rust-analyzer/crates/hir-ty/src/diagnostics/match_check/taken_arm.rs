@@ -0,0 +1,295 @@
+//! Finds the arm a `match` takes when its scrutinee is known at compile time, like `match 3 {..}`
+//! or `match SOME_CONST {..}`: the scrutinee is evaluated, with const eval for the constants it
+//! refers to, and matched against the lowered patterns of the arms in order.
+//!
+//! Only `bool`, integer and string literal patterns and `&str` constant patterns are lowered yet
+//! (see [`PatKind::LiteralBool`]), so other literal patterns and constant patterns are compared
+//! with the patterns of the body instead.
+
+use hir_def::{
+    body::Body,
+    expr::{Expr, ExprId, Literal, PatId, UnaryOp},
+    resolver::{resolver_for_expr, ValueNs},
+    AssocItemId, ConstId, DefWithBodyId, EnumVariantId, VariantId,
+};
+
+use crate::{
+    db::HirDatabase, mir::pad16, ConstScalar, InferenceResult, Interner, Scalar, Ty, TyKind,
+};
+
+use super::{const_str_value, FieldPat, IntWidth, LoweredPatterns, Pat, PatKind};
+
+/// A value as far as it is known at compile time.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Value {
+    Unknown,
+    Literal(Literal),
+    /// A struct, tuple or enum variant, with its fields in declaration order.
+    Adt(Option<EnumVariantId>, Vec<Value>),
+    Ref(Box<Value>),
+}
+
+/// The index of the arm `match_expr` takes, or `None` if its scrutinee isn't known at compile
+/// time or we can't tell whether one of the arms before the taken one matches it.
+pub(crate) fn taken_arm(
+    db: &dyn HirDatabase,
+    owner: DefWithBodyId,
+    body: &Body,
+    infer: &InferenceResult,
+    lowered_patterns: &LoweredPatterns,
+    match_expr: ExprId,
+) -> Option<usize> {
+    let Expr::Match { expr: scrutinee_expr, arms } = &body[match_expr] else { return None };
+    let evaluator = Evaluator { db, owner, body, infer, match_expr };
+    let scrutinee = evaluator.eval_expr(*scrutinee_expr);
+    if scrutinee == Value::Unknown {
+        return None;
+    }
+    for (idx, arm) in arms.iter().enumerate() {
+        let (pat, _) = lowered_patterns.pats.get(&arm.pat)?;
+        if !evaluator.matches(pat, &scrutinee)? {
+            continue;
+        }
+        let guard_holds = match arm.guard {
+            Some(guard) => match evaluator.eval_expr(guard) {
+                Value::Literal(Literal::Bool(value)) => value,
+                _ => return None,
+            },
+            None => true,
+        };
+        if guard_holds {
+            return Some(idx);
+        }
+    }
+    None
+}
+
+struct Evaluator<'a> {
+    db: &'a dyn HirDatabase,
+    owner: DefWithBodyId,
+    body: &'a Body,
+    infer: &'a InferenceResult,
+    match_expr: ExprId,
+}
+
+impl Evaluator<'_> {
+    fn eval_expr(&self, expr: ExprId) -> Value {
+        match &self.body[expr] {
+            Expr::Literal(lit) => Value::Literal(lit.clone()),
+            Expr::UnaryOp { expr, op: UnaryOp::Neg } => match self.eval_expr(*expr) {
+                Value::Literal(lit) => lit.negate().map_or(Value::Unknown, Value::Literal),
+                _ => Value::Unknown,
+            },
+            Expr::Path(path) => {
+                if let Some(variant) = self.infer.variant_resolution_for_expr(expr) {
+                    return self.unit_variant(variant);
+                }
+                let konst = match self.infer.assoc_resolutions_for_expr(expr) {
+                    Some((AssocItemId::ConstId(it), _)) => it,
+                    Some(_) => return Value::Unknown,
+                    None => {
+                        let resolver = resolver_for_expr(self.db.upcast(), self.owner, expr);
+                        match resolver
+                            .resolve_path_in_value_ns_fully(self.db.upcast(), path.mod_path())
+                        {
+                            Some(ValueNs::ConstId(it)) => it,
+                            _ => return Value::Unknown,
+                        }
+                    }
+                };
+                self.const_value(konst, &self.infer[expr])
+            }
+            Expr::Call { callee, args, .. } => {
+                match self.infer.variant_resolution_for_expr(*callee) {
+                    Some(variant) => Value::Adt(
+                        enum_variant(variant),
+                        args.iter().map(|&arg| self.eval_expr(arg)).collect(),
+                    ),
+                    None => Value::Unknown,
+                }
+            }
+            Expr::RecordLit { fields, spread: None, .. } => {
+                let Some(variant) = self.infer.variant_resolution_for_expr(expr) else {
+                    return Value::Unknown;
+                };
+                let variant_data = variant.variant_data(self.db.upcast());
+                let mut values = vec![Value::Unknown; variant_data.fields().len()];
+                for field in fields.iter() {
+                    if let Some(id) = variant_data.field(&field.name) {
+                        values[u32::from(id.into_raw()) as usize] = self.eval_expr(field.expr);
+                    }
+                }
+                Value::Adt(enum_variant(variant), values)
+            }
+            Expr::Tuple { exprs, .. } => {
+                Value::Adt(None, exprs.iter().map(|&expr| self.eval_expr(expr)).collect())
+            }
+            Expr::Ref { expr, .. } => Value::Ref(Box::new(self.eval_expr(*expr))),
+            _ => Value::Unknown,
+        }
+    }
+
+    /// The value of a path to a struct or variant, which is only known if it has no fields:
+    /// otherwise the path is to its constructor function.
+    fn unit_variant(&self, variant: VariantId) -> Value {
+        if variant.variant_data(self.db.upcast()).fields().is_empty() {
+            Value::Adt(enum_variant(variant), Vec::new())
+        } else {
+            Value::Unknown
+        }
+    }
+
+    fn const_value(&self, konst: ConstId, ty: &Ty) -> Value {
+        if let TyKind::Ref(_, _, pointee) = ty.kind(Interner) {
+            return match (pointee.kind(Interner), const_str_value(self.db, konst)) {
+                (TyKind::Str, Some(value)) => Value::Literal(Literal::String(value)),
+                _ => Value::Unknown,
+            };
+        }
+        let Ok(konst) = self.db.const_eval(konst) else { return Value::Unknown };
+        let chalk_ir::ConstValue::Concrete(c) = &konst.data(Interner).value else {
+            return Value::Unknown;
+        };
+        let ConstScalar::Bytes(bytes, _) = &c.interned else { return Value::Unknown };
+        let lit = match ty.kind(Interner) {
+            TyKind::Scalar(Scalar::Bool) => Literal::Bool(bytes.iter().any(|&byte| byte != 0)),
+            TyKind::Scalar(Scalar::Int(_)) => {
+                Literal::Int(i128::from_le_bytes(pad16(bytes, true)), None)
+            }
+            TyKind::Scalar(Scalar::Uint(_)) => {
+                Literal::Uint(u128::from_le_bytes(pad16(bytes, false)), None)
+            }
+            TyKind::Scalar(Scalar::Char) => {
+                let value = u128::from_le_bytes(pad16(bytes, false));
+                match u32::try_from(value).ok().and_then(char::from_u32) {
+                    Some(c) => Literal::Char(c),
+                    None => return Value::Unknown,
+                }
+            }
+            _ => return Value::Unknown,
+        };
+        Value::Literal(lit)
+    }
+
+    /// Whether `pat` matches `value`, or `None` if that depends on what we don't know.
+    fn matches(&self, pat: &Pat, value: &Value) -> Option<bool> {
+        if pat.is_wildcard() {
+            return Some(true);
+        }
+        match (&*pat.kind, value) {
+            (_, Value::Unknown) => None,
+            (PatKind::Binding { subpattern: Some(subpattern), .. }, _) => {
+                self.matches(subpattern, value)
+            }
+            (PatKind::LiteralBool { value: expected }, Value::Literal(Literal::Bool(value))) => {
+                Some(expected == value)
+            }
+            (&PatKind::Range { lo, hi }, Value::Literal(value)) => {
+                let width = IntWidth::of(&pat.ty)?;
+                let value = width.literal_bits(value)?;
+                // With their sign bits flipped, signed values are ordered like unsigned ones.
+                let bias = width.min_bits();
+                Some((lo ^ bias..=hi ^ bias).contains(&(value ^ bias)))
+            }
+            (PatKind::Variant { enum_variant, subpatterns, .. }, Value::Adt(Some(id), fields)) => {
+                if enum_variant != id {
+                    return Some(false);
+                }
+                self.fields_match(subpatterns, fields)
+            }
+            (PatKind::Leaf { subpatterns }, Value::Adt(None, fields)) => {
+                self.fields_match(subpatterns, fields)
+            }
+            (PatKind::Deref { subpattern }, Value::Ref(value)) => self.matches(subpattern, value),
+            // String literals and `&str` constants are evaluated to `&str` values, which are
+            // matched by `Deref` patterns of `Str` ones.
+            (PatKind::Deref { subpattern }, Value::Literal(Literal::String(_))) => {
+                self.matches(subpattern, value)
+            }
+            (PatKind::Str { value: expected }, Value::Literal(Literal::String(value))) => {
+                Some(expected == value)
+            }
+            (PatKind::Or { pats }, _) => {
+                let mut res = Some(false);
+                for alt in pats {
+                    match self.matches(alt, value) {
+                        Some(true) => return Some(true),
+                        Some(false) => {}
+                        None => res = None,
+                    }
+                }
+                res
+            }
+            (PatKind::Error | PatKind::Opaque, _) => self.matches_unlowered(pat.id?, value),
+            _ => None,
+        }
+    }
+
+    fn fields_match(&self, subpatterns: &[FieldPat], fields: &[Value]) -> Option<bool> {
+        let mut res = Some(true);
+        for FieldPat { field, pattern } in subpatterns {
+            let value = fields.get(u32::from(field.into_raw()) as usize)?;
+            match self.matches(pattern, value) {
+                // A field that doesn't match decides, even if others are unknown.
+                Some(false) => return Some(false),
+                Some(true) => {}
+                None => res = None,
+            }
+        }
+        res
+    }
+
+    /// Matches `value` against a pattern of the body that couldn't be lowered, as long as it is
+    /// a literal or a constant.
+    fn matches_unlowered(&self, pat: PatId, value: &Value) -> Option<bool> {
+        let expected = match &self.body[pat] {
+            hir_def::expr::Pat::Lit(expr) => self.eval_expr(*expr),
+            hir_def::expr::Pat::Path(path) => {
+                let konst = match self.infer.assoc_resolutions_for_pat(pat) {
+                    Some((AssocItemId::ConstId(it), _)) => it,
+                    Some(_) => return None,
+                    None => {
+                        let resolver =
+                            resolver_for_expr(self.db.upcast(), self.owner, self.match_expr);
+                        match resolver
+                            .resolve_path_in_value_ns_fully(self.db.upcast(), path.mod_path())?
+                        {
+                            ValueNs::ConstId(it) => it,
+                            _ => return None,
+                        }
+                    }
+                };
+                self.const_value(konst, &self.infer[pat])
+            }
+            _ => return None,
+        };
+        match (expected, value) {
+            (Value::Literal(expected), Value::Literal(value)) => literals_eq(&expected, value),
+            _ => None,
+        }
+    }
+}
+
+fn enum_variant(variant: VariantId) -> Option<EnumVariantId> {
+    match variant {
+        VariantId::EnumVariantId(it) => Some(it),
+        VariantId::StructId(_) | VariantId::UnionId(_) => None,
+    }
+}
+
+/// Whether two literals of the same type are equal. Unsuffixed integer literals may be lowered as
+/// either signed or unsigned, so those are compared by value.
+fn literals_eq(a: &Literal, b: &Literal) -> Option<bool> {
+    match (a, b) {
+        (Literal::Int(a, _), Literal::Int(b, _)) => Some(a == b),
+        (Literal::Uint(a, _), Literal::Uint(b, _)) => Some(a == b),
+        (Literal::Int(a, _), Literal::Uint(b, _)) | (Literal::Uint(b, _), Literal::Int(a, _)) => {
+            Some(u128::try_from(*a).map_or(false, |a| a == *b))
+        }
+        (Literal::Bool(a), Literal::Bool(b)) => Some(a == b),
+        (Literal::Char(a), Literal::Char(b)) => Some(a == b),
+        (Literal::String(a), Literal::String(b)) => Some(a == b),
+        (Literal::ByteString(a), Literal::ByteString(b)) => Some(a == b),
+        _ => None,
+    }
+}
@@ -0,0 +1,1807 @@
+//! Tests of the usefulness algorithm: snapshots of the missing patterns and unreachable arms found
+//! in fixtures, conformance with the errors of rustc's UI tests, and differential tests where random
+//! matches on small types are checked both with [`compute_match_usefulness`] and by enumerating all
+//! the values of the scrutinee type. The differential tests also run the algorithm on its own, with
+//! a [`TypeCx`] that needs no database.
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt,
+    fmt::Write,
+    fs, iter,
+    path::Path,
+};
+
+use base_db::{
+    fixture::WithFixture,
+    salsa::{InternId, InternKey},
+    FileId, SourceDatabaseExt,
+};
+use chalk_ir::Mutability;
+use expect_test::{expect, Expect};
+use hir_def::{
+    body::Body,
+    db::DefDatabase,
+    expr::{Expr, ExprId},
+    DefWithBodyId, EnumId, EnumVariantId, LocalEnumVariantId, ModuleDefId,
+};
+use itertools::Itertools;
+use la_arena::RawIdx;
+use oorandom::Rand32;
+use smallvec::{smallvec, SmallVec};
+use stdx::format_to;
+use syntax::TextRange;
+use test_utils::{bench, skip_slow_tests};
+use typed_arena::Arena;
+
+use crate::{
+    db::HirDatabase,
+    diagnostics::{
+        check_match_arms, expr::missing_match_arms, fill_match_arms, is_refutable,
+        match_decision_tree, pretty_print_match_patterns, ArmReachability, DecisionTreeFormat,
+        MatchCheckArm, MatchCheckError, PatSourceOptions,
+    },
+    display::HirDisplay,
+    static_lifetime,
+    test_db::TestDB,
+    AdtId, InferenceResult, Interner, Scalar, Substitution, TyKind,
+};
+
+use super::{
+    deconstruct_pat::{
+        Constructor, ConstructorSet, DeconstructedPat, Fields, IntRange, Slice, SliceKind,
+    },
+    pat_analysis::MatchCheckCtx,
+    usefulness::{
+        compute_match_usefulness, MatchArm, PatCtxt as ColumnCtxt, Reachability, TypeCx,
+        UsefulnessCtxt,
+    },
+    PatCtxt, PatternError,
+};
+
+const VARIANTS: [&str; 3] = ["A", "B", "C"];
+/// The number of elements on each side of the `..` of a slice pattern.
+const MAX_SLICE_PAT_LEN: usize = 2;
+/// Slices longer than this behave like slices of this length for all the generated patterns.
+const MAX_SLICE_LEN: usize = 2 * MAX_SLICE_PAT_LEN + 1;
+/// Types with more values are too slow to enumerate.
+const MAX_VALUES: usize = 4096;
+
+/// The types of the generated scrutinees. Slices are matched through a reference.
+#[derive(Clone, Debug)]
+enum Ty {
+    Bool,
+    /// `enum E { A, B, C }`
+    Enum,
+    Option(Box<Ty>),
+    Tuple(Box<Ty>, Box<Ty>),
+    Slice(Box<Ty>),
+}
+
+#[derive(Clone, Debug)]
+enum Value {
+    Bool(bool),
+    Variant(usize),
+    Option(Option<Box<Value>>),
+    Tuple(Box<Value>, Box<Value>),
+    Slice(Vec<Value>),
+}
+
+#[derive(Clone, Debug)]
+enum Pattern {
+    Wild,
+    Bool(bool),
+    Variant(usize),
+    None,
+    Some(Box<Pattern>),
+    Tuple(Box<Pattern>, Box<Pattern>),
+    /// `[prefix.., .., suffix..]` if there is a suffix, `[prefix..]` otherwise.
+    Slice(Vec<Pattern>, Option<Vec<Pattern>>),
+    Or(Vec<Pattern>),
+}
+
+impl Ty {
+    fn value_count(&self) -> usize {
+        match self {
+            Ty::Bool => 2,
+            Ty::Enum => VARIANTS.len(),
+            Ty::Option(ty) => ty.value_count().saturating_add(1),
+            Ty::Tuple(ty1, ty2) => ty1.value_count().saturating_mul(ty2.value_count()),
+            Ty::Slice(ty) => {
+                let count = ty.value_count();
+                (0..=MAX_SLICE_LEN as u32)
+                    .map(|len| count.saturating_pow(len))
+                    .fold(0, usize::saturating_add)
+            }
+        }
+    }
+
+    fn values(&self) -> Vec<Value> {
+        match self {
+            Ty::Bool => vec![Value::Bool(false), Value::Bool(true)],
+            Ty::Enum => (0..VARIANTS.len()).map(Value::Variant).collect(),
+            Ty::Option(ty) => iter::once(Value::Option(None))
+                .chain(ty.values().into_iter().map(|value| Value::Option(Some(Box::new(value)))))
+                .collect(),
+            Ty::Tuple(ty1, ty2) => {
+                let values2 = ty2.values();
+                ty1.values()
+                    .into_iter()
+                    .cartesian_product(values2)
+                    .map(|(value1, value2)| Value::Tuple(Box::new(value1), Box::new(value2)))
+                    .collect()
+            }
+            Ty::Slice(ty) => {
+                let values = ty.values();
+                iter::once(Value::Slice(Vec::new()))
+                    .chain((1..=MAX_SLICE_LEN).flat_map(|len| {
+                        iter::repeat(values.clone())
+                            .take(len)
+                            .multi_cartesian_product()
+                            .map(Value::Slice)
+                    }))
+                    .collect()
+            }
+        }
+    }
+}
+
+impl fmt::Display for Ty {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Ty::Bool => f.write_str("bool"),
+            Ty::Enum => f.write_str("E"),
+            Ty::Option(ty) => write!(f, "Option<{ty}>"),
+            Ty::Tuple(ty1, ty2) => write!(f, "({ty1}, {ty2})"),
+            Ty::Slice(ty) => write!(f, "&[{ty}]"),
+        }
+    }
+}
+
+impl Pattern {
+    fn matches(&self, value: &Value) -> bool {
+        match (self, value) {
+            (Pattern::Wild, _) => true,
+            (Pattern::Or(alts), _) => alts.iter().any(|alt| alt.matches(value)),
+            (Pattern::Bool(pat), Value::Bool(value)) => pat == value,
+            (Pattern::Variant(pat), Value::Variant(value)) => pat == value,
+            (Pattern::None, Value::Option(value)) => value.is_none(),
+            (Pattern::Some(pat), Value::Option(Some(value))) => pat.matches(value),
+            (Pattern::Tuple(pat1, pat2), Value::Tuple(value1, value2)) => {
+                pat1.matches(value1) && pat2.matches(value2)
+            }
+            (Pattern::Slice(prefix, None), Value::Slice(values)) => {
+                prefix.len() == values.len()
+                    && prefix.iter().zip(values).all(|(pat, value)| pat.matches(value))
+            }
+            (Pattern::Slice(prefix, Some(suffix)), Value::Slice(values)) => {
+                prefix.len() + suffix.len() <= values.len()
+                    && prefix.iter().zip(values).all(|(pat, value)| pat.matches(value))
+                    && suffix
+                        .iter()
+                        .rev()
+                        .zip(values.iter().rev())
+                        .all(|(pat, value)| pat.matches(value))
+            }
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for Pattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Pattern::Wild => f.write_str("_"),
+            Pattern::Bool(value) => write!(f, "{value}"),
+            Pattern::Variant(idx) => write!(f, "E::{}", VARIANTS[*idx]),
+            Pattern::None => f.write_str("None"),
+            Pattern::Some(pat) => write!(f, "Some({pat})"),
+            Pattern::Tuple(pat1, pat2) => write!(f, "({pat1}, {pat2})"),
+            Pattern::Slice(prefix, None) => write!(f, "[{}]", prefix.iter().format(", ")),
+            Pattern::Slice(prefix, Some(suffix)) => {
+                let rest = iter::once("..".to_owned());
+                let pats = prefix.iter().map(ToString::to_string).chain(rest);
+                write!(f, "[{}]", pats.chain(suffix.iter().map(ToString::to_string)).format(", "))
+            }
+            Pattern::Or(alts) => write!(f, "{}", alts.iter().format(" | ")),
+        }
+    }
+}
+
+fn gen_bool(rng: &mut Rand32) -> bool {
+    rng.rand_range(0..2) == 1
+}
+
+fn gen_ty(rng: &mut Rand32, depth: usize) -> Ty {
+    let kinds = if depth == 0 { 2 } else { 5 };
+    match rng.rand_range(0..kinds) {
+        0 => Ty::Bool,
+        1 => Ty::Enum,
+        2 => Ty::Option(Box::new(gen_ty(rng, depth - 1))),
+        3 => Ty::Tuple(Box::new(gen_ty(rng, depth - 1)), Box::new(gen_ty(rng, depth - 1))),
+        _ => Ty::Slice(Box::new(gen_ty(rng, depth - 1))),
+    }
+}
+
+fn gen_pat(rng: &mut Rand32, ty: &Ty) -> Pattern {
+    match rng.rand_range(0..8) {
+        0 => Pattern::Wild,
+        1 => Pattern::Or((0..rng.rand_range(2..4)).map(|_| gen_ctor_pat(rng, ty)).collect()),
+        _ => gen_ctor_pat(rng, ty),
+    }
+}
+
+/// Generates a pattern that is neither a wildcard nor an or-pattern at the top.
+fn gen_ctor_pat(rng: &mut Rand32, ty: &Ty) -> Pattern {
+    match ty {
+        Ty::Bool => Pattern::Bool(gen_bool(rng)),
+        Ty::Enum => Pattern::Variant(rng.rand_range(0..VARIANTS.len() as u32) as usize),
+        Ty::Option(_) if gen_bool(rng) => Pattern::None,
+        Ty::Option(ty) => Pattern::Some(Box::new(gen_pat(rng, ty))),
+        Ty::Tuple(ty1, ty2) => {
+            Pattern::Tuple(Box::new(gen_pat(rng, ty1)), Box::new(gen_pat(rng, ty2)))
+        }
+        Ty::Slice(ty) => {
+            let gen_pats = |rng: &mut Rand32| {
+                let len = rng.rand_range(0..MAX_SLICE_PAT_LEN as u32 + 1);
+                (0..len).map(|_| gen_pat(rng, ty)).collect::<Vec<_>>()
+            };
+            let prefix = gen_pats(rng);
+            let suffix = gen_bool(rng).then(|| gen_pats(rng));
+            Pattern::Slice(prefix, suffix)
+        }
+    }
+}
+
+/// Lowers the patterns of the arms of a match, along with the errors lowering each of them.
+fn lower_arms<'p>(
+    cx: &MatchCheckCtx<'_, 'p>,
+    infer: &InferenceResult,
+    body: &Body,
+    arms: &[hir_def::expr::MatchArm],
+) -> (Vec<MatchArm<'p>>, Vec<Vec<PatternError>>) {
+    arms.iter()
+        .map(|arm| {
+            let mut patcx = PatCtxt::new(cx.db, cx.body, infer, body);
+            let pat = patcx.lower_pattern(arm.pat);
+            let pat = cx.pattern_arena.alloc(DeconstructedPat::from_pat(cx, &pat));
+            (MatchArm { pat, guard: arm.guard }, patcx.errors)
+        })
+        .unzip()
+}
+
+/// A match of a fixture, and what checking it found.
+struct CheckedMatch {
+    scrutinee: TextRange,
+    /// The ranges of the patterns of the arms.
+    arms: Vec<TextRange>,
+    outcome: MatchOutcome,
+}
+
+enum MatchOutcome {
+    TooComplex,
+    Checked {
+        /// The errors lowering the patterns. Like in `validate_match`, arms whose patterns were
+        /// lowered as errors aren't reported as unreachable.
+        errors: Vec<PatternError>,
+        /// The message of the missing match arms diagnostic, if the match isn't exhaustive.
+        missing: Option<String>,
+        /// The indices of the unreachable arms.
+        unreachable: Vec<usize>,
+        is_approximate: bool,
+    },
+}
+
+/// Checks all the matches in the functions of the file, in source order.
+fn check_all_matches(db: &TestDB, file_id: FileId) -> Vec<CheckedMatch> {
+    let module = db.module_for_file(file_id);
+    let def_map = module.def_map(db);
+    let mut matches = Vec::new();
+    for def in def_map[module.local_id].scope.declarations() {
+        let ModuleDefId::FunctionId(func) = def else { continue };
+        let owner = DefWithBodyId::from(func);
+        let (body, source_map) = db.body_with_source_map(owner);
+        let infer = db.infer(owner);
+        for (_, expr) in body.exprs.iter() {
+            let Expr::Match { expr: scrutinee, arms: match_arms } = expr else { continue };
+            let scrutinee_range = source_map.expr_syntax(*scrutinee).unwrap().value.text_range();
+            let arm_ranges = match_arms
+                .iter()
+                .map(|arm| {
+                    let pat = source_map.pat_syntax(arm.pat).unwrap().value;
+                    pat.either(|it| it.text_range(), |it| it.text_range())
+                })
+                .collect();
+
+            let pattern_arena = Arena::new();
+            let row_arena = Arena::new();
+            let cx = MatchCheckCtx::new(module, owner, db, &pattern_arena, &row_arena);
+            let scrut_ty = infer.type_of_expr_with_adjust(*scrutinee);
+            let (arms, arm_errors) = lower_arms(&cx, &infer, &body, match_arms);
+            let outcome = match cx.compute_match_usefulness(&arms, scrut_ty) {
+                Err(_) => MatchOutcome::TooComplex,
+                Ok(report) => {
+                    let is_approximate = report.is_approximate;
+                    let unreachable = report
+                        .arm_usefulness
+                        .iter()
+                        .zip(&arm_errors)
+                        .positions(|((_, reachability), errors)| {
+                            matches!(reachability, Reachability::Unreachable)
+                                && !errors.iter().any(PatternError::lowers_to_error)
+                        })
+                        .collect();
+                    let errors = arm_errors.concat();
+                    let witnesses = report.non_exhaustiveness_witnesses;
+                    let missing = (!witnesses.is_empty())
+                        .then(|| missing_match_arms(&cx, scrut_ty, witnesses, &match_arms));
+                    MatchOutcome::Checked { errors, missing, unreachable, is_approximate }
+                }
+            };
+            matches.push(CheckedMatch { scrutinee: scrutinee_range, arms: arm_ranges, outcome });
+        }
+    }
+    matches.sort_by_key(|it| it.scrutinee.start());
+    matches
+}
+
+/// Checks all the matches in the functions of `ra_fixture`, and compares the patterns they miss and
+/// their unreachable arms with `expect`. Run with `UPDATE_EXPECT=1` to update the snapshots.
+fn check_matches(ra_fixture: &str, expect: Expect) {
+    let (db, file_id) = TestDB::with_single_file(ra_fixture);
+    let text = db.file_text(file_id);
+    let mut actual = String::new();
+    for checked in check_all_matches(&db, file_id) {
+        format_to!(actual, "match {}\n", &text[checked.scrutinee]);
+        match checked.outcome {
+            MatchOutcome::TooComplex => actual.push_str("    too complex\n"),
+            MatchOutcome::Checked { errors, missing, unreachable, is_approximate } => {
+                if !errors.is_empty() {
+                    format_to!(actual, "    errors: {errors:?}\n");
+                }
+                if let Some(missing) = missing {
+                    format_to!(actual, "    missing: {missing}\n");
+                }
+                for arm in unreachable {
+                    format_to!(actual, "    unreachable: {}\n", &text[checked.arms[arm]]);
+                }
+                if is_approximate {
+                    actual.push_str("    approximate\n");
+                }
+            }
+        }
+    }
+    expect.assert_eq(&actual);
+}
+
+/// The errors about matches of a rustc UI test, or the diagnostics we found for the same file.
+#[derive(Debug, Default, PartialEq, Eq)]
+struct UiErrors {
+    /// The witnesses of the non-exhaustive matches, by the line of their scrutinee.
+    non_exhaustive: BTreeMap<usize, Witnesses>,
+    /// The line and column of the unreachable patterns.
+    unreachable: BTreeSet<(usize, usize)>,
+}
+
+/// The patterns listed in a non-exhaustive patterns error, with their paths stripped since we
+/// don't qualify variants like rustc does, and the number of those that were left out.
+#[derive(Debug, PartialEq, Eq)]
+struct Witnesses {
+    listed: BTreeSet<String>,
+    more: usize,
+}
+
+impl Witnesses {
+    /// Parses "`A::B`, `C` and 2 more not covered".
+    fn parse(message: &str) -> Witnesses {
+        let mut parts = message.split('`');
+        let rest = parts.next_back().unwrap_or_default();
+        let listed = parts.skip(1).step_by(2).map(strip_paths).collect();
+        let more = rest
+            .strip_prefix(" and ")
+            .and_then(|rest| rest.strip_suffix(" more not covered"))
+            .map_or(0, |count| count.parse().unwrap());
+        Witnesses { listed, more }
+    }
+
+    /// rustc and we may pick different witnesses to list when there are too many.
+    fn matches(&self, other: &Witnesses) -> bool {
+        if self.more == 0 && other.more == 0 {
+            self.listed == other.listed
+        } else {
+            self.listed.len() + self.more == other.listed.len() + other.more
+        }
+    }
+}
+
+/// Turns `(E::A, Some(E::B))` into `(A, Some(B))`.
+fn strip_paths(pat: &str) -> String {
+    let mut res = String::new();
+    let mut rest = pat;
+    while let Some(c) = rest.chars().next() {
+        let ident_len =
+            rest.find(|c: char| !(c.is_alphanumeric() || c == '_')).unwrap_or(rest.len());
+        if ident_len == 0 {
+            res.push(c);
+            rest = &rest[c.len_utf8()..];
+        } else {
+            let (ident, after) = rest.split_at(ident_len);
+            match after.strip_prefix("::") {
+                Some(after) => rest = after,
+                None => {
+                    res.push_str(ident);
+                    rest = after;
+                }
+            }
+        }
+    }
+    res
+}
+
+impl UiErrors {
+    /// Collects the non-exhaustive patterns errors and the unreachable pattern errors or warnings
+    /// of a `.stderr` file.
+    fn parse_stderr(stderr: &str) -> UiErrors {
+        let mut errors = UiErrors::default();
+        let mut lines = stderr.lines();
+        while let Some(line) = lines.next() {
+            let Some((level, message)) = line.split_once(": ") else { continue };
+            if !level.starts_with("error") && !level.starts_with("warning") {
+                continue;
+            }
+            let Some(location) = lines.next().and_then(|it| it.trim_start().strip_prefix("--> "))
+            else {
+                continue;
+            };
+            let mut location = location.rsplit(':');
+            let col = location.next().unwrap().parse().unwrap();
+            let line = location.next().unwrap().parse().unwrap();
+            if level == "error[E0004]" {
+                let message = message.strip_prefix("non-exhaustive patterns: ").unwrap();
+                errors.non_exhaustive.insert(line, Witnesses::parse(message));
+            } else if message == "unreachable pattern" {
+                errors.unreachable.insert((line, col));
+            }
+        }
+        errors
+    }
+
+    /// Collects our diagnostics, and removes from `expected` what we can't compare: errors in
+    /// matches too complex to check or with patterns lowered as errors, which rustc can check and
+    /// we can't, and unreachable patterns inside or-patterns or outside matches,
+    /// since we only check the reachability of whole arms.
+    fn check(text: &str, matches: &[CheckedMatch], expected: &mut UiErrors) -> UiErrors {
+        let line_col = |range: TextRange| {
+            let before = &text[..usize::from(range.start())];
+            let line_start = before.rfind('\n').map_or(0, |it| it + 1);
+            (before.matches('\n').count() + 1, before.len() - line_start + 1)
+        };
+        let mut actual = UiErrors::default();
+        let mut arm_starts = BTreeSet::new();
+        for checked in matches {
+            let scrutinee_line = line_col(checked.scrutinee).0;
+            let arms = checked.arms.iter().map(|&range| line_col(range));
+            match &checked.outcome {
+                MatchOutcome::Checked { errors, missing, unreachable, .. }
+                    if !errors.iter().any(PatternError::lowers_to_error) =>
+                {
+                    if let Some(missing) = missing {
+                        actual.non_exhaustive.insert(scrutinee_line, Witnesses::parse(missing));
+                    }
+                    for &arm in unreachable {
+                        actual.unreachable.insert(line_col(checked.arms[arm]));
+                    }
+                    arm_starts.extend(arms);
+                }
+                _ => {
+                    expected.non_exhaustive.remove(&scrutinee_line);
+                    for arm in arms {
+                        expected.unreachable.remove(&arm);
+                    }
+                }
+            }
+        }
+        expected.unreachable.retain(|location| arm_starts.contains(location));
+        actual
+    }
+
+    fn matches(&self, other: &UiErrors) -> bool {
+        self.unreachable == other.unreachable
+            && self.non_exhaustive.len() == other.non_exhaustive.len()
+            && self.non_exhaustive.iter().zip(&other.non_exhaustive).all(
+                |((line, witnesses), (other_line, other_witnesses))| {
+                    line == other_line && witnesses.matches(other_witnesses)
+                },
+            )
+    }
+}
+
+/// Checks the rustc UI tests vendored in `test_data/match_check`, taken from
+/// `tests/ui/pattern/usefulness` in the rust repository, and compares the diagnostics of the
+/// matches with the errors in their `.stderr` files. Matches with patterns we can't lower yet are
+/// skipped, so that tests can be added as soon as some of their matches are supported.
+#[test]
+fn rustc_ui_tests() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("test_data/match_check");
+    let mut tests = fs::read_dir(&dir)
+        .unwrap_or_else(|err| panic!("can't `read_dir` {}: {err}", dir.display()))
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().unwrap_or_default() == "rs")
+        .collect::<Vec<_>>();
+    tests.sort();
+    assert!(!tests.is_empty());
+
+    let mut failures = String::new();
+    for path in tests {
+        let text = fs::read_to_string(&path).unwrap();
+        let stderr = fs::read_to_string(path.with_extension("stderr")).unwrap_or_default();
+        let (db, file_id) =
+            TestDB::with_single_file(&format!("//- minicore: option, result\n{text}"));
+        let text = db.file_text(file_id);
+        let matches = check_all_matches(&db, file_id);
+
+        let mut expected = UiErrors::parse_stderr(&stderr);
+        let actual = UiErrors::check(&text, &matches, &mut expected);
+        if !actual.matches(&expected) {
+            format_to!(
+                failures,
+                "{}:\n  expected: {expected:?}\n  actual:   {actual:?}\n",
+                path.display()
+            );
+        }
+    }
+    assert!(failures.is_empty(), "diagnostics differ from rustc's:\n{failures}");
+}
+
+/// Checks the match with `compute_match_usefulness`, returning whether it is exhaustive and which
+/// of its arms are reachable.
+fn check_with_usefulness(ty: &Ty, arms: &[(Pattern, bool)]) -> (bool, Vec<bool>) {
+    let mut source =
+        format!("//- minicore: option\nenum E {{ A, B, C }}\nfn f(x: {ty}) {{\n    match x {{\n");
+    for (pat, has_guard) in arms {
+        let guard = if *has_guard { " if true" } else { "" };
+        writeln!(source, "        {pat}{guard} => {{}}").unwrap();
+    }
+    source.push_str("    }\n}\n");
+
+    let (db, file_id) = TestDB::with_single_file(&source);
+    let module = db.module_for_file(file_id);
+    let def_map = module.def_map(&db);
+    let func = def_map[module.local_id]
+        .scope
+        .declarations()
+        .find_map(|def| match def {
+            ModuleDefId::FunctionId(it) => Some(it),
+            _ => None,
+        })
+        .unwrap();
+    let owner = DefWithBodyId::from(func);
+    let body = db.body(owner);
+    let infer = db.infer(owner);
+    let (scrutinee, match_arms) = body
+        .exprs
+        .iter()
+        .find_map(|(_, expr)| match expr {
+            Expr::Match { expr, arms } => Some((*expr, arms)),
+            _ => None,
+        })
+        .unwrap();
+
+    let pattern_arena = Arena::new();
+    let row_arena = Arena::new();
+    let cx = MatchCheckCtx::new(module, owner, &db, &pattern_arena, &row_arena);
+    let (arms, errors) = lower_arms(&cx, &infer, &body, match_arms);
+    let errors = errors.concat();
+    assert!(errors.is_empty(), "{source}lowering errors: {errors:?}");
+    let report =
+        cx.compute_match_usefulness(&arms, infer.type_of_expr_with_adjust(scrutinee)).unwrap();
+    let reachable = report
+        .arm_usefulness
+        .iter()
+        .map(|(_, reachability)| matches!(reachability, Reachability::Reachable(_)))
+        .collect();
+    (report.non_exhaustiveness_witnesses.is_empty(), reachable)
+}
+
+/// A [`TypeCx`] for the types of the differential tests, so that the usefulness algorithm can be
+/// run on them without a database. `E` and `Option` are enums with made-up ids, which are never
+/// looked up.
+struct TestTypeCx;
+
+impl TestTypeCx {
+    const E: u32 = 0;
+    const OPTION: u32 = 1;
+
+    fn enum_id(idx: u32) -> EnumId {
+        EnumId::from_intern_id(InternId::from(idx))
+    }
+
+    fn variant(enum_idx: u32, idx: usize) -> EnumVariantId {
+        let local_id = LocalEnumVariantId::from_raw(RawIdx::from(idx as u32));
+        EnumVariantId { parent: Self::enum_id(enum_idx), local_id }
+    }
+
+    fn lower_ty(ty: &Ty) -> crate::Ty {
+        let adt = |enum_idx, substs| {
+            let adt = hir_def::AdtId::EnumId(Self::enum_id(enum_idx));
+            TyKind::Adt(AdtId(adt), Substitution::from_iter(Interner, substs)).intern(Interner)
+        };
+        match ty {
+            Ty::Bool => TyKind::Scalar(Scalar::Bool).intern(Interner),
+            Ty::Enum => adt(Self::E, vec![]),
+            Ty::Option(ty) => adt(Self::OPTION, vec![Self::lower_ty(ty)]),
+            Ty::Tuple(ty1, ty2) => {
+                let substs =
+                    Substitution::from_iter(Interner, [ty1, ty2].map(|ty| Self::lower_ty(ty)));
+                TyKind::Tuple(2, substs).intern(Interner)
+            }
+            Ty::Slice(ty) => {
+                let slice = TyKind::Slice(Self::lower_ty(ty)).intern(Interner);
+                TyKind::Ref(Mutability::Not, static_lifetime(), slice).intern(Interner)
+            }
+        }
+    }
+
+    fn lower_pat<'p>(
+        arena: &'p Arena<DeconstructedPat<'p>>,
+        pat: &Pattern,
+        ty: &Ty,
+    ) -> DeconstructedPat<'p> {
+        let lower = |pat, ty| Self::lower_pat(arena, pat, ty);
+        let (ctor, fields) = match (pat, ty) {
+            (Pattern::Wild, _) => return DeconstructedPat::wildcard(Self::lower_ty(ty)),
+            (Pattern::Or(alts), _) => {
+                (Constructor::Or, alts.iter().map(|alt| lower(alt, ty)).collect())
+            }
+            (Pattern::Bool(value), _) => {
+                (Constructor::IntRange(IntRange::from_bool(*value)), vec![])
+            }
+            (Pattern::Variant(idx), _) => {
+                (Constructor::Variant(Self::variant(Self::E, *idx)), vec![])
+            }
+            (Pattern::None, _) => (Constructor::Variant(Self::variant(Self::OPTION, 0)), vec![]),
+            (Pattern::Some(pat), Ty::Option(ty)) => {
+                (Constructor::Variant(Self::variant(Self::OPTION, 1)), vec![lower(pat, ty)])
+            }
+            (Pattern::Tuple(pat1, pat2), Ty::Tuple(ty1, ty2)) => {
+                (Constructor::Single, vec![lower(pat1, ty1), lower(pat2, ty2)])
+            }
+            (Pattern::Slice(prefix, suffix), Ty::Slice(elem_ty)) => {
+                let kind = match suffix {
+                    Some(suffix) => SliceKind::VarLen(prefix.len(), suffix.len()),
+                    None => SliceKind::FixedLen(prefix.len()),
+                };
+                // Lowered before allocating, as the arena can't be borrowed twice.
+                let pats: Vec<_> = prefix
+                    .iter()
+                    .chain(suffix.iter().flatten())
+                    .map(|pat| lower(pat, elem_ty))
+                    .collect();
+                let slice_ty = TyKind::Slice(Self::lower_ty(elem_ty)).intern(Interner);
+                let slice = DeconstructedPat::new(
+                    Constructor::Slice(Slice::new(None, kind)),
+                    Fields::from_iter(arena, pats),
+                    slice_ty,
+                );
+                // Slices are matched through a reference.
+                (Constructor::Single, vec![slice])
+            }
+            _ => unreachable!("`{pat}` is not a pattern of type `{ty}`"),
+        };
+        DeconstructedPat::new(ctor, Fields::from_iter(arena, fields), Self::lower_ty(ty))
+    }
+}
+
+impl TypeCx for TestTypeCx {
+    fn is_foreign_non_exhaustive_enum(&self, _ty: &crate::Ty) -> bool {
+        false
+    }
+
+    fn ctors_for_ty(&self, ty: &crate::Ty, _is_top_level: bool) -> ConstructorSet {
+        match ty.kind(Interner) {
+            TyKind::Scalar(Scalar::Bool) => ConstructorSet::Bool,
+            TyKind::Adt(AdtId(hir_def::AdtId::EnumId(id)), _) => {
+                let enum_idx = id.as_intern_id().as_u32();
+                let variant_count = if enum_idx == Self::E { VARIANTS.len() } else { 2 };
+                let variants = (0..variant_count).map(|idx| Self::variant(enum_idx, idx)).collect();
+                ConstructorSet::Variants { variants, variant_count, non_exhaustive: false }
+            }
+            TyKind::Slice(_) => ConstructorSet::Slice(Slice::new(None, SliceKind::VarLen(0, 0))),
+            _ => ConstructorSet::Single,
+        }
+    }
+
+    fn ctor_sub_tys(&self, ctor: &Constructor, ty: &crate::Ty) -> SmallVec<[crate::Ty; 2]> {
+        match (ctor, ty.kind(Interner)) {
+            (Constructor::Single, TyKind::Tuple(_, substs)) => {
+                substs.iter(Interner).map(|ty| ty.assert_ty_ref(Interner).clone()).collect()
+            }
+            (Constructor::Single, TyKind::Ref(.., ty)) => smallvec![ty.clone()],
+            // `Some(_)`, the second variant of `Option`.
+            (Constructor::Variant(variant), TyKind::Adt(_, substs))
+                if u32::from(variant.local_id.into_raw()) == 1 && !substs.is_empty(Interner) =>
+            {
+                smallvec![substs.at(Interner, 0).assert_ty_ref(Interner).clone()]
+            }
+            (Constructor::Slice(slice), TyKind::Slice(ty)) => {
+                (0..slice.arity()).map(|_| ty.clone()).collect()
+            }
+            _ => SmallVec::new(),
+        }
+    }
+
+    fn complexity_limit(&self) -> usize {
+        usize::MAX
+    }
+
+    fn display_pat(&self, pat: &DeconstructedPat<'_>) -> String {
+        format!("{pat:?}")
+    }
+
+    fn display_ty(&self, ty: &crate::Ty) -> String {
+        format!("{ty:?}")
+    }
+}
+
+/// Checks the match with [`compute_match_usefulness`] and [`TestTypeCx`], returning whether it is
+/// exhaustive and which of its arms are reachable, and the missing patterns.
+fn check_without_database(ty: &Ty, arms: &[(Pattern, bool)]) -> (bool, Vec<bool>, Vec<String>) {
+    let pattern_arena = Arena::new();
+    let row_arena = Arena::new();
+    let cx = UsefulnessCtxt::new(&TestTypeCx, &pattern_arena, &row_arena);
+    let arms: Vec<_> = arms
+        .iter()
+        .map(|(pat, has_guard)| MatchArm {
+            pat: pattern_arena.alloc(TestTypeCx::lower_pat(&pattern_arena, pat, ty)),
+            // The guard is never looked at, only whether there is one.
+            guard: has_guard.then(|| ExprId::from_raw(RawIdx::from(0))),
+        })
+        .collect();
+    let report = compute_match_usefulness(&cx, &arms, &TestTypeCx::lower_ty(ty)).unwrap();
+    let reachable = report
+        .arm_usefulness
+        .iter()
+        .map(|(_, reachability)| matches!(reachability, Reachability::Reachable(_)))
+        .collect();
+    let witnesses = report
+        .non_exhaustiveness_witnesses
+        .iter()
+        .flat_map(|witness| witness.expand_or_pats(&pattern_arena))
+        .map(|witness| format!("{witness:?}"))
+        .collect();
+    (report.non_exhaustiveness_witnesses.is_empty(), reachable, witnesses)
+}
+
+/// Checks the match by trying all the values of `ty` against the arms in turn.
+fn check_with_brute_force(ty: &Ty, arms: &[(Pattern, bool)]) -> (bool, Vec<bool>) {
+    let values = ty.values();
+    // Whether each value is matched by an arm without a guard so far.
+    let mut covered = vec![false; values.len()];
+    let mut reachable = Vec::with_capacity(arms.len());
+    for (pat, has_guard) in arms {
+        let mut is_reachable = false;
+        for (value, covered) in values.iter().zip(&mut covered) {
+            if pat.matches(value) {
+                is_reachable |= !*covered;
+                *covered |= !has_guard;
+            }
+        }
+        reachable.push(is_reachable);
+    }
+    (covered.into_iter().all(|covered| covered), reachable)
+}
+
+#[test]
+fn snapshot_missing_variants() {
+    check_matches(
+        r#"
+//- minicore: option
+enum E { A, B, C }
+fn f(x: E, y: Option<E>) {
+    match x {
+        E::A => {}
+    }
+    match y {
+        Some(E::A) => {}
+        None => {}
+    }
+    match x {
+        E::A | E::B | E::C => {}
+    }
+}
+"#,
+        expect![[r#"
+            match x
+                missing: `B` and `C` not covered
+            match y
+                missing: `Some(B)` and `Some(C)` not covered
+            match x
+        "#]],
+    );
+}
+
+#[test]
+fn snapshot_slice_lengths() {
+    check_matches(
+        r#"
+//- minicore: option
+fn f(x: &[bool], y: [Option<bool>; 2]) {
+    match x {
+        [] | [_] | [_, _] => {}
+    }
+    match x {
+        [] => {}
+        [true, ..] => {}
+    }
+    match y {
+        [Some(_), ..] => {}
+        [None, Some(true)] => {}
+    }
+}
+"#,
+        expect![[r#"
+            match x
+                missing: `&[_, _, _, ..]` not covered: slices of length 3 or more
+            match x
+                missing: `&[false, ..]` not covered
+            match y
+                missing: `[None, None]` not covered
+        "#]],
+    );
+}
+
+#[test]
+fn snapshot_out_of_range_literals() {
+    check_matches(
+        r#"
+fn f(x: (bool, u8), y: i8) {
+    match x {
+        (true, 300) => {}
+        (false, _) => {}
+    }
+    match x {
+        (_, 256) => {}
+        (true, _) => {}
+        _ => {}
+    }
+    match y {
+        128 => {}
+        _ => {}
+    }
+}
+"#,
+        expect![[r#"
+            match x
+                errors: [LiteralOutOfRange]
+                missing: `(true, _)` not covered
+            match x
+                errors: [LiteralOutOfRange]
+            match y
+                errors: [LiteralOutOfRange]
+        "#]],
+    );
+}
+
+#[test]
+fn snapshot_tuples() {
+    check_matches(
+        r#"
+fn f(x: (bool, bool, bool)) {
+    match x {
+        (true, _, _) => {}
+        (_, true, _) => {}
+        (_, _, true) => {}
+    }
+    match x {
+        (true, true, true) => {}
+        (false, ..) => {}
+        (.., false) => {}
+    }
+}
+"#,
+        expect![[r#"
+            match x
+                missing: `(false, false, false)` not covered
+            match x
+                missing: `(true, false, true)` not covered
+        "#]],
+    );
+}
+
+#[test]
+fn snapshot_tuple_witnesses_grouped_by_first_field() {
+    check_matches(
+        r#"
+enum E { A, B, C }
+enum Sub { X, Y, Z }
+fn f(x: (E, Sub), y: (E, Sub, bool)) {
+    match x {
+        (E::B, _) => {}
+        (_, Sub::Z) => {}
+    }
+    match y {
+        (E::B, _, _) => {}
+        (_, Sub::Z, _) => {}
+        (_, _, true) => {}
+    }
+}
+"#,
+        expect![[r#"
+            match x
+                missing: `(A, X | Y)` and `(C, X | Y)` not covered
+            match y
+                missing: `(A, X | Y, false)` and `(C, X | Y, false)` not covered
+        "#]],
+    );
+}
+
+#[test]
+fn snapshot_unreachable_arms() {
+    check_matches(
+        r#"
+//- minicore: option
+fn f(x: Option<bool>) {
+    match x {
+        Some(_) => {}
+        Some(true) => {}
+        None => {}
+        _ => {}
+    }
+    match x {
+        Some(true) | None => {}
+        Some(false) | None => {}
+    }
+}
+"#,
+        expect![[r#"
+            match x
+                unreachable: Some(true)
+                unreachable: _
+            match x
+        "#]],
+    );
+}
+
+#[test]
+fn snapshot_arms_after_approximated_arm() {
+    check_matches(
+        r#"
+fn f(x: (bool, u8, u8, u8, u8, u8, u8, u8, u8, u8, u8, u8)) {
+    match x {
+        (true, 1 | 2, 1 | 2, 1 | 2, 1 | 2, 1 | 2, 1 | 2, 1 | 2, 1 | 2, 1 | 2, 1 | 2, 1 | 2) => {}
+        (true, 3, _, _, _, _, _, _, _, _, _, _) => {}
+        (false, _, _, _, _, _, _, _, _, _, _, _) => {}
+    }
+}
+"#,
+        expect![[r#"
+            match x
+                approximate
+        "#]],
+    );
+}
+
+#[test]
+fn snapshot_negative_literals() {
+    check_matches(
+        r#"
+fn f(x: i8, y: (bool, i64), z: isize) {
+    match x {
+        -128 => {}
+        -1 => {}
+        0 => {}
+        127 => {}
+    }
+    match x {
+        -128 | -1 => {}
+        -1 => {}
+        _ => {}
+    }
+    match y {
+        (true, _) => {}
+        (false, -9223372036854775808) => {}
+    }
+    match z {
+        -1 => {}
+        -1 => {}
+    }
+}
+"#,
+        expect![[r#"
+            match x
+                missing: `-127_i8..=-2_i8` and `1_i8..=126_i8` not covered
+            match x
+                unreachable: -1
+            match y
+                missing: `(false, -9223372036854775807_i64..=i64::MAX)` not covered
+            match z
+                missing: `_` not covered
+                unreachable: -1
+        "#]],
+    );
+}
+
+#[test]
+fn snapshot_extreme_literals() {
+    check_matches(
+        r#"
+fn f(x: i128, y: u128) {
+    match x {
+        -170141183460469231731687303715884105728 => {}
+        170141183460469231731687303715884105727 => {}
+        -170141183460469231731687303715884105728i128 => {}
+        -1 | 0 => {}
+    }
+    match y {
+        0 => {}
+        170141183460469231731687303715884105728..=340282366920938463463374607431768211454 => {}
+        340282366920938463463374607431768211455 => {}
+        340282366920938463463374607431768211455u128 => {}
+    }
+}
+"#,
+        expect![[r#"
+            match x
+                missing: `-170141183460469231731687303715884105727_i128..=-2_i128` and `1_i128..=170141183460469231731687303715884105726_i128` not covered
+                unreachable: -170141183460469231731687303715884105728i128
+            match y
+                missing: `1_u128..=170141183460469231731687303715884105727_u128` not covered
+                unreachable: 340282366920938463463374607431768211455u128
+        "#]],
+    );
+}
+
+#[test]
+fn snapshot_range_patterns() {
+    check_matches(
+        r#"
+fn f(x: u8, y: i8) {
+    match x {
+        0..=9 => {}
+        10..20 => {}
+        15 => {}
+        21..=255 => {}
+    }
+    match y {
+        -128..=-1 | 1..=127 => {}
+    }
+    match (y,) {
+        (..=-1,) | (0..,) => {}
+    }
+    match x {
+        5..=1 => {}
+        _ => {}
+    }
+    match x {
+        0..=300 => {}
+        _ => {}
+    }
+}
+"#,
+        expect![[r#"
+            match x
+                missing: `20_u8` not covered
+                unreachable: 15
+            match y
+                missing: `0_i8` not covered
+            match (y,)
+            match x
+                errors: [EmptyRange]
+            match x
+                errors: [LiteralOutOfRange]
+        "#]],
+    );
+}
+
+#[test]
+fn snapshot_pattern_types() {
+    check_matches(
+        r#"
+#[rustc_builtin_macro]
+macro pattern_type($($arg:tt)*) {
+    /* compiler built-in */
+}
+
+fn f(x: pattern_type!(u8 is 1..), y: pattern_type!(i8 is -5..5), z: (pattern_type!(u8 is ..=9), bool)) {
+    match x {
+        1..=100 => {}
+        101.. => {}
+    }
+    match x {
+        1..=100 => {}
+    }
+    match y {
+        -5..0 => {}
+        0..=4 => {}
+    }
+    match z {
+        (0..=9, true) => {}
+        (_, false) => {}
+    }
+    match z {
+        (..=8, _) => {}
+    }
+}
+"#,
+        expect![[r#"
+            match x
+            match x
+                missing: `101_u8..=u8::MAX` not covered
+            match y
+            match z
+            match z
+                missing: `(9_u8, _)` not covered
+        "#]],
+    );
+}
+
+#[test]
+fn snapshot_string_patterns() {
+    check_matches(
+        r#"
+const S: &str = "same value";
+const N: u8 = 1;
+fn f(s: &str, t: (&str, bool)) {
+    match s {
+        S => {}
+        "same value" => {}
+        "other" => {}
+    }
+    match t {
+        ("a", true) => {}
+        (_, false) => {}
+        ("a", _) => {}
+    }
+    match 1 {
+        N => {}
+        _ => {}
+    }
+}
+"#,
+        expect![[r#"
+            match s
+                missing: `&_` not covered
+                unreachable: "same value"
+            match t
+                missing: `(&_, true)` not covered
+                unreachable: ("a", _)
+            match 1
+                errors: [Unimplemented]
+        "#]],
+    );
+}
+
+#[test]
+fn snapshot_error_patterns() {
+    check_matches(
+        r#"
+enum E { A, B }
+fn f(x: char, y: (bool, E), z: E) {
+    match x {
+        'a' => {}
+    }
+    match y {
+        (true, E::C) => {}
+        (true, E::A) => {}
+        (true, E::B) => {}
+    }
+    match z {
+        E::A => {}
+        E::A => {}
+        E::C => {}
+        E::B => {}
+        E::B => {}
+    }
+}
+"#,
+        expect![[r#"
+            match x
+                errors: [Unimplemented]
+                missing: `_` not covered
+            match y
+                errors: [UnresolvedVariant]
+                missing: `(false, _)` not covered
+            match z
+                errors: [UnresolvedVariant]
+                unreachable: E::A
+                unreachable: E::B
+        "#]],
+    );
+}
+
+#[test]
+fn snapshot_broken_first_arm() {
+    check_matches(
+        r#"
+enum E { A, B }
+fn f(x: E) {
+    match x {
+        E::C => {}
+        E::A => {}
+        E::A => {}
+    }
+    match x {
+        _y @ E::C => {}
+        E::A => {}
+        E::B => {}
+    }
+}
+"#,
+        expect![[r#"
+            match x
+                errors: [UnresolvedVariant]
+                missing: `E::B` not covered
+                unreachable: E::A
+            match x
+                errors: [UnresolvedVariant]
+        "#]],
+    );
+}
+
+#[test]
+fn constructor_display() {
+    fn render(
+        cx: &UsefulnessCtxt<'_, '_>,
+        pat: &DeconstructedPat<'_>,
+        depth: usize,
+        buf: &mut String,
+    ) {
+        let pcx =
+            ColumnCtxt { cx, ty: pat.ty(), is_top_level: depth == 0, is_non_exhaustive: false };
+        format_to!(buf, "{:indent$}{}\n", "", pat.ctor().display(pcx), indent = 4 * depth);
+        for field in pat.iter_fields() {
+            render(cx, field, depth + 1, buf);
+        }
+    }
+
+    let (db, file_id) = TestDB::with_single_file(
+        r#"
+//- minicore: option
+enum E { A, B(bool), C { x: bool } }
+fn f(x: Option<E>, y: (bool, E), z: &[bool]) {
+    match x {
+        Some(E::B(true)) => {}
+        None => {}
+        _ => {}
+    }
+    match y {
+        (false, E::C { x: true }) => {}
+    }
+    match z {
+        [true, ..] => {}
+        [] => {}
+    }
+}
+"#,
+    );
+    let module = db.module_for_file(file_id);
+    let def_map = module.def_map(&db);
+    let func = def_map[module.local_id]
+        .scope
+        .declarations()
+        .find_map(|def| match def {
+            ModuleDefId::FunctionId(it) => Some(it),
+            _ => None,
+        })
+        .unwrap();
+    let owner = DefWithBodyId::from(func);
+    let (body, source_map) = db.body_with_source_map(owner);
+    let infer = db.infer(owner);
+    let pattern_arena = Arena::new();
+    let row_arena = Arena::new();
+    let cx = MatchCheckCtx::new(module, owner, &db, &pattern_arena, &row_arena);
+
+    let mut matches = body
+        .exprs
+        .iter()
+        .filter_map(|(_, expr)| match expr {
+            Expr::Match { expr, arms } => Some((*expr, arms)),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    matches.sort_by_key(|(scrutinee, _)| {
+        source_map.expr_syntax(*scrutinee).unwrap().value.text_range().start()
+    });
+    let mut actual = String::new();
+    for (_, arms) in matches {
+        let (arms, errors) = lower_arms(&cx, &infer, &body, arms);
+        assert_eq!(errors.concat(), []);
+        for arm in arms {
+            render(&cx.usefulness_cx(), arm.pat, 0, &mut actual);
+        }
+    }
+    expect![[r#"
+        Some(_)
+            B(_)
+                true
+        None
+        _
+        (_, _)
+            false
+            C { .. }
+                true
+        &_
+            [_, ..]
+                true
+        &_
+            []
+    "#]]
+    .assert_eq(&actual);
+}
+
+#[test]
+fn pretty_print_lowered_patterns() {
+    let (db, file_id) = TestDB::with_single_file(
+        r#"
+//- minicore: option
+enum E { A, B(bool) }
+fn f(x: &Option<(E, bool)>) {
+    match x {
+        Some((E::B(b), true)) => {}
+        Some((E::A, _)) | None => {}
+        _ if true => {}
+    }
+}
+"#,
+    );
+    let module = db.module_for_file(file_id);
+    let def_map = module.def_map(&db);
+    let func = def_map[module.local_id]
+        .scope
+        .declarations()
+        .find_map(|def| match def {
+            ModuleDefId::FunctionId(it) => Some(it),
+            _ => None,
+        })
+        .unwrap();
+    let owner = DefWithBodyId::from(func);
+    let body = db.body(owner);
+    let match_expr =
+        body.exprs.iter().find(|(_, expr)| matches!(expr, Expr::Match { .. })).unwrap().0;
+
+    expect![[r#"
+        arm 0: &Some((B(ref b), true))
+            deref: &Option<(E, bool)>
+                variant Some: Option<(E, bool)>
+                    .0: leaf: (E, bool)
+                        .0: variant B: E
+                            .0: binding b (Ref(Not)): bool
+                        .1: true: bool
+        arm 1: &(Some((A, _)) | None)
+            deref: &Option<(E, bool)>
+                or: Option<(E, bool)>
+                    | variant Some: Option<(E, bool)>
+                        .0: leaf: (E, bool)
+                            .0: variant A: E
+                            .1: _: bool
+                    | variant None: Option<(E, bool)>
+        arm 2 (guarded): _
+            _: &Option<(E, bool)>
+    "#]]
+    .assert_eq(&pretty_print_match_patterns(&db, owner, match_expr));
+}
+
+#[test]
+fn fill_arms_bind_fields() {
+    let (db, file_id) = TestDB::with_single_file(
+        r#"
+//- minicore: option, result
+struct Name;
+enum E { A { id: u32, name: Name }, B(Name, Name), C(u32, bool) }
+fn f(x: E, y: Result<u32, bool>, z: (Option<Name>, bool)) {
+    match x {
+        E::C(_, true) => {}
+    }
+    match y {}
+    match z {
+        (None, _) => {}
+    }
+}
+"#,
+    );
+    let module = db.module_for_file(file_id);
+    let def_map = module.def_map(&db);
+    let mut actual = String::new();
+    for def in def_map[module.local_id].scope.declarations() {
+        let ModuleDefId::FunctionId(func) = def else { continue };
+        let owner = DefWithBodyId::from(func);
+        let body = db.body(owner);
+        for (match_expr, expr) in body.exprs.iter() {
+            if matches!(expr, Expr::Match { .. }) {
+                let fill_arms = fill_match_arms(&db, owner, match_expr).unwrap();
+                format_to!(actual, "{}\n", fill_arms.join("\n"));
+            }
+        }
+    }
+    expect![[r#"
+        A { id, name }
+        B(name, name1)
+        C(value, false)
+        Ok(value)
+        Err(value)
+        (Some(name), value)
+    "#]]
+    .assert_eq(&actual);
+}
+
+#[test]
+fn export_decision_trees() {
+    let (db, file_id) = TestDB::with_single_file(
+        r#"
+//- minicore: option
+enum E { A, B }
+fn f(x: Option<(E, bool)>, b: bool) {
+    match x {
+        Some((E::A, true)) => {}
+        Some((_, false)) if true => {}
+        None => {}
+    }
+    match b {
+        true => {}
+    }
+}
+"#,
+    );
+    let module = db.module_for_file(file_id);
+    let def_map = module.def_map(&db);
+    let func = def_map[module.local_id]
+        .scope
+        .declarations()
+        .find_map(|def| match def {
+            ModuleDefId::FunctionId(it) => Some(it),
+            _ => None,
+        })
+        .unwrap();
+    let owner = DefWithBodyId::from(func);
+    let body = db.body(owner);
+    let mut matches =
+        body.exprs.iter().filter(|(_, expr)| matches!(expr, Expr::Match { .. })).map(|(id, _)| id);
+    let (first, second) = (matches.next().unwrap(), matches.next().unwrap());
+
+    expect![[r#"
+        digraph match {
+            n0 [label="scrutinee: Option<(E, bool)>"];
+            n1 [label="arm 2", shape=box];
+            n0 -> n1 [label="None"];
+            n2 [label="scrutinee.0.0: E"];
+            n3 [label="scrutinee.0.1: bool"];
+            n4 [label="arm 1 if guard", shape=box];
+            n5 [label="not covered", shape=box, style=dashed];
+            n4 -> n5 [label="guard fails", style=dashed];
+            n3 -> n4 [label="false"];
+            n6 [label="arm 0", shape=box];
+            n3 -> n6 [label="true"];
+            n2 -> n3 [label="A"];
+            n7 [label="scrutinee.0.1: bool"];
+            n8 [label="arm 1 if guard", shape=box];
+            n9 [label="not covered", shape=box, style=dashed];
+            n8 -> n9 [label="guard fails", style=dashed];
+            n7 -> n8 [label="false"];
+            n10 [label="not covered", shape=box, style=dashed];
+            n7 -> n10 [label="_"];
+            n2 -> n7 [label="_"];
+            n0 -> n2 [label="Some(_)"];
+        }
+    "#]]
+    .assert_eq(&match_decision_tree(&db, owner, first, DecisionTreeFormat::Dot));
+    expect![[r#"
+        {"kind":"switch","place":"scrutinee","ty":"bool","branches":[{"ctor":"true","node":{"kind":"arm","arm":0,"otherwise":null}},{"ctor":"_","node":{"kind":"fail"}}]}
+    "#]]
+    .assert_eq(&match_decision_tree(&db, owner, second, DecisionTreeFormat::Json));
+}
+
+#[test]
+fn check_arms_through_public_api() {
+    let (db, file_id) = TestDB::with_single_file(
+        r#"
+//- minicore: option
+enum E { A, B }
+fn f(x: Option<E>, b: bool) {
+    match x {
+        Some(E::A | E::A) => {}
+        None => {}
+        Some(E::A) => {}
+        _ if b => {}
+    }
+    match b {
+        0 => {}
+    }
+}
+"#,
+    );
+    let module = db.module_for_file(file_id);
+    let def_map = module.def_map(&db);
+    let func = def_map[module.local_id]
+        .scope
+        .declarations()
+        .find_map(|def| match def {
+            ModuleDefId::FunctionId(it) => Some(it),
+            _ => None,
+        })
+        .unwrap();
+    let owner = DefWithBodyId::from(func);
+    let body = db.body(owner);
+    let infer = db.infer(owner);
+    let mut matches = body.exprs.iter().filter_map(|(_, expr)| match expr {
+        Expr::Match { expr, arms } => Some((*expr, arms)),
+        _ => None,
+    });
+    let (first, second) = (matches.next().unwrap(), matches.next().unwrap());
+
+    let arms: Vec<_> =
+        first.1.iter().map(|arm| MatchCheckArm { pat: arm.pat, guard: arm.guard }).collect();
+    let report = check_match_arms(&db, owner, &infer[first.0], &arms).unwrap();
+    let missing = report.missing_patterns.iter().map(|pat| pat.display(&db).to_string());
+    let mut actual = format!("missing: {}\n", missing.format(", "));
+    for reachability in &report.arms {
+        match reachability {
+            ArmReachability::Reachable { unreachable_alternatives } => {
+                format_to!(
+                    actual,
+                    "reachable, {} unreachable alternatives\n",
+                    unreachable_alternatives.len()
+                )
+            }
+            ArmReachability::Unreachable => actual.push_str("unreachable\n"),
+        }
+    }
+    expect![[r#"
+        missing: Some(B)
+        reachable, 1 unreachable alternatives
+        reachable, 0 unreachable alternatives
+        unreachable
+        reachable, 0 unreachable alternatives
+    "#]]
+    .assert_eq(&actual);
+
+    let refutable: Vec<_> = arms.iter().map(|arm| is_refutable(&db, owner, arm.pat)).collect();
+    assert_eq!(refutable, [Ok(true), Ok(true), Ok(true), Ok(false)]);
+
+    let arm = MatchCheckArm { pat: second.1[0].pat, guard: None };
+    assert_eq!(
+        check_match_arms(&db, owner, &infer[second.0], &[arm]),
+        Err(MatchCheckError::InvalidPattern(arm.pat))
+    );
+}
+
+#[test]
+fn witnesses_as_source_code() {
+    let (db, file_id) = TestDB::with_single_file(
+        r#"
+mod m {
+    pub enum E { A, B, C }
+}
+enum F { X, Y(bool) }
+fn f(x: &m::E, y: (F, bool)) {
+    match x {
+        m::E::A => {}
+    }
+    match y {
+        (F::X, _) => {}
+    }
+}
+"#,
+    );
+    let module = db.module_for_file(file_id);
+    let def_map = module.def_map(&db);
+    let func = def_map[module.local_id]
+        .scope
+        .declarations()
+        .find_map(|def| match def {
+            ModuleDefId::FunctionId(it) => Some(it),
+            _ => None,
+        })
+        .unwrap();
+    let owner = DefWithBodyId::from(func);
+    let body = db.body(owner);
+    let infer = db.infer(owner);
+
+    let mut actual = String::new();
+    for (_, expr) in body.exprs.iter() {
+        let Expr::Match { expr, arms } = expr else { continue };
+        let arms: Vec<_> =
+            arms.iter().map(|arm| MatchCheckArm { pat: arm.pat, guard: arm.guard }).collect();
+        let report = check_match_arms(&db, owner, &infer[*expr], &arms).unwrap();
+        for pat in &report.missing_patterns {
+            let arm = PatSourceOptions { bind_fields: true, placeholders: true };
+            format_to!(
+                actual,
+                "{} => {} / {}\n",
+                pat.display(&db),
+                pat.to_source(&db, module, PatSourceOptions::default()).unwrap(),
+                pat.to_source(&db, module, arm).unwrap(),
+            );
+        }
+    }
+    expect![[r#"
+        &B => &m::E::B / &m::E::B
+        &C => &m::E::C / &m::E::C
+        (Y(_), _) => (F::Y(_), _) / (F::Y(${1:value}), ${2:value1})
+    "#]]
+    .assert_eq(&actual);
+}
+
+#[test]
+fn usefulness_matches_brute_force() {
+    const REPEATS: usize = 128;
+
+    let mut rng = Rand32::new(123456789);
+    let mut checked = 0;
+    while checked < REPEATS {
+        let ty = gen_ty(&mut rng, 3);
+        if ty.value_count() > MAX_VALUES {
+            continue;
+        }
+        let arms = (0..rng.rand_range(1..6))
+            .map(|_| (gen_pat(&mut rng, &ty), rng.rand_range(0..6) == 0))
+            .collect::<Vec<_>>();
+
+        let expected = check_with_brute_force(&ty, &arms);
+        let actual = check_with_usefulness(&ty, &arms);
+        let arms = arms
+            .iter()
+            .map(|(pat, has_guard)| format!("{pat}{}", if *has_guard { " if true" } else { "" }))
+            .join(", ");
+        assert_eq!(
+            actual, expected,
+            "(exhaustive, reachable arms) mismatch matching on `{ty}` with {arms}"
+        );
+        checked += 1;
+    }
+}
+
+#[test]
+fn usefulness_without_database_matches_brute_force() {
+    const REPEATS: usize = 1024;
+
+    let mut rng = Rand32::new(987654321);
+    let mut checked = 0;
+    while checked < REPEATS {
+        let ty = gen_ty(&mut rng, 3);
+        if ty.value_count() > MAX_VALUES {
+            continue;
+        }
+        let arms = (0..rng.rand_range(1..6))
+            .map(|_| (gen_pat(&mut rng, &ty), rng.rand_range(0..6) == 0))
+            .collect::<Vec<_>>();
+
+        let expected = check_with_brute_force(&ty, &arms);
+        let (is_exhaustive, reachable, _) = check_without_database(&ty, &arms);
+        let arms = arms
+            .iter()
+            .map(|(pat, has_guard)| format!("{pat}{}", if *has_guard { " if true" } else { "" }))
+            .join(", ");
+        assert_eq!(
+            (is_exhaustive, reachable),
+            expected,
+            "(exhaustive, reachable arms) mismatch matching on `{ty}` with {arms}"
+        );
+        checked += 1;
+    }
+}
+
+#[test]
+fn witnesses_without_database() {
+    let ty = Ty::Tuple(
+        Box::new(Ty::Option(Box::new(Ty::Enum))),
+        Box::new(Ty::Slice(Box::new(Ty::Bool))),
+    );
+    let e_b = Pattern::Some(Box::new(Pattern::Variant(1)));
+    let arms = [
+        (Pattern::Tuple(Box::new(Pattern::None), Box::new(Pattern::Wild)), false),
+        (Pattern::Tuple(Box::new(e_b), Box::new(Pattern::Slice(vec![], Some(vec![])))), false),
+        (Pattern::Tuple(Box::new(Pattern::Wild), Box::new(Pattern::Slice(vec![], None))), false),
+    ];
+    let (is_exhaustive, reachable, witnesses) = check_without_database(&ty, &arms);
+    assert!(!is_exhaustive);
+    assert_eq!(reachable, [true, true, true]);
+    // Without a database, the Debug output shows variants by their index: `Some(A)` is `#1(#0())`.
+    expect![[r#"
+        [
+            "(#1(#0()), &[_, ..])",
+            "(#1(#2()), &[_, ..])",
+        ]
+    "#]]
+    .assert_debug_eq(&witnesses);
+}
+
+#[test]
+fn deeply_nested_patterns_without_database() {
+    // `is_useful` recurses once per level, which would overflow the stack without growing it.
+    const DEPTH: usize = 2000;
+
+    let ty = (0..DEPTH).fold(Ty::Bool, |ty, _| Ty::Option(Box::new(ty)));
+    let pat = (0..DEPTH).fold(Pattern::Bool(true), |pat, _| Pattern::Some(Box::new(pat)));
+    let arms = [(pat.clone(), false), (pat, false), (Pattern::Wild, false)];
+    let (is_exhaustive, reachable, _) = check_without_database(&ty, &arms);
+    assert!(is_exhaustive);
+    assert_eq!(reachable, [true, false, true]);
+}
+
+/// Checks all the matches of `ra_fixture` and prints how long it took. The fixture is checked once
+/// beforehand, so that only match checking is measured and not name resolution or inference.
+fn bench_matches(label: &'static str, ra_fixture: &str) -> Vec<CheckedMatch> {
+    let (db, file_id) = TestDB::with_single_file(ra_fixture);
+    check_all_matches(&db, file_id);
+    let _b = bench(label);
+    check_all_matches(&db, file_id)
+}
+
+#[test]
+fn benchmark_giant_enum() {
+    if skip_slow_tests() {
+        return;
+    }
+    const VARIANT_COUNT: usize = 2000;
+
+    let variants = (0..VARIANT_COUNT).map(|i| format!("V{i}")).join(", ");
+    let arms = (0..VARIANT_COUNT - 1).map(|i| format!("        E::V{i} => {{}}\n")).join("");
+    let fixture =
+        format!("enum E {{ {variants} }}\nfn f(x: E) {{\n    match x {{\n{arms}    }}\n}}\n");
+
+    let matches = bench_matches("match on a giant enum", &fixture);
+    let MatchOutcome::Checked { missing, .. } = &matches[0].outcome else { panic!() };
+    assert_eq!(missing.as_deref(), Some(&*format!("`V{}` not covered", VARIANT_COUNT - 1)));
+}
+
+#[test]
+fn benchmark_deep_or_patterns() {
+    if skip_slow_tests() {
+        return;
+    }
+    const WIDTH: usize = 10;
+
+    // Each arm covers one of the columns with an or-pattern, and the other columns with wildcards.
+    let arms = (0..WIDTH)
+        .map(|i| {
+            let mut pats = vec!["_"; WIDTH];
+            pats[i] = "Some(true) | None";
+            format!("        ({}) => {{}}\n", pats.join(", "))
+        })
+        .join("");
+    let ty = vec!["Option<bool>"; WIDTH].join(", ");
+    let fixture =
+        format!("//- minicore: option\nfn f(x: ({ty})) {{\n    match x {{\n{arms}    }}\n}}\n");
+
+    let matches = bench_matches("match with deep or-patterns", &fixture);
+    assert!(matches!(matches[0].outcome, MatchOutcome::Checked { .. }));
+}
+
+#[test]
+fn benchmark_wide_integer_ranges() {
+    if skip_slow_tests() {
+        return;
+    }
+    const RANGES: u32 = 1000;
+
+    let arms =
+        (0..RANGES).map(|i| format!("        {}..={} => {{}}\n", i * 60, i * 60 + 59)).join("");
+    let fixture = format!("fn f(x: u16) {{\n    match x {{\n{arms}    }}\n}}\n");
+
+    bench_matches("match on wide integer ranges", &fixture);
+}
+
+#[test]
+fn benchmark_long_slice_patterns() {
+    if skip_slow_tests() {
+        return;
+    }
+    const LEN: usize = 40;
+
+    // Arms with a `true` at each position from either end, of increasing lengths.
+    let arms = (0..LEN)
+        .flat_map(|i| {
+            let wildcards = vec!["_"; i].join(", ");
+            let sep = if i == 0 { "" } else { ", " };
+            [
+                format!("        [{wildcards}{sep}true, ..] => {{}}\n"),
+                format!("        [.., true{sep}{wildcards}] => {{}}\n"),
+                format!("        [{wildcards}{sep}false, .., false{sep}{wildcards}] => {{}}\n"),
+            ]
+        })
+        .join("");
+    let fixture = format!("fn f(x: &[bool]) {{\n    match x {{\n{arms}    }}\n}}\n");
+
+    let matches = bench_matches("match with long slice patterns", &fixture);
+    assert!(matches!(matches[0].outcome, MatchOutcome::Checked { .. }));
+}
@@ -271,69 +271,225 @@
 //! The details are not necessary to understand this file, so we explain them in
 //! [`super::deconstruct_pat`]. Splitting is done by the [`Constructor::split`] function.
 
-use std::iter::once;
+use std::{
+    cell::{Cell, RefCell},
+    fmt,
+    iter::once,
+};
 
-use hir_def::{AdtId, DefWithBodyId, HasModule, ModuleId};
-use smallvec::{smallvec, SmallVec};
+use hir_def::expr::{ExprId, PatId};
+use itertools::Itertools;
+use rustc_hash::{FxHashMap, FxHashSet};
+use smallvec::SmallVec;
+use stdx::{format_to, never};
 use typed_arena::Arena;
 
-use crate::{db::HirDatabase, inhabitedness::is_ty_uninhabited_from, Ty, TyExt};
+use crate::Ty;
 
-use super::deconstruct_pat::{Constructor, DeconstructedPat, Fields, SplitWildcard};
+use super::{
+    deconstruct_pat::{
+        Constructor, ConstructorSet, DeconstructedPat, FieldlessCtors, Fields, SplitKey,
+        SplitWildcard,
+    },
+    ensure_sufficient_stack, Pat,
+};
 
 use self::{helper::Captures, ArmType::*, Usefulness::*};
 
-pub(crate) struct MatchCheckCtx<'a, 'p> {
-    pub(crate) module: ModuleId,
-    pub(crate) body: DefWithBodyId,
-    pub(crate) db: &'a dyn HirDatabase,
+/// The number of rows a single arm may expand to through its or-patterns, e.g. the 1024 rows of
+/// `(1 | 2 | 3 | 4, 1 | 2 | 3 | 4, 1 | 2 | 3 | 4, 1 | 2 | 3 | 4, 1 | 2 | 3 | 4)`. Larger arms are
+/// approximated, see [`UsefulnessReport::is_approximate`].
+const OR_EXPANSION_LIMIT: usize = 1024;
+
+/// Counters of the work done checking a match, to look into slow matches without a profiler. They
+/// are only collected when the `RA_MATCH_CHECK_STATS` environment variable is set.
+#[derive(Debug, Default, Clone, Copy)]
+pub(crate) struct MatchCheckStats {
+    /// Patterns allocated in the pattern arena, including the lowered arms.
+    pub(crate) patterns_allocated: usize,
+    /// Rows created by specializing a matrix.
+    pub(crate) rows_specialized: usize,
+    /// Constructors resulting from splitting, each of which is specialized with.
+    pub(crate) ctors_split: usize,
+    /// Witnesses built while looking for missing patterns.
+    pub(crate) witnesses_built: usize,
+    /// Sub-problems whose usefulness was found in the memo, see [`UsefulnessKey`].
+    pub(crate) memo_hits: usize,
+}
+
+fn is_match_check_stats_enabled() -> bool {
+    std::env::var("RA_MATCH_CHECK_STATS").is_ok()
+}
+
+/// Returned when checking a match would exceed its complexity limit.
+#[derive(Debug)]
+pub(crate) struct TooComplex;
+
+/// What the usefulness algorithm needs to know about the types it checks patterns of. Keeping this
+/// behind a trait separates the algorithm from the database: [`MatchCheckCtx`] implements it for
+/// the types of a body, and the algorithm can be tested with types that need no database.
+///
+/// [`MatchCheckCtx`]: super::pat_analysis::MatchCheckCtx
+pub(super) trait TypeCx {
+    /// Whether `ty` is an enum declared `#[non_exhaustive]` in another crate, whose missing
+    /// variants are reported as a single `_`.
+    fn is_foreign_non_exhaustive_enum(&self, ty: &Ty) -> bool;
+
+    /// The set of all the constructors of `ty`, which is what a wildcard of that type stands for.
+    /// `is_top_level` tells whether `ty` is the type of the scrutinee.
+    fn ctors_for_ty(&self, ty: &Ty, is_top_level: bool) -> ConstructorSet;
+
+    /// The types of the fields of `ctor`, a constructor of `ty`, in the order of the fields of
+    /// patterns. Patterns and their types are compared as they are, so these must be normalized
+    /// like the types of the lowered patterns.
+    fn ctor_sub_tys(&self, ctor: &Constructor, ty: &Ty) -> SmallVec<[Ty; 2]>;
+
+    /// The amount of work the check of a single match may do, see [`TooComplex`].
+    fn complexity_limit(&self) -> usize;
+
+    /// Called regularly during the check, so that a check whose result is no longer wanted can be
+    /// cancelled.
+    fn unwind_if_cancelled(&self) {}
+
+    /// Renders `pat` as it would be written in source, for diagnostics and tracing.
+    fn display_pat(&self, pat: &DeconstructedPat<'_>) -> String;
+
+    /// Renders `ty` as it would be written in source, for tracing.
+    fn display_ty(&self, ty: &Ty) -> String;
+}
+
+/// The state of the usefulness algorithm while checking a match: the arenas the patterns it
+/// creates live in, its caches and the work done so far.
+pub(super) struct UsefulnessCtxt<'a, 'p> {
+    pub(super) tcx: &'a dyn TypeCx,
     /// Lowered patterns from arms plus generated by the check.
-    pub(crate) pattern_arena: &'p Arena<DeconstructedPat<'p>>,
-    exhaustive_patterns: bool,
+    pub(super) pattern_arena: &'p Arena<DeconstructedPat<'p>>,
+    /// The rows of the matrices built during the check, see [`PatStack`].
+    row_arena: &'p Arena<&'p DeconstructedPat<'p>>,
+    /// Wildcard field lists generated by specialization, interned by type and constructor.
+    pub(super) wildcard_fields: RefCell<FxHashMap<(Ty, Constructor), Fields<'p>>>,
+    /// Memoized results of [`Constructor::split`].
+    pub(super) split_cache: RefCell<FxHashMap<SplitKey, SmallVec<[Constructor; 1]>>>,
+    /// The ids of the structures of the patterns and rows seen during the check, see
+    /// [`PatStructure`]. Patterns are keyed by their address in the `pattern_arena`.
+    pat_structures: RefCell<FxHashMap<(Constructor, Ty, Box<[PatStructure]>), PatStructure>>,
+    pat_structure_cache: RefCell<FxHashMap<*const DeconstructedPat<'p>, PatStructure>>,
+    row_structures: RefCell<FxHashMap<Box<[PatStructure]>, RowStructure>>,
+    /// Memoized results of `is_useful` while checking exhaustiveness, only filled when
+    /// `memoize_usefulness` is set.
+    usefulness_memo: RefCell<FxHashMap<UsefulnessKey, Usefulness<'p>>>,
+    memoize_usefulness: Cell<bool>,
+    /// The amount of work (matrix rows visited per specialization) the check may do before
+    /// giving up, see [`TooComplex`].
+    complexity_limit: usize,
+    complexity: Cell<usize>,
+    /// The range patterns found sharing an endpoint with the range patterns before them.
+    overlapping_range_endpoints: RefCell<Vec<OverlappingRangeEndpoints>>,
+    /// `None` unless stats are enabled. Patterns allocated are counted from `arena_len_at_start`.
+    stats: Option<Cell<MatchCheckStats>>,
+    arena_len_at_start: usize,
 }
 
-impl<'a, 'p> MatchCheckCtx<'a, 'p> {
-    pub(crate) fn new(
-        module: ModuleId,
-        body: DefWithBodyId,
-        db: &'a dyn HirDatabase,
+impl<'a, 'p> UsefulnessCtxt<'a, 'p> {
+    pub(super) fn new(
+        tcx: &'a dyn TypeCx,
         pattern_arena: &'p Arena<DeconstructedPat<'p>>,
+        row_arena: &'p Arena<&'p DeconstructedPat<'p>>,
     ) -> Self {
-        let def_map = db.crate_def_map(module.krate());
-        let exhaustive_patterns = def_map.is_unstable_feature_enabled("exhaustive_patterns");
-        Self { module, body, db, pattern_arena, exhaustive_patterns }
+        Self {
+            tcx,
+            pattern_arena,
+            row_arena,
+            wildcard_fields: Default::default(),
+            split_cache: Default::default(),
+            pat_structures: Default::default(),
+            pat_structure_cache: Default::default(),
+            row_structures: Default::default(),
+            usefulness_memo: Default::default(),
+            memoize_usefulness: Cell::new(false),
+            complexity_limit: tcx.complexity_limit(),
+            complexity: Cell::new(0),
+            overlapping_range_endpoints: Default::default(),
+            stats: is_match_check_stats_enabled().then(Default::default),
+            arena_len_at_start: pattern_arena.len(),
+        }
     }
 
-    pub(super) fn is_uninhabited(&self, ty: &Ty) -> bool {
-        if self.feature_exhaustive_patterns() {
-            is_ty_uninhabited_from(ty, self.module, self.db)
-        } else {
-            false
+    fn record_stats(&self, f: impl FnOnce(&mut MatchCheckStats)) {
+        if let Some(stats) = &self.stats {
+            let mut new_stats = stats.get();
+            f(&mut new_stats);
+            stats.set(new_stats);
         }
     }
 
-    /// Returns whether the given type is an enum from another crate declared `#[non_exhaustive]`.
-    pub(super) fn is_foreign_non_exhaustive_enum(&self, ty: &Ty) -> bool {
-        match ty.as_adt() {
-            Some((adt @ AdtId::EnumId(_), _)) => {
-                let has_non_exhaustive_attr =
-                    self.db.attrs(adt.into()).by_key("non_exhaustive").exists();
-                let is_local = adt.module(self.db.upcast()).krate() == self.module.krate();
-                has_non_exhaustive_attr && !is_local
+    /// Records the range patterns of `others` that share an endpoint with the range pattern `pat`.
+    /// The same pattern can be checked against several matrices, whose overlaps are merged.
+    fn record_overlapping_range_endpoints(
+        &self,
+        pat: &'p DeconstructedPat<'p>,
+        others: impl Iterator<Item = &'p DeconstructedPat<'p>>,
+    ) {
+        let Some(id) = pat.id() else { return };
+        let overlaps = pat.overlapping_range_endpoints(others);
+        if overlaps.is_empty() {
+            return;
+        }
+        let mut recorded = self.overlapping_range_endpoints.borrow_mut();
+        match recorded.iter_mut().find(|it| it.pat == id) {
+            Some(it) => {
+                for overlap in overlaps {
+                    if !it.overlaps.contains(&overlap) {
+                        it.overlaps.push(overlap);
+                    }
+                }
             }
-            _ => false,
+            None => recorded.push(OverlappingRangeEndpoints { pat: id, overlaps }),
+        }
+    }
+
+    /// The id of the structure of `pat`, interned from the ids of its fields.
+    fn pat_structure(&self, pat: &'p DeconstructedPat<'p>) -> PatStructure {
+        if let Some(&structure) = self.pat_structure_cache.borrow().get(&(pat as *const _)) {
+            return structure;
         }
+        let fields = pat.iter_fields().map(|field| self.pat_structure(field)).collect();
+        let mut structures = self.pat_structures.borrow_mut();
+        let next = PatStructure(structures.len() as u32);
+        let structure =
+            *structures.entry((pat.ctor().clone(), pat.ty().clone(), fields)).or_insert(next);
+        self.pat_structure_cache.borrow_mut().insert(pat as *const _, structure);
+        structure
+    }
+
+    /// The id of the structure of the row made of `pats`.
+    fn row_structure(&self, pats: &[&'p DeconstructedPat<'p>]) -> RowStructure {
+        let pats = pats.iter().map(|&pat| self.pat_structure(pat)).collect();
+        let mut structures = self.row_structures.borrow_mut();
+        let next = RowStructure(structures.len() as u32);
+        *structures.entry(pats).or_insert(next)
     }
 
-    // Rust's unstable feature described as "Allows exhaustive pattern matching on types that contain uninhabited types."
-    pub(super) fn feature_exhaustive_patterns(&self) -> bool {
-        self.exhaustive_patterns
+    fn stats(&self) -> Option<MatchCheckStats> {
+        let mut stats = self.stats.as_ref()?.get();
+        stats.patterns_allocated = self.pattern_arena.len() - self.arena_len_at_start;
+        Some(stats)
+    }
+
+    /// Accounts for `amount` units of work, failing once the complexity limit is exceeded.
+    fn increase_complexity(&self, amount: usize) -> Result<(), TooComplex> {
+        let complexity = self.complexity.get() + amount;
+        self.complexity.set(complexity);
+        if complexity > self.complexity_limit {
+            return Err(TooComplex);
+        }
+        Ok(())
     }
 }
 
 #[derive(Copy, Clone)]
 pub(super) struct PatCtxt<'a, 'p> {
-    pub(super) cx: &'a MatchCheckCtx<'a, 'p>,
+    pub(super) cx: &'a UsefulnessCtxt<'a, 'p>,
     /// Type of the current column under investigation.
     pub(super) ty: &'a Ty,
     /// Whether the current pattern is the whole pattern as found in a match arm, or if it's a
@@ -343,20 +499,36 @@ pub(super) struct PatCtxt<'a, 'p> {
     pub(super) is_non_exhaustive: bool,
 }
 
-/// A row of a matrix. Rows of len 1 are very common, which is why `SmallVec[_; 2]`
-/// works well.
-#[derive(Clone)]
+/// Identifies patterns with the same constructor, type and fields within a [`UsefulnessCtxt`],
+/// i.e. patterns that are [`DeconstructedPat::is_same_as`] each other, so that they can be
+/// compared and hashed without walking their fields.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+struct PatStructure(u32);
+
+/// Identifies rows whose patterns have the same [`PatStructure`]s within a [`UsefulnessCtxt`].
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+struct RowStructure(u32);
+
+/// A row of a matrix. Specialization creates a lot of rows, so they are allocated in the
+/// `row_arena` of the [`UsefulnessCtxt`] rather than each on the heap, which also makes them cheap
+/// to copy.
+#[derive(Clone, Copy)]
 pub(super) struct PatStack<'p> {
-    pats: SmallVec<[&'p DeconstructedPat<'p>; 2]>,
+    pats: &'p [&'p DeconstructedPat<'p>],
+    structure: RowStructure,
 }
 
 impl<'p> PatStack<'p> {
-    fn from_pattern(pat: &'p DeconstructedPat<'p>) -> Self {
-        Self::from_vec(smallvec![pat])
+    fn from_pattern(cx: &UsefulnessCtxt<'_, 'p>, pat: &'p DeconstructedPat<'p>) -> Self {
+        Self::from_iter(cx, once(pat))
     }
 
-    fn from_vec(vec: SmallVec<[&'p DeconstructedPat<'p>; 2]>) -> Self {
-        PatStack { pats: vec }
+    fn from_iter(
+        cx: &UsefulnessCtxt<'_, 'p>,
+        pats: impl IntoIterator<Item = &'p DeconstructedPat<'p>>,
+    ) -> Self {
+        let pats = cx.row_arena.alloc_extend(pats);
+        PatStack { pats, structure: cx.row_structure(pats) }
     }
 
     fn is_empty(&self) -> bool {
@@ -373,11 +545,12 @@ fn head(&self) -> &'p DeconstructedPat<'p> {
 
     // Recursively expand the first pattern into its subpatterns. Only useful if the pattern is an
     // or-pattern. Panics if `self` is empty.
-    fn expand_or_pat(&self) -> impl Iterator<Item = PatStack<'p>> + Captures<'_> {
+    fn expand_or_pat<'a>(
+        self,
+        cx: &'a UsefulnessCtxt<'a, 'p>,
+    ) -> impl Iterator<Item = PatStack<'p>> + Captures<'a> {
         self.head().iter_fields().map(move |pat| {
-            let mut new_patstack = PatStack::from_pattern(pat);
-            new_patstack.pats.extend_from_slice(&self.pats[1..]);
-            new_patstack
+            PatStack::from_iter(cx, once(pat).chain(self.pats[1..].iter().copied()))
         })
     }
 
@@ -387,12 +560,35 @@ fn expand_or_pat(&self) -> impl Iterator<Item = PatStack<'p>> + Captures<'_> {
     /// fields filled with wild patterns.
     ///
     /// This is roughly the inverse of `Constructor::apply`.
-    fn pop_head_constructor(&self, cx: &MatchCheckCtx<'_, 'p>, ctor: &Constructor) -> PatStack<'p> {
+    fn pop_head_constructor(
+        &self,
+        cx: &UsefulnessCtxt<'_, 'p>,
+        ctor: &Constructor,
+    ) -> PatStack<'p> {
         // We pop the head pattern and push the new fields extracted from the arguments of
         // `self.head()`.
-        let mut new_fields: SmallVec<[_; 2]> = self.head().specialize(cx, ctor);
-        new_fields.extend_from_slice(&self.pats[1..]);
-        PatStack::from_vec(new_fields)
+        let new_fields = self.head().specialize(cx, ctor);
+        PatStack::from_iter(cx, new_fields.into_iter().chain(self.pats[1..].iter().copied()))
+    }
+
+    /// Renders the row as `+ pat + pat +`, for tracing.
+    fn display(&self, cx: &UsefulnessCtxt<'_, 'p>) -> String {
+        let mut buf = String::from("+");
+        for pat in self.pats {
+            format_to!(buf, " {} +", cx.tcx.display_pat(pat));
+        }
+        buf
+    }
+}
+
+/// Prints the row as `+ pat + pat +`, see the `Debug` impl of [`Matrix`].
+impl fmt::Debug for PatStack<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "+")?;
+        for pat in self.pats {
+            write!(f, " {pat:?} +")?;
+        }
+        Ok(())
     }
 }
 
@@ -400,28 +596,34 @@ fn pop_head_constructor(&self, cx: &MatchCheckCtx<'_, 'p>, ctor: &Constructor) -
 #[derive(Clone)]
 pub(super) struct Matrix<'p> {
     patterns: Vec<PatStack<'p>>,
+    /// The structures of the rows of `patterns`. A row identical to one already present can't
+    /// change the result of the check, so it isn't pushed again. Reachability is not taken into
+    /// account.
+    rows: FxHashSet<RowStructure>,
 }
 
 impl<'p> Matrix<'p> {
     fn empty() -> Self {
-        Matrix { patterns: vec![] }
-    }
-
-    /// Number of columns of this matrix. `None` is the matrix is empty.
-    pub(super) fn _column_count(&self) -> Option<usize> {
-        self.patterns.get(0).map(|r| r.len())
+        Matrix { patterns: vec![], rows: FxHashSet::default() }
     }
 
     /// Pushes a new row to the matrix. If the row starts with an or-pattern, this recursively
     /// expands it.
-    fn push(&mut self, row: PatStack<'p>) {
+    fn push(&mut self, cx: &UsefulnessCtxt<'_, 'p>, row: PatStack<'p>) {
         if !row.is_empty() && row.head().is_or_pat() {
-            self.patterns.extend(row.expand_or_pat());
-        } else {
+            row.expand_or_pat(cx).for_each(|row| self.push(cx, row));
+        } else if self.rows.insert(row.structure) {
             self.patterns.push(row);
         }
     }
 
+    /// Removes the rows pushed after the matrix had `len` rows.
+    fn truncate(&mut self, len: usize) {
+        for row in self.patterns.drain(len..) {
+            self.rows.remove(&row.structure);
+        }
+    }
+
     /// Iterate over the first component of each row
     fn heads(&self) -> impl Iterator<Item = &'p DeconstructedPat<'p>> + Clone + Captures<'_> {
         self.patterns.iter().map(|r| r.head())
@@ -433,11 +635,65 @@ fn specialize_constructor(&self, pcx: PatCtxt<'_, 'p>, ctor: &Constructor) -> Ma
         for row in &self.patterns {
             if ctor.is_covered_by(pcx, row.head().ctor()) {
                 let new_row = row.pop_head_constructor(pcx.cx, ctor);
-                matrix.push(new_row);
+                matrix.push(pcx.cx, new_row);
             }
         }
+        pcx.cx.record_stats(|stats| stats.rows_specialized += matrix.patterns.len());
         matrix
     }
+
+    /// Renders the matrix one row per line, for tracing. Unlike the `Debug` impl, this has
+    /// access to the database and shows patterns as they are written in source.
+    fn display(&self, cx: &UsefulnessCtxt<'_, 'p>) -> String {
+        self.patterns.iter().map(|row| row.display(cx)).collect::<Vec<_>>().join("\n")
+    }
+}
+
+/// Pretty-printer for matrices of patterns, example:
+///
+/// ```text
+/// + _     + []                +
+/// + true  + [#0]              +
+/// + true  + [#1(true)]        +
+/// + false + [_]               +
+/// + _     + [_, _, ..]        +
+/// ```
+impl fmt::Debug for Matrix<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f)?;
+
+        let pretty_printed_matrix: Vec<Vec<String>> = self
+            .patterns
+            .iter()
+            .map(|row| row.pats.iter().map(|pat| format!("{pat:?}")).collect())
+            .collect();
+
+        let column_count = self.patterns.first().map_or(0, |row| row.len());
+        assert!(self.patterns.iter().all(|row| row.len() == column_count));
+        let column_widths: Vec<usize> = (0..column_count)
+            .map(|col| pretty_printed_matrix.iter().map(|row| row[col].len()).max().unwrap_or(0))
+            .collect();
+
+        for row in pretty_printed_matrix {
+            write!(f, "+")?;
+            for (column, pat_str) in row.into_iter().enumerate() {
+                write!(f, " {:1$} +", pat_str, column_widths[column])?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+/// A sub-problem of the exhaustiveness check: the rows of the matrix and the vector checked against
+/// them, compared by [`RowStructure`]. Or-patterns expand to rows that only differ in a column or
+/// two, so specializing them leads to the same sub-problems over and over, which is what the
+/// `usefulness_memo` of the [`UsefulnessCtxt`] saves us from recomputing.
+#[derive(PartialEq, Eq, Hash)]
+struct UsefulnessKey {
+    rows: Box<[RowStructure]>,
+    v: RowStructure,
+    is_top_level: bool,
 }
 
 /// This carries the results of computing usefulness, as described at the top of the file. When
@@ -476,6 +732,44 @@ fn is_useful(&self) -> bool {
         }
     }
 
+    /// Renders the result, for tracing.
+    fn display(&self, cx: &UsefulnessCtxt<'_, 'p>) -> String {
+        match self {
+            NoWitnesses { useful } => if *useful { "useful" } else { "not useful" }.to_owned(),
+            WithWitnesses(witnesses) => {
+                let mut buf = format!("{} witnesses", witnesses.len());
+                for Witness(pats) in witnesses {
+                    // The patterns of a witness are in reverse order.
+                    buf.push_str("\n+");
+                    for pat in pats.iter().rev() {
+                        format_to!(buf, " {} +", cx.tcx.display_pat(pat));
+                    }
+                }
+                buf
+            }
+        }
+    }
+
+    /// Copies a memoized result. Witnesses are never checked for reachability, so forgetting it
+    /// is fine.
+    fn clone_witnesses(&self) -> Self {
+        match self {
+            NoWitnesses { useful } => NoWitnesses { useful: *useful },
+            WithWitnesses(witnesses) => WithWitnesses(
+                witnesses
+                    .iter()
+                    .map(|Witness(pats)| {
+                        Witness(
+                            pats.iter()
+                                .map(DeconstructedPat::clone_and_forget_reachability)
+                                .collect(),
+                        )
+                    })
+                    .collect(),
+            ),
+        }
+    }
+
     /// Combine usefulnesses from two branches. This is an associative operation.
     fn extend(&mut self, other: Self) {
         match (&mut *self, other) {
@@ -495,23 +789,27 @@ fn extend(&mut self, other: Self) {
     fn apply_constructor(
         self,
         pcx: PatCtxt<'_, 'p>,
-        matrix: &Matrix<'p>,
+        split_wildcard: Option<&SplitWildcard>,
         ctor: &Constructor,
     ) -> Self {
         match self {
             NoWitnesses { .. } => self,
             WithWitnesses(ref witnesses) if witnesses.is_empty() => self,
             WithWitnesses(witnesses) => {
-                let new_witnesses = if let Constructor::Missing { .. } = ctor {
+                let new_witnesses: Vec<_> = if let Constructor::Missing { .. } = ctor {
                     // We got the special `Missing` constructor, so each of the missing constructors
                     // gives a new pattern that is not caught by the match. We list those patterns.
-                    let new_patterns = if pcx.is_non_exhaustive {
+                    let mut new_patterns = if pcx.is_non_exhaustive {
                         // Here we don't want the user to try to list all variants, we want them to add
                         // a wildcard, so we only suggest that.
                         vec![DeconstructedPat::wildcard(pcx.ty.clone())]
                     } else {
-                        let mut split_wildcard = SplitWildcard::new(pcx);
-                        split_wildcard.split(pcx, matrix.heads().map(DeconstructedPat::ctor));
+                        // `Missing` only comes out of splitting a wildcard, so the split has
+                        // already been computed for this column.
+                        let Some(split_wildcard) = split_wildcard else {
+                            never!("`Missing` constructor without a split wildcard");
+                            return WithWitnesses(Vec::new());
+                        };
 
                         // This lets us know if we skipped any variants because they are marked
                         // `doc(hidden)` or they are unstable feature gate (only stdlib types).
@@ -541,19 +839,23 @@ fn apply_constructor(
                         new
                     };
 
+                    // Rather than a witness for each of those patterns, which multiplies quickly
+                    // with nested missing constructors, we add an or-pattern of all of them. It is
+                    // only expanded if the witnesses get displayed, see `expand_or_pats`.
+                    let new_pattern = match new_patterns.len() {
+                        0 => return WithWitnesses(Vec::new()),
+                        1 => new_patterns.pop().unwrap(),
+                        _ => DeconstructedPat::new(
+                            Constructor::Or,
+                            Fields::from_iter(pcx.cx.pattern_arena, new_patterns),
+                            pcx.ty.clone(),
+                        ),
+                    };
                     witnesses
                         .into_iter()
-                        .flat_map(|witness| {
-                            new_patterns.iter().map(move |pat| {
-                                Witness(
-                                    witness
-                                        .0
-                                        .iter()
-                                        .chain(once(pat))
-                                        .map(DeconstructedPat::clone_and_forget_reachability)
-                                        .collect(),
-                                )
-                            })
+                        .map(|mut witness| {
+                            witness.0.push(new_pattern.clone_and_forget_reachability());
+                            witness
                         })
                         .collect()
                 } else {
@@ -562,6 +864,7 @@ fn apply_constructor(
                         .map(|witness| witness.apply_constructor(pcx, ctor))
                         .collect()
                 };
+                pcx.cx.record_stats(|stats| stats.witnesses_built += new_witnesses.len());
                 WithWitnesses(new_witnesses)
             }
         }
@@ -634,7 +937,7 @@ fn apply_constructor(mut self, pcx: PatCtxt<'_, 'p>, ctor: &Constructor) -> Self
             let len = self.0.len();
             let arity = ctor.arity(pcx);
             let pats = self.0.drain((len - arity)..).rev();
-            let fields = Fields::from_iter(pcx.cx, pats);
+            let fields = Fields::from_iter(pcx.cx.pattern_arena, pats);
             DeconstructedPat::new(ctor.clone(), fields, pcx.ty.clone())
         };
 
@@ -666,15 +969,20 @@ fn apply_constructor(mut self, pcx: PatCtxt<'_, 'p>, ctor: &Constructor) -> Self
 /// `is_under_guard` is used to inform if the pattern has a guard. If it
 /// has one it must not be inserted into the matrix. This shouldn't be
 /// relied on for soundness.
+///
+/// Each step is traced at the `trace` level, e.g. with
+/// `RA_LOG=hir_ty::diagnostics::match_check=trace`: the column type, the constructors it is split
+/// into, the specialized matrices and the resulting usefulness.
 fn is_useful<'p>(
-    cx: &MatchCheckCtx<'_, 'p>,
-    matrix: &Matrix<'p>,
+    cx: &UsefulnessCtxt<'_, 'p>,
+    matrix: &mut Matrix<'p>,
     v: &PatStack<'p>,
     witness_preference: ArmType,
     is_under_guard: bool,
     is_top_level: bool,
-) -> Usefulness<'p> {
-    let Matrix { patterns: rows, .. } = matrix;
+) -> Result<Usefulness<'p>, TooComplex> {
+    cx.tcx.unwind_if_cancelled();
+    let rows = &matrix.patterns;
 
     // The base case. We are pattern-matching on () and the return value is
     // based on whether our matrix has a row or not.
@@ -687,47 +995,97 @@ fn is_useful<'p>(
         } else {
             Usefulness::new_not_useful(witness_preference)
         };
-        return ret;
+        return Ok(ret);
     }
 
     debug_assert!(rows.iter().all(|r| r.len() == v.len()));
 
+    // Only the exhaustiveness check is memoized: checking an arm marks its patterns reachable,
+    // which a memoized result would skip.
+    let memo_key = (matches!(witness_preference, FakeExtraWildcard) && cx.memoize_usefulness.get())
+        .then(|| UsefulnessKey {
+            rows: rows.iter().map(|row| row.structure).collect(),
+            v: v.structure,
+            is_top_level,
+        });
+    if let Some(key) = &memo_key {
+        if let Some(usefulness) = cx.usefulness_memo.borrow().get(key) {
+            cx.record_stats(|stats| stats.memo_hits += 1);
+            return Ok(usefulness.clone_witnesses());
+        }
+    }
+
+    // Patterns are created with normalized types, see `DeconstructedPat::from_pat` and
+    // `TypeCx::ctor_sub_tys`.
     let ty = v.head().ty();
-    let is_non_exhaustive = cx.is_foreign_non_exhaustive_enum(ty);
+    let is_non_exhaustive = cx.tcx.is_foreign_non_exhaustive_enum(ty);
     let pcx = PatCtxt { cx, ty, is_top_level, is_non_exhaustive };
+    let _span = tracing::trace_span!("is_useful", columns = v.len()).entered();
+    tracing::trace!(
+        "column type: {}\nv: {}\nmatrix:\n{}",
+        cx.tcx.display_ty(ty),
+        v.display(cx),
+        matrix.display(cx)
+    );
 
     // If the first pattern is an or-pattern, expand it.
     let mut ret = Usefulness::new_not_useful(witness_preference);
     if v.head().is_or_pat() {
-        // We try each or-pattern branch in turn.
-        let mut matrix = matrix.clone();
-        for v in v.expand_or_pat() {
-            let usefulness = is_useful(cx, &matrix, &v, witness_preference, is_under_guard, false);
+        // We try each or-pattern branch in turn. The branches are pushed onto the caller's matrix
+        // rather than a copy of it, and removed again once all of them have been checked.
+        let original_len = matrix.patterns.len();
+        for v in v.expand_or_pat(cx) {
+            // `is_useful` recurses once per column, so it can go as deep as patterns are large.
+            let usefulness = ensure_sufficient_stack(|| {
+                is_useful(cx, matrix, &v, witness_preference, is_under_guard, false)
+            })?;
             ret.extend(usefulness);
             // If pattern has a guard don't add it to the matrix.
             if !is_under_guard {
                 // We push the already-seen patterns into the matrix in order to detect redundant
                 // branches like `Some(_) | Some(0)`.
-                matrix.push(v);
+                matrix.push(cx, v);
             }
         }
+        matrix.truncate(original_len);
     } else {
         let v_ctor = v.head().ctor();
 
-        // FIXME: implement `overlapping_range_endpoints` lint
+        // Only ranges in the last column are linted: the ranges of `(0..=5, true)` and
+        // `(5..=10, false)` share `5`, but the patterns don't overlap.
+        if matches!(witness_preference, RealArm) && v.len() == 1 {
+            cx.record_overlapping_range_endpoints(v.head(), matrix.heads());
+        }
 
-        // We split the head constructor of `v`.
-        let split_ctors = v_ctor.split(pcx, matrix.heads().map(DeconstructedPat::ctor));
+        // We split the head constructor of `v`. A split wildcard is kept so that the `Missing`
+        // constructor can list the missing constructors without splitting again.
+        let split_wildcard = v_ctor.is_wildcard().then(|| {
+            let mut split_wildcard = SplitWildcard::new(pcx);
+            split_wildcard.split(pcx, matrix.heads().map(DeconstructedPat::ctor));
+            split_wildcard
+        });
+        let split_ctors = match &split_wildcard {
+            Some(split_wildcard) => split_wildcard.to_ctors(pcx),
+            None => v_ctor.split(pcx, matrix.heads().map(DeconstructedPat::ctor)),
+        };
+        cx.record_stats(|stats| stats.ctors_split += split_ctors.len());
+        tracing::trace!(
+            "split constructors: {}",
+            split_ctors.iter().map(|ctor| ctor.display(pcx)).format(", ")
+        );
         // For each constructor, we compute whether there's a value that starts with it that would
         // witness the usefulness of `v`.
-        let start_matrix = matrix;
+        let start_matrix = &*matrix;
         for ctor in split_ctors {
+            cx.increase_complexity(start_matrix.patterns.len())?;
             // We cache the result of `Fields::wildcards` because it is used a lot.
-            let spec_matrix = start_matrix.specialize_constructor(pcx, &ctor);
+            let mut spec_matrix = start_matrix.specialize_constructor(pcx, &ctor);
+            tracing::trace!("specialized with {}:\n{}", ctor.display(pcx), spec_matrix.display(cx));
             let v = v.pop_head_constructor(cx, &ctor);
-            let usefulness =
-                is_useful(cx, &spec_matrix, &v, witness_preference, is_under_guard, false);
-            let usefulness = usefulness.apply_constructor(pcx, start_matrix, &ctor);
+            let usefulness = ensure_sufficient_stack(|| {
+                is_useful(cx, &mut spec_matrix, &v, witness_preference, is_under_guard, false)
+            })?;
+            let usefulness = usefulness.apply_constructor(pcx, split_wildcard.as_ref(), &ctor);
 
             // FIXME: implement `non_exhaustive_omitted_patterns` lint
 
@@ -738,15 +1096,26 @@ fn is_useful<'p>(
     if ret.is_useful() {
         v.head().set_reachable();
     }
+    tracing::trace!("usefulness: {}", ret.display(cx));
+
+    if let Some(key) = memo_key {
+        cx.usefulness_memo.borrow_mut().insert(key, ret.clone_witnesses());
+    }
 
-    ret
+    Ok(ret)
 }
 
 /// The arm of a match expression.
 #[derive(Clone, Copy)]
 pub(crate) struct MatchArm<'p> {
     pub(crate) pat: &'p DeconstructedPat<'p>,
-    pub(crate) has_guard: bool,
+    pub(crate) guard: Option<ExprId>,
+}
+
+impl MatchArm<'_> {
+    pub(crate) fn has_guard(&self) -> bool {
+        self.guard.is_some()
+    }
 }
 
 /// Indicates whether or not a given arm is reachable.
@@ -755,8 +1124,7 @@ pub(crate) enum Reachability {
     /// The arm is reachable. This additionally carries a set of or-pattern branches that have been
     /// found to be unreachable despite the overall arm being reachable. Used only in the presence
     /// of or-patterns, otherwise it stays empty.
-    // FIXME: store ureachable subpattern IDs
-    Reachable,
+    Reachable(Vec<PatId>),
     /// The arm is unreachable.
     Unreachable,
 }
@@ -764,49 +1132,190 @@ pub(crate) enum Reachability {
 /// The output of checking a match for exhaustiveness and arm reachability.
 pub(crate) struct UsefulnessReport<'p> {
     /// For each arm of the input, whether that arm is reachable after the arms above it.
-    pub(crate) _arm_usefulness: Vec<(MatchArm<'p>, Reachability)>,
+    pub(crate) arm_usefulness: Vec<(MatchArm<'p>, Reachability)>,
     /// If the match is exhaustive, this is empty. If not, this contains witnesses for the lack of
-    /// exhaustiveness.
+    /// exhaustiveness. These may contain or-patterns, each alternative of which is missing from
+    /// the match: use [`DeconstructedPat::expand_or_pats`] to list them.
     pub(crate) non_exhaustiveness_witnesses: Vec<DeconstructedPat<'p>>,
+    /// Whether some arms expanded to too many alternatives and were checked as if their
+    /// or-patterns were wildcards. The witnesses are still missing from the match, but there may
+    /// be others that went unnoticed. The arms from the first such arm on are considered
+    /// reachable, since the arms above them were taken to cover more than they do.
+    pub(crate) is_approximate: bool,
+    /// The range patterns that share an endpoint with range patterns before them, in this arm or
+    /// in earlier ones.
+    pub(crate) overlapping_range_endpoints: Vec<OverlappingRangeEndpoints>,
+    /// See [`MatchCheckStats`].
+    pub(crate) stats: Option<MatchCheckStats>,
+}
+
+/// A range pattern that shares only an endpoint with range patterns that can match the same
+/// values before it, like `5..=10` after `0..=5`, which likely meant to exclude the endpoint.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct OverlappingRangeEndpoints {
+    pub(crate) pat: PatId,
+    /// The earlier range patterns with the value each shares with `pat`.
+    pub(crate) overlaps: Vec<(PatId, Pat)>,
 }
 
 /// The entrypoint for the usefulness algorithm. Computes whether a match is exhaustive and which
 /// of its arms are reachable.
 ///
 /// Note: the input patterns must have been lowered through
-/// `check_match::MatchVisitor::lower_pattern`.
-pub(crate) fn compute_match_usefulness<'p>(
-    cx: &MatchCheckCtx<'_, 'p>,
+/// `check_match::MatchVisitor::lower_pattern`, and `scrut_ty` normalized like their types.
+pub(super) fn compute_match_usefulness<'p>(
+    cx: &UsefulnessCtxt<'_, 'p>,
     arms: &[MatchArm<'p>],
     scrut_ty: &Ty,
-) -> UsefulnessReport<'p> {
+) -> Result<UsefulnessReport<'p>, TooComplex> {
+    let _p = profile::span("compute_match_usefulness").detail(|| format!("{} arms", arms.len()));
+    if let Some(report) = compute_fieldless_match_usefulness(cx, arms, scrut_ty) {
+        return Ok(report);
+    }
+
     let mut matrix = Matrix::empty();
+    // Whether an arm without a guard matches anything, e.g. `_` or `x`. Such an arm makes the
+    // match exhaustive and all the arms after it unreachable, so we needn't check them. Patterns
+    // that failed to lower are opaque rather than wildcards, so they never count.
+    let mut has_irrefutable_arm = false;
+    let mut is_approximate = false;
     let arm_usefulness = arms
         .iter()
         .copied()
         .map(|arm| {
-            let v = PatStack::from_pattern(arm.pat);
-            is_useful(cx, &matrix, &v, RealArm, arm.has_guard, true);
-            if !arm.has_guard {
-                matrix.push(v);
+            if has_irrefutable_arm {
+                return Ok((arm, Reachability::Unreachable));
             }
-            let reachability = if arm.pat.is_reachable() {
-                Reachability::Reachable
+            cx.tcx.unwind_if_cancelled();
+            let is_arm_approximated = arm.pat.or_expansion_count() > OR_EXPANSION_LIMIT;
+            let pat = if is_arm_approximated {
+                // Expanding the or-patterns of this arm would swamp the matrix, so we check a
+                // pattern that covers at least what the arm covers instead.
+                is_approximate = true;
+                arm.pat.set_reachable();
+                cx.pattern_arena.alloc(arm.pat.without_or_pats(cx.pattern_arena))
+            } else {
+                arm.pat
+            };
+            let v = PatStack::from_pattern(cx, pat);
+            is_useful(cx, &mut matrix, &v, RealArm, arm.has_guard(), true)?;
+            if !arm.has_guard() {
+                has_irrefutable_arm = arm.pat.ctor().is_wildcard();
+                matrix.push(cx, v);
+            }
+            let reachability = if is_approximate {
+                // The alternatives of an approximated arm aren't checked, and the arms after it
+                // are checked against a matrix that covers too much.
+                Reachability::Reachable(Vec::new())
+            } else if arm.pat.is_reachable() {
+                Reachability::Reachable(arm.pat.unreachable_alternatives())
             } else {
                 Reachability::Unreachable
             };
-            (arm, reachability)
+            Ok((arm, reachability))
         })
-        .collect();
+        .collect::<Result<_, _>>()?;
+    if has_irrefutable_arm {
+        return Ok(UsefulnessReport {
+            arm_usefulness,
+            non_exhaustiveness_witnesses: Vec::new(),
+            is_approximate,
+            overlapping_range_endpoints: cx.overlapping_range_endpoints.take(),
+            stats: cx.stats(),
+        });
+    }
 
+    // Only matches with or-patterns run into the same sub-problems often enough for memoizing to
+    // pay off.
+    cx.memoize_usefulness.set(arms.iter().any(|arm| arm.pat.or_expansion_count() > 1));
     let wild_pattern = cx.pattern_arena.alloc(DeconstructedPat::wildcard(scrut_ty.clone()));
-    let v = PatStack::from_pattern(wild_pattern);
-    let usefulness = is_useful(cx, &matrix, &v, FakeExtraWildcard, false, true);
+    let v = PatStack::from_pattern(cx, wild_pattern);
+    let _p = profile::span("compute_match_usefulness: exhaustiveness")
+        .detail(|| format!("{} rows", matrix.patterns.len()));
+    let usefulness = is_useful(cx, &mut matrix, &v, FakeExtraWildcard, false, true)?;
     let non_exhaustiveness_witnesses = match usefulness {
         WithWitnesses(pats) => pats.into_iter().map(Witness::single_pattern).collect(),
         NoWitnesses { .. } => panic!("bug"),
     };
-    UsefulnessReport { _arm_usefulness: arm_usefulness, non_exhaustiveness_witnesses }
+    Ok(UsefulnessReport {
+        arm_usefulness,
+        non_exhaustiveness_witnesses,
+        is_approximate,
+        overlapping_range_endpoints: cx.overlapping_range_endpoints.take(),
+        stats: cx.stats(),
+    })
+}
+
+/// Checks a match on a `bool` or a fieldless enum, see [`FieldlessCtors`]. Returns `None` if the
+/// match isn't of that shape, in which case `is_useful` has to do the work.
+fn compute_fieldless_match_usefulness<'p>(
+    cx: &UsefulnessCtxt<'_, 'p>,
+    arms: &[MatchArm<'p>],
+    scrut_ty: &Ty,
+) -> Option<UsefulnessReport<'p>> {
+    let pcx = PatCtxt {
+        cx,
+        ty: scrut_ty,
+        is_top_level: true,
+        is_non_exhaustive: cx.tcx.is_foreign_non_exhaustive_enum(scrut_ty),
+    };
+    let ctors = FieldlessCtors::new(pcx)?;
+    let masks = arms.iter().map(|arm| ctors.mask(pcx, arm.pat)).collect::<Option<Vec<_>>>()?;
+
+    // The values matched by the arms without a guard so far, i.e. the rows of the matrix.
+    let mut covered = 0;
+    let arm_usefulness = arms
+        .iter()
+        .copied()
+        .zip(masks)
+        .map(|(arm, mask)| {
+            if arm.pat.is_or_pat() {
+                mark_reachable_alternatives(pcx, &ctors, arm.pat, covered, arm.has_guard());
+            }
+            let reachability = if mask & !covered != 0 {
+                arm.pat.set_reachable();
+                Reachability::Reachable(arm.pat.unreachable_alternatives())
+            } else {
+                Reachability::Unreachable
+            };
+            if !arm.has_guard() {
+                covered |= mask;
+            }
+            (arm, reachability)
+        })
+        .collect();
+
+    let non_exhaustiveness_witnesses = ctors.witnesses(pcx, covered);
+    Some(UsefulnessReport {
+        arm_usefulness,
+        non_exhaustiveness_witnesses,
+        is_approximate: false,
+        overlapping_range_endpoints: Vec::new(),
+        stats: cx.stats(),
+    })
+}
+
+/// Like `is_useful`, checks each alternative of the or-pattern `pat` against the values in `seen`
+/// and the alternatives before it, and those of the or-patterns nested in it in turn.
+fn mark_reachable_alternatives(
+    pcx: PatCtxt<'_, '_>,
+    ctors: &FieldlessCtors,
+    pat: &DeconstructedPat<'_>,
+    mut seen: u64,
+    has_guard: bool,
+) {
+    for alt in pat.iter_fields() {
+        let alt_mask = ctors.mask(pcx, alt).unwrap_or(0);
+        if alt_mask & !seen != 0 {
+            alt.set_reachable();
+            if alt.is_or_pat() {
+                mark_reachable_alternatives(pcx, ctors, alt, seen, has_guard);
+            }
+        }
+        if !has_guard {
+            seen |= alt_mask;
+        }
+    }
 }
 
 pub(crate) mod helper {
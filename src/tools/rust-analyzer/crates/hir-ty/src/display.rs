@@ -26,6 +26,7 @@
 
 use crate::{
     db::HirDatabase,
+    diagnostics::IntWidth,
     from_assoc_type_id, from_foreign_def_id, from_placeholder_idx,
     layout::layout_of_ty,
     lt_from_placeholder_idx,
@@ -35,8 +36,8 @@
     utils::{self, generics},
     AdtId, AliasEq, AliasTy, Binders, CallableDefId, CallableSig, Const, ConstScalar, ConstValue,
     DomainGoal, GenericArg, ImplTraitId, Interner, Lifetime, LifetimeData, LifetimeOutlives,
-    MemoryMap, Mutability, OpaqueTy, ProjectionTy, ProjectionTyExt, QuantifiedWhereClause, Scalar,
-    Substitution, TraitRef, TraitRefExt, Ty, TyExt, WhereClause,
+    MemoryMap, Mutability, OpaqueTy, PatternTypeRange, ProjectionTy, ProjectionTyExt,
+    QuantifiedWhereClause, Scalar, Substitution, TraitRef, TraitRefExt, Ty, TyExt, WhereClause,
 };
 
 pub trait HirWrite: fmt::Write {
@@ -225,6 +226,14 @@ pub fn should_truncate(&self) -> bool {
     pub fn omit_verbose_types(&self) -> bool {
         self.omit_verbose_types
     }
+
+    /// The module paths are written relative to, when displaying source code.
+    pub(crate) fn source_code_module(&self) -> Option<ModuleId> {
+        match self.display_target {
+            DisplayTarget::SourceCode { module_id } => Some(module_id),
+            DisplayTarget::Diagnostics | DisplayTarget::Test => None,
+        }
+    }
 }
 
 #[derive(Clone, Copy)]
@@ -614,10 +623,23 @@ fn hir_fmt(
                     }))
                     | TyKind::OpaqueType(opaque_ty_id, parameters) => {
                         let impl_trait_id = db.lookup_intern_impl_trait_id((*opaque_ty_id).into());
-                        if let ImplTraitId::ReturnTypeImplTrait(func, idx) = impl_trait_id {
-                            let datas = db
-                                .return_type_impl_traits(func)
-                                .expect("impl trait id without data");
+                        let datas = match impl_trait_id {
+                            ImplTraitId::ReturnTypeImplTrait(func, idx) => {
+                                let datas = db
+                                    .return_type_impl_traits(func)
+                                    .expect("impl trait id without data");
+                                Some((datas, idx, func.lookup(db.upcast()).module(db.upcast())))
+                            }
+                            ImplTraitId::TypeAliasImplTrait(alias, idx) => {
+                                let datas = db
+                                    .type_alias_impl_traits(alias)
+                                    .expect("impl trait id without data");
+                                Some((datas, idx, alias.lookup(db.upcast()).module(db.upcast())))
+                            }
+                            ImplTraitId::AsyncBlockTypeImplTrait(..)
+                            | ImplTraitId::PatternType(_) => None,
+                        };
+                        if let Some((datas, idx, module)) = datas {
                             let data =
                                 (*datas).as_ref().map(|rpit| rpit.impl_traits[idx].bounds.clone());
                             let bounds = data.substitute(Interner, parameters);
@@ -625,9 +647,7 @@ fn hir_fmt(
 
                             // Don't count Sized but count when it absent
                             // (i.e. when explicit ?Sized bound is set).
-                            let default_sized = SizedByDefault::Sized {
-                                anchor: func.lookup(db.upcast()).module(db.upcast()).krate(),
-                            };
+                            let default_sized = SizedByDefault::Sized { anchor: module.krate() };
                             let sized_bounds = bounds
                                 .skip_binders()
                                 .iter()
@@ -890,6 +910,20 @@ fn should_show(
                         )?;
                         // FIXME: it would maybe be good to distinguish this from the alias type (when debug printing), and to show the substitution
                     }
+                    ImplTraitId::TypeAliasImplTrait(alias, idx) => {
+                        let datas =
+                            db.type_alias_impl_traits(alias).expect("impl trait id without data");
+                        let data =
+                            (*datas).as_ref().map(|tait| tait.impl_traits[idx].bounds.clone());
+                        let bounds = data.substitute(Interner, &parameters);
+                        let krate = alias.lookup(db.upcast()).module(db.upcast()).krate();
+                        write_bounds_like_dyn_trait_with_prefix(
+                            f,
+                            "impl",
+                            bounds.skip_binders(),
+                            SizedByDefault::Sized { anchor: krate },
+                        )?;
+                    }
                     ImplTraitId::AsyncBlockTypeImplTrait(body, ..) => {
                         let future_trait = db
                             .lang_item(body.module(db.upcast()).krate(), LangItem::Future)
@@ -917,6 +951,7 @@ fn should_show(
                         parameters.at(Interner, 0).hir_fmt(f)?;
                         write!(f, ">")?;
                     }
+                    ImplTraitId::PatternType(range) => write_pattern_type(f, parameters, range)?,
                 }
             }
             TyKind::Closure(.., substs) => {
@@ -1020,9 +1055,26 @@ fn should_show(
                             SizedByDefault::Sized { anchor: krate },
                         )?;
                     }
+                    ImplTraitId::TypeAliasImplTrait(alias, idx) => {
+                        let datas =
+                            db.type_alias_impl_traits(alias).expect("impl trait id without data");
+                        let data =
+                            (*datas).as_ref().map(|tait| tait.impl_traits[idx].bounds.clone());
+                        let bounds = data.substitute(Interner, &opaque_ty.substitution);
+                        let krate = alias.lookup(db.upcast()).module(db.upcast()).krate();
+                        write_bounds_like_dyn_trait_with_prefix(
+                            f,
+                            "impl",
+                            bounds.skip_binders(),
+                            SizedByDefault::Sized { anchor: krate },
+                        )?;
+                    }
                     ImplTraitId::AsyncBlockTypeImplTrait(..) => {
                         write!(f, "{{async block}}")?;
                     }
+                    ImplTraitId::PatternType(range) => {
+                        write_pattern_type(f, &opaque_ty.substitution, range)?
+                    }
                 };
             }
             TyKind::Error => {
@@ -1113,6 +1165,31 @@ fn is_sized_trait(self, trait_: TraitId, db: &dyn DefDatabase) -> bool {
     }
 }
 
+/// Writes a pattern type like the `pattern_type!` macro call that names it, e.g.
+/// `pattern_type!(u32 is 1..)`.
+fn write_pattern_type(
+    f: &mut HirFormatter<'_>,
+    parameters: &Substitution,
+    range: PatternTypeRange,
+) -> Result<(), HirDisplayError> {
+    let base = parameters.at(Interner, 0).assert_ty_ref(Interner);
+    write!(f, "pattern_type!(")?;
+    base.hir_fmt(f)?;
+    write!(f, " is ")?;
+    let Some(width) = IntWidth::of(base) else { return write!(f, "_)") };
+    // Omit the endpoints that are the smallest and the largest value of the type, but not both.
+    if range.lo != width.min_bits() || range.hi == width.max_bits() {
+        width.fmt_number(f, range.lo)?;
+    }
+    if range.hi == width.max_bits() {
+        write!(f, "..")?;
+    } else {
+        write!(f, "..=")?;
+        width.fmt_number(f, range.hi)?;
+    }
+    write!(f, ")")
+}
+
 pub fn write_bounds_like_dyn_trait_with_prefix(
     f: &mut HirFormatter<'_>,
     prefix: &str,
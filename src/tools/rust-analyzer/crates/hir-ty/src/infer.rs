@@ -16,6 +16,7 @@
 use std::ops::Index;
 use std::sync::Arc;
 
+use base_db::CrateId;
 use chalk_ir::{cast::Cast, ConstValue, DebruijnIndex, Mutability, Safety, Scalar, TypeFlags};
 use either::Either;
 use hir_def::{
@@ -29,7 +30,7 @@
     resolver::{HasResolver, ResolveValueResult, Resolver, TypeNs, ValueNs},
     type_ref::TypeRef,
     AdtId, AssocItemId, DefWithBodyId, EnumVariantId, FieldId, FunctionId, HasModule,
-    ItemContainerId, Lookup, TraitId, TypeAliasId, VariantId,
+    ItemContainerId, Lookup, ModuleDefId, ModuleId, TraitId, TypeAliasId, VariantId,
 };
 use hir_expand::name::{name, Name};
 use la_arena::ArenaMap;
@@ -38,9 +39,10 @@
 
 use crate::{
     db::HirDatabase, fold_tys, fold_tys_and_consts, infer::coerce::CoerceMany,
-    lower::ImplTraitLoweringMode, to_assoc_type_id, AliasEq, AliasTy, Const, DomainGoal,
-    GenericArg, Goal, ImplTraitId, InEnvironment, Interner, ProjectionTy, RpitId, Substitution,
-    TraitEnvironment, TraitRef, Ty, TyBuilder, TyExt, TyKind,
+    lower::ImplTraitLoweringMode, to_assoc_type_id, utils::generics, AliasEq, AliasTy, Binders,
+    Const, DomainGoal, GenericArg, Goal, ImplTraitId, InEnvironment, Interner, ProjectionTy,
+    QuantifiedWhereClause, RpitId, Substitution, TraitEnvironment, TraitRef, Ty, TyBuilder, TyExt,
+    TyKind,
 };
 
 // This lint has a false positive here. See the link below for details.
@@ -48,6 +50,7 @@
 // https://github.com/rust-lang/rust/issues/57411
 #[allow(unreachable_pub)]
 pub use coerce::could_coerce;
+pub(crate) use coerce::coerce_unsized_info_query;
 #[allow(unreachable_pub)]
 pub use unify::could_unify;
 
@@ -122,6 +125,116 @@ pub(crate) fn normalize(db: &dyn HirDatabase, owner: DefWithBodyId, ty: Ty) -> T
     table.resolve_completely(ty_with_vars)
 }
 
+/// Returns the hidden type of the opaque type `ty` if `owner` is in its defining scope, so that
+/// it can be matched against. Non-opaque types and the opaque types whose hidden type isn't
+/// known are returned as is.
+pub(crate) fn reveal_opaque_ty(db: &dyn HirDatabase, owner: DefWithBodyId, ty: Ty) -> Ty {
+    let TyKind::OpaqueType(opaque_ty_id, _) = ty.kind(Interner) else { return ty };
+    let hidden_ty = match db.lookup_intern_impl_trait_id((*opaque_ty_id).into()) {
+        ImplTraitId::ReturnTypeImplTrait(func, idx) if owner == DefWithBodyId::FunctionId(func) => {
+            db.infer(owner).type_of_rpit.get(idx).cloned()
+        }
+        ImplTraitId::TypeAliasImplTrait(alias, idx)
+            if is_in_tait_defining_scope(db, alias, owner.module(db.upcast())) =>
+        {
+            match db.infer(owner).type_of_tait.get(&(alias, idx)) {
+                Some(hidden_ty) => Some(hidden_ty.clone()),
+                None => type_alias_impl_trait_hidden_ty(db, alias, idx),
+            }
+        }
+        _ => None,
+    };
+    match hidden_ty {
+        Some(hidden_ty) if !hidden_ty.is_unknown() => hidden_ty,
+        _ => ty,
+    }
+}
+
+/// Whether code in `module` is within the defining scope of the type alias `impl Trait`s of
+/// `alias`, i.e. the module that contains the alias and its descendants.
+pub(crate) fn is_in_tait_defining_scope(
+    db: &dyn HirDatabase,
+    alias: TypeAliasId,
+    module: ModuleId,
+) -> bool {
+    let alias_module = alias.lookup(db.upcast()).module(db.upcast());
+    let mut module = Some(module);
+    while let Some(m) = module {
+        if m == alias_module {
+            return true;
+        }
+        module = m.containing_module(db.upcast());
+    }
+    false
+}
+
+/// Looks for the hidden type of a type alias `impl Trait` in the functions of its defining scope
+/// that constrain it. Only the aliases without generic parameters are handled, as the hidden
+/// types are expressed in terms of the generic parameters of the function defining them.
+pub(crate) fn type_alias_impl_trait_hidden_ty(
+    db: &dyn HirDatabase,
+    alias: TypeAliasId,
+    idx: RpitId,
+) -> Option<Ty> {
+    if generics(db.upcast(), alias.into()).len() != 0 {
+        return None;
+    }
+    let alias_module = alias.lookup(db.upcast()).module(db.upcast());
+    let def_map = alias_module.def_map(db.upcast());
+    let mut modules = vec![alias_module.local_id];
+    while let Some(local_id) = modules.pop() {
+        let data = &def_map[local_id];
+        modules.extend(data.children.values().copied());
+        let impl_items = data.scope.impls().flat_map(|it| db.impl_data(it).items.clone());
+        let impl_fns = impl_items.filter_map(|item| match item {
+            AssocItemId::FunctionId(func) => Some(func),
+            _ => None,
+        });
+        let fns = data
+            .scope
+            .declarations()
+            .filter_map(|def| match def {
+                ModuleDefId::FunctionId(func) => Some(func),
+                _ => None,
+            })
+            .chain(impl_fns);
+        for func in fns {
+            let infer = db.infer(func.into());
+            let placeholder_bits = TypeFlags::HAS_TY_PLACEHOLDER | TypeFlags::HAS_CT_PLACEHOLDER;
+            let hidden_ty = infer.type_of_tait.get(&(alias, idx)).filter(|ty| {
+                !ty.is_unknown() && !ty.data(Interner).flags.intersects(placeholder_bits)
+            });
+            if let Some(hidden_ty) = hidden_ty {
+                return Some(hidden_ty.clone());
+            }
+        }
+    }
+    None
+}
+
+/// Returns the type of the innermost last field of `ty`, walking into structs and tuples, e.g.
+/// `[u8]` for `Foo<(u32, [u8])>` with `struct Foo<T: ?Sized>(u8, T)`. This is the part of a type
+/// that can be unsized.
+fn struct_tail_without_normalization(db: &dyn HirDatabase, mut ty: Ty) -> Ty {
+    loop {
+        let last_field_ty = match ty.kind(Interner) {
+            TyKind::Adt(chalk_ir::AdtId(AdtId::StructId(id)), subst) => {
+                db.struct_data(*id).variant_data.fields().iter().last().map(|(field, _)| {
+                    db.field_types((*id).into())[field].clone().substitute(Interner, subst)
+                })
+            }
+            TyKind::Tuple(_, subst) => {
+                subst.iter(Interner).last().and_then(|it| it.ty(Interner)).cloned()
+            }
+            _ => None,
+        };
+        match last_field_ty {
+            Some(last_field_ty) => ty = last_field_ty,
+            None => return ty,
+        }
+    }
+}
+
 /// Binding modes inferred for patterns.
 /// <https://doc.rust-lang.org/reference/patterns.html#binding-modes>
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
@@ -285,6 +398,9 @@ pub enum Adjust {
     /// Take the address and produce either a `&` or `*` pointer.
     Borrow(AutoBorrow),
     Pointer(PointerCast),
+    /// Cast into a dyn* object.
+    // FIXME: chalk can't represent `dyn*` types yet, so coercion never produces this.
+    DynStar,
 }
 
 /// An overloaded autoderef step, representing a `Deref(Mut)::deref(_mut)`
@@ -296,14 +412,77 @@ pub enum Adjust {
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub struct OverloadedDeref(pub Option<Mutability>);
 
+impl OverloadedDeref {
+    /// Returns the `Deref::deref` or `DerefMut::deref_mut` method invoked by this step when
+    /// dereferencing a value of type `source`, together with the substitution for its trait.
+    ///
+    /// Returns `None` if the mutability of the step is unknown or the lang item is missing.
+    pub fn method_call(
+        &self,
+        db: &dyn HirDatabase,
+        krate: CrateId,
+        source: Ty,
+    ) -> Option<(FunctionId, Substitution)> {
+        let (lang_item, method_name) = match self.0? {
+            Mutability::Not => (LangItem::Deref, name![deref]),
+            Mutability::Mut => (LangItem::DerefMut, name![deref_mut]),
+        };
+        let trait_ = db.lang_item(krate, lang_item)?.as_trait()?;
+        let method = db.trait_data(trait_).method_by_name(&method_name)?;
+        Some((method, Substitution::from1(Interner, source)))
+    }
+}
+
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub enum AutoBorrow {
     /// Converts from T to &T.
-    Ref(Mutability),
-    /// Converts from T to *T.
+    Ref(AutoBorrowMutability),
+    /// Converts from T to *T. This is only produced when coercing a reference to a raw
+    /// pointer; raw borrows of a place (`&raw const place`) are `Expr::Ref`s instead.
     RawPtr(Mutability),
 }
 
+/// At least for initial deployment, we want to limit two-phase borrows to
+/// only a few specific cases. Right now, those are mostly "things that desugar"
+/// into method calls:
+/// - using `x.some_method()` syntax, where some_method takes `&mut self`,
+/// - using `Foo::some_method(&mut x, ...)` syntax,
+/// - binary assignment operators (`+=`, `-=`, `*=`, etc.).
+/// Anything else should be rejected until generalized two-phase borrow support
+/// is implemented. Right now, dataflow can't handle the general case where there
+/// is more than one use of a mutable borrow, and we don't want to accept too much
+/// new code via two-phase borrows, so we try to limit where we create two-phase
+/// capable mutable borrows.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum AllowTwoPhase {
+    Yes,
+    No,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum AutoBorrowMutability {
+    Mut { allow_two_phase_borrow: AllowTwoPhase },
+    Not,
+}
+
+impl AutoBorrowMutability {
+    pub fn new(m: Mutability, allow_two_phase_borrow: AllowTwoPhase) -> Self {
+        match m {
+            Mutability::Mut => AutoBorrowMutability::Mut { allow_two_phase_borrow },
+            Mutability::Not => AutoBorrowMutability::Not,
+        }
+    }
+}
+
+impl From<AutoBorrowMutability> for Mutability {
+    fn from(m: AutoBorrowMutability) -> Self {
+        match m {
+            AutoBorrowMutability::Mut { .. } => Mutability::Mut,
+            AutoBorrowMutability::Not => Mutability::Not,
+        }
+    }
+}
+
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub enum PointerCast {
     /// Go from a fn-item type to a fn-pointer type.
@@ -319,7 +498,6 @@ pub enum PointerCast {
     /// Go from a mut raw pointer to a const raw pointer.
     MutToConstPointer,
 
-    #[allow(dead_code)]
     /// Go from `*const [T; N]` to `*const T`
     ArrayToPointer,
 
@@ -334,6 +512,67 @@ pub enum PointerCast {
     /// type. Codegen backends and miri figure out what has to be done
     /// based on the precise source/target type at hand.
     Unsize,
+
+    /// Unsize a pointer to a trait object into a pointer to a trait object
+    /// of one of its supertraits, e.g. `&dyn Sub` to `&dyn Super`. Unlike
+    /// `Unsize`, this records the principal traits of the source and target
+    /// trait objects, as the vtable has to be switched between them.
+    TraitUpcasting { source: TraitId, target: TraitId },
+}
+
+/// Information for `CoerceUnsized` impls, storing information we
+/// have computed about the coercion.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct CoerceUnsizedInfo {
+    /// If this is a "custom coerce" impl, then what kind of custom
+    /// coercion is it? This applies to impls of `CoerceUnsized` for
+    /// structs, primarily, where we store a bit of info about which
+    /// fields need to be coerced.
+    pub custom_kind: Option<CustomCoerceUnsized>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum CustomCoerceUnsized {
+    /// Records the field being coerced.
+    Struct(FieldId),
+}
+
+/// What an `Unsize` pointer cast actually unsized: the innermost pair of types
+/// that differ between the source and target pointer types, e.g. `[u8; 4]` and
+/// `[u8]` for `&[u8; 4]` to `&[u8]`, or `S` and `dyn Trait` for `Box<S>` to
+/// `Box<dyn Trait>`. The source type proves `Unsize<target>`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct UnsizeCoercion {
+    pub source: Ty,
+    pub target: Ty,
+}
+
+impl UnsizeCoercion {
+    fn between(from: &Ty, to: &Ty) -> UnsizeCoercion {
+        let inner = match (from.kind(Interner), to.kind(Interner)) {
+            (TyKind::Ref(_, _, from), TyKind::Ref(_, _, to))
+            | (TyKind::Raw(_, from), TyKind::Raw(_, to))
+                if from != to =>
+            {
+                Some((from.clone(), to.clone()))
+            }
+            // `CoerceUnsized` structs like `Box`, `Rc` or `Pin` coerce exactly one of their
+            // type parameters.
+            (TyKind::Adt(from_adt, from_subst), TyKind::Adt(to_adt, to_subst))
+                if from_adt == to_adt =>
+            {
+                from_subst
+                    .type_parameters(Interner)
+                    .zip(to_subst.type_parameters(Interner))
+                    .find(|(from, to)| from != to)
+            }
+            _ => None,
+        };
+        match inner {
+            Some((from, to)) => UnsizeCoercion::between(&from, &to),
+            None => UnsizeCoercion { source: from.clone(), target: to.clone() },
+        }
+    }
 }
 
 /// The result of type inference: A mapping from expressions and patterns to types.
@@ -356,6 +595,9 @@ pub struct InferenceResult {
     pub type_of_pat: ArenaMap<PatId, Ty>,
     pub type_of_binding: ArenaMap<BindingId, Ty>,
     pub type_of_rpit: ArenaMap<RpitId, Ty>,
+    /// The hidden types of the type alias `impl Trait`s in the return type, when this body is in
+    /// their defining scope.
+    pub type_of_tait: FxHashMap<(TypeAliasId, RpitId), Ty>,
     /// Type of the result of `.into_iter()` on the for. `ExprId` is the one of the whole for loop.
     pub type_of_for_iterator: FxHashMap<ExprId, Ty>,
     type_mismatches: FxHashMap<ExprOrPatId, TypeMismatch>,
@@ -364,13 +606,38 @@ pub struct InferenceResult {
     /// Stores the types which were implicitly dereferenced in pattern binding modes.
     pub pat_adjustments: FxHashMap<PatId, Vec<Ty>>,
     pub pat_binding_modes: FxHashMap<PatId, BindingMode>,
-    pub expr_adjustments: FxHashMap<ExprId, Vec<Adjustment>>,
+    /// For each expression, records the implicit coercions applied to it, in order.
+    pub(crate) expr_adjustments: FxHashMap<ExprId, Vec<Adjustment>>,
+    /// For each expression with a `PointerCast::Unsize` adjustment, records what was unsized.
+    pub unsize_coercions: FxHashMap<ExprId, UnsizeCoercion>,
 }
 
 impl InferenceResult {
     pub fn method_resolution(&self, expr: ExprId) -> Option<(FunctionId, Substitution)> {
         self.method_resolutions.get(&expr).cloned()
     }
+    /// Returns the adjustments applied to `expr`, or an empty slice if it is used as is.
+    pub fn expr_adjustments(&self, expr: ExprId) -> &[Adjustment] {
+        self.expr_adjustments.get(&expr).map_or(&[], |it| &**it)
+    }
+    /// Returns the expressions that were implicitly coerced into an unsafe fn pointer from a safe
+    /// fn item, fn pointer or closure. Lints about implicitly gained unsafety start from here.
+    pub fn unsafe_fn_pointer_coercions(&self) -> impl Iterator<Item = ExprId> + '_ {
+        self.expr_adjustments.iter().filter_map(|(&expr, adjustments)| {
+            adjustments
+                .iter()
+                .any(|adj| {
+                    matches!(
+                        adj.kind,
+                        Adjust::Pointer(
+                            PointerCast::UnsafeFnPointer
+                                | PointerCast::ClosureFnPointer(Safety::Unsafe)
+                        )
+                    )
+                })
+                .then_some(expr)
+        })
+    }
     pub fn field_resolution(&self, expr: ExprId) -> Option<FieldId> {
         self.field_resolutions.get(&expr).copied()
     }
@@ -401,6 +668,17 @@ pub fn expr_type_mismatches(&self) -> impl Iterator<Item = (ExprId, &TypeMismatc
             _ => None,
         })
     }
+    /// The type of `expr` after the adjustments recorded for it have been applied, ignoring the
+    /// coercion of a diverging expression to an arbitrary type.
+    pub fn type_of_expr_with_adjust(&self, expr: ExprId) -> &Ty {
+        let adjusted = self.expr_adjustments.get(&expr).and_then(|adjustments| {
+            adjustments.iter().filter(|adj| !matches!(adj.kind, Adjust::NeverToAny)).next_back()
+        });
+        match adjusted {
+            Some(adjustment) => &adjustment.target,
+            None => &self[expr],
+        }
+    }
 }
 
 impl Index<ExprId> for InferenceResult {
@@ -553,6 +831,9 @@ pub(crate) fn resolve_all(self) -> InferenceResult {
         for ty in result.type_of_rpit.values_mut() {
             *ty = table.resolve_completely(ty.clone());
         }
+        for ty in result.type_of_tait.values_mut() {
+            *ty = table.resolve_completely(ty.clone());
+        }
         for ty in result.type_of_for_iterator.values_mut() {
             *ty = table.resolve_completely(ty.clone());
         }
@@ -599,6 +880,16 @@ pub(crate) fn resolve_all(self) -> InferenceResult {
         for adjustment in result.pat_adjustments.values_mut().flatten() {
             *adjustment = table.resolve_completely(adjustment.clone());
         }
+        for (&expr, adjustments) in &result.expr_adjustments {
+            let Some(mut source) = result.type_of_expr.get(expr) else { continue };
+            for adjustment in adjustments {
+                if adjustment.kind == Adjust::Pointer(PointerCast::Unsize) {
+                    let coercion = UnsizeCoercion::between(source, &adjustment.target);
+                    result.unsize_coercions.insert(expr, coercion);
+                }
+                source = &adjustment.target;
+            }
+        }
         result
     }
 
@@ -646,47 +937,99 @@ fn collect_fn(&mut self, func: FunctionId) {
         let return_ty = ctx.lower_ty(return_ty);
         let return_ty = self.insert_type_vars(return_ty);
 
-        let return_ty = if let Some(rpits) = self.db.return_type_impl_traits(func) {
-            // RPIT opaque types use substitution of their parent function.
-            let fn_placeholders = TyBuilder::placeholder_subst(self.db, func);
-            fold_tys(
-                return_ty,
-                |ty, _| {
-                    let opaque_ty_id = match ty.kind(Interner) {
-                        TyKind::OpaqueType(opaque_ty_id, _) => *opaque_ty_id,
-                        _ => return ty,
-                    };
-                    let idx = match self.db.lookup_intern_impl_trait_id(opaque_ty_id.into()) {
-                        ImplTraitId::ReturnTypeImplTrait(_, idx) => idx,
-                        _ => unreachable!(),
-                    };
-                    let bounds = (*rpits).map_ref(|rpits| {
-                        rpits.impl_traits[idx].bounds.map_ref(|it| it.into_iter())
-                    });
-                    let var = self.table.new_type_var();
-                    let var_subst = Substitution::from1(Interner, var.clone());
-                    for bound in bounds {
-                        let predicate =
-                            bound.map(|it| it.cloned()).substitute(Interner, &fn_placeholders);
-                        let (var_predicate, binders) = predicate
-                            .substitute(Interner, &var_subst)
-                            .into_value_and_skipped_binders();
-                        always!(binders.is_empty(Interner)); // quantified where clauses not yet handled
-                        self.push_obligation(var_predicate.cast(Interner));
+        let rpits = self.db.return_type_impl_traits(func);
+        // RPIT opaque types use substitution of their parent function.
+        let fn_placeholders = TyBuilder::placeholder_subst(self.db, func);
+        let module = self.resolver.module();
+        let return_ty = fold_tys(
+            return_ty,
+            |ty, _| {
+                let (opaque_ty_id, subst) = match ty.kind(Interner) {
+                    TyKind::OpaqueType(opaque_ty_id, subst) => (*opaque_ty_id, subst),
+                    _ => return ty,
+                };
+                match self.db.lookup_intern_impl_trait_id(opaque_ty_id.into()) {
+                    ImplTraitId::ReturnTypeImplTrait(_, idx) => {
+                        let Some(rpits) = &rpits else { return ty };
+                        let bounds = (**rpits)
+                            .as_ref()
+                            .map(|rpits| rpits.impl_traits[idx].bounds.clone())
+                            .substitute(Interner, &fn_placeholders);
+                        let var = self.new_opaque_hidden_ty_var(bounds);
+                        self.result.type_of_rpit.insert(idx, var.clone());
+                        var
                     }
-                    self.result.type_of_rpit.insert(idx, var.clone());
-                    var
-                },
-                DebruijnIndex::INNERMOST,
-            )
-        } else {
-            return_ty
-        };
+                    // Functions in the defining scope of a type alias `impl Trait` returning it
+                    // constrain its hidden type.
+                    ImplTraitId::TypeAliasImplTrait(alias, idx)
+                        if is_in_tait_defining_scope(self.db, alias, module) =>
+                    {
+                        self.tait_hidden_ty_var(alias, idx, subst).unwrap_or(ty)
+                    }
+                    _ => ty,
+                }
+            },
+            DebruijnIndex::INNERMOST,
+        );
 
         self.return_ty = self.normalize_associated_types_in(return_ty);
         self.return_coercion = Some(CoerceMany::new(self.return_ty.clone()));
     }
 
+    /// Creates a type variable standing for the hidden type of an opaque type with the given
+    /// bounds, and registers the bounds as obligations on it.
+    fn new_opaque_hidden_ty_var(&mut self, bounds: Binders<Vec<QuantifiedWhereClause>>) -> Ty {
+        let var = self.table.new_type_var();
+        let var_subst = Substitution::from1(Interner, var.clone());
+        for bound in bounds.substitute(Interner, &var_subst) {
+            let (var_predicate, binders) = bound.into_value_and_skipped_binders();
+            always!(binders.is_empty(Interner)); // quantified where clauses not yet handled
+            self.push_obligation(var_predicate.cast(Interner));
+        }
+        var
+    }
+
+    /// Returns the type variable standing for the hidden type of a type alias `impl Trait` this
+    /// body defines. All the uses of the alias share it, whatever its generic arguments.
+    fn tait_hidden_ty_var(
+        &mut self,
+        alias: TypeAliasId,
+        idx: RpitId,
+        subst: &Substitution,
+    ) -> Option<Ty> {
+        if let Some(var) = self.result.type_of_tait.get(&(alias, idx)) {
+            return Some(var.clone());
+        }
+        let taits = self.db.type_alias_impl_traits(alias)?;
+        let bounds = (*taits)
+            .as_ref()
+            .map(|taits| taits.impl_traits[idx].bounds.clone())
+            .substitute(Interner, subst);
+        let var = self.new_opaque_hidden_ty_var(bounds);
+        self.result.type_of_tait.insert((alias, idx), var.clone());
+        Some(var)
+    }
+
+    /// Within its defining scope, an opaque type a pattern is checked against is replaced by its
+    /// hidden type, so that the pattern constrains it instead of mismatching.
+    fn reveal_opaque_ty_for_pat(&mut self, ty: Ty) -> Ty {
+        let TyKind::OpaqueType(opaque_ty_id, subst) = ty.kind(Interner) else { return ty };
+        match self.db.lookup_intern_impl_trait_id((*opaque_ty_id).into()) {
+            ImplTraitId::ReturnTypeImplTrait(func, idx)
+                if self.owner == DefWithBodyId::FunctionId(func) =>
+            {
+                self.result.type_of_rpit.get(idx).cloned().unwrap_or(ty)
+            }
+            ImplTraitId::TypeAliasImplTrait(alias, idx)
+                if is_in_tait_defining_scope(self.db, alias, self.resolver.module()) =>
+            {
+                let subst = subst.clone();
+                self.tait_hidden_ty_var(alias, idx, &subst).unwrap_or(ty)
+            }
+            _ => ty,
+        }
+    }
+
     fn infer_body(&mut self) {
         match self.return_coercion {
             Some(_) => self.infer_return(self.body.body_expr),
@@ -1137,8 +1480,8 @@ fn has_type(ty: Ty) -> Self {
     /// See the test case `test/ui/coerce-expect-unsized.rs` and #20169
     /// for examples of where this comes up,.
     fn rvalue_hint(table: &mut unify::InferenceTable<'_>, ty: Ty) -> Self {
-        // FIXME: do struct_tail_without_normalization
-        match table.resolve_ty_shallow(&ty).kind(Interner) {
+        let tail = struct_tail_without_normalization(table.db, table.resolve_ty_shallow(&ty));
+        match tail.kind(Interner) {
             TyKind::Slice(_) | TyKind::Str | TyKind::Dyn(_) => Expectation::RValueLikeUnsized(ty),
             _ => Expectation::has_type(ty),
         }
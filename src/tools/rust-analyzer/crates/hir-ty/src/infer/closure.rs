@@ -1,12 +1,17 @@
 //! Inference of closure parameter types based on the closure's expected type.
 
 use chalk_ir::{cast::Cast, AliasEq, AliasTy, FnSubst, WhereClause};
-use hir_def::{expr::ExprId, HasModule};
+use hir_def::{
+    expr::{BindingId, Expr, ExprId},
+    HasModule,
+};
+use hir_expand::name::name;
+use rustc_hash::FxHashSet;
 use smallvec::SmallVec;
 
 use crate::{
-    to_chalk_trait_id, utils, ChalkTraitId, DynTy, FnPointer, FnSig, Interner, Substitution, Ty,
-    TyExt, TyKind,
+    db::HirDatabase, to_chalk_trait_id, utils, ChalkTraitId, ClosureId, DynTy, FnPointer, FnSig,
+    Interner, Substitution, Ty, TyExt, TyKind,
 };
 
 use super::{Expectation, InferenceContext};
@@ -25,7 +30,19 @@ pub(super) fn deduce_closure_type_from_expectations(
             None => return,
         };
 
-        // Deduction from where-clauses in scope, as well as fn-pointer coercion are handled here.
+        // A closure is only coerced to an fn pointer once its body has been inferred, as one that
+        // captures can't be, but its signature is that of the fn pointer either way.
+        if let TyKind::Function(fn_ptr) = expected_ty.kind(Interner) {
+            let expected_sig_ty = TyKind::Function(FnPointer {
+                sig: FnSig { safety: chalk_ir::Safety::Safe, ..fn_ptr.sig },
+                ..fn_ptr.clone()
+            })
+            .intern(Interner);
+            self.unify(sig_ty, &expected_sig_ty);
+            return;
+        }
+
+        // Deduction from where-clauses in scope is handled here.
         let _ = self.coerce(Some(closure_expr), closure_ty, &expected_ty);
 
         // Generators are not Fn* so return early.
@@ -86,3 +103,39 @@ fn deduce_sig_from_dyn_ty(&self, dyn_ty: &DynTy) -> Option<FnPointer> {
         None
     }
 }
+
+/// Returns whether the closure refers to any local binding of its enclosing body, i.e. whether it
+/// captures anything.
+pub(super) fn closure_has_captures(db: &dyn HirDatabase, closure: ClosureId) -> bool {
+    let (owner, closure_expr) = db.lookup_intern_closure(closure.into());
+    let body = db.body(owner);
+    let scopes = db.expr_scopes(owner);
+    let Expr::Closure { body: closure_body, .. } = &body[closure_expr] else { return false };
+
+    // Everything in scope at the closure expression lives outside of the closure.
+    let outer_bindings: FxHashSet<BindingId> = scopes
+        .scope_chain(scopes.scope_for(closure_expr))
+        .flat_map(|scope| scopes.entries(scope))
+        .map(|entry| entry.binding())
+        .collect();
+    if outer_bindings.is_empty() {
+        return false;
+    }
+
+    let self_name = name![self];
+    let mut stack = vec![*closure_body];
+    while let Some(expr) = stack.pop() {
+        if let Expr::Path(path) = &body[expr] {
+            let path = path.mod_path();
+            let name = if path.is_self() { Some(&self_name) } else { path.as_ident() };
+            let entry = name
+                .zip(scopes.scope_for(expr))
+                .and_then(|(name, scope)| scopes.resolve_name_in_scope(scope, name));
+            if entry.map_or(false, |entry| outer_bindings.contains(&entry.binding())) {
+                return true;
+            }
+        }
+        body[expr].walk_child_exprs(|child| stack.push(child));
+    }
+    false
+}
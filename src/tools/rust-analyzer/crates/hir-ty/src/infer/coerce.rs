@@ -11,6 +11,7 @@
 use hir_def::{
     expr::ExprId,
     lang_item::{LangItem, LangItemTarget},
+    FieldId, ImplId, Lookup, TraitId,
 };
 use stdx::always;
 
@@ -18,14 +19,19 @@
     autoderef::{Autoderef, AutoderefKind},
     db::HirDatabase,
     infer::{
-        Adjust, Adjustment, AutoBorrow, InferOk, InferenceContext, OverloadedDeref, PointerCast,
-        TypeError, TypeMismatch,
+        Adjust, Adjustment, AllowTwoPhase, AutoBorrow, AutoBorrowMutability, CoerceUnsizedInfo,
+        CustomCoerceUnsized, InferOk, InferenceContext, OverloadedDeref, PointerCast, TypeError,
+        TypeMismatch,
     },
-    static_lifetime, Canonical, DomainGoal, FnPointer, FnSig, Guidance, InEnvironment, Interner,
-    Solution, Substitution, TraitEnvironment, Ty, TyBuilder, TyExt, TyKind,
+    lang_items::is_box,
+    static_lifetime,
+    utils::all_super_trait_refs,
+    AdtId, Canonical, ClosureId, DomainGoal, FnPointer, FnSig, Guidance, InEnvironment, Interner,
+    QuantifiedWhereClauses, Solution, Substitution, TraitEnvironment, TraitRef, TraitRefExt, Ty,
+    TyBuilder, TyExt, TyKind, WhereClause,
 };
 
-use super::unify::InferenceTable;
+use super::{closure::closure_has_captures, unify::InferenceTable};
 
 pub(crate) type CoerceResult = Result<InferOk<(Vec<Adjustment>, Ty)>, TypeError>;
 
@@ -51,11 +57,14 @@ fn success(
 pub(super) struct CoerceMany {
     expected_ty: Ty,
     final_ty: Option<Ty>,
+    /// The expressions that have been merged so far, so that their adjustments
+    /// can be updated when a later branch forces a least upper bound coercion.
+    expressions: Vec<ExprId>,
 }
 
 impl CoerceMany {
     pub(super) fn new(expected: Ty) -> Self {
-        CoerceMany { expected_ty: expected, final_ty: None }
+        CoerceMany { expected_ty: expected, final_ty: None, expressions: vec![] }
     }
 
     /// Returns the "expected type" with which this coercion was
@@ -125,8 +134,15 @@ pub(super) fn coerce(
             let result1 = ctx.table.coerce_inner(self.merged_ty(), &target_ty);
             let result2 = ctx.table.coerce_inner(expr_ty.clone(), &target_ty);
             if let (Ok(result1), Ok(result2)) = (result1, result2) {
-                ctx.table.register_infer_ok(result1);
-                ctx.table.register_infer_ok(result2);
+                let InferOk { value: (adjustments1, _), goals } = result1;
+                ctx.table.register_infer_ok(InferOk { value: (), goals });
+                self.write_lub_adjustments(ctx, &adjustments1);
+                let InferOk { value: (adjustments2, _), goals } = result2;
+                ctx.table.register_infer_ok(InferOk { value: (), goals });
+                if let Some(id) = expr {
+                    ctx.write_expr_adj(id, adjustments2);
+                    self.expressions.push(id);
+                }
                 return self.final_ty = Some(target_ty);
             }
         }
@@ -135,9 +151,14 @@ pub(super) fn coerce(
         // type is a type variable and the new one is `!`, trying it the other
         // way around first would mean we make the type variable `!`, instead of
         // just marking it as possibly diverging.
+        //
+        // If the new expression can't be coerced to the merged type, try the
+        // opposite direction, adjusting all previously merged expressions instead
+        // (e.g. `&mut T` followed by `&T` merges to `&T`).
         if let Ok(res) = ctx.coerce(expr, &expr_ty, &self.merged_ty()) {
             self.final_ty = Some(res);
-        } else if let Ok(res) = ctx.coerce(expr, &self.merged_ty(), &expr_ty) {
+        } else if let Ok((adjustments, res)) = ctx.table.coerce(&self.merged_ty(), &expr_ty) {
+            self.write_lub_adjustments(ctx, &adjustments);
             self.final_ty = Some(res);
         } else {
             if let Some(id) = expr {
@@ -147,6 +168,40 @@ pub(super) fn coerce(
                 );
             }
             cov_mark::hit!(coerce_merge_fail_fallback);
+            return;
+        }
+        if let Some(id) = expr {
+            self.expressions.push(id);
+        }
+    }
+
+    /// Records `adjustments` on every expression merged so far, composing them
+    /// with the adjustments those expressions already had.
+    fn write_lub_adjustments(&self, ctx: &mut InferenceContext<'_>, adjustments: &[Adjustment]) {
+        if adjustments.is_empty() {
+            return;
+        }
+        for &expr in &self.expressions {
+            let existing = ctx.result.expr_adjustments.entry(expr).or_default();
+            // `!` already coerces to whatever the merged type ends up being.
+            if matches!(existing.first(), Some(Adjustment { kind: Adjust::NeverToAny, .. })) {
+                continue;
+            }
+            // A reborrow is subsumed by a new autoref of the same place.
+            let is_reborrow = matches!(
+                existing[..],
+                [
+                    Adjustment { kind: Adjust::Deref(None), .. },
+                    Adjustment { kind: Adjust::Borrow(_), .. }
+                ]
+            );
+            if is_reborrow
+                && matches!(adjustments.first(), Some(Adjustment { kind: Adjust::Deref(None), .. }))
+            {
+                *existing = adjustments.to_vec();
+            } else {
+                existing.extend(adjustments.iter().cloned());
+            }
         }
     }
 }
@@ -272,11 +327,11 @@ fn coerce_inner(&mut self, from_ty: Ty, to_ty: &Ty) -> CoerceResult {
                 // unsafe qualifier.
                 self.coerce_from_fn_pointer(from_ty.clone(), from_fn_ptr, to_ty)
             }
-            TyKind::Closure(_, from_substs) => {
+            TyKind::Closure(closure, from_substs) => {
                 // Non-capturing closures are coercible to
                 // function pointers or unsafe function pointers.
                 // It cannot convert closures that require unsafe.
-                self.coerce_closure_to_fn(from_ty.clone(), from_substs, to_ty)
+                self.coerce_closure_to_fn(from_ty.clone(), *closure, from_substs, to_ty)
             }
             _ => {
                 // Otherwise, just use unification rules.
@@ -307,7 +362,7 @@ fn coerce_ptr(&mut self, from_ty: Ty, to_ty: &Ty, to_mt: Mutability) -> CoerceRe
         let from_raw = TyKind::Raw(to_mt, from_inner.clone()).intern(Interner);
 
         // Although references and unsafe ptrs have the same
-        // representation, we still register an Adjust::DerefRef so that
+        // representation, we still register a deref and a raw borrow so that
         // regionck knows that the region for `a` must be valid here.
         if is_ref {
             self.unify_and(&from_raw, to_ty, |target| {
@@ -425,8 +480,9 @@ fn coerce_ref(&mut self, from_ty: Ty, to_ty: &Ty, to_mt: Mutability) -> CoerceRe
         }
 
         let mut adjustments = auto_deref_adjust_steps(&autoderef);
+        let m = AutoBorrowMutability::new(to_mt, AllowTwoPhase::No);
         adjustments
-            .push(Adjustment { kind: Adjust::Borrow(AutoBorrow::Ref(to_mt)), target: ty.clone() });
+            .push(Adjustment { kind: Adjust::Borrow(AutoBorrow::Ref(m)), target: ty.clone() });
 
         success(adjustments, ty, goals)
     }
@@ -513,12 +569,12 @@ fn coerce_from_safe_fn<F, G>(
     fn coerce_closure_to_fn(
         &mut self,
         from_ty: Ty,
+        closure: ClosureId,
         from_substs: &Substitution,
         to_ty: &Ty,
     ) -> CoerceResult {
         match to_ty.kind(Interner) {
-            // if from_substs is non-capturing (FIXME)
-            TyKind::Function(fn_ty) => {
+            TyKind::Function(fn_ty) if !closure_has_captures(self.db, closure) => {
                 // We coerce the closure, which has fn type
                 //     `extern "rust-call" fn((arg0,arg1,...)) -> _`
                 // to
@@ -578,7 +634,10 @@ fn try_coerce_unsized(&mut self, from_ty: &Ty, to_ty: &Ty) -> CoerceResult {
                 Some((
                     Adjustment { kind: Adjust::Deref(None), target: from_inner.clone() },
                     Adjustment {
-                        kind: Adjust::Borrow(AutoBorrow::Ref(to_mt)),
+                        kind: Adjust::Borrow(AutoBorrow::Ref(AutoBorrowMutability::new(
+                            to_mt,
+                            AllowTwoPhase::No,
+                        ))),
                         target: TyKind::Ref(to_mt, lt, from_inner.clone()).intern(Interner),
                     },
                 ))
@@ -599,6 +658,18 @@ fn try_coerce_unsized(&mut self, from_ty: &Ty, to_ty: &Ty) -> CoerceResult {
         let coerce_from =
             reborrow.as_ref().map_or_else(|| from_ty.clone(), |(_, adj)| adj.target.clone());
 
+        if let Some((source, target)) = self.coerce_trait_upcasting(&coerce_from, to_ty) {
+            let upcast = Adjustment {
+                kind: Adjust::Pointer(PointerCast::TraitUpcasting { source, target }),
+                target: to_ty.clone(),
+            };
+            let adjustments = match reborrow {
+                None => vec![upcast],
+                Some((deref, autoref)) => vec![deref, autoref, upcast],
+            };
+            return success(adjustments, to_ty.clone(), vec![]);
+        }
+
         let krate = self.trait_env.krate;
         let coerce_unsized_trait = match self.db.lang_item(krate, LangItem::CoerceUnsized) {
             Some(LangItemTarget::Trait(trait_)) => trait_,
@@ -654,6 +725,81 @@ fn try_coerce_unsized(&mut self, from_ty: &Ty, to_ty: &Ty) -> CoerceResult {
         };
         success(adjustments, to_ty.clone(), vec![])
     }
+
+    /// Tries to coerce a pointer to a trait object into a pointer to a trait object of one of its
+    /// supertraits, e.g. `&dyn Sub` to `&dyn Super`. Returns the source and target principal
+    /// traits on success.
+    ///
+    /// Chalk only proves `dyn A: Unsize<dyn B>` when both share the same principal trait, so
+    /// upcasts have to be handled before we ask it about `CoerceUnsized`.
+    fn coerce_trait_upcasting(&mut self, from_ty: &Ty, to_ty: &Ty) -> Option<(TraitId, TraitId)> {
+        let (from_pointee, to_pointee) = match (from_ty.kind(Interner), to_ty.kind(Interner)) {
+            (TyKind::Ref(_, _, from), TyKind::Ref(_, _, to))
+            | (TyKind::Raw(_, from), TyKind::Raw(_, to)) => (from.clone(), to.clone()),
+            (TyKind::Adt(AdtId(from_adt), from_subst), TyKind::Adt(AdtId(to_adt), to_subst))
+                if from_adt == to_adt && is_box(*from_adt, self.db) =>
+            {
+                (
+                    from_subst.at(Interner, 0).ty(Interner)?.clone(),
+                    to_subst.at(Interner, 0).ty(Interner)?.clone(),
+                )
+            }
+            _ => return None,
+        };
+        let from_pointee = self.resolve_ty_shallow(&from_pointee);
+        let to_pointee = self.resolve_ty_shallow(&to_pointee);
+        let (TyKind::Dyn(from_dyn), TyKind::Dyn(to_dyn)) =
+            (from_pointee.kind(Interner), to_pointee.kind(Interner))
+        else {
+            return None;
+        };
+
+        // Use the same self type for both so that the trait refs can be unified.
+        let self_ty = [from_pointee.clone().cast(Interner)];
+        let implemented_traits = |bounds: QuantifiedWhereClauses| -> Vec<TraitRef> {
+            bounds
+                .iter(Interner)
+                .filter_map(|bound| match bound.skip_binders() {
+                    WhereClause::Implemented(trait_ref) => Some(trait_ref.clone()),
+                    _ => None,
+                })
+                .collect()
+        };
+        let from_traits =
+            implemented_traits(from_dyn.bounds.clone().substitute(Interner, &self_ty));
+        let to_traits = implemented_traits(to_dyn.bounds.clone().substitute(Interner, &self_ty));
+
+        // The principal trait always comes first, see `TyLoweringContext::lower_dyn_trait()`.
+        let (from_principal, _) = from_traits.split_first()?;
+        let (to_principal, to_auto_traits) = to_traits.split_first()?;
+        let source = from_principal.hir_trait_id();
+        let target = to_principal.hir_trait_id();
+        if source == target
+            || self.db.trait_data(source).is_auto
+            || self.db.trait_data(target).is_auto
+        {
+            return None;
+        }
+        if !to_auto_traits
+            .iter()
+            .all(|auto| from_traits.iter().any(|t| t.trait_id == auto.trait_id))
+        {
+            return None;
+        }
+
+        // FIXME: check associated type bindings of the target trait object as well
+        let super_trait_ref = all_super_trait_refs(self.db, from_principal.clone(), |trait_ref| {
+            (trait_ref.trait_id == to_principal.trait_id).then_some(trait_ref)
+        })?;
+        let super_trait_args = super_trait_ref.substitution.iter(Interner);
+        let to_principal_args = to_principal.substitution.iter(Interner);
+        if super_trait_args.len() != to_principal_args.len()
+            || !super_trait_args.zip(to_principal_args).all(|(a, b)| self.unify(a, b))
+        {
+            return None;
+        }
+        Some((source, target))
+    }
 }
 
 fn coerce_closure_fn_ty(closure_substs: &Substitution, safety: chalk_ir::Safety) -> Ty {
@@ -700,3 +846,51 @@ pub(super) fn auto_deref_adjust_steps(autoderef: &Autoderef<'_, '_>) -> Vec<Adju
         .map(|(autoderef, target)| Adjustment { kind: Adjust::Deref(autoderef), target })
         .collect()
 }
+
+/// Finds the field a `CoerceUnsized` impl for a struct coerces, i.e. the only field (ignoring
+/// `PhantomData`s) whose type differs between the impl's self type and its target type.
+pub(crate) fn coerce_unsized_info_query(
+    db: &dyn HirDatabase,
+    impl_id: ImplId,
+) -> CoerceUnsizedInfo {
+    let err_info = CoerceUnsizedInfo { custom_kind: None };
+    let krate = impl_id.lookup(db.upcast()).container.krate();
+    let Some(trait_ref) = db.impl_trait(impl_id) else { return err_info };
+    let trait_ref = trait_ref.skip_binders();
+    let coerce_unsized_trait =
+        db.lang_item(krate, LangItem::CoerceUnsized).and_then(|it| it.as_trait());
+    if coerce_unsized_trait != Some(trait_ref.hir_trait_id()) {
+        return err_info;
+    }
+    let source = trait_ref.self_type_parameter(Interner);
+    let Some(target) = trait_ref.substitution.at(Interner, 1).ty(Interner) else { return err_info };
+    let (
+        TyKind::Adt(AdtId(hir_def::AdtId::StructId(def_a)), substs_a),
+        TyKind::Adt(AdtId(hir_def::AdtId::StructId(def_b)), substs_b),
+    ) = (source.kind(Interner), target.kind(Interner))
+    else {
+        return err_info;
+    };
+    if def_a != def_b {
+        return err_info;
+    }
+
+    let phantom_data = db.lang_item(krate, LangItem::PhantomData).and_then(|it| it.as_struct());
+    let field_types = db.field_types((*def_a).into());
+    let mut diff_fields = field_types.iter().filter_map(|(local_id, ty)| {
+        if let Some((hir_def::AdtId::StructId(id), _)) = ty.skip_binders().as_adt() {
+            if Some(id) == phantom_data {
+                return None;
+            }
+        }
+        let a = ty.clone().substitute(Interner, substs_a);
+        let b = ty.clone().substitute(Interner, substs_b);
+        (a != b).then_some(FieldId { parent: (*def_a).into(), local_id })
+    });
+    match (diff_fields.next(), diff_fields.next()) {
+        (Some(field), None) => {
+            CoerceUnsizedInfo { custom_kind: Some(CustomCoerceUnsized::Struct(field)) }
+        }
+        _ => err_info,
+    }
+}
@@ -35,8 +35,9 @@
     primitive::{self, UintTy},
     static_lifetime, to_chalk_trait_id,
     utils::{generics, Generics},
-    Adjust, Adjustment, AdtId, AutoBorrow, Binders, CallableDefId, FnPointer, FnSig, FnSubst,
-    Interner, Rawness, Scalar, Substitution, TraitRef, Ty, TyBuilder, TyExt,
+    Adjust, Adjustment, AdtId, AllowTwoPhase, AutoBorrow, AutoBorrowMutability, Binders,
+    CallableDefId, FnPointer, FnSig, FnSubst, Interner, Rawness, Scalar, Substitution, TraitRef,
+    Ty, TyBuilder, TyExt,
 };
 
 use super::{
@@ -1142,9 +1143,11 @@ fn infer_overloadable_binop(
 
         let ret_ty = match method_ty.callable_sig(self.db) {
             Some(sig) => {
+                // Allow two-phase borrows for binops, since they desugar to method calls.
                 let p_left = &sig.params()[0];
                 if matches!(op, BinaryOp::CmpOp(..) | BinaryOp::Assignment { .. }) {
                     if let &TyKind::Ref(mtbl, _, _) = p_left.kind(Interner) {
+                        let mtbl = AutoBorrowMutability::new(mtbl, AllowTwoPhase::Yes);
                         self.write_expr_adj(
                             lhs,
                             vec![Adjustment {
@@ -1157,6 +1160,7 @@ fn infer_overloadable_binop(
                 let p_right = &sig.params()[1];
                 if matches!(op, BinaryOp::CmpOp(..)) {
                     if let &TyKind::Ref(mtbl, _, _) = p_right.kind(Interner) {
+                        let mtbl = AutoBorrowMutability::new(mtbl, AllowTwoPhase::Yes);
                         self.write_expr_adj(
                             rhs,
                             vec![Adjustment {
@@ -7,6 +7,7 @@
     body::Body,
     expr::{Binding, BindingAnnotation, BindingId, Expr, ExprId, ExprOrPatId, Literal, Pat, PatId},
     path::Path,
+    resolver::ValueNs,
 };
 use hir_expand::name::Name;
 
@@ -188,7 +189,7 @@ pub(super) fn infer_top_pat(&mut self, pat: PatId, expected: &Ty) {
     fn infer_pat(&mut self, pat: PatId, expected: &Ty, mut default_bm: BindingMode) -> Ty {
         let mut expected = self.resolve_ty_shallow(expected);
 
-        if is_non_ref_pat(self.body, pat) {
+        if is_non_ref_pat(self.body, pat) && !self.is_const_path_pat(pat) {
             let mut pat_adjustments = Vec::new();
             while let Some((inner, _lifetime, mutability)) = expected.as_reference() {
                 pat_adjustments.push(expected.clone());
@@ -213,7 +214,11 @@ fn infer_pat(&mut self, pat: PatId, expected: &Ty, mut default_bm: BindingMode)
 
         // Lose mutability.
         let default_bm = default_bm;
-        let expected = expected;
+        // The patterns that destructure an opaque type this body defines constrain its hidden type.
+        let expected = match &self.body[pat] {
+            Pat::Wild | Pat::Missing | Pat::Or(_) | Pat::Bind { subpat: None, .. } => expected,
+            _ => self.reveal_opaque_ty_for_pat(expected),
+        };
 
         let ty = match &self.body[pat] {
             Pat::Tuple { args, ellipsis } => {
@@ -255,9 +260,18 @@ fn infer_pat(&mut self, pat: PatId, expected: &Ty, mut default_bm: BindingMode)
                 self.infer_slice_pat(&expected, prefix, slice, suffix, default_bm)
             }
             Pat::Wild => expected.clone(),
-            Pat::Range { start, end } => {
-                let start_ty = self.infer_expr(*start, &Expectation::has_type(expected.clone()));
-                self.infer_expr(*end, &Expectation::has_type(start_ty))
+            Pat::Range { start, end, .. } => {
+                // The endpoints of a range matching a pattern type are of its base type.
+                let pattern_ty = expected.as_pattern_type(self.db).map(|(base, _)| base.clone());
+                let mut ty = pattern_ty.clone().unwrap_or_else(|| expected.clone());
+                for &endpoint in start.iter().chain(end) {
+                    ty = self.infer_expr(endpoint, &Expectation::has_type(ty));
+                }
+                if pattern_ty.is_some() {
+                    expected.clone()
+                } else {
+                    ty
+                }
             }
             &Pat::Lit(expr) => {
                 // Don't emit type mismatches again, the expression lowering already did that.
@@ -389,6 +403,12 @@ fn infer_slice_pat(
     }
 
     fn infer_lit_pat(&mut self, expr: ExprId, expected: &Ty) -> Ty {
+        // Like range patterns, literal patterns can match pattern types.
+        if let Some((base, _)) = expected.as_pattern_type(self.db) {
+            self.infer_expr(expr, &Expectation::has_type(base.clone()));
+            return expected.clone();
+        }
+
         // Like slice patterns, byte string patterns can denote both `&[u8; N]` and `&[u8]`.
         if let Expr::Literal(Literal::ByteString(_)) = self.body[expr] {
             if let Some((inner, ..)) = expected.as_reference() {
@@ -406,6 +426,16 @@ fn infer_lit_pat(&mut self, expr: ExprId, expected: &Ty) -> Ty {
 
         self.infer_expr(expr, &Expectation::has_type(expected.clone()))
     }
+
+    /// Whether `pat` is a path to a constant. Like rustc, we don't peel references off the
+    /// expected type for those, as the constant may well be of a reference type itself.
+    fn is_const_path_pat(&self, pat: PatId) -> bool {
+        let Pat::Path(path) = &self.body[pat] else { return false };
+        matches!(
+            self.resolver.resolve_path_in_value_ns_fully(self.db.upcast(), path.mod_path()),
+            Some(ValueNs::ConstId(_))
+        )
+    }
 }
 
 fn is_non_ref_pat(body: &hir_def::body::Body, pat: PatId) -> bool {
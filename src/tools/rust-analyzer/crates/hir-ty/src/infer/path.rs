@@ -75,6 +75,7 @@ fn resolve_value_path(&mut self, path: &Path, id: ExprOrPatId) -> Option<Ty> {
                 let substs = generics.placeholder_subst(self.db);
                 let ty = self.db.impl_self_ty(impl_id).substitute(Interner, &substs);
                 if let Some((AdtId::StructId(struct_id), substs)) = ty.as_adt() {
+                    self.write_variant_resolution(id, struct_id.into());
                     let ty = self.db.value_ty(struct_id.into()).substitute(Interner, &substs);
                     return Some(ty);
                 } else {
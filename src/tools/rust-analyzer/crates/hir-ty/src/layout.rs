@@ -217,16 +217,26 @@ pub fn layout_of_ty(db: &dyn HirDatabase, ty: &Ty, krate: CrateId) -> Result<Lay
             ptr.valid_range_mut().start = 1;
             Layout::scalar(dl, ptr)
         }
-        TyKind::OpaqueType(opaque_ty_id, _) => {
+        TyKind::OpaqueType(opaque_ty_id, subst) => {
             let impl_trait_id = db.lookup_intern_impl_trait_id((*opaque_ty_id).into());
             match impl_trait_id {
                 crate::ImplTraitId::ReturnTypeImplTrait(func, idx) => {
                     let infer = db.infer(func.into());
                     layout_of_ty(db, &infer.type_of_rpit[idx], krate)?
                 }
+                crate::ImplTraitId::TypeAliasImplTrait(alias, idx) => {
+                    match crate::infer::type_alias_impl_trait_hidden_ty(db, alias, idx) {
+                        Some(hidden_ty) => layout_of_ty(db, &hidden_ty, krate)?,
+                        None => return Err(LayoutError::NotImplemented),
+                    }
+                }
                 crate::ImplTraitId::AsyncBlockTypeImplTrait(_, _) => {
                     return Err(LayoutError::NotImplemented)
                 }
+                // FIXME: the values outside of the pattern are a niche
+                crate::ImplTraitId::PatternType(_) => {
+                    layout_of_ty(db, subst.at(Interner, 0).assert_ty_ref(Interner), krate)?
+                }
             }
         }
         TyKind::Closure(_, _) | TyKind::Generator(_, _) | TyKind::GeneratorWitness(_, _) => {
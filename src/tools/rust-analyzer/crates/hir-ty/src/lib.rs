@@ -60,8 +60,9 @@ macro_rules! eprintln {
 pub use builder::{ParamKind, TyBuilder};
 pub use chalk_ext::*;
 pub use infer::{
-    could_coerce, could_unify, Adjust, Adjustment, AutoBorrow, BindingMode, InferenceDiagnostic,
-    InferenceResult, OverloadedDeref, PointerCast,
+    could_coerce, could_unify, Adjust, Adjustment, AllowTwoPhase, AutoBorrow, AutoBorrowMutability,
+    BindingMode, CoerceUnsizedInfo, CustomCoerceUnsized, InferenceDiagnostic, InferenceResult,
+    OverloadedDeref, PointerCast, UnsizeCoercion,
 };
 pub use interner::Interner;
 pub use lower::{
@@ -337,7 +338,21 @@ fn try_fold_with<E>(
 #[derive(Copy, Clone, PartialEq, Eq, Debug, Hash)]
 pub enum ImplTraitId {
     ReturnTypeImplTrait(hir_def::FunctionId, RpitId),
+    /// An `impl Trait` in the type of a type alias, e.g. `type Alias = impl Trait;`.
+    TypeAliasImplTrait(hir_def::TypeAliasId, RpitId),
     AsyncBlockTypeImplTrait(hir_def::DefWithBodyId, ExprId),
+    /// A pattern type like `pattern_type!(u32 is 1..)`, whose base type is the only parameter of
+    /// the opaque type. Pattern types are distinct from their base type, so we represent them as
+    /// opaque types without bounds.
+    PatternType(PatternTypeRange),
+}
+
+/// The values of a pattern type. Only integer ranges are supported: the endpoints are inclusive
+/// and given by their bits, like in range patterns, so `u8 is 1..` is `lo: 1, hi: 0xff`.
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Hash)]
+pub struct PatternTypeRange {
+    pub lo: u128,
+    pub hi: u128,
 }
 
 #[derive(Clone, PartialEq, Eq, Debug, Hash)]
@@ -21,6 +21,7 @@
     adt::StructKind,
     body::{Expander, LowerCtx},
     builtin_type::BuiltinType,
+    expr::Literal,
     generics::{
         TypeOrConstParamData, TypeParamProvenance, WherePredicate, WherePredicateTypeTarget,
     },
@@ -32,27 +33,31 @@
     HasModule, ImplId, ItemContainerId, LocalFieldId, Lookup, ModuleDefId, StaticId, StructId,
     TraitId, TypeAliasId, TypeOrConstParamId, TypeParamId, UnionId, VariantId,
 };
-use hir_expand::{name::Name, ExpandResult};
+use hir_expand::{
+    builtin_fn_macro::BuiltinFnLikeExpander, name::Name, ExpandResult, HirFileId, MacroDefKind,
+};
 use intern::Interned;
 use la_arena::{Arena, ArenaMap};
 use rustc_hash::FxHashSet;
 use smallvec::SmallVec;
 use stdx::{impl_from, never};
-use syntax::ast;
+use syntax::{ast, AstNode, AstToken, SyntaxToken, T};
 
 use crate::{
     all_super_traits,
     consteval::{intern_const_ref, path_to_const, unknown_const, unknown_const_as_generic},
     db::HirDatabase,
+    diagnostics::IntWidth,
     make_binders,
     mapping::{from_chalk_trait_id, ToChalk},
     static_lifetime, to_assoc_type_id, to_chalk_trait_id, to_placeholder_idx,
     utils::Generics,
     utils::{all_super_trait_refs, associated_type_by_name_including_super_traits, generics},
     AliasEq, AliasTy, Binders, BoundVar, CallableSig, Const, DebruijnIndex, DynTy, FnPointer,
-    FnSig, FnSubst, GenericArgData, ImplTraitId, Interner, ParamKind, PolyFnSig, ProjectionTy,
-    QuantifiedWhereClause, QuantifiedWhereClauses, ReturnTypeImplTrait, ReturnTypeImplTraits,
-    Substitution, TraitEnvironment, TraitRef, TraitRefExt, Ty, TyBuilder, TyKind, WhereClause,
+    FnSig, FnSubst, GenericArgData, ImplTraitId, Interner, ParamKind, PatternTypeRange, PolyFnSig,
+    ProjectionTy, QuantifiedWhereClause, QuantifiedWhereClauses, ReturnTypeImplTrait,
+    ReturnTypeImplTraits, Substitution, TraitEnvironment, TraitRef, TraitRefExt, Ty, TyBuilder,
+    TyKind, WhereClause,
 };
 
 #[derive(Debug)]
@@ -273,9 +278,10 @@ pub fn lower_ty_ext(&self, type_ref: &TypeRef) -> (Ty, Option<TypeNs>) {
             TypeRef::ImplTrait(bounds) => {
                 match &self.impl_trait_mode {
                     ImplTraitLoweringState::Opaque(opaque_type_data) => {
-                        let func = match self.resolver.generic_def() {
-                            Some(GenericDefId::FunctionId(f)) => f,
-                            _ => panic!("opaque impl trait lowering in non-function"),
+                        let origin = match self.resolver.generic_def() {
+                            Some(GenericDefId::FunctionId(f)) => Either::Left(f),
+                            Some(GenericDefId::TypeAliasId(t)) => Either::Right(t),
+                            _ => panic!("opaque impl trait lowering in non-function or type alias"),
                         };
 
                         // this dance is to make sure the data is in the right
@@ -295,13 +301,17 @@ pub fn lower_ty_ext(&self, type_ref: &TypeRef) -> (Ty, Option<TypeNs>) {
                         // away instead of two.
                         let actual_opaque_type_data = self
                             .with_debruijn(DebruijnIndex::INNERMOST, |ctx| {
-                                ctx.lower_impl_trait(bounds, func)
+                                ctx.lower_impl_trait(bounds, self.resolver.krate())
                             });
                         opaque_type_data.borrow_mut()[idx] = actual_opaque_type_data;
 
-                        let impl_trait_id = ImplTraitId::ReturnTypeImplTrait(func, idx);
+                        let impl_trait_id = match origin {
+                            Either::Left(func) => ImplTraitId::ReturnTypeImplTrait(func, idx),
+                            Either::Right(alias) => ImplTraitId::TypeAliasImplTrait(alias, idx),
+                        };
                         let opaque_ty_id = self.db.intern_impl_trait_id(impl_trait_id).into();
-                        let generics = generics(self.db.upcast(), func.into());
+                        let generics =
+                            generics(self.db.upcast(), origin.either(Into::into, Into::into));
                         let parameters = generics.bound_vars_subst(self.db, self.in_binders);
                         TyKind::OpaqueType(opaque_ty_id, parameters).intern(Interner)
                     }
@@ -378,13 +388,15 @@ pub fn lower_ty_ext(&self, type_ref: &TypeRef) -> (Ty, Option<TypeNs>) {
                 };
                 let ty = {
                     let macro_call = macro_call.to_node(self.db.upcast());
-                    match expander.enter_expand::<ast::Type>(self.db.upcast(), macro_call) {
+                    match expander.enter_expand::<ast::Type>(self.db.upcast(), macro_call.clone()) {
                         Ok(ExpandResult { value: Some((mark, expanded)), .. }) => {
-                            let ctx = LowerCtx::new(self.db.upcast(), expander.current_file_id());
+                            let expansion = expander.current_file_id();
+                            let ctx = LowerCtx::new(self.db.upcast(), expansion);
                             let type_ref = TypeRef::from_ast(&ctx, expanded);
 
                             drop(expander);
                             let ty = self.lower_ty(&type_ref);
+                            let ty = self.lower_pattern_type(expansion, &macro_call, ty);
 
                             self.expander
                                 .borrow_mut()
@@ -411,6 +423,29 @@ pub fn lower_ty_ext(&self, type_ref: &TypeRef) -> (Ty, Option<TypeNs>) {
         (ty, res)
     }
 
+    /// Turns `base` into the pattern type named by `macro_call`, if it is a `pattern_type!` call
+    /// whose expansion is `expansion`. The macro expands to the base type only.
+    fn lower_pattern_type(
+        &self,
+        expansion: HirFileId,
+        macro_call: &ast::MacroCall,
+        base: Ty,
+    ) -> Ty {
+        let Some(macro_file) = expansion.macro_file() else { return base };
+        let loc = self.db.lookup_intern_macro_call(macro_file.macro_call_id);
+        if !matches!(loc.def.kind, MacroDefKind::BuiltIn(BuiltinFnLikeExpander::PatternType, _)) {
+            return base;
+        }
+        match macro_call.token_tree().and_then(|tt| pattern_type_range(&tt, &base)) {
+            Some(range) => {
+                let id = self.db.intern_impl_trait_id(ImplTraitId::PatternType(range)).into();
+                TyKind::OpaqueType(id, Substitution::from1(Interner, base)).intern(Interner)
+            }
+            // FIXME: report unsupported or empty patterns
+            None => TyKind::Error.intern(Interner),
+        }
+    }
+
     /// This is only for `generic_predicates_for_param`, where we can't just
     /// lower the self types of the predicates since that could lead to cycles.
     /// So we just check here if the `type_ref` resolves to a generic param, and which.
@@ -1165,7 +1200,7 @@ fn lower_dyn_trait(&self, bounds: &[Interned<TypeBound>]) -> Ty {
     fn lower_impl_trait(
         &self,
         bounds: &[Interned<TypeBound>],
-        func: FunctionId,
+        krate: CrateId,
     ) -> ReturnTypeImplTrait {
         cov_mark::hit!(lower_rpit);
         let self_ty = TyKind::BoundVar(BoundVar::new(DebruijnIndex::INNERMOST, 0)).intern(Interner);
@@ -1176,7 +1211,6 @@ fn lower_impl_trait(
                 .collect();
 
             if !ctx.unsized_types.borrow().contains(&self_ty) {
-                let krate = func.lookup(ctx.db.upcast()).module(ctx.db.upcast()).krate();
                 let sized_trait = ctx
                     .db
                     .lang_item(krate, LangItem::Sized)
@@ -1207,6 +1241,49 @@ fn count_impl_traits(type_ref: &TypeRef) -> usize {
     count
 }
 
+/// The values of the pattern type `pattern_type!(base is pat)`, given the arguments of the macro
+/// call. Only integer ranges with literal endpoints are supported, like `1..`, `..=9` or `-5..5`.
+fn pattern_type_range(tt: &ast::TokenTree, base: &Ty) -> Option<PatternTypeRange> {
+    let width = IntWidth::of(base)?;
+    let mut elements = tt.syntax().children_with_tokens().filter(|it| !it.kind().is_trivia());
+    elements.find(|it| it.as_token().map_or(false, |it| it.text() == "is"))?;
+    let mut tokens = elements.map(|it| it.into_token()).collect::<Option<Vec<_>>>()?;
+    // The closing delimiter.
+    tokens.pop();
+
+    let op_start = tokens.iter().position(|it| it.text().starts_with('.'))?;
+    let op_end = tokens[op_start..]
+        .iter()
+        .position(|it| !matches!(it.text(), "." | "=" | ".." | "..="))
+        .map_or(tokens.len(), |len| op_start + len);
+    let op: String = tokens[op_start..op_end].iter().map(|it| it.text()).collect();
+    let endpoint = |tokens: &[SyntaxToken]| {
+        let int = |token: &SyntaxToken| {
+            let value = <ast::IntNumber as AstToken>::cast(token.clone())?.value()?;
+            Some(Literal::Uint(value, None))
+        };
+        let lit = match tokens {
+            [] => return Some(None),
+            [lit] => int(lit)?,
+            [minus, lit] if minus.kind() == T![-] => int(lit)?.negate()?,
+            _ => return None,
+        };
+        width.literal_bits(&lit).map(Some)
+    };
+    let lo = endpoint(&tokens[..op_start])?.unwrap_or(width.min_bits());
+    let end = endpoint(&tokens[op_end..])?;
+
+    // Compare the values in the biased encoding, where the smallest value is `0`.
+    let bias = width.min_bits();
+    let hi = match (op.as_str(), end) {
+        ("..", None) => width.max_bits(),
+        ("..", Some(end)) => ((end ^ bias).checked_sub(1)?) ^ bias,
+        ("..=", Some(end)) => end,
+        _ => return None,
+    };
+    (lo ^ bias <= hi ^ bias).then_some(PatternTypeRange { lo, hi })
+}
+
 /// Build the signature of a callable item (function, struct or enum variant).
 pub(crate) fn callable_item_sig(db: &dyn HirDatabase, def: CallableDefId) -> PolyFnSig {
     match def {
@@ -1716,8 +1793,9 @@ fn type_for_adt(db: &dyn HirDatabase, adt: AdtId) -> Binders<Ty> {
 fn type_for_type_alias(db: &dyn HirDatabase, t: TypeAliasId) -> Binders<Ty> {
     let generics = generics(db.upcast(), t.into());
     let resolver = t.resolver(db.upcast());
-    let ctx =
-        TyLoweringContext::new(db, &resolver).with_type_param_mode(ParamLoweringMode::Variable);
+    let ctx = TyLoweringContext::new(db, &resolver)
+        .with_impl_trait_mode(ImplTraitLoweringMode::Opaque)
+        .with_type_param_mode(ParamLoweringMode::Variable);
     if db.type_alias_data(t).is_extern {
         Binders::empty(Interner, TyKind::Foreign(crate::to_foreign_def_id(t)).intern(Interner))
     } else {
@@ -1906,6 +1984,32 @@ pub(crate) fn return_type_impl_traits(
     }
 }
 
+pub(crate) fn type_alias_impl_traits(
+    db: &dyn HirDatabase,
+    def: hir_def::TypeAliasId,
+) -> Option<Arc<Binders<ReturnTypeImplTraits>>> {
+    let data = db.type_alias_data(def);
+    let resolver = def.resolver(db.upcast());
+    let ctx = TyLoweringContext::new(db, &resolver)
+        .with_impl_trait_mode(ImplTraitLoweringMode::Opaque)
+        .with_type_param_mode(ParamLoweringMode::Variable);
+    if let Some(type_ref) = &data.type_ref {
+        let _ty = ctx.lower_ty(type_ref);
+    }
+    let generics = generics(db.upcast(), def.into());
+    let type_alias_impl_traits = ReturnTypeImplTraits {
+        impl_traits: match ctx.impl_trait_mode {
+            ImplTraitLoweringState::Opaque(x) => x.into_inner(),
+            _ => unreachable!(),
+        },
+    };
+    if type_alias_impl_traits.impl_traits.is_empty() {
+        None
+    } else {
+        Some(Arc::new(make_binders(db, &generics, type_alias_impl_traits)))
+    }
+}
+
 pub(crate) fn lower_to_chalk_mutability(m: hir_def::type_ref::Mutability) -> Mutability {
     match m {
         hir_def::type_ref::Mutability::Shared => Mutability::Not,
@@ -20,7 +20,10 @@
     autoderef::{self, AutoderefKind},
     db::HirDatabase,
     from_chalk_trait_id, from_foreign_def_id,
-    infer::{unify::InferenceTable, Adjust, Adjustment, AutoBorrow, OverloadedDeref, PointerCast},
+    infer::{
+        unify::InferenceTable, Adjust, Adjustment, AllowTwoPhase, AutoBorrow, AutoBorrowMutability,
+        OverloadedDeref, PointerCast,
+    },
     primitive::{FloatTy, IntTy, UintTy},
     static_lifetime, to_chalk_trait_id,
     utils::all_super_traits,
@@ -601,6 +604,8 @@ pub(crate) fn apply(&self, table: &mut InferenceTable<'_>, ty: Ty) -> (Ty, Vec<A
         }
         if let Some(m) = self.autoref {
             ty = TyKind::Ref(m, static_lifetime(), ty).intern(Interner);
+            // Method receivers are the canonical place where two-phase borrows are allowed.
+            let m = AutoBorrowMutability::new(m, AllowTwoPhase::Yes);
             adjust
                 .push(Adjustment { kind: Adjust::Borrow(AutoBorrow::Ref(m)), target: ty.clone() });
         }
@@ -3,7 +3,8 @@
 use std::{fmt::Display, iter};
 
 use crate::{
-    infer::PointerCast, Const, ConstScalar, InferenceResult, Interner, MemoryMap, Substitution, Ty,
+    infer::{AllowTwoPhase, AutoBorrowMutability, PointerCast},
+    Const, ConstScalar, InferenceResult, Interner, MemoryMap, Substitution, Ty,
 };
 use chalk_ir::Mutability;
 use hir_def::{
@@ -500,10 +501,12 @@ fn from_hir(m: hir_def::type_ref::Mutability) -> Self {
         }
     }
 
-    fn from_chalk(m: Mutability) -> Self {
+    fn from_auto_borrow(m: AutoBorrowMutability) -> Self {
         match m {
-            Mutability::Not => BorrowKind::Shared,
-            Mutability::Mut => BorrowKind::Mut { allow_two_phase_borrow: false },
+            AutoBorrowMutability::Not => BorrowKind::Shared,
+            AutoBorrowMutability::Mut { allow_two_phase_borrow } => BorrowKind::Mut {
+                allow_two_phase_borrow: allow_two_phase_borrow == AllowTwoPhase::Yes,
+            },
         }
     }
 }
@@ -690,7 +693,7 @@ pub enum Rvalue {
     ///
     /// Like with references, the semantics of this operation are heavily dependent on the aliasing
     /// model.
-    //AddressOf(Mutability, Place),
+    AddressOf(Mutability, Place),
 
     /// Yields the length of the place, as a `usize`.
     ///
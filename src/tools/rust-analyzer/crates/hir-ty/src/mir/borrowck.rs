@@ -5,6 +5,7 @@
 
 use std::sync::Arc;
 
+use chalk_ir::Mutability;
 use hir_def::DefWithBodyId;
 use la_arena::ArenaMap;
 use stdx::never;
@@ -178,7 +179,9 @@ fn mutability_of_locals(body: &MirBody) -> ArenaMap<LocalId, MutabilityReason> {
                         }
                         ProjectionCase::Indirect => (),
                     }
-                    if let Rvalue::Ref(BorrowKind::Mut { .. }, p) = value {
+                    if let Rvalue::Ref(BorrowKind::Mut { .. }, p)
+                    | Rvalue::AddressOf(Mutability::Mut, p) = value
+                    {
                         if is_place_direct(p) {
                             push_mut_span(p.local, statement.span);
                         }
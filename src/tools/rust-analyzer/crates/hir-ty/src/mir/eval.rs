@@ -11,7 +11,7 @@
     builtin_type::BuiltinType,
     lang_item::{lang_attr, LangItem},
     layout::{Layout, LayoutError, RustcEnumVariantIdx, TagEncoding, Variants},
-    AdtId, DefWithBodyId, EnumVariantId, FunctionId, HasModule, Lookup, VariantId,
+    AdtId, DefWithBodyId, EnumVariantId, FieldId, FunctionId, HasModule, Lookup, VariantId,
 };
 use intern::Interned;
 use la_arena::ArenaMap;
@@ -20,10 +20,10 @@
     consteval::{intern_const_scalar, ConstEvalError},
     db::HirDatabase,
     from_placeholder_idx,
-    infer::{normalize, PointerCast},
+    infer::{normalize, CustomCoerceUnsized, PointerCast},
     layout::layout_of_ty,
     mapping::from_chalk,
-    method_resolution::lookup_impl_method,
+    method_resolution::{lookup_impl_method, TyFingerprint},
     CallableDefId, Const, ConstScalar, Interner, MemoryMap, Substitution, Ty, TyBuilder, TyExt,
 };
 
@@ -599,7 +599,7 @@ fn eval_rvalue<'a>(
         use IntervalOrOwned::*;
         Ok(match r {
             Rvalue::Use(x) => Borrowed(self.eval_operand(x, locals)?),
-            Rvalue::Ref(_, p) => {
+            Rvalue::Ref(_, p) | Rvalue::AddressOf(_, p) => {
                 let addr = self.place_addr(p, locals)?;
                 Owned(addr.to_bytes())
             }
@@ -803,35 +803,12 @@ fn eval_rvalue<'a>(
                 CastKind::Pointer(cast) => match cast {
                     PointerCast::Unsize => {
                         let current_ty = self.operand_ty(operand, locals)?;
-                        match &target_ty.data(Interner).kind {
-                            TyKind::Raw(_, ty) | TyKind::Ref(_, _, ty) => {
-                                match &ty.data(Interner).kind {
-                                    TyKind::Slice(_) => match &current_ty.data(Interner).kind {
-                                        TyKind::Raw(_, ty) | TyKind::Ref(_, _, ty) => {
-                                            match &ty.data(Interner).kind {
-                                                TyKind::Array(_, size) => {
-                                                    let addr = self
-                                                        .eval_operand(operand, locals)?
-                                                        .get(&self)?;
-                                                    let len = const_as_usize(size);
-                                                    let mut r = Vec::with_capacity(16);
-                                                    r.extend(addr.iter().copied());
-                                                    r.extend(len.to_le_bytes().into_iter());
-                                                    Owned(r)
-                                                }
-                                                _ => {
-                                                    not_supported!("slice unsizing from non arrays")
-                                                }
-                                            }
-                                        }
-                                        _ => not_supported!("slice unsizing from non pointers"),
-                                    },
-                                    TyKind::Dyn(_) => not_supported!("dyn pointer unsize cast"),
-                                    _ => not_supported!("unknown unsized cast"),
-                                }
-                            }
-                            _ => not_supported!("unsized cast on unknown pointer type"),
-                        }
+                        let value = self.eval_operand(operand, locals)?.get(&self)?;
+                        Owned(self.coerce_unsized(value, &current_ty, target_ty, locals)?)
+                    }
+                    // Both keep the address of a thin pointer as is.
+                    PointerCast::ArrayToPointer | PointerCast::MutToConstPointer => {
+                        Borrowed(self.eval_operand(operand, locals)?)
                     }
                     x => not_supported!("pointer cast {x:?}"),
                 },
@@ -846,12 +823,96 @@ fn eval_rvalue<'a>(
                 CastKind::FloatToInt => not_supported!("float to int cast"),
                 CastKind::FloatToFloat => not_supported!("float to float cast"),
                 CastKind::IntToFloat => not_supported!("float to int cast"),
-                CastKind::PtrToPtr => not_supported!("ptr to ptr cast"),
+                CastKind::PtrToPtr => {
+                    // Casting a fat pointer to a thin one drops the metadata.
+                    let current = self.eval_operand(operand, locals)?.get(&self)?;
+                    let dest_size =
+                        self.size_of_sized(target_ty, locals, "destination of ptr to ptr cast")?;
+                    Owned(current[0..dest_size].to_vec())
+                }
                 CastKind::FnPtrToPtr => not_supported!("fn ptr to ptr cast"),
             },
         })
     }
 
+    /// Unsizes `value` from `current_ty` to `target_ty`, turning the thin pointer in it into a fat
+    /// one. Structs are coerced field by field, where only the field named by their `CoerceUnsized`
+    /// impl changes.
+    fn coerce_unsized(
+        &self,
+        value: &[u8],
+        current_ty: &Ty,
+        target_ty: &Ty,
+        locals: &Locals<'_>,
+    ) -> Result<Vec<u8>> {
+        match (current_ty.kind(Interner), target_ty.kind(Interner)) {
+            (
+                TyKind::Raw(_, current) | TyKind::Ref(_, _, current),
+                TyKind::Raw(_, target) | TyKind::Ref(_, _, target),
+            ) => match (current.kind(Interner), target.kind(Interner)) {
+                (TyKind::Array(_, size), TyKind::Slice(_)) => {
+                    let len = const_as_usize(size);
+                    let mut r = Vec::with_capacity(16);
+                    r.extend(value.iter().copied());
+                    r.extend(len.to_le_bytes().into_iter());
+                    Ok(r)
+                }
+                (_, TyKind::Slice(_)) => not_supported!("slice unsizing from non arrays"),
+                (_, TyKind::Dyn(_)) => not_supported!("dyn pointer unsize cast"),
+                _ => not_supported!("unknown unsized cast"),
+            },
+            (
+                TyKind::Adt(chalk_ir::AdtId(adt @ AdtId::StructId(s)), current_subst),
+                TyKind::Adt(_, target_subst),
+            ) => {
+                let coerced_field = self.coerce_unsized_field(*adt)?;
+                let current_layout = self.layout_adt(*adt, current_subst.clone())?;
+                let target_layout = self.layout_adt(*adt, target_subst.clone())?;
+                let mut result = vec![0; target_layout.size.bytes_usize()];
+                for (id, ty) in self.db.field_types((*s).into()).iter() {
+                    let idx = u32::from(id.into_raw()) as usize;
+                    let current_offset = current_layout.fields.offset(idx).bytes_usize();
+                    let target_offset = target_layout.fields.offset(idx).bytes_usize();
+                    let current_ty = ty.clone().substitute(Interner, current_subst);
+                    let size = self.size_of_sized(&current_ty, locals, "coerced struct field")?;
+                    let field = &value[current_offset..current_offset + size];
+                    let field = if id == coerced_field.local_id {
+                        let target_ty = ty.clone().substitute(Interner, target_subst);
+                        self.coerce_unsized(field, &current_ty, &target_ty, locals)?
+                    } else {
+                        field.to_vec()
+                    };
+                    result[target_offset..target_offset + field.len()].copy_from_slice(&field);
+                }
+                Ok(result)
+            }
+            _ => not_supported!("unsized cast on unknown pointer type"),
+        }
+    }
+
+    /// Finds the field that the `CoerceUnsized` impl of `adt` coerces.
+    fn coerce_unsized_field(&self, adt: AdtId) -> Result<FieldId> {
+        let coerce_unsized = match self
+            .db
+            .lang_item(self.crate_id, LangItem::CoerceUnsized)
+            .and_then(|it| it.as_trait())
+        {
+            Some(it) => it,
+            None => not_supported!("unsizing without the coerce_unsized lang item"),
+        };
+        let impls = self.db.trait_impls_in_deps(self.crate_id);
+        let field = impls.for_trait_and_self_ty(coerce_unsized, TyFingerprint::Adt(adt)).find_map(
+            |impl_id| match self.db.coerce_unsized_info(impl_id).custom_kind {
+                Some(CustomCoerceUnsized::Struct(field)) => Some(field),
+                None => None,
+            },
+        );
+        match field {
+            Some(field) => Ok(field),
+            None => not_supported!("unsizing a struct without a CoerceUnsized impl"),
+        }
+    }
+
     fn layout_of_variant(
         &mut self,
         x: VariantId,
@@ -1085,9 +1146,21 @@ fn try_fold_ty(
                                 let filler = &mut Filler { db: self.db, subst, skip_params: 0 };
                                 filler.try_fold_ty(infer.type_of_rpit[idx].clone(), outer_binder)
                             }
+                            crate::ImplTraitId::TypeAliasImplTrait(alias, idx) => {
+                                let db = self.db;
+                                match crate::infer::type_alias_impl_trait_hidden_ty(db, alias, idx)
+                                {
+                                    Some(hidden_ty) => self.try_fold_ty(hidden_ty, outer_binder),
+                                    None => not_supported!("generic type alias impl trait"),
+                                }
+                            }
                             crate::ImplTraitId::AsyncBlockTypeImplTrait(_, _) => {
                                 not_supported!("async block impl trait");
                             }
+                            crate::ImplTraitId::PatternType(_) => {
+                                let base = subst.at(Interner, 0).assert_ty_ref(Interner);
+                                self.try_fold_ty(base.clone(), outer_binder)
+                            }
                         }
                     }
                     _ => ty.try_super_fold_with(self.as_dyn(), outer_binder),
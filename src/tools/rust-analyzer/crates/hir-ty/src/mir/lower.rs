@@ -13,6 +13,7 @@
     layout::LayoutError,
     path::Path,
     resolver::{resolver_for_expr, ResolveValueResult, ValueNs},
+    type_ref::Rawness,
     DefWithBodyId, EnumVariantId, HasModule,
 };
 use hir_expand::name::Name;
@@ -20,8 +21,9 @@
 
 use crate::{
     consteval::ConstEvalError, db::HirDatabase, display::HirDisplay, infer::TypeMismatch,
-    inhabitedness::is_ty_uninhabited_from, layout::layout_of_ty, mapping::ToChalk, static_lifetime,
-    utils::generics, Adjust, Adjustment, AutoBorrow, CallableDefId, TyBuilder, TyExt,
+    inhabitedness::is_ty_uninhabited_from, layout::layout_of_ty, lower::lower_to_chalk_mutability,
+    mapping::ToChalk, static_lifetime, utils::generics, Adjust, Adjustment, AutoBorrow,
+    CallableDefId, OverloadedDeref, TyBuilder, TyExt,
 };
 
 use super::*;
@@ -152,26 +154,29 @@ fn lower_expr_to_place_with_adjust(
                     self.push_assignment(current, place, Operand::Copy(p).into(), expr_id.into());
                     Ok(Some(current))
                 }
-                Adjust::Borrow(AutoBorrow::Ref(m) | AutoBorrow::RawPtr(m)) => {
+                Adjust::Borrow(borrow) => {
                     let Some((p, current)) = self.lower_expr_as_place_with_adjust(current, expr_id, true, rest)? else {
                             return Ok(None);
                         };
-                    let bk = BorrowKind::from_chalk(*m);
-                    self.push_assignment(current, place, Rvalue::Ref(bk, p), expr_id.into());
+                    let rvalue = match *borrow {
+                        AutoBorrow::Ref(m) => Rvalue::Ref(BorrowKind::from_auto_borrow(m), p),
+                        AutoBorrow::RawPtr(m) => Rvalue::AddressOf(m, p),
+                    };
+                    self.push_assignment(current, place, rvalue, expr_id.into());
                     Ok(Some(current))
                 }
-                Adjust::Pointer(cast) => {
+                Adjust::Pointer(_) | Adjust::DynStar => {
                     let Some((p, current)) = self.lower_expr_as_place_with_adjust(current, expr_id, true, rest)? else {
                             return Ok(None);
                         };
+                    let cast_kind = match last.kind {
+                        Adjust::Pointer(cast) => CastKind::Pointer(cast),
+                        _ => CastKind::DynStar,
+                    };
                     self.push_assignment(
                         current,
                         place,
-                        Rvalue::Cast(
-                            CastKind::Pointer(cast.clone()),
-                            Operand::Copy(p).into(),
-                            last.target.clone(),
-                        ),
+                        Rvalue::Cast(cast_kind, Operand::Copy(p).into(), last.target.clone()),
                         expr_id.into(),
                     );
                     Ok(Some(current))
@@ -627,12 +632,15 @@ fn lower_expr_to_place_without_adjust(
                 );
                 Ok(Some(current))
             }
-            Expr::Ref { expr, rawness: _, mutability } => {
+            Expr::Ref { expr, rawness, mutability } => {
                 let Some((p, current)) = self.lower_expr_as_place(current, *expr, true)? else {
                     return Ok(None);
                 };
-                let bk = BorrowKind::from_hir(*mutability);
-                self.push_assignment(current, place, Rvalue::Ref(bk, p), expr_id.into());
+                let rvalue = match rawness {
+                    Rawness::Ref => Rvalue::Ref(BorrowKind::from_hir(*mutability), p),
+                    Rawness::RawPtr => Rvalue::AddressOf(lower_to_chalk_mutability(*mutability), p),
+                };
+                self.push_assignment(current, place, rvalue, expr_id.into());
                 Ok(Some(current))
             }
             Expr::Box { .. } => not_supported!("box expression"),
@@ -1070,12 +1078,23 @@ fn pattern_match(
                 match &self.body.exprs[*l] {
                     Expr::Literal(l) => match l {
                         hir_def::expr::Literal::Int(x, _) => {
+                            // The discriminant is read zero-extended, so negative values have to
+                            // be truncated to the size of the type.
+                            let size = layout_of_ty(
+                                self.db,
+                                &cond_ty,
+                                self.owner.module(self.db.upcast()).krate(),
+                            )?
+                            .size
+                            .bytes_usize();
+                            let value =
+                                u128::from_le_bytes(pad16(&x.to_le_bytes()[0..size], false));
                             self.set_terminator(
                                 current,
                                 Terminator::SwitchInt {
                                     discr: Operand::Copy(cond_place),
                                     targets: SwitchTargets::static_if(
-                                        *x as u128,
+                                        value,
                                         then_target,
                                         else_target,
                                     ),
@@ -1283,7 +1302,7 @@ fn lower_loop(
     }
 
     fn has_adjustments(&self, expr_id: ExprId) -> bool {
-        !self.infer.expr_adjustments.get(&expr_id).map(|x| x.is_empty()).unwrap_or(true)
+        !self.infer.expr_adjustments(expr_id).is_empty()
     }
 
     fn merge_blocks(
@@ -1456,6 +1475,17 @@ fn cast_kind(source_ty: &Ty, target_ty: &Ty) -> Result<CastKind> {
         (TyKind::Scalar(_), TyKind::Adt(..)) | (TyKind::Adt(..), TyKind::Scalar(_)) => {
             CastKind::IntToInt
         }
+        (TyKind::Raw(_, a) | TyKind::Ref(_, _, a), TyKind::Raw(m, b)) => match a.kind(Interner) {
+            TyKind::Array(elem, _) if elem == b => CastKind::Pointer(PointerCast::ArrayToPointer),
+            _ if a == b
+                && *m == Mutability::Not
+                && matches!(source_ty.kind(Interner), TyKind::Raw(Mutability::Mut, _)) =>
+            {
+                CastKind::Pointer(PointerCast::MutToConstPointer)
+            }
+            _ => CastKind::PtrToPtr,
+        },
+        (TyKind::Function(_), TyKind::Raw(..)) => CastKind::FnPtrToPtr,
         (a, b) => not_supported!("Unknown cast between {a:?} and {b:?}"),
     })
 }
@@ -1,7 +1,6 @@
 //! MIR lowering for places
 
 use super::*;
-use hir_expand::name;
 
 macro_rules! not_supported {
     ($x: expr) => {
@@ -91,7 +90,9 @@ pub(super) fn lower_expr_as_place_with_adjust(
                         },
                     )
                 }
-                Adjust::NeverToAny | Adjust::Borrow(_) | Adjust::Pointer(_) => try_rvalue(self),
+                Adjust::NeverToAny | Adjust::Borrow(_) | Adjust::Pointer(_) | Adjust::DynStar => {
+                    try_rvalue(self)
+                }
             }
         } else {
             self.lower_expr_as_place_without_adjust(current, expr_id, upgrade_rvalue)
@@ -197,33 +198,23 @@ fn lower_overloaded_deref(
         span: MirSpan,
         mutability: bool,
     ) -> Result<Option<(Place, BasicBlockId)>> {
-        let (chalk_mut, trait_lang_item, trait_method_name, borrow_kind) = if !mutability {
-            (Mutability::Not, LangItem::Deref, name![deref], BorrowKind::Shared)
+        let (chalk_mut, trait_lang_item, borrow_kind) = if !mutability {
+            (Mutability::Not, LangItem::Deref, BorrowKind::Shared)
         } else {
-            (
-                Mutability::Mut,
-                LangItem::DerefMut,
-                name![deref_mut],
-                BorrowKind::Mut { allow_two_phase_borrow: false },
-            )
+            (Mutability::Mut, LangItem::DerefMut, BorrowKind::Mut { allow_two_phase_borrow: false })
         };
         let ty_ref = TyKind::Ref(chalk_mut, static_lifetime(), source_ty.clone()).intern(Interner);
         let target_ty_ref = TyKind::Ref(chalk_mut, static_lifetime(), target_ty).intern(Interner);
         let ref_place: Place = self.temp(ty_ref)?.into();
         self.push_assignment(current, ref_place.clone(), Rvalue::Ref(borrow_kind, place), span);
-        let deref_trait = self
-            .resolve_lang_item(trait_lang_item)?
-            .as_trait()
-            .ok_or(MirLowerError::LangItemNotFound(trait_lang_item))?;
-        let deref_fn = self
-            .db
-            .trait_data(deref_trait)
-            .method_by_name(&trait_method_name)
+        let krate = self.owner.module(self.db.upcast()).krate();
+        let (deref_fn, subst) = OverloadedDeref(Some(chalk_mut))
+            .method_call(self.db, krate, source_ty)
             .ok_or(MirLowerError::LangItemNotFound(trait_lang_item))?;
         let deref_fn_op = Operand::const_zst(
             TyKind::FnDef(
                 self.db.intern_callable_def(CallableDefId::FunctionId(deref_fn)).into(),
-                Substitution::from1(Interner, source_ty),
+                subst,
             )
             .intern(Interner),
         );
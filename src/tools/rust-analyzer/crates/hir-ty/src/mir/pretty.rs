@@ -2,6 +2,7 @@
 
 use std::fmt::{Display, Write};
 
+use chalk_ir::Mutability;
 use hir_def::{body::Body, expr::BindingId};
 use hir_expand::name::Name;
 use la_arena::ArenaMap;
@@ -274,6 +275,13 @@ fn rvalue(&mut self, r: &Rvalue) {
                 }
                 self.place(p);
             }
+            Rvalue::AddressOf(m, p) => {
+                match m {
+                    Mutability::Not => w!(self, "&raw const "),
+                    Mutability::Mut => w!(self, "&raw mut "),
+                }
+                self.place(p);
+            }
             Rvalue::Aggregate(AggregateKind::Tuple(_), x) => {
                 w!(self, "(");
                 self.operand_list(x);
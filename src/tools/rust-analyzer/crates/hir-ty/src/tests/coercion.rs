@@ -1,3 +1,8 @@
+use base_db::fixture::WithFixture;
+use hir_def::{db::DefDatabase, ModuleDefId};
+
+use crate::{db::HirDatabase, display::HirDisplay, test_db::TestDB, CustomCoerceUnsized};
+
 use super::{check, check_no_mismatches, check_types};
 
 #[test]
@@ -157,6 +162,25 @@ fn test(i: i32) {
     );
 }
 
+#[test]
+fn coerce_never_to_any() {
+    check(
+        r#"
+fn diverge() -> ! { loop {} }
+fn test() {
+    let _: u32 = diverge();
+               //^^^^^^^^^ adjustments: NeverToAny
+    loop {
+        let _: u32 = break;
+                   //^^^^^ adjustments: NeverToAny
+    }
+    let _: u32 = return;
+               //^^^^^^ adjustments: NeverToAny
+}
+"#,
+    );
+}
+
 #[test]
 fn match_second_coerce() {
     check_no_mismatches(
@@ -175,6 +199,34 @@ fn test(i: i32) {
     );
 }
 
+#[test]
+fn coerce_reborrow_mut() {
+    check(
+        r"
+fn f(_: &mut i32) {}
+fn test(t: &mut i32) {
+    f(t);
+    //^ adjustments: Deref(None), Borrow(Ref(Mut { allow_two_phase_borrow: No }))
+}
+        ",
+    );
+}
+
+#[test]
+fn coerce_reborrow_mut_to_shared() {
+    check(
+        r"
+fn f(_: &i32) {}
+fn test(t: &mut i32) {
+    f(t);
+    //^ adjustments: Deref(None), Borrow(Ref(Not))
+    let _: &mut i32 = t;
+                    //^ adjustments: Deref(None), Borrow(Ref(Mut { allow_two_phase_borrow: No }))
+}
+        ",
+    );
+}
+
 #[test]
 fn coerce_merge_one_by_one1() {
     cov_mark::check!(coerce_merge_fail_fallback);
@@ -185,10 +237,11 @@ fn test() {
     let t = &mut 1;
     let x = match 1 {
         1 => t as *mut i32,
+           //^^^^^^^^^^^^^ adjustments: Pointer(MutToConstPointer)
         2 => t as &i32,
            //^^^^^^^^^ expected *mut i32, got &i32
         _ => t as *const i32,
-          // ^^^^^^^^^^^^^^^ adjustments: Pointer(MutToConstPointer)
+           //^^^^^^^^^^^^^^^ adjustments:
 
     };
     x;
@@ -199,6 +252,34 @@ fn test() {
     );
 }
 
+#[test]
+fn coerce_lub_branches() {
+    check(
+        r"
+fn foo1(x: u32) -> isize { 1 }
+fn foo2(x: u32) -> isize { 2 }
+fn test(t: &mut i32, c: bool) {
+    let x = if c { t } else { &1 };
+               //^^^^^ adjustments: Deref(None), Borrow(Ref(Not))
+    x;
+  //^ type: &i32
+    let y = match c {
+        true => foo1,
+              //^^^^ adjustments: Pointer(ReifyFnPointer)
+        false => foo2,
+               //^^^^ adjustments: Pointer(ReifyFnPointer)
+    };
+    y;
+  //^ type: fn(u32) -> isize
+    let z = [&mut 2, &3];
+           //^^^^^^ adjustments: Deref(None), Borrow(Ref(Not))
+    z;
+  //^ type: [&i32; 2]
+}
+        ",
+    );
+}
+
 #[test]
 fn match_adjust_for_branches_discard_type_var() {
     check_no_mismatches(
@@ -396,6 +477,29 @@ fn test() {
     );
 }
 
+#[test]
+fn coerce_higher_ranked_fn_ptr() {
+    check_no_mismatches(
+        r"
+struct S { f: fn(&'static u8) }
+struct Wrap<T>(T);
+fn take(_: fn(&'static u8)) {}
+fn foo(_: &u8) {}
+fn test<'b>(f: for<'a> fn(&'a u8), g: fn(&'b u8)) {
+    let h: fn(&'static u8) = f;
+                           //^ adjustments:
+    take(f);
+       //^ adjustments:
+    take(g);
+       //^ adjustments:
+    S { f };
+    let w: Wrap<fn(&'static u8)> = Wrap(f);
+    let r: for<'a> fn(&'a u8) = foo;
+                              //^^^ adjustments: Pointer(ReifyFnPointer)
+}",
+    );
+}
+
 #[test]
 fn coerce_fn_items_in_match_arms() {
     cov_mark::check!(coerce_fn_reification);
@@ -430,6 +534,19 @@ fn test() {
     );
 }
 
+#[test]
+fn coerce_capturing_closure_to_fn_ptr() {
+    check(
+        r"
+fn test(y: u32) {
+    let f: fn(u32) -> u32 = |x| y;
+                         // ^^^^^ expected fn(u32) -> u32, got |u32| -> u32
+    let f: fn(u32) -> u32 = |y| y;
+                         // ^^^^^ adjustments: Pointer(ClosureFnPointer(Safe))
+}",
+    );
+}
+
 #[test]
 fn coerce_placeholder_ref() {
     // placeholders should unify, even behind references
@@ -456,6 +573,79 @@ fn test() {
     );
 }
 
+#[test]
+fn coerce_unsize_array_pointers() {
+    check(
+        r#"
+//- minicore: coerce_unsized
+#[lang = "owned_box"]
+struct Box<T: ?Sized>(*mut T);
+impl<T: ?Sized + core::marker::Unsize<U>, U: ?Sized> core::ops::CoerceUnsized<Box<U>> for Box<T> {}
+
+fn test(a: &mut [i32; 2], p: *mut [i32; 2], b: Box<[i32; 2]>) {
+    let _: &mut [i32] = a;
+                      //^ adjustments: Deref(None), Borrow(Ref(Mut { allow_two_phase_borrow: No })), Pointer(Unsize)
+    let _: *const [i32] = p;
+                        //^ adjustments: Pointer(Unsize)
+    let _: Box<[i32]> = b;
+                      //^ adjustments: Pointer(Unsize)
+}
+"#,
+    );
+}
+
+#[test]
+fn coerce_unsize_box_to_trait_object() {
+    check(
+        r#"
+//- minicore: coerce_unsized
+#[lang = "owned_box"]
+struct Box<T: ?Sized>(*mut T);
+impl<T: ?Sized + core::marker::Unsize<U>, U: ?Sized> core::ops::CoerceUnsized<Box<U>> for Box<T> {}
+
+trait Trait {}
+struct S;
+impl Trait for S {}
+struct NotTrait;
+impl Trait for [S] {}
+
+fn test(a: Box<S>, b: Box<NotTrait>, c: Box<[S]>) {
+    let _: Box<dyn Trait> = a;
+                          //^ adjustments: Pointer(Unsize)
+    let _: Box<dyn Trait> = b;
+                          //^ expected Box<dyn Trait>, got Box<NotTrait>
+    let _: Box<dyn Trait> = c;
+                          //^ expected Box<dyn Trait>, got Box<[S]>
+}
+"#,
+    );
+}
+
+#[test]
+fn coerce_unsize_user_smart_pointer() {
+    check(
+        r#"
+//- minicore: coerce_unsized
+use core::{marker::Unsize, ops::CoerceUnsized};
+
+struct ArcInner<T: ?Sized> { count: usize, data: T }
+struct Arc<T: ?Sized> { ptr: *const ArcInner<T> }
+impl<T: ?Sized + Unsize<U>, U: ?Sized> CoerceUnsized<Arc<U>> for Arc<T> {}
+
+trait Trait {}
+struct S;
+impl Trait for S {}
+
+fn test(a: Arc<S>, b: Arc<[S; 2]>) {
+    let _: Arc<dyn Trait> = a;
+                          //^ adjustments: Pointer(Unsize)
+    let _: Arc<[S]> = b;
+                    //^ adjustments: Pointer(Unsize)
+}
+"#,
+    );
+}
+
 #[test]
 fn coerce_unsize_trait_object_simple() {
     check_types(
@@ -505,20 +695,48 @@ fn test() {
     );
 }
 
+#[test]
+fn coerce_trait_upcasting() {
+    check(
+        r#"
+//- minicore: coerce_unsized, send
+trait Super<T> {}
+trait Mid: Super<u32> {}
+trait Sub: Mid {}
+trait Other {}
+
+#[lang = "owned_box"]
+struct Box<T: ?Sized>(*mut T);
+
+fn test(sub: &dyn Sub, sub_mut: &mut dyn Sub, ptr: *const (dyn Sub + Send), boxed: Box<dyn Sub>) {
+    let _: &dyn Mid = sub;
+    let _: &dyn Super<u32> = sub_mut;
+    let _: *const (dyn Super<u32> + Send) = ptr;
+    let inferred: Box<dyn Super<_>> = boxed;
+    inferred;
+  //^^^^^^^^ type: Box<dyn Super<u32>>
+    let _: &dyn Super<i32> = sub;
+                           //^^^ expected &dyn Super<i32>, got &dyn Sub
+    let _: &dyn Other = sub;
+                      //^^^ expected &dyn Other, got &dyn Sub
+}
+"#,
+    );
+}
+
 #[test]
 fn coerce_unsize_generic() {
-    // FIXME: fix the type mismatches here
     check(
         r#"
 //- minicore: coerce_unsized
-struct Foo<T> { t: T };
-struct Bar<T>(Foo<T>);
+struct Foo<T: ?Sized> { t: T };
+struct Bar<T: ?Sized>(Foo<T>);
 
 fn test() {
     let _: &Foo<[usize]> = &Foo { t: [1, 2, 3] };
-                                   //^^^^^^^^^ expected [usize], got [usize; 3]
+                         //^^^^^^^^^^^^^^^^^^^^^ adjustments: Deref(None), Borrow(Ref(Not)), Pointer(Unsize)
     let _: &Bar<[usize]> = &Bar(Foo { t: [1, 2, 3] });
-                                       //^^^^^^^^^ expected [usize], got [usize; 3]
+                         //^^^^^^^^^^^^^^^^^^^^^^^^^^ adjustments: Deref(None), Borrow(Ref(Not)), Pointer(Unsize)
 }
 "#,
     );
@@ -837,8 +1055,127 @@ fn add_assign(&mut self, other: Self) {}
 }
 fn test() {
     Struct += Struct;
- // ^^^^^^ adjustments: Borrow(Ref(Mut))
+ // ^^^^^^ adjustments: Borrow(Ref(Mut { allow_two_phase_borrow: Yes }))
            // ^^^^^^ adjustments:
 }",
     );
 }
+
+#[test]
+fn coerce_pointee_derive() {
+    check_no_mismatches(
+        r#"
+//- minicore: coerce_pointee
+use core::marker::CoercePointee;
+
+trait Trait {}
+struct S;
+impl Trait for S {}
+
+#[derive(CoercePointee)]
+struct MyPtr<T: ?Sized>(*const T);
+
+fn test(p: MyPtr<S>) {
+    let _: MyPtr<dyn Trait> = p;
+}
+"#,
+    );
+}
+
+#[test]
+fn coerce_unsized_info() {
+    let (db, file_id) = TestDB::with_single_file(
+        r#"
+//- minicore: coerce_unsized
+use core::{marker::Unsize, ops::CoerceUnsized};
+
+#[lang = "phantom_data"]
+struct PhantomData<T: ?Sized>;
+
+struct Ptr<T: ?Sized> { marker: PhantomData<T>, len: usize, ptr: *const T }
+impl<T: ?Sized + Unsize<U>, U: ?Sized> CoerceUnsized<Ptr<U>> for Ptr<T> {}
+
+struct Two<T: ?Sized> { a: *const T, b: *const T }
+impl<T: ?Sized + Unsize<U>, U: ?Sized> CoerceUnsized<Two<U>> for Two<T> {}
+"#,
+    );
+    let module = db.module_for_file(file_id);
+    let def_map = module.def_map(&db);
+    let coerced_fields: Vec<_> = def_map[module.local_id]
+        .scope
+        .impls()
+        .map(|impl_id| {
+            db.coerce_unsized_info(impl_id).custom_kind.map(|CustomCoerceUnsized::Struct(field)| {
+                field.parent.variant_data(&db).fields()[field.local_id].name.to_string()
+            })
+        })
+        .collect();
+    assert_eq!(coerced_fields, [Some("ptr".to_owned()), None]);
+}
+
+#[test]
+fn unsize_coercions() {
+    let (db, file_id) = TestDB::with_single_file(
+        r#"
+//- minicore: coerce_unsized
+trait Trait {}
+struct S;
+impl Trait for S {}
+
+fn test() {
+    let _: &[i32] = &[1, 2, 3];
+    let _: &dyn Trait = &S;
+}
+"#,
+    );
+    let module = db.module_for_file(file_id);
+    let def_map = module.def_map(&db);
+    let func = def_map[module.local_id]
+        .scope
+        .declarations()
+        .find_map(|decl| match decl {
+            ModuleDefId::FunctionId(it) => Some(it),
+            _ => None,
+        })
+        .unwrap();
+    let infer = db.infer(func.into());
+    let mut coercions: Vec<_> = infer
+        .unsize_coercions
+        .values()
+        .map(|it| format!("{} -> {}", it.source.display(&db), it.target.display(&db)))
+        .collect();
+    coercions.sort();
+    assert_eq!(coercions, ["S -> dyn Trait", "[i32; 3] -> [i32]"]);
+}
+
+#[test]
+fn unsafe_fn_pointer_coercions() {
+    let (db, file_id) = TestDB::with_single_file(
+        r#"
+fn foo() {}
+unsafe fn bar() {}
+
+fn test(f: fn()) {
+    let _: unsafe fn() = foo;
+    let _: unsafe fn() = f;
+    let _: unsafe fn() = || {};
+    let _: unsafe fn() = bar;
+    let _: fn() = foo;
+}
+"#,
+    );
+    let module = db.module_for_file(file_id);
+    let def_map = module.def_map(&db);
+    let func = def_map[module.local_id]
+        .scope
+        .declarations()
+        .find_map(|decl| match decl {
+            ModuleDefId::FunctionId(it) if db.function_data(it).name.to_smol_str() == "test" => {
+                Some(it)
+            }
+            _ => None,
+        })
+        .unwrap();
+    let infer = db.infer(func.into());
+    assert_eq!(infer.unsafe_fn_pointer_coercions().count(), 3);
+}
@@ -963,6 +963,49 @@ fn main() {
     );
 }
 
+#[test]
+fn infer_builtin_macros_pattern_type() {
+    check_types(
+        r#"
+//- minicore: pattern_types
+use core::pat::pattern_type;
+
+fn test(a: pattern_type!(u32 is 1..), b: pattern_type!(i8 is -5..5), c: pattern_type!(u8 is ..=9)) {
+    a;
+  //^ pattern_type!(u32 is 1..)
+    b;
+  //^ pattern_type!(i8 is -5..=4)
+    c;
+  //^ pattern_type!(u8 is ..=9)
+    match a {
+        n @ 1..=9 => { n; }
+                     //^ pattern_type!(u32 is 1..)
+        _ => {}
+    }
+}
+"#,
+    );
+}
+
+#[test]
+fn infer_builtin_macros_pattern_type_unsupported() {
+    check_types(
+        r#"
+//- minicore: pattern_types
+use core::pat::pattern_type;
+
+fn test(a: pattern_type!(u32 is 5..5), b: pattern_type!(u8 is 300..), c: pattern_type!(bool is true)) {
+    a;
+  //^ {unknown}
+    b;
+  //^ {unknown}
+    c;
+  //^ {unknown}
+}
+"#,
+    );
+}
+
 #[test]
 fn infer_derive_clone_simple() {
     check_types(
@@ -1729,6 +1729,84 @@ fn test() {
     );
 }
 
+#[test]
+fn receiver_adjustment_autoref_mut() {
+    check(
+        r#"
+struct Foo;
+impl Foo {
+    fn foo(&mut self) {}
+}
+fn test() {
+    let mut foo = Foo;
+    foo.foo();
+  //^^^ adjustments: Borrow(Ref(Mut { allow_two_phase_borrow: Yes }))
+}
+"#,
+    );
+}
+
+#[test]
+fn receiver_adjustment_overloaded_deref() {
+    check(
+        r#"
+//- minicore: deref_mut
+struct Foo;
+impl Foo {
+    fn foo(&self) {}
+    fn foo_mut(&mut self) {}
+}
+struct Wrapper(Foo);
+impl core::ops::Deref for Wrapper {
+    type Target = Foo;
+    fn deref(&self) -> &Foo { &self.0 }
+}
+impl core::ops::DerefMut for Wrapper {
+    fn deref_mut(&mut self) -> &mut Foo { &mut self.0 }
+}
+fn test(w: &mut Wrapper) {
+    w.foo();
+  //^ adjustments: Deref(None), Deref(Some(OverloadedDeref(Some(Not)))), Borrow(Ref(Not))
+    w.foo_mut();
+  //^ adjustments: Deref(None), Deref(Some(OverloadedDeref(Some(Mut)))), Borrow(Ref(Mut { allow_two_phase_borrow: Yes }))
+}
+"#,
+    );
+}
+
+#[test]
+fn receiver_adjustment_arbitrary_self_types() {
+    check(
+        r#"
+//- minicore: pin, deref
+use core::pin::Pin;
+struct Foo;
+impl Foo {
+    fn by_ref(&self) {}
+    fn pinned(self: Pin<&mut Self>) {}
+    fn rc(self: Rc<Self>) {}
+}
+struct Rc<T>(T);
+impl<T> core::ops::Deref for Rc<T> {
+    type Target = T;
+    fn deref(&self) -> &T { &self.0 }
+}
+fn test(p: Pin<&mut Foo>, r: &mut Pin<&mut Foo>, rc: Rc<Foo>) {
+    p.by_ref();
+  //^ adjustments: Deref(Some(OverloadedDeref(Some(Not)))), Borrow(Ref(Not))
+    p.pinned();
+  //^ adjustments:
+    r.pinned();
+  //^ adjustments: Deref(None)
+    rc.by_ref();
+  //^^ adjustments: Deref(Some(OverloadedDeref(Some(Not)))), Borrow(Ref(Not))
+    rc.rc();
+  //^^ adjustments:
+}
+"#,
+    );
+}
+
 #[test]
 fn receiver_adjustment_unsize_array() {
     check(
@@ -1338,6 +1338,46 @@ fn foo<const C: u8, T>() -> (impl FnOnce(&str, T), impl Trait<u8>) {
     );
 }
 
+#[test]
+fn type_alias_impl_trait() {
+    check_types(
+        r#"
+//- minicore: sized
+trait Trait { fn method(&self) -> u32; }
+struct S;
+impl Trait for S { fn method(&self) -> u32 { 0 } }
+mod m {
+    pub type Alias = impl super::Trait;
+    pub fn f() -> Alias { super::S }
+}
+fn g() {
+    let x = m::f();
+     // ^ impl Trait
+    let y = x.method();
+     // ^ u32
+}
+"#,
+    );
+}
+
+#[test]
+fn type_alias_impl_trait_defining_scope() {
+    check_no_mismatches(
+        r#"
+//- minicore: sized
+enum E { A, B }
+type Alias = impl Sized;
+fn f() -> Alias { E::A }
+fn g(x: Alias) -> bool {
+    match x {
+        E::A => true,
+        E::B => false,
+    }
+}
+"#,
+    );
+}
+
 #[test]
 fn return_pos_impl_trait_in_projection() {
     // Note that the unused type param `X` is significant; see #13307.
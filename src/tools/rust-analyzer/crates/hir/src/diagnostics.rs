@@ -34,18 +34,24 @@ fn from(d: $diag) -> AnyDiagnostic {
 diagnostics![
     BreakOutsideOfLoop,
     ExpectedFunction,
+    FloatLiteralPattern,
     InactiveCode,
     IncorrectCase,
     InvalidDeriveTarget,
     IncoherentImpl,
     MacroError,
     MalformedDerive,
+    MatchCheckApproximated,
+    MatchCheckTooComplex,
     MismatchedArgCount,
+    MismatchedTupleStructPatArgCount,
     MissingFields,
     MissingMatchArms,
     MissingUnsafe,
     NeedMut,
     NoSuchField,
+    NoSuchRecordPatFields,
+    OverlappingRangeEndpoints,
     PrivateAssocItem,
     PrivateField,
     ReplaceFilterMapNextWithFindMap,
@@ -126,11 +132,28 @@ pub struct MalformedDerive {
     pub node: InFile<SyntaxNodePtr>,
 }
 
+#[derive(Debug)]
+pub struct FloatLiteralPattern {
+    pub literal: InFile<AstPtr<ast::Expr>>,
+}
+
 #[derive(Debug)]
 pub struct NoSuchField {
     pub field: InFile<AstPtr<ast::RecordExprField>>,
 }
 
+#[derive(Debug)]
+pub struct NoSuchRecordPatFields {
+    pub pat: InFile<Either<AstPtr<ast::Pat>, AstPtr<ast::SelfParam>>>,
+    pub fields: Vec<Name>,
+}
+
+#[derive(Debug)]
+pub struct OverlappingRangeEndpoints {
+    pub pat: InFile<Either<AstPtr<ast::Pat>, AstPtr<ast::SelfParam>>>,
+    pub overlaps: Vec<String>,
+}
+
 #[derive(Debug)]
 pub struct PrivateAssocItem {
     pub expr_or_pat:
@@ -200,12 +223,29 @@ pub struct MismatchedArgCount {
     pub found: usize,
 }
 
+#[derive(Debug)]
+pub struct MismatchedTupleStructPatArgCount {
+    pub expr_or_pat: InFile<Either<AstPtr<ast::Pat>, AstPtr<ast::SelfParam>>>,
+    pub expected: usize,
+    pub found: usize,
+}
+
 #[derive(Debug)]
 pub struct MissingMatchArms {
     pub scrutinee_expr: InFile<AstPtr<ast::Expr>>,
     pub uncovered_patterns: String,
 }
 
+#[derive(Debug)]
+pub struct MatchCheckTooComplex {
+    pub scrutinee_expr: InFile<AstPtr<ast::Expr>>,
+}
+
+#[derive(Debug)]
+pub struct MatchCheckApproximated {
+    pub scrutinee_expr: InFile<AstPtr<ast::Expr>>,
+}
+
 #[derive(Debug)]
 pub struct TypeMismatch {
     pub expr_or_pat: Either<InFile<AstPtr<ast::Expr>>, InFile<AstPtr<ast::Pat>>>,
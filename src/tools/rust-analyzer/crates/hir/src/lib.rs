@@ -41,7 +41,7 @@
 use hir_def::{
     adt::VariantData,
     body::{BodyDiagnostic, SyntheticSyntax},
-    expr::{BindingAnnotation, BindingId, ExprOrPatId, LabelId, Pat},
+    expr::{BindingAnnotation, BindingId, Expr, ExprOrPatId, LabelId, Pat},
     generics::{LifetimeParamData, TypeOrConstParamData, TypeParamProvenance},
     item_tree::ItemTreeNode,
     lang_item::{LangItem, LangItemTarget},
@@ -67,8 +67,9 @@
     primitive::UintTy,
     traits::FnTrait,
     AliasTy, CallableDefId, CallableSig, Canonical, CanonicalVarKinds, Cast, ClosureId,
-    GenericArgData, Interner, ParamKind, QuantifiedWhereClause, Scalar, Substitution,
-    TraitEnvironment, TraitRefExt, Ty, TyBuilder, TyDefId, TyExt, TyKind, WhereClause,
+    CustomCoerceUnsized, GenericArgData, Interner, ParamKind, QuantifiedWhereClause, Scalar,
+    Substitution, TraitEnvironment, TraitRefExt, Ty, TyBuilder, TyDefId, TyExt, TyKind,
+    WhereClause,
 };
 use itertools::Itertools;
 use nameres::diagnostics::DefDiagnosticKind;
@@ -85,9 +86,11 @@
 pub use crate::{
     attrs::{HasAttrs, Namespace},
     diagnostics::{
-        AnyDiagnostic, BreakOutsideOfLoop, ExpectedFunction, InactiveCode, IncoherentImpl,
-        IncorrectCase, InvalidDeriveTarget, MacroError, MalformedDerive, MismatchedArgCount,
-        MissingFields, MissingMatchArms, MissingUnsafe, NeedMut, NoSuchField, PrivateAssocItem,
+        AnyDiagnostic, BreakOutsideOfLoop, ExpectedFunction, FloatLiteralPattern, InactiveCode,
+        IncoherentImpl, IncorrectCase, InvalidDeriveTarget, MacroError, MalformedDerive,
+        MatchCheckApproximated, MatchCheckTooComplex, MismatchedArgCount,
+        MismatchedTupleStructPatArgCount, MissingFields, MissingMatchArms, MissingUnsafe, NeedMut,
+        NoSuchField, NoSuchRecordPatFields, OverlappingRangeEndpoints, PrivateAssocItem,
         PrivateField, ReplaceFilterMapNextWithFindMap, TypeMismatch, UnimplementedBuiltinMacro,
         UnresolvedExternCrate, UnresolvedField, UnresolvedImport, UnresolvedMacroCall,
         UnresolvedMethodCall, UnresolvedModule, UnresolvedProcMacro, UnusedMut,
@@ -129,6 +132,10 @@
         ExpandResult, HirFileId, InFile, MacroFile, Origin,
     },
     hir_ty::{
+        diagnostics::{
+            ArmReachability, DecisionTreeFormat, PatSourceOptions, UnreachablePatternKind,
+            WitnessPat,
+        },
         display::{HirDisplay, HirDisplayError, HirWrite},
         mir::MirEvalError,
         PointerCast, Safety,
@@ -1295,6 +1302,61 @@ pub enum DefWithBody {
 }
 impl_from!(Function, Const, Static, Variant for DefWithBody);
 
+/// An arm of a `match`, or an alternative of an or-pattern in one, that can never be reached.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnreachablePattern {
+    pub pat: InFile<AstPtr<ast::Pat>>,
+    pub kind: UnreachablePatternKind,
+}
+
+/// The arm a `match` takes because its scrutinee is known at compile time, and the other arms of
+/// the `match`, which are dead there. Arms are identified by their patterns.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TakenMatchArm {
+    pub taken: InFile<AstPtr<ast::Pat>>,
+    pub not_taken: Vec<InFile<AstPtr<ast::Pat>>>,
+}
+
+/// The exhaustiveness and reachability of the arms of a `match`, or of any list of patterns
+/// checked like one, see [`DefWithBody::check_match`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MatchCheck {
+    /// The patterns no arm matches, or nothing if the arms are exhaustive. They can be displayed
+    /// like in the diagnostics, or written as source code for new arms.
+    pub missing_patterns: Vec<WitnessPat>,
+    /// The reachability of each arm, in order.
+    pub arms: Vec<ArmReachability>,
+    /// Whether the arms were too large to be checked exactly: there may be more missing patterns,
+    /// and some unreachable arms may be reported as reachable.
+    pub is_approximate: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MatchCheckError {
+    /// The type of the scrutinee isn't known.
+    UnknownType,
+    /// The pattern of the arm with this index doesn't have the type of the scrutinee, or can't be
+    /// checked, e.g. because a path in it doesn't resolve.
+    InvalidArm(usize),
+    /// Checking the arms takes more work than the `pattern_complexity` limit allows.
+    TooComplex,
+}
+
+impl MatchCheckError {
+    fn from_hir_ty(
+        err: hir_ty::diagnostics::MatchCheckError,
+        arms: &[hir_ty::diagnostics::MatchCheckArm],
+    ) -> MatchCheckError {
+        match err {
+            hir_ty::diagnostics::MatchCheckError::UnknownType => MatchCheckError::UnknownType,
+            hir_ty::diagnostics::MatchCheckError::InvalidPattern(pat) => {
+                MatchCheckError::InvalidArm(arms.iter().position(|arm| arm.pat == pat).unwrap_or(0))
+            }
+            hir_ty::diagnostics::MatchCheckError::TooComplex => MatchCheckError::TooComplex,
+        }
+    }
+}
+
 impl DefWithBody {
     pub fn module(self, db: &dyn HirDatabase) -> Module {
         match self {
@@ -1348,6 +1410,159 @@ pub fn debug_mir(self, db: &dyn HirDatabase) -> String {
         }
     }
 
+    /// A textual representation of the patterns of a `match` in this def's body, as lowered for
+    /// match checking, for debugging purposes.
+    pub fn debug_match_patterns(
+        self,
+        db: &dyn HirDatabase,
+        match_expr: InFile<&ast::MatchExpr>,
+    ) -> Option<String> {
+        let (_, source_map) = db.body_with_source_map(self.id());
+        let expr = ast::Expr::from(match_expr.value.clone());
+        let match_expr = source_map.node_expr(match_expr.with_value(&expr))?;
+        Some(hir_ty::diagnostics::pretty_print_match_patterns(db, self.id(), match_expr))
+    }
+
+    /// The decision tree a `match` in this def's body can be compiled to, exported in `format`
+    /// for visualization.
+    pub fn match_decision_tree(
+        self,
+        db: &dyn HirDatabase,
+        match_expr: InFile<&ast::MatchExpr>,
+        format: DecisionTreeFormat,
+    ) -> Option<String> {
+        let (_, source_map) = db.body_with_source_map(self.id());
+        let expr = ast::Expr::from(match_expr.value.clone());
+        let match_expr = source_map.node_expr(match_expr.with_value(&expr))?;
+        Some(hir_ty::diagnostics::match_decision_tree(db, self.id(), match_expr, format))
+    }
+
+    /// The arms of the `match` expressions in this def's body, and the alternatives of the
+    /// or-patterns in them, that can never be reached.
+    pub fn unreachable_patterns(self, db: &dyn HirDatabase) -> Vec<UnreachablePattern> {
+        let (_, source_map) = db.body_with_source_map(self.id());
+        hir_ty::diagnostics::unreachable_patterns(db, self.id())
+            .into_iter()
+            .filter_map(|it| {
+                let InFile { file_id, value } = source_map.pat_syntax(it.pat).ok()?;
+                Some(UnreachablePattern { pat: InFile::new(file_id, value.left()?), kind: it.kind })
+            })
+            .collect()
+    }
+
+    /// The arms taken by the `match` expressions in this def's body whose scrutinee is known at
+    /// compile time, like `match 3 {..}` or `match SOME_CONST {..}`.
+    pub fn taken_match_arms(self, db: &dyn HirDatabase) -> Vec<TakenMatchArm> {
+        let (body, source_map) = db.body_with_source_map(self.id());
+        let pat_syntax = |pat| {
+            let InFile { file_id, value } = source_map.pat_syntax(pat).ok()?;
+            Some(InFile::new(file_id, value.left()?))
+        };
+        hir_ty::diagnostics::taken_match_arms(db, self.id())
+            .into_iter()
+            .filter_map(|it| {
+                let Expr::Match { arms, .. } = &body[it.match_expr] else { return None };
+                let taken = pat_syntax(arms[it.arm].pat)?;
+                let not_taken = arms
+                    .iter()
+                    .enumerate()
+                    .filter(|&(idx, _)| idx != it.arm)
+                    .filter_map(|(_, arm)| pat_syntax(arm.pat))
+                    .collect();
+                Some(TakenMatchArm { taken, not_taken })
+            })
+            .collect()
+    }
+
+    /// Checks the exhaustiveness of a `match` in this def's body and the reachability of its
+    /// arms, like the `missing-match-arm` diagnostic does.
+    pub fn check_match(
+        self,
+        db: &dyn HirDatabase,
+        match_expr: InFile<&ast::MatchExpr>,
+    ) -> Option<Result<MatchCheck, MatchCheckError>> {
+        let (body, source_map) = db.body_with_source_map(self.id());
+        let expr = ast::Expr::from(match_expr.value.clone());
+        let match_expr = source_map.node_expr(match_expr.with_value(&expr))?;
+        let Expr::Match { expr: scrutinee, arms } = &body[match_expr] else { return None };
+        let infer = db.infer(self.id());
+        let scrut_ty = infer.type_of_expr_with_adjust(*scrutinee);
+        let arms: Vec<_> = arms
+            .iter()
+            .map(|arm| hir_ty::diagnostics::MatchCheckArm { pat: arm.pat, guard: arm.guard })
+            .collect();
+        Some(self.check_match_arms_by_id(db, scrut_ty, &arms))
+    }
+
+    /// The patterns of the arms to add to a `match` in this def's body to make it exhaustive, with
+    /// bindings named after the fields, e.g. `Variant { id, name }` or `Ok(value)`. `None` if the
+    /// match can't be checked or misses too many arms.
+    pub fn fill_match_arms(
+        self,
+        db: &dyn HirDatabase,
+        match_expr: InFile<&ast::MatchExpr>,
+    ) -> Option<Vec<String>> {
+        let (_, source_map) = db.body_with_source_map(self.id());
+        let expr = ast::Expr::from(match_expr.value.clone());
+        let match_expr = source_map.node_expr(match_expr.with_value(&expr))?;
+        hir_ty::diagnostics::fill_match_arms(db, self.id(), match_expr)
+    }
+
+    /// Checks patterns of this def's body, each with its guard if it has one, as if they were the
+    /// arms of a `match` on a value of type `scrutinee`, e.g. to check an arm before adding it.
+    /// Returns `None` if one of the patterns or guards isn't in this def's body.
+    pub fn check_match_arms(
+        self,
+        db: &dyn HirDatabase,
+        scrutinee: &Type,
+        arms: &[(InFile<&ast::Pat>, Option<InFile<&ast::Expr>>)],
+    ) -> Option<Result<MatchCheck, MatchCheckError>> {
+        let (_, source_map) = db.body_with_source_map(self.id());
+        let arms = arms
+            .iter()
+            .map(|&(pat, guard)| {
+                let pat = source_map.node_pat(pat)?;
+                let guard = match guard {
+                    Some(guard) => Some(source_map.node_expr(guard)?),
+                    None => None,
+                };
+                Some(hir_ty::diagnostics::MatchCheckArm { pat, guard })
+            })
+            .collect::<Option<Vec<_>>>()?;
+        Some(self.check_match_arms_by_id(db, &scrutinee.ty, &arms))
+    }
+
+    /// Whether a pattern of this def's body is refutable, i.e. doesn't match every value of its
+    /// type, so that it can't be used in a `let` without an `else`.
+    pub fn is_refutable(
+        self,
+        db: &dyn HirDatabase,
+        pat: InFile<&ast::Pat>,
+    ) -> Option<Result<bool, MatchCheckError>> {
+        let (_, source_map) = db.body_with_source_map(self.id());
+        let pat = source_map.node_pat(pat)?;
+        let arms = [hir_ty::diagnostics::MatchCheckArm { pat, guard: None }];
+        Some(
+            hir_ty::diagnostics::is_refutable(db, self.id(), pat)
+                .map_err(|err| MatchCheckError::from_hir_ty(err, &arms)),
+        )
+    }
+
+    fn check_match_arms_by_id(
+        self,
+        db: &dyn HirDatabase,
+        scrut_ty: &Ty,
+        arms: &[hir_ty::diagnostics::MatchCheckArm],
+    ) -> Result<MatchCheck, MatchCheckError> {
+        let report = hir_ty::diagnostics::check_match_arms(db, self.id(), scrut_ty, arms)
+            .map_err(|err| MatchCheckError::from_hir_ty(err, arms))?;
+        Ok(MatchCheck {
+            missing_patterns: report.missing_patterns,
+            arms: report.arms,
+            is_approximate: report.is_approximate,
+        })
+    }
+
     pub fn diagnostics(self, db: &dyn HirDatabase, acc: &mut Vec<AnyDiagnostic>) {
         let krate = self.module(db).id.krate();
 
@@ -1556,7 +1771,7 @@ pub fn diagnostics(self, db: &dyn HirDatabase, acc: &mut Vec<AnyDiagnostic>) {
             }
         }
 
-        for diagnostic in BodyValidationDiagnostic::collect(db, self.into()) {
+        for diagnostic in db.body_validation_diagnostics(self.into()).iter().cloned() {
             match diagnostic {
                 BodyValidationDiagnostic::RecordMissingFields {
                     record,
@@ -1657,6 +1872,73 @@ pub fn diagnostics(self, db: &dyn HirDatabase, acc: &mut Vec<AnyDiagnostic>) {
                         Err(SyntheticSyntax) => (),
                     }
                 }
+                BodyValidationDiagnostic::MatchCheckTooComplex { match_expr } => {
+                    if let Ok(source_ptr) = source_map.expr_syntax(match_expr) {
+                        let root = source_ptr.file_syntax(db.upcast());
+                        if let ast::Expr::MatchExpr(match_expr) = &source_ptr.value.to_node(&root) {
+                            if let Some(scrut_expr) = match_expr.expr() {
+                                acc.push(
+                                    MatchCheckTooComplex {
+                                        scrutinee_expr: InFile::new(
+                                            source_ptr.file_id,
+                                            AstPtr::new(&scrut_expr),
+                                        ),
+                                    }
+                                    .into(),
+                                );
+                            }
+                        }
+                    }
+                }
+                BodyValidationDiagnostic::MatchCheckApproximated { match_expr } => {
+                    if let Ok(source_ptr) = source_map.expr_syntax(match_expr) {
+                        let root = source_ptr.file_syntax(db.upcast());
+                        if let ast::Expr::MatchExpr(match_expr) = &source_ptr.value.to_node(&root) {
+                            if let Some(scrut_expr) = match_expr.expr() {
+                                acc.push(
+                                    MatchCheckApproximated {
+                                        scrutinee_expr: InFile::new(
+                                            source_ptr.file_id,
+                                            AstPtr::new(&scrut_expr),
+                                        ),
+                                    }
+                                    .into(),
+                                );
+                            }
+                        }
+                    }
+                }
+                BodyValidationDiagnostic::MismatchedTupleStructPatArgCount {
+                    pat,
+                    expected,
+                    found,
+                } => {
+                    if let Ok(source_ptr) = source_map.pat_syntax(pat) {
+                        acc.push(
+                            MismatchedTupleStructPatArgCount {
+                                expr_or_pat: source_ptr,
+                                expected,
+                                found,
+                            }
+                            .into(),
+                        );
+                    }
+                }
+                BodyValidationDiagnostic::RecordPatNoSuchFields { pat, fields } => {
+                    if let Ok(source_ptr) = source_map.pat_syntax(pat) {
+                        acc.push(NoSuchRecordPatFields { pat: source_ptr, fields }.into());
+                    }
+                }
+                BodyValidationDiagnostic::OverlappingRangeEndpoints { pat, overlaps } => {
+                    if let Ok(source_ptr) = source_map.pat_syntax(pat) {
+                        acc.push(OverlappingRangeEndpoints { pat: source_ptr, overlaps }.into());
+                    }
+                }
+                BodyValidationDiagnostic::FloatLiteralPattern { literal } => {
+                    if let Ok(source_ptr) = source_map.expr_syntax(literal) {
+                        acc.push(FloatLiteralPattern { literal: source_ptr }.into());
+                    }
+                }
             }
         }
 
@@ -3065,6 +3347,13 @@ pub fn is_negative(self, db: &dyn HirDatabase) -> bool {
         db.impl_data(self.id).is_negative
     }
 
+    /// If this is a `CoerceUnsized` impl for a struct, returns the field that gets coerced.
+    pub fn coerce_unsized_field(self, db: &dyn HirDatabase) -> Option<Field> {
+        match db.coerce_unsized_info(self.id).custom_kind? {
+            CustomCoerceUnsized::Struct(field) => Some(field.into()),
+        }
+    }
+
     pub fn module(self, db: &dyn HirDatabase) -> Module {
         self.id.lookup(db.upcast()).container.into()
     }
@@ -4165,6 +4454,8 @@ pub enum Adjust {
     /// Take the address and produce either a `&` or `*` pointer.
     Borrow(AutoBorrow),
     Pointer(PointerCast),
+    /// Cast into a dyn* object.
+    DynStar,
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
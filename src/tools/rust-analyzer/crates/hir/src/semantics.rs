@@ -1113,9 +1113,10 @@ fn expr_adjustments(&self, expr: &ast::Expr) -> Option<Vec<Adjustment>> {
                             Adjust::Borrow(AutoBorrow::RawPtr(mutability(m)))
                         }
                         hir_ty::Adjust::Borrow(hir_ty::AutoBorrow::Ref(m)) => {
-                            Adjust::Borrow(AutoBorrow::Ref(mutability(m)))
+                            Adjust::Borrow(AutoBorrow::Ref(mutability(m.into())))
                         }
                         hir_ty::Adjust::Pointer(pc) => Adjust::Pointer(pc),
+                        hir_ty::Adjust::DynStar => Adjust::DynStar,
                     };
 
                     // Update `source_ty` for the next adjustment
@@ -164,7 +164,7 @@ pub(crate) fn expr_adjustments(
     ) -> Option<&[Adjustment]> {
         let expr_id = self.expr_id(db, expr)?;
         let infer = self.infer.as_ref()?;
-        infer.expr_adjustments.get(&expr_id).map(|v| &**v)
+        Some(infer.expr_adjustments(expr_id))
     }
 
     pub(crate) fn type_of_expr(
@@ -174,10 +174,7 @@ pub(crate) fn type_of_expr(
     ) -> Option<(Type, Option<Type>)> {
         let expr_id = self.expr_id(db, expr)?;
         let infer = self.infer.as_ref()?;
-        let coerced = infer
-            .expr_adjustments
-            .get(&expr_id)
-            .and_then(|adjusts| adjusts.last().map(|adjust| adjust.target.clone()));
+        let coerced = infer.expr_adjustments(expr_id).last().map(|adjust| adjust.target.clone());
         let ty = infer[expr_id].clone();
         let mk_ty = |ty| Type::new_with_resolver(db, &self.resolver, ty);
         Some((mk_ty(ty), coerced.map(mk_ty)))
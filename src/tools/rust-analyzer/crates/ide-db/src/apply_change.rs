@@ -125,6 +125,8 @@ macro_rules! purge_each_query {
             hir::db::InferQueryQuery
             hir::db::MirBodyQuery
             hir::db::BorrowckQuery
+            hir::db::LoweredPatternsQuery
+            hir::db::BodyValidationDiagnosticsQuery
             hir::db::TyQuery
             hir::db::ValueTyQuery
             hir::db::ImplSelfTyQuery
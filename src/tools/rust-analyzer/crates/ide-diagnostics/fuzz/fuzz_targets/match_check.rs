@@ -0,0 +1,266 @@
+//! Fuzzing for match checking: random ADTs and matches on them are checked for panics, and for
+//! the missing match arms found being consistent with the match.
+
+#![no_main]
+use std::fmt::{self, Write};
+
+use arbitrary::{Result, Unstructured};
+use ide_db::{assists::AssistResolveStrategy, base_db::fixture::WithFixture, RootDatabase};
+use ide_diagnostics::{diagnostics, DiagnosticsConfig};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(program) = Program::generate(&mut Unstructured::new(data)) {
+        program.check();
+    }
+});
+
+#[derive(Debug)]
+enum Ty {
+    Bool,
+    U8,
+    Enum(usize),
+    Struct(usize),
+    Option(Box<Ty>),
+    Tuple(Vec<Ty>),
+    Slice(Box<Ty>),
+}
+
+impl fmt::Display for Ty {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Ty::Bool => f.write_str("bool"),
+            Ty::U8 => f.write_str("u8"),
+            Ty::Enum(idx) => write!(f, "E{idx}"),
+            Ty::Struct(idx) => write!(f, "S{idx}"),
+            Ty::Option(ty) => write!(f, "Option<{ty}>"),
+            Ty::Tuple(tys) if tys.len() == 1 => write!(f, "({},)", tys[0]),
+            Ty::Tuple(tys) => {
+                write!(
+                    f,
+                    "({})",
+                    tys.iter().map(ToString::to_string).collect::<Vec<_>>().join(", ")
+                )
+            }
+            Ty::Slice(ty) => write!(f, "&[{ty}]"),
+        }
+    }
+}
+
+/// `enum E{i} { V{i}_{j}(fields..), .. }`, the variants of which are glob-imported, so that the
+/// witnesses of missing arms can be pasted back as patterns.
+#[derive(Debug)]
+struct EnumDef {
+    variants: Vec<Vec<Ty>>,
+}
+
+/// `struct S{i} { f{j}: field, .. }`
+#[derive(Debug)]
+struct StructDef {
+    fields: Vec<Ty>,
+}
+
+#[derive(Debug)]
+struct Program {
+    enums: Vec<EnumDef>,
+    structs: Vec<StructDef>,
+    scrutinee_ty: Ty,
+    /// The patterns of the arms, and whether they have a guard.
+    arms: Vec<(String, bool)>,
+}
+
+impl Program {
+    fn generate(u: &mut Unstructured<'_>) -> Result<Program> {
+        let mut program = Program {
+            enums: Vec::new(),
+            structs: Vec::new(),
+            scrutinee_ty: Ty::Bool,
+            arms: Vec::new(),
+        };
+        // Fields only refer to the ADTs defined before them, so that all the types are finite.
+        for _ in 0..u.int_in_range(0..=3)? {
+            let variants = (0..u.int_in_range(1..=4)?)
+                .map(|_| {
+                    (0..u.int_in_range(0..=2)?)
+                        .map(|_| program.generate_ty(u, 1))
+                        .collect::<Result<Vec<_>>>()
+                })
+                .collect::<Result<_>>()?;
+            program.enums.push(EnumDef { variants });
+            if u.arbitrary()? {
+                let fields = (0..u.int_in_range(1..=3)?)
+                    .map(|_| program.generate_ty(u, 1))
+                    .collect::<Result<_>>()?;
+                program.structs.push(StructDef { fields });
+            }
+        }
+        program.scrutinee_ty = program.generate_ty(u, 2)?;
+        for _ in 0..u.int_in_range(0..=6)? {
+            let pat = program.generate_pat(u, &program.scrutinee_ty, 0)?;
+            let has_guard = u.int_in_range(0..=4)? == 0;
+            program.arms.push((pat, has_guard));
+        }
+        Ok(program)
+    }
+
+    fn generate_ty(&self, u: &mut Unstructured<'_>, depth: usize) -> Result<Ty> {
+        let ty = match u.int_in_range(0..=6)? {
+            0 => Ty::Bool,
+            1 => Ty::U8,
+            2 if !self.enums.is_empty() => Ty::Enum(u.int_in_range(0..=self.enums.len() - 1)?),
+            3 if !self.structs.is_empty() => {
+                Ty::Struct(u.int_in_range(0..=self.structs.len() - 1)?)
+            }
+            4 if depth > 0 => Ty::Option(Box::new(self.generate_ty(u, depth - 1)?)),
+            5 if depth > 0 => Ty::Tuple(
+                (0..u.int_in_range(1..=3)?)
+                    .map(|_| self.generate_ty(u, depth - 1))
+                    .collect::<Result<_>>()?,
+            ),
+            6 if depth > 0 => Ty::Slice(Box::new(self.generate_ty(u, depth - 1)?)),
+            _ => Ty::Bool,
+        };
+        Ok(ty)
+    }
+
+    fn generate_pat(&self, u: &mut Unstructured<'_>, ty: &Ty, depth: usize) -> Result<String> {
+        match u.int_in_range(0..=7)? {
+            0 => Ok("_".to_owned()),
+            // Too many nested or-patterns make the check approximate.
+            1 if depth < 2 => {
+                let alts = (0..u.int_in_range(2..=3)?)
+                    .map(|_| self.generate_ctor_pat(u, ty, depth + 1))
+                    .collect::<Result<Vec<_>>>()?;
+                Ok(alts.join(" | "))
+            }
+            _ => self.generate_ctor_pat(u, ty, depth + 1),
+        }
+    }
+
+    fn generate_ctor_pat(&self, u: &mut Unstructured<'_>, ty: &Ty, depth: usize) -> Result<String> {
+        let pat = match ty {
+            Ty::Bool => format!("{}", u.arbitrary::<bool>()?),
+            Ty::U8 => {
+                let start = u.arbitrary::<u8>()?;
+                let end = u.int_in_range(start..=u8::MAX)?;
+                if start == end {
+                    format!("{start}")
+                } else {
+                    format!("{start}..={end}")
+                }
+            }
+            Ty::Enum(idx) => {
+                let variant = u.int_in_range(0..=self.enums[*idx].variants.len() - 1)?;
+                let fields = &self.enums[*idx].variants[variant];
+                let mut pat = format!("V{idx}_{variant}");
+                if !fields.is_empty() {
+                    let fields = self.generate_pats(u, fields, depth)?;
+                    write!(pat, "({})", fields.join(", ")).unwrap();
+                }
+                pat
+            }
+            Ty::Struct(idx) => {
+                let mut fields = Vec::new();
+                let mut has_rest = false;
+                for (field_idx, field) in self.structs[*idx].fields.iter().enumerate() {
+                    if u.int_in_range(0..=3)? == 0 {
+                        has_rest = true;
+                    } else {
+                        fields
+                            .push(format!("f{field_idx}: {}", self.generate_pat(u, field, depth)?));
+                    }
+                }
+                if has_rest {
+                    fields.push("..".to_owned());
+                }
+                format!("S{idx} {{ {} }}", fields.join(", "))
+            }
+            Ty::Option(_) if u.arbitrary()? => "None".to_owned(),
+            Ty::Option(ty) => format!("Some({})", self.generate_pat(u, ty, depth)?),
+            Ty::Tuple(tys) if tys.len() == 1 => {
+                format!("({},)", self.generate_pat(u, &tys[0], depth)?)
+            }
+            Ty::Tuple(tys) => format!("({})", self.generate_pats(u, tys, depth)?.join(", ")),
+            Ty::Slice(ty) => {
+                let mut pats = (0..u.int_in_range(0..=2)?)
+                    .map(|_| self.generate_pat(u, ty, depth))
+                    .collect::<Result<Vec<_>>>()?;
+                if u.arbitrary()? {
+                    let rest = u.int_in_range(0..=pats.len())?;
+                    pats.insert(rest, "..".to_owned());
+                }
+                format!("[{}]", pats.join(", "))
+            }
+        };
+        Ok(pat)
+    }
+
+    fn generate_pats(
+        &self,
+        u: &mut Unstructured<'_>,
+        tys: &[Ty],
+        depth: usize,
+    ) -> Result<Vec<String>> {
+        tys.iter().map(|ty| self.generate_pat(u, ty, depth)).collect()
+    }
+
+    fn source(&self, extra_arms: &[String]) -> String {
+        let mut source = "//- minicore: option\n".to_owned();
+        for (idx, enum_) in self.enums.iter().enumerate() {
+            let variants = enum_.variants.iter().enumerate().map(|(variant, fields)| {
+                if fields.is_empty() {
+                    format!("V{idx}_{variant}")
+                } else {
+                    let fields = fields.iter().map(ToString::to_string).collect::<Vec<_>>();
+                    format!("V{idx}_{variant}({})", fields.join(", "))
+                }
+            });
+            writeln!(source, "enum E{idx} {{ {} }}", variants.collect::<Vec<_>>().join(", "))
+                .unwrap();
+            writeln!(source, "use E{idx}::*;").unwrap();
+        }
+        for (idx, struct_) in self.structs.iter().enumerate() {
+            let fields =
+                struct_.fields.iter().enumerate().map(|(field, ty)| format!("f{field}: {ty}"));
+            writeln!(source, "struct S{idx} {{ {} }}", fields.collect::<Vec<_>>().join(", "))
+                .unwrap();
+        }
+        writeln!(source, "fn f(x: {}) {{\n    match x {{", self.scrutinee_ty).unwrap();
+        for (pat, has_guard) in &self.arms {
+            writeln!(source, "        {pat}{} => {{}}", if *has_guard { " if true" } else { "" })
+                .unwrap();
+        }
+        for pat in extra_arms {
+            writeln!(source, "        {pat} => {{}}").unwrap();
+        }
+        source.push_str("    }\n}\n");
+        source
+    }
+
+    /// Checks the program and returns the message of the missing match arm diagnostic, if any.
+    fn missing_match_arms(&self, extra_arms: &[String]) -> Option<String> {
+        let (db, file_id) = RootDatabase::with_single_file(&self.source(extra_arms));
+        let config = DiagnosticsConfig::test_sample();
+        diagnostics(&db, &config, &AssistResolveStrategy::None, file_id)
+            .into_iter()
+            .find(|diagnostic| diagnostic.code.0 == "missing-match-arm")
+            .map(|diagnostic| diagnostic.message)
+    }
+
+    fn check(&self) {
+        let Some(message) = self.missing_match_arms(&[]) else { return };
+
+        // A wildcard arm covers everything that is missing.
+        let with_wildcard = self.missing_match_arms(&["_".to_owned()]);
+        assert_eq!(with_wildcard, None, "{}", self.source(&["_".to_owned()]));
+
+        // When all the witnesses are listed, adding arms for them makes the match exhaustive.
+        if message.contains(" more not covered") {
+            return;
+        }
+        let witnesses =
+            message.split('`').skip(1).step_by(2).map(str::to_owned).collect::<Vec<_>>();
+        let with_witnesses = self.missing_match_arms(&witnesses);
+        assert_eq!(with_witnesses, None, "{}\nmissing: {message}", self.source(&witnesses));
+    }
+}
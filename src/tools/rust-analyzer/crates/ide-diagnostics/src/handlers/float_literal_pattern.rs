@@ -0,0 +1,96 @@
+use crate::{Diagnostic, DiagnosticsContext, Severity};
+
+// Diagnostic: illegal-floating-point-literal-pattern
+//
+// This diagnostic is triggered if a floating-point literal is used in a pattern. Such patterns
+// compare floats for equality, which is fragile, and are going to be rejected by rustc. It is
+// not reported where `#[allow(illegal_floating_point_literal_pattern)]` applies.
+pub(crate) fn float_literal_pattern(
+    ctx: &DiagnosticsContext<'_>,
+    d: &hir::FloatLiteralPattern,
+) -> Diagnostic {
+    Diagnostic::new(
+        "illegal-floating-point-literal-pattern",
+        "floating-point types cannot be used in patterns",
+        ctx.sema.diagnostics_display_range(d.literal.clone().map(|it| it.into())).range,
+    )
+    .severity(Severity::WeakWarning)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::check_diagnostics;
+
+    #[test]
+    fn float_literal_patterns() {
+        check_diagnostics(
+            r#"
+fn main(x: f64, y: (f32, bool)) {
+    match x {
+        1.0 => {}
+      //^^^ weak: floating-point types cannot be used in patterns
+        -2.5 | 0.0..=1.0 => {}
+       //^^^ weak: floating-point types cannot be used in patterns
+             //^^^ weak: floating-point types cannot be used in patterns
+                   //^^^ weak: floating-point types cannot be used in patterns
+        _ => {}
+    }
+    match y {
+        (0.5f32, true) => {}
+       //^^^^^^ weak: floating-point types cannot be used in patterns
+        (_, _) => {}
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn float_literal_patterns_are_still_checked() {
+        check_diagnostics(
+            r#"
+fn main(x: f64) {
+    match x {
+        //^ 💡 error: missing match arm: `_` not covered
+        1.0 => {}
+      //^^^ weak: floating-point types cannot be used in patterns
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn allowed_float_literal_patterns() {
+        check_diagnostics(
+            r#"
+#[allow(illegal_floating_point_literal_pattern)]
+fn f(x: f64) {
+    match x {
+        1.0 => {}
+        _ => {}
+    }
+}
+
+mod m {
+    #![allow(illegal_floating_point_literal_pattern)]
+    fn g(x: f64) {
+        match x {
+            1.0 => {}
+            _ => {}
+        }
+    }
+}
+
+#[allow(unused)]
+fn h(x: f64) {
+    match x {
+        1.0 => {}
+      //^^^ weak: floating-point types cannot be used in patterns
+        _ => {}
+    }
+}
+"#,
+        );
+    }
+}
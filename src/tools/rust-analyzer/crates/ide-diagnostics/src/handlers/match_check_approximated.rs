@@ -0,0 +1,54 @@
+use crate::{Diagnostic, DiagnosticsContext, Severity};
+
+// Diagnostic: match-check-approximated
+//
+// This diagnostic is triggered if an arm of a `match` has too many or-pattern alternatives to
+// check each of them. Such arms are checked as if their or-patterns were wildcards, so some missing
+// arms may not be reported.
+pub(crate) fn match_check_approximated(
+    ctx: &DiagnosticsContext<'_>,
+    d: &hir::MatchCheckApproximated,
+) -> Diagnostic {
+    Diagnostic::new(
+        "match-check-approximated",
+        "match has too many alternatives to check exactly, some missing arms may not be reported",
+        ctx.sema.diagnostics_display_range(d.scrutinee_expr.clone().map(Into::into)).range,
+    )
+    .severity(Severity::WeakWarning)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::check_diagnostics;
+
+    #[test]
+    fn approximated_arm() {
+        check_diagnostics(
+            r#"
+fn main() {
+    let x = (true, 0u8, 0u8, 0u8, 0u8, 0u8, 0u8, 0u8, 0u8, 0u8, 0u8, 0u8);
+    match x {
+        //^ weak: match has too many alternatives to check exactly, some missing arms may not be reported
+        (true, 1 | 2, 1 | 2, 1 | 2, 1 | 2, 1 | 2, 1 | 2, 1 | 2, 1 | 2, 1 | 2, 1 | 2, 1 | 2) => {}
+        (true, 3, _, _, _, _, _, _, _, _, _, _) => {}
+        (false, _, _, _, _, _, _, _, _, _, _, _) => {}
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn few_alternatives() {
+        check_diagnostics(
+            r#"
+fn main() {
+    match (true, 0u8, 0u8) {
+        (true, 1 | 2, 1 | 2) => {}
+        _ => {}
+    }
+}
+"#,
+        );
+    }
+}
@@ -0,0 +1,54 @@
+use crate::{Diagnostic, DiagnosticsContext, Severity};
+
+// Diagnostic: match-check-too-complex
+//
+// This diagnostic is triggered if checking a `match` for exhaustiveness would exceed the
+// `#![pattern_complexity]` limit. The match is then left unchecked.
+pub(crate) fn match_check_too_complex(
+    ctx: &DiagnosticsContext<'_>,
+    d: &hir::MatchCheckTooComplex,
+) -> Diagnostic {
+    Diagnostic::new(
+        "match-check-too-complex",
+        "match is too complex to check for exhaustiveness",
+        ctx.sema.diagnostics_display_range(d.scrutinee_expr.clone().map(Into::into)).range,
+    )
+    .severity(Severity::WeakWarning)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::check_diagnostics;
+
+    #[test]
+    fn exceeds_pattern_complexity() {
+        check_diagnostics(
+            r#"
+#![pattern_complexity = "1"]
+fn main() {
+    match (true, true) {
+        //^^^^^^^^^^^^ weak: match is too complex to check for exhaustiveness
+        (true, true) => {}
+        (true, false) => {}
+        (false, _) => {}
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn within_pattern_complexity() {
+        check_diagnostics(
+            r#"
+fn main() {
+    match (true, true) {
+        (true, true) => {}
+        (true, false) => {}
+        (false, _) => {}
+    }
+}
+"#,
+        );
+    }
+}
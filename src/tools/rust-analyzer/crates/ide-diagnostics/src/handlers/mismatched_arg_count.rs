@@ -1,3 +1,4 @@
+use either::Either;
 use syntax::{
     ast::{self, HasArgList},
     AstNode, TextRange,
@@ -17,6 +18,27 @@ pub(crate) fn mismatched_arg_count(
     Diagnostic::new("mismatched-arg-count", message, invalid_args_range(ctx, d))
 }
 
+// Diagnostic: mismatched-tuple-struct-pat-arg-count
+//
+// This diagnostic is triggered if a tuple struct pattern has an incorrect amount of fields.
+pub(crate) fn mismatched_tuple_struct_pat_arg_count(
+    ctx: &DiagnosticsContext<'_>,
+    d: &hir::MismatchedTupleStructPatArgCount,
+) -> Diagnostic {
+    let s = if d.expected == 1 { "" } else { "s" };
+    let message = format!("expected {} field{s}, found {}", d.expected, d.found);
+    Diagnostic::new(
+        "mismatched-tuple-struct-pat-arg-count",
+        message,
+        ctx.sema
+            .diagnostics_display_range(d.expr_or_pat.clone().map(|it| match it {
+                Either::Left(it) => it.into(),
+                Either::Right(it) => it.into(),
+            }))
+            .range,
+    )
+}
+
 fn invalid_args_range(ctx: &DiagnosticsContext<'_>, d: &hir::MismatchedArgCount) -> TextRange {
     adjusted_display_range::<ast::Expr>(ctx, d.call_expr.clone().map(|it| it.into()), &|expr| {
         let arg_list = match expr {
@@ -331,4 +353,42 @@ fn g() {
             "#,
         )
     }
+
+    #[test]
+    fn tuple_struct_pat() {
+        check_diagnostics(
+            r#"
+enum E { A(u32, u32), B }
+fn f(e: E) {
+    match e {
+        E::A(_, _, _) => (),
+      //^^^^^^^^^^^^^ error: expected 2 fields, found 3
+        E::A(_) => (),
+      //^^^^^^^ error: expected 2 fields, found 1
+        E::A(..) | E::A(_, ..) => (),
+        E::B => (),
+    }
+}
+"#,
+        )
+    }
+
+    #[test]
+    fn tuple_struct_pat_outside_match() {
+        check_diagnostics(
+            r#"
+//- minicore: option
+struct S(u32, u32);
+fn f(S(a): S) {
+   //^^^^ error: expected 2 fields, found 1
+    let S(_, _, _) = S(1, 2);
+      //^^^^^^^^^^ error: expected 2 fields, found 3
+    if let Some(a, b) = Some(1) {}
+         //^^^^^^^^^^ error: expected 1 field, found 2
+    let _ = |S(a, b, c): S| a;
+           //^^^^^^^^^^ error: expected 2 fields, found 3
+}
+"#,
+        )
+    }
 }
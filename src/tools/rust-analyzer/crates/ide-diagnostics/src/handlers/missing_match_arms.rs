@@ -1,4 +1,12 @@
-use crate::{Diagnostic, DiagnosticsContext};
+use hir::db::ExpandDatabase;
+use ide_db::{assists::Assist, source_change::SourceChange};
+use syntax::{
+    ast::{self, edit::IndentLevel},
+    AstNode, Direction, SyntaxKind, TextSize,
+};
+use text_edit::TextEdit;
+
+use crate::{fix, Diagnostic, DiagnosticsContext};
 
 // Diagnostic: missing-match-arm
 //
@@ -12,11 +20,74 @@ pub(crate) fn missing_match_arms(
         format!("missing match arm: {}", d.uncovered_patterns),
         ctx.sema.diagnostics_display_range(d.scrutinee_expr.clone().map(Into::into)).range,
     )
+    .with_fixes(fixes(ctx, d))
+}
+
+fn fixes(ctx: &DiagnosticsContext<'_>, d: &hir::MissingMatchArms) -> Option<Vec<Assist>> {
+    // The arm can't be inserted into the macro call the match was expanded from.
+    if d.scrutinee_expr.file_id.is_macro() {
+        return None;
+    }
+
+    let root = ctx.sema.db.parse_or_expand(d.scrutinee_expr.file_id)?;
+    let scrutinee = d.scrutinee_expr.value.to_node(&root);
+    let match_expr = scrutinee.syntax().parent().and_then(ast::MatchExpr::cast)?;
+    let (offset, text) = wildcard_arm(&match_expr)?;
+    let edit = TextEdit::insert(offset, text);
+    let source_change =
+        SourceChange::from_text_edit(d.scrutinee_expr.file_id.original_file(ctx.sema.db), edit);
+    Some(vec![fix(
+        "add_wildcard_arm",
+        "Add a wildcard arm",
+        source_change,
+        scrutinee.syntax().text_range(),
+    )])
+}
+
+/// Where and what to insert to add a `_ => todo!()` arm after the last arm of `match_expr` and a
+/// comment on the same line as it, if any, so that comments on the following lines stay after
+/// the new arm.
+fn wildcard_arm(match_expr: &ast::MatchExpr) -> Option<(TextSize, String)> {
+    let arm_list = match_expr.match_arm_list()?;
+    let Some(last_arm) = arm_list.arms().last() else {
+        let l_curly = arm_list.l_curly_token()?;
+        let r_curly = arm_list.r_curly_token()?;
+        let match_indent = IndentLevel::from_node(match_expr.syntax());
+        let arm = format!("{}_ => todo!(),", match_indent + 1);
+        // `match x {}` gets the closing brace moved to its own line.
+        let multiline = l_curly
+            .siblings_with_tokens(Direction::Next)
+            .take_while(|it| it.as_token() != Some(&r_curly))
+            .any(|it| it.to_string().contains('\n'));
+        return Some(if multiline {
+            (l_curly.text_range().end(), format!("\n{arm}"))
+        } else {
+            (r_curly.text_range().start(), format!("\n{arm}\n{match_indent}"))
+        });
+    };
+
+    let indent = IndentLevel::from_node(last_arm.syntax());
+    let needs_comma = last_arm.comma_token().is_none()
+        && !last_arm.expr().map_or(false, |expr| expr.is_block_like());
+    let mut offset = last_arm.syntax().text_range().end();
+    // The comma has to directly follow the last arm, so a trailing comment then ends up after
+    // the new arm.
+    if !needs_comma {
+        for element in last_arm.syntax().siblings_with_tokens(Direction::Next).skip(1) {
+            match element.kind() {
+                SyntaxKind::WHITESPACE if !element.to_string().contains('\n') => {}
+                SyntaxKind::COMMENT => offset = element.text_range().end(),
+                _ => break,
+            }
+        }
+    }
+    let comma = if needs_comma { "," } else { "" };
+    Some((offset, format!("{comma}\n{indent}_ => todo!(),")))
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::tests::check_diagnostics;
+    use crate::tests::{check_diagnostics, check_fix};
 
     fn check_diagnostics_no_bails(ra_fixture: &str) {
         cov_mark::check_count!(validate_match_bailed_out, 0);
@@ -29,9 +100,9 @@ fn empty_tuple() {
             r#"
 fn main() {
     match () { }
-        //^^ error: missing match arm: type `()` is non-empty
+        //^^ 💡 error: missing match arm: type `()` is non-empty
     match (()) { }
-        //^^^^ error: missing match arm: type `()` is non-empty
+        //^^^^ 💡 error: missing match arm: type `()` is non-empty
 
     match () { _ => (), }
     match () { () => (), }
@@ -47,7 +118,7 @@ fn tuple_of_two_empty_tuple() {
             r#"
 fn main() {
     match ((), ()) { }
-        //^^^^^^^^ error: missing match arm: type `((), ())` is non-empty
+        //^^^^^^^^ 💡 error: missing match arm: type `((), ())` is non-empty
 
     match ((), ()) { ((), ()) => (), }
 }
@@ -61,21 +132,21 @@ fn boolean() {
             r#"
 fn test_main() {
     match false { }
-        //^^^^^ error: missing match arm: type `bool` is non-empty
+        //^^^^^ 💡 error: missing match arm: type `bool` is non-empty
     match false { true => (), }
-        //^^^^^ error: missing match arm: `false` not covered
+        //^^^^^ 💡 error: missing match arm: `false` not covered
     match (false, true) {}
-        //^^^^^^^^^^^^^ error: missing match arm: type `(bool, bool)` is non-empty
+        //^^^^^^^^^^^^^ 💡 error: missing match arm: type `(bool, bool)` is non-empty
     match (false, true) { (true, true) => (), }
-        //^^^^^^^^^^^^^ error: missing match arm: `(false, _)` not covered
+        //^^^^^^^^^^^^^ 💡 error: missing match arm: `(false, _)` not covered
     match (false, true) {
-        //^^^^^^^^^^^^^ error: missing match arm: `(true, true)` not covered
+        //^^^^^^^^^^^^^ 💡 error: missing match arm: `(true, true)` not covered
         (false, true) => (),
         (false, false) => (),
         (true, false) => (),
     }
     match (false, true) { (true, _x) => (), }
-        //^^^^^^^^^^^^^ error: missing match arm: `(false, _)` not covered
+        //^^^^^^^^^^^^^ 💡 error: missing match arm: `(false, _)` not covered
 
     match false { true => (), false => (), }
     match (false, true) {
@@ -114,11 +185,11 @@ fn tuple_of_tuple_and_bools() {
             r#"
 fn main() {
     match (false, ((), false)) {}
-        //^^^^^^^^^^^^^^^^^^^^ error: missing match arm: type `(bool, ((), bool))` is non-empty
+        //^^^^^^^^^^^^^^^^^^^^ 💡 error: missing match arm: type `(bool, ((), bool))` is non-empty
     match (false, ((), false)) { (true, ((), true)) => (), }
-        //^^^^^^^^^^^^^^^^^^^^ error: missing match arm: `(false, _)` not covered
+        //^^^^^^^^^^^^^^^^^^^^ 💡 error: missing match arm: `(false, _)` not covered
     match (false, ((), false)) { (true, _) => (), }
-        //^^^^^^^^^^^^^^^^^^^^ error: missing match arm: `(false, _)` not covered
+        //^^^^^^^^^^^^^^^^^^^^ 💡 error: missing match arm: `(false, _)` not covered
 
     match (false, ((), false)) {
         (true, ((), true)) => (),
@@ -144,12 +215,12 @@ enum Either { A, B, }
 
 fn main() {
     match Either::A { }
-        //^^^^^^^^^ error: missing match arm: `A` and `B` not covered
+        //^^^^^^^^^ 💡 error: missing match arm: `A` and `B` not covered
     match Either::B { Either::A => (), }
-        //^^^^^^^^^ error: missing match arm: `B` not covered
+        //^^^^^^^^^ 💡 error: missing match arm: `B` not covered
 
     match &Either::B {
-        //^^^^^^^^^^ error: missing match arm: `&B` not covered
+        //^^^^^^^^^^ 💡 error: missing match arm: `&B` not covered
         Either::A => (),
     }
 
@@ -172,9 +243,9 @@ enum Either { A(bool), B }
 
 fn main() {
     match Either::B { }
-        //^^^^^^^^^ error: missing match arm: `A(_)` and `B` not covered
+        //^^^^^^^^^ 💡 error: missing match arm: `A(_)` and `B` not covered
     match Either::B {
-        //^^^^^^^^^ error: missing match arm: `A(false)` not covered
+        //^^^^^^^^^ 💡 error: missing match arm: `A(false)` not covered
         Either::A(true) => (), Either::B => ()
     }
 
@@ -205,7 +276,7 @@ enum Either { A(bool), B(bool, bool) }
 
 fn main() {
     match Either::A(false) {
-        //^^^^^^^^^^^^^^^^ error: missing match arm: `B(true, _)` not covered
+        //^^^^^^^^^^^^^^^^ 💡 error: missing match arm: `B(true, _)` not covered
         Either::A(_) => (),
         Either::B(false, _) => (),
     }
@@ -311,6 +382,7 @@ fn main() {
     match Either::A {
         Either::A => (),
         Either::B() => (),
+      //^^^^^^^^^^^ error: expected 1 field, found 0
     }
 }
 "#,
@@ -319,16 +391,24 @@ fn main() {
 
     #[test]
     fn malformed_match_arm_extra_fields() {
-        cov_mark::check_count!(validate_match_bailed_out, 2);
-        check_diagnostics(
+        check_diagnostics_no_bails(
             r#"
 enum A { B(isize, isize), C }
 fn main() {
     match A::B(1, 2) {
+        //^^^^^^^^^^ 💡 error: missing match arm: `C` not covered
         A::B(_, _, _) => (),
+      //^^^^^^^^^^^^^ error: expected 2 fields, found 3
     }
     match A::B(1, 2) {
+        //^^^^^^^^^^ 💡 error: missing match arm: `B(_, _)` not covered
         A::C(_) => (),
+      //^^^^^^^ error: expected 0 fields, found 1
+    }
+    match A::B(1, 2) {
+        A::B(_, _, _) => (),
+      //^^^^^^^^^^^^^ error: expected 2 fields, found 3
+        A::C => (),
     }
 }
 "#,
@@ -351,7 +431,7 @@ fn main() {
         Either::A => (),
     }
     match loop { break Foo::A } {
-        //^^^^^^^^^^^^^^^^^^^^^ error: missing match arm: `B` not covered
+        //^^^^^^^^^^^^^^^^^^^^^ 💡 error: missing match arm: `B` not covered
         Either::A => (),
     }
     match loop { break Foo::A } {
@@ -389,9 +469,9 @@ enum Either { A { foo: bool }, B }
 fn main() {
     let a = Either::A { foo: true };
     match a { }
-        //^ error: missing match arm: `A { .. }` and `B` not covered
+        //^ 💡 error: missing match arm: `A { .. }` and `B` not covered
     match a { Either::A { foo: true } => () }
-        //^ error: missing match arm: `B` not covered
+        //^ 💡 error: missing match arm: `B` not covered
     match a {
         Either::A { } => (),
       //^^^^^^^^^ 💡 error: missing structure fields:
@@ -399,7 +479,7 @@ fn main() {
         Either::B => (),
     }
     match a {
-        //^ error: missing match arm: `B` not covered
+        //^ 💡 error: missing match arm: `B` not covered
         Either::A { } => (),
     } //^^^^^^^^^ 💡 error: missing structure fields:
       //        | - foo
@@ -430,7 +510,7 @@ enum Either {
 fn main() {
     let a = Either::A { foo: true, bar: () };
     match a {
-        //^ error: missing match arm: `B` not covered
+        //^ 💡 error: missing match arm: `B` not covered
         Either::A { bar: (), foo: false } => (),
         Either::A { foo: true, bar: () } => (),
     }
@@ -457,12 +537,12 @@ enum Either {
 fn main() {
     let a = Either::B;
     match a {
-        //^ error: missing match arm: `A { foo: false, .. }` not covered
+        //^ 💡 error: missing match arm: `A { foo: false, .. }` not covered
         Either::A { foo: true, .. } => (),
         Either::B => (),
     }
     match a {
-        //^ error: missing match arm: `B` not covered
+        //^ 💡 error: missing match arm: `B` not covered
         Either::A { .. } => (),
     }
 
@@ -492,14 +572,14 @@ enum Either {
 
 fn main() {
     match Either::B {
-        //^^^^^^^^^ error: missing match arm: `A(false, _, _, true)` not covered
+        //^^^^^^^^^ 💡 error: missing match arm: `A(false, _, _, true)` not covered
         Either::A(true, .., true) => (),
         Either::A(true, .., false) => (),
         Either::A(false, .., false) => (),
         Either::B => (),
     }
     match Either::B {
-        //^^^^^^^^^ error: missing match arm: `A(false, _, _, false)` not covered
+        //^^^^^^^^^ 💡 error: missing match arm: `A(false, _, _, false)` not covered
         Either::A(true, .., true) => (),
         Either::A(true, .., false) => (),
         Either::A(.., true) => (),
@@ -536,7 +616,7 @@ fn enum_(never: Never) {
 }
 fn enum_ref(never: &Never) {
     match never {}
-        //^^^^^ error: missing match arm: type `&Never` is non-empty
+        //^^^^^ 💡 error: missing match arm: type `&Never` is non-empty
 }
 fn bang(never: !) {
     match never {}
@@ -547,8 +627,6 @@ fn bang(never: !) {
 
     #[test]
     fn unknown_type() {
-        cov_mark::check_count!(validate_match_bailed_out, 1);
-
         check_diagnostics(
             r#"
 enum Option<T> { Some(T), None }
@@ -560,7 +638,7 @@ fn main() {
         Some(never) => match never {},
     }
     match Option::<Never>::None {
-        //^^^^^^^^^^^^^^^^^^^^^ error: missing match arm: `None` not covered
+        //^^^^^^^^^^^^^^^^^^^^^ 💡 error: missing match arm: `None` not covered
         Option::Some(_never) => {},
     }
 }
@@ -574,7 +652,7 @@ fn tuple_of_bools_with_ellipsis_at_end_missing_arm() {
             r#"
 fn main() {
     match (false, true, false) {
-        //^^^^^^^^^^^^^^^^^^^^ error: missing match arm: `(true, _, _)` not covered
+        //^^^^^^^^^^^^^^^^^^^^ 💡 error: missing match arm: `(true, _, _)` not covered
         (false, ..) => (),
     }
 }"#,
@@ -587,7 +665,7 @@ fn tuple_of_bools_with_ellipsis_at_beginning_missing_arm() {
             r#"
 fn main() {
     match (false, true, false) {
-        //^^^^^^^^^^^^^^^^^^^^ error: missing match arm: `(_, _, true)` not covered
+        //^^^^^^^^^^^^^^^^^^^^ 💡 error: missing match arm: `(_, _, true)` not covered
         (.., false) => (),
     }
 }"#,
@@ -600,7 +678,7 @@ fn tuple_of_bools_with_ellipsis_in_middle_missing_arm() {
             r#"
 fn main() {
     match (false, true, false) {
-        //^^^^^^^^^^^^^^^^^^^^ error: missing match arm: `(false, _, _)` not covered
+        //^^^^^^^^^^^^^^^^^^^^ 💡 error: missing match arm: `(false, _, _)` not covered
         (true, .., false) => (),
     }
 }"#,
@@ -613,11 +691,11 @@ fn record_struct() {
             r#"struct Foo { a: bool }
 fn main(f: Foo) {
     match f {}
-        //^ error: missing match arm: type `Foo` is non-empty
+        //^ 💡 error: missing match arm: type `Foo` is non-empty
     match f { Foo { a: true } => () }
-        //^ error: missing match arm: `Foo { a: false }` not covered
+        //^ 💡 error: missing match arm: `Foo { a: false }` not covered
     match &f { Foo { a: true } => () }
-        //^^ error: missing match arm: `&Foo { a: false }` not covered
+        //^^ 💡 error: missing match arm: `&Foo { a: false }` not covered
     match f { Foo { a: _ } => () }
     match f {
         Foo { a: true } => (),
@@ -638,9 +716,9 @@ fn tuple_struct() {
             r#"struct Foo(bool);
 fn main(f: Foo) {
     match f {}
-        //^ error: missing match arm: type `Foo` is non-empty
+        //^ 💡 error: missing match arm: type `Foo` is non-empty
     match f { Foo(true) => () }
-        //^ error: missing match arm: `Foo(false)` not covered
+        //^ 💡 error: missing match arm: `Foo(false)` not covered
     match f {
         Foo(true) => (),
         Foo(false) => (),
@@ -656,7 +734,7 @@ fn unit_struct() {
             r#"struct Foo;
 fn main(f: Foo) {
     match f {}
-        //^ error: missing match arm: type `Foo` is non-empty
+        //^ 💡 error: missing match arm: type `Foo` is non-empty
     match f { Foo => () }
 }
 "#,
@@ -669,9 +747,9 @@ fn record_struct_ellipsis() {
             r#"struct Foo { foo: bool, bar: bool }
 fn main(f: Foo) {
     match f { Foo { foo: true, .. } => () }
-        //^ error: missing match arm: `Foo { foo: false, .. }` not covered
+        //^ 💡 error: missing match arm: `Foo { foo: false, .. }` not covered
     match f {
-        //^ error: missing match arm: `Foo { foo: false, bar: true }` not covered
+        //^ 💡 error: missing match arm: `Foo { foo: false, bar: true }` not covered
         Foo { foo: true, .. } => (),
         Foo { bar: false, .. } => ()
     }
@@ -692,7 +770,7 @@ fn internal_or() {
 fn main() {
     enum Either { A(bool), B }
     match Either::B {
-        //^^^^^^^^^ error: missing match arm: `B` not covered
+        //^^^^^^^^^ 💡 error: missing match arm: `B` not covered
         Either::A(true | false) => (),
     }
 }
@@ -702,8 +780,6 @@ enum Either { A(bool), B }
 
     #[test]
     fn no_panic_at_unimplemented_subpattern_type() {
-        cov_mark::check_count!(validate_match_bailed_out, 1);
-
         check_diagnostics(
             r#"
 struct S { a: char}
@@ -711,10 +787,11 @@ fn main(v: S) {
     match v { S{ a }      => {} }
     match v { S{ a: _x }  => {} }
     match v { S{ a: 'a' } => {} }
+        //^ 💡 error: missing match arm: `S { .. }` not covered
     match v { S{..}       => {} }
     match v { _           => {} }
     match v { }
-        //^ error: missing match arm: type `S` is non-empty
+        //^ 💡 error: missing match arm: type `S` is non-empty
 }
 "#,
         );
@@ -730,7 +807,44 @@ fn main() {
         false     => {}
     }
     match true { _x @ true => {} }
-        //^^^^ error: missing match arm: `false` not covered
+        //^^^^ 💡 error: missing match arm: `false` not covered
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn negative_integer_literals() {
+        check_diagnostics_no_bails(
+            r#"
+fn main(x: i8) {
+    match x {
+        //^ 💡 error: missing match arm: `i8::MIN..=-2_i8` and `0_i8..=i8::MAX` not covered
+        -1 => {}
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn integer_ranges() {
+        check_diagnostics_no_bails(
+            r#"
+fn main(x: u8) {
+    match 5 {
+        //^ 💡 error: missing match arm: `i32::MIN..=9_i32` and `20_i32..=i32::MAX` not covered
+        10 => (),
+        11..20 => (),
+    }
+    match (x,) {
+        (..=9,) => (),
+        (10..,) => (),
+    }
+    match x {
+        //^ 💡 error: missing match arm: `5_u8` not covered
+        0..5 | 6..=255 => (),
+    }
 }
 "#,
         );
@@ -785,12 +899,12 @@ fn _local() {
 fn main() {
     match E::A { _ => {} }
     match E::A {
-        //^^^^ error: missing match arm: `_` not covered
+        //^^^^ 💡 error: missing match arm: `_` not covered
         E::A => {}
         E::B => {}
     }
     match E::A {
-        //^^^^ error: missing match arm: `_` not covered
+        //^^^^ 💡 error: missing match arm: `_` not covered
         E::A | E::B => {}
     }
 }
@@ -798,6 +912,37 @@ fn main() {
         );
     }
 
+    #[test]
+    fn empty_enum_non_exhaustive() {
+        check_diagnostics_no_bails(
+            r#"
+//- minicore: option
+//- /lib.rs crate:lib
+#[non_exhaustive]
+pub enum Never {}
+fn _local(x: Never, y: Option<Never>) {
+    match x {}
+    match y {
+        //^ 💡 error: missing match arm: `Some(_)` not covered
+        None => {}
+    }
+}
+
+//- /main.rs crate:main deps:lib
+use lib::Never;
+fn main(x: Never, y: Option<Never>) {
+    match x {}
+        //^ 💡 error: missing match arm: type `Never` is non-empty
+    match x { _ => {} }
+    match y {
+        //^ 💡 error: missing match arm: `Some(_)` not covered
+        None => {}
+    }
+}
+"#,
+        );
+    }
+
     #[test]
     fn match_guard() {
         check_diagnostics_no_bails(
@@ -809,7 +954,7 @@ fn main() {
         false         => {}
     }
     match true {
-        //^^^^ error: missing match arm: `true` not covered
+        //^^^^ 💡 error: missing match arm: `true` not covered
         true if false => {}
         false         => {}
     }
@@ -835,13 +980,12 @@ fn main() {
 
     #[test]
     fn record_struct_no_such_field() {
-        cov_mark::check_count!(validate_match_bailed_out, 1);
-
-        check_diagnostics(
+        check_diagnostics_no_bails(
             r#"
 struct Foo { }
 fn main(f: Foo) {
     match f { Foo { bar } => () }
+            //^^^^^^^^^^^ error: no such field: `bar`
 }
 "#,
         );
@@ -874,7 +1018,7 @@ enum E {Foo, Bar}
 static __: () = {
     let n: Next<A> = Next(E::Foo);
     match n { Next(E::Foo) => {} }
-    //    ^ error: missing match arm: `Next(Bar)` not covered
+    //    ^ 💡 error: missing match arm: `Next(Bar)` not covered
     match n { Next(E::Foo | E::Bar) => {} }
     match n { Next(E::Foo | _     ) => {} }
     match n { Next(_      | E::Bar) => {} }
@@ -885,6 +1029,361 @@ enum E {Foo, Bar}
         );
     }
 
+    #[test]
+    fn normalize_tuple_column_ty() {
+        check_diagnostics_no_bails(
+            r"
+trait Trait { type Projection; }
+enum E {Foo, Bar}
+struct A;
+impl Trait for A { type Projection = E; }
+fn f<T: Trait<Projection = E>>(t: (T::Projection, bool)) {
+    match t { (E::Foo, _) => {} }
+    //    ^ 💡 error: missing match arm: `(Bar, _)` not covered
+    match t { (E::Foo, _) | (E::Bar, true) => {} }
+    //    ^ 💡 error: missing match arm: `(Bar, false)` not covered
+    match t { (E::Foo | E::Bar, _) => {} }
+}",
+        );
+    }
+
+    #[test]
+    fn normalize_scrutinee_ty() {
+        check_diagnostics_no_bails(
+            r"
+trait Trait { type Assoc; fn f(x: Self::Assoc); }
+enum E {Foo, Bar}
+struct A;
+impl Trait for A {
+    type Assoc = E;
+    fn f(x: Self::Assoc) {
+        match x { E::Foo => {} }
+        //    ^ 💡 error: missing match arm: `Bar` not covered
+        match x {}
+        //    ^ 💡 error: missing match arm: `Foo` and `Bar` not covered
+        match x { E::Foo | E::Bar => {} }
+    }
+}
+fn g(x: <A as Trait>::Assoc) {
+    match x { E::Bar => {} }
+    //    ^ 💡 error: missing match arm: `Foo` not covered
+}",
+        );
+    }
+
+    #[test]
+    fn reveal_opaque_ty_in_defining_scope() {
+        check_diagnostics_no_bails(
+            r"
+//- minicore: sized
+enum E {Foo, Bar}
+fn f(b: bool) -> impl Sized {
+    if b {
+        match f(false) {}
+            //^^^^^^^^ 💡 error: missing match arm: `Foo` and `Bar` not covered
+    }
+    E::Foo
+}
+fn g() {
+    match f(false) {}
+        //^^^^^^^^ 💡 error: missing match arm: type `impl Sized` is non-empty
+}",
+        );
+    }
+
+    #[test]
+    fn reveal_type_alias_impl_trait_in_defining_scope() {
+        check_diagnostics_no_bails(
+            r"
+//- minicore: sized
+mod m {
+    pub enum E {Foo, Bar}
+    pub type Alias = impl Sized;
+    pub fn f() -> Alias {
+        E::Foo
+    }
+    fn g(x: Alias) {
+        match x {}
+            //^ 💡 error: missing match arm: `Foo` and `Bar` not covered
+        match f() {
+            E::Foo => {}
+            E::Bar => {}
+        }
+    }
+    mod inner {
+        fn h() {
+            match super::f() {
+                //^^^^^^^^^^ 💡 error: missing match arm: `Bar` not covered
+                super::E::Foo => {}
+            }
+        }
+    }
+}
+fn i() {
+    match m::f() {}
+        //^^^^^^ 💡 error: missing match arm: type `impl Sized` is non-empty
+}",
+        );
+    }
+
+    #[test]
+    fn slice_patterns() {
+        check_diagnostics_no_bails(
+            r"
+fn f(a: [bool; 2], s: &[bool]) {
+    match a {
+        //^ 💡 error: missing match arm: `[false, _]` not covered
+        [true, _] => {}
+    }
+    match a {
+        [true, ..] | [.., false] => {}
+        [false, true] => {}
+    }
+    match s {
+        //^ 💡 error: missing match arm: `&[_, _, ..]` not covered: slices of length 2 or more
+        [] => {}
+        [_] => {}
+    }
+}",
+        );
+    }
+
+    #[test]
+    fn slice_of_enum_patterns() {
+        check_diagnostics_no_bails(
+            r"
+//- minicore: option
+fn f(a: [Option<bool>; 2]) {
+    match a {
+        //^ 💡 error: missing match arm: `[None, None]` not covered
+        [Some(_), ..] => {}
+        [None, Some(true)] => {}
+    }
+}",
+        );
+    }
+
+    #[test]
+    fn generic_const_array_len() {
+        check_diagnostics_no_bails(
+            r"
+fn f<const N: usize>(a: [bool; N]) {
+    match a {
+        //^ 💡 error: missing match arm: `[_, ..]` not covered
+        [] => {}
+    }
+    match a {
+        [] => {}
+        [_, ..] => {}
+    }
+    match a {
+        [..] => {}
+    }
+}",
+        );
+    }
+
+    #[test]
+    fn self_and_alias_paths() {
+        check_diagnostics_no_bails(
+            r"
+struct S;
+impl S {
+    fn f(self) {
+        match self {
+            Self => {}
+        }
+    }
+}
+enum E { A, B(bool), C { x: bool } }
+type Alias = E;
+impl E {
+    fn f(self) {
+        match self {
+            //^^^^ 💡 error: missing match arm: `Self::B(false)` not covered
+            Self::A => {}
+            Self::B(true) => {}
+            Self::C { .. } => {}
+        }
+    }
+}
+fn g(e: E) {
+    match e {
+        //^ 💡 error: missing match arm: `C { .. }` not covered
+        Alias::A | Alias::B(_) => {}
+    }
+}",
+        );
+    }
+
+    #[test]
+    fn self_variant_witnesses() {
+        check_diagnostics_no_bails(
+            r"
+//- minicore: option
+enum E { A, B }
+impl E {
+    fn f(self, o: Option<Self>) {
+        match o {
+            //^ 💡 error: missing match arm: `Some(Self::B)` not covered
+            None | Some(Self::A) => {}
+        }
+        match Some(true) {
+            //^^^^^^^^^^ 💡 error: missing match arm: `None` not covered
+            Some(_) => {}
+        }
+    }
+}
+fn g(e: E) {
+    match e {
+        //^ 💡 error: missing match arm: `B` not covered
+        E::A => {}
+    }
+}",
+        );
+    }
+
+    #[test]
+    fn duplicate_arms() {
+        check_diagnostics_no_bails(
+            r"
+enum E { A, B, C }
+fn f(e: E, b: bool) {
+    match (e, b) {
+        //^^^^^^ 💡 error: missing match arm: `(C, _)` not covered
+        (E::A, _) => {}
+        (E::A, _) => {}
+        (E::B, true) | (E::B, true) => {}
+        (E::B, false) => {}
+        (E::B, false) => {}
+    }
+}",
+        );
+    }
+
+    #[test]
+    fn fieldless_enum_with_guards_and_or_patterns() {
+        check_diagnostics_no_bails(
+            r"
+enum E { A, B, C, D }
+fn f(e: E, b: bool) {
+    match e {
+        //^ 💡 error: missing match arm: `B` and `D` not covered
+        E::A | E::C => {}
+        E::B if b => {}
+    }
+    match e {
+        E::A | E::B if b => {}
+        E::A | E::B | E::C | E::D => {}
+    }
+    match b {
+        //^ 💡 error: missing match arm: `_` not covered
+        true if b => {}
+    }
+    match b {
+        //^ 💡 error: missing match arm: `true` not covered
+        false => {}
+        true if b => {}
+    }
+}",
+        );
+    }
+
+    #[test]
+    fn approximates_arms_with_many_alternatives() {
+        check_diagnostics_no_bails(
+            r"
+//- minicore: option
+fn f(o: Option<bool>, b: bool) {
+    match (o, b, b, b, b, b, b, b, b, b, b) {
+        //^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^ weak: match has too many alternatives to check exactly, some missing arms may not be reported
+        //^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^ 💡 error: missing match arm: `(None, _, _, _, _, _, _, _, _, _, _)` not covered
+        (
+            Some(true | false),
+            true | false,
+            true | false,
+            true | false,
+            true | false,
+            true | false,
+            true | false,
+            true | false,
+            true | false,
+            true | false,
+            true | false,
+        ) => {}
+    }
+}",
+        );
+    }
+
+    #[test]
+    fn many_nested_witnesses() {
+        check_diagnostics_no_bails(
+            r"
+//- minicore: option
+enum E { A, B, C, D, E }
+fn f(x: Option<E>, y: Option<E>) {
+    match x {
+        //^ 💡 error: missing match arm: `Some(B)`, `Some(C)`, `Some(D)` and 1 more not covered
+        None => {}
+        Some(E::A) => {}
+    }
+    match (x, y) {
+        //^^^^^^ 💡 error: missing match arm: `(Some(B), _)`, `(Some(C), _)`, `(Some(D), _)` and 1 more not covered
+        (None, _) => {}
+        (Some(E::A), None) => {}
+    }
+}",
+        );
+    }
+
+    #[test]
+    fn or_patterns_in_every_column() {
+        check_diagnostics_no_bails(
+            r"
+enum E { A, B, C }
+fn f(x: (E, E, E)) {
+    match x {
+        //^ 💡 error: missing match arm: `(C, C, C)` not covered
+        (E::A | E::B, _, _) => {}
+        (_, E::A | E::B, _) => {}
+        (_, _, E::A | E::B) => {}
+    }
+}",
+        );
+    }
+
+    #[test]
+    fn skip_match_check_attribute() {
+        check_diagnostics_no_bails(
+            r"
+enum E { A, B }
+#[rust_analyzer::skip_match_check]
+mod skipped {
+    fn f(e: super::E) {
+        match e {
+            super::E::A => {}
+        }
+    }
+}
+struct S;
+impl S {
+    #[rust_analyzer::skip_match_check]
+    fn g(e: E) {
+        match e {
+            E::A => {}
+        }
+    }
+    fn h(e: E) {
+        match e {
+            //^ 💡 error: missing match arm: `B` not covered
+            E::A => {}
+        }
+    }
+}",
+        );
+    }
+
     #[test]
     fn binding_mode_by_ref() {
         check_diagnostics_no_bails(
@@ -917,7 +1416,7 @@ enum Enum {
 
 fn f(ty: Enum) {
     match ty {
-        //^^ error: missing match arm: `Type3` not covered
+        //^^ 💡 error: missing match arm: `Type3` not covered
         m!() => (),
     }
 
@@ -931,7 +1430,6 @@ fn f(ty: Enum) {
 
     #[test]
     fn unexpected_ty_fndef() {
-        cov_mark::check!(validate_match_bailed_out);
         check_diagnostics(
             r"
 enum Exp {
@@ -939,12 +1437,54 @@ enum Exp {
 }
 fn f() {
     match __unknown {
+        //^^^^^^^^^ 💡 error: missing match arm: `_` not covered
         Exp::Tuple => {}
     }
 }",
         );
     }
 
+    #[test]
+    fn out_of_range_literal_arm() {
+        check_diagnostics_no_bails(
+            r#"
+fn main(x: (bool, u8)) {
+    match x {
+        //^ 💡 error: missing match arm: `(true, _)` not covered
+        (true, 300) => {}
+        (false, _) => {}
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn malformed_arm_covers_nothing() {
+        cov_mark::check_count!(match_check_error_arm_not_reported, 1);
+        check_diagnostics(
+            r#"
+enum E { A, B }
+fn main(b: bool, e: E) {
+    match (b, e) {
+        //^^^^^^ 💡 error: missing match arm: `(false, _)` not covered
+        (true, E::C) => {}
+        (true, E::A) => {}
+    }
+    match e {
+        //^ 💡 error: missing match arm: `E::B` not covered
+        E::C => {}
+        E::A => {}
+    }
+    match e {
+        _ => {}
+        E::C => {}
+    }
+}
+"#,
+        );
+    }
+
     mod rust_unstable {
         use super::*;
 
@@ -962,7 +1502,28 @@ fn test() {
     match (2, loop {}) {}
     match Result::<!, !>::Ok(loop {}) {}
     match (&loop {}) {} // https://github.com/rust-lang/rust/issues/50642#issuecomment-388234919
-    //    ^^^^^^^^^^ error: missing match arm: type `&!` is non-empty
+    //    ^^^^^^^^^^ 💡 error: missing match arm: type `&!` is non-empty
+}",
+            );
+        }
+
+        #[test]
+        fn rfc_1872_foreign_non_exhaustive_empty_enum() {
+            check_diagnostics_no_bails(
+                r"
+//- minicore: option
+//- /lib.rs crate:lib
+#[non_exhaustive]
+pub enum Never {}
+//- /main.rs crate:main deps:lib
+#![feature(exhaustive_patterns)]
+fn test(x: lib::Never, y: Option<lib::Never>) {
+    match x {}
+    //    ^ 💡 error: missing match arm: type `Never` is non-empty
+    match y {
+        //^ 💡 error: missing match arm: `Some(_)` not covered
+        None => {}
+    }
 }",
             );
         }
@@ -978,17 +1539,98 @@ pub struct PrivatelyUninhabited { private_field: Void }
 enum Void {}
 fn test_local(x: Option<PrivatelyUninhabited>) {
     match x {}
-} //      ^ error: missing match arm: `None` not covered
+} //      ^ 💡 error: missing match arm: `None` not covered
 //- /main.rs crate:main deps:lib
 #![feature(exhaustive_patterns)]
 fn test(x: Option<lib::PrivatelyUninhabited>) {
     match x {}
-    //    ^ error: missing match arm: `None` and `Some(_)` not covered
+    //    ^ 💡 error: missing match arm: `None` and `Some(_)` not covered
 }",
             );
         }
     }
 
+    #[test]
+    fn wildcard_arm_after_last_arm() {
+        check_fix(
+            r#"
+enum E { A, B, C }
+fn main(e: E) {
+    match e$0 {
+        E::A => (),
+        E::B => {} // trailing comment
+        // comment before the closing brace
+    }
+}
+"#,
+            r#"
+enum E { A, B, C }
+fn main(e: E) {
+    match e {
+        E::A => (),
+        E::B => {} // trailing comment
+        _ => todo!(),
+        // comment before the closing brace
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn wildcard_arm_adds_comma() {
+        check_fix(
+            r#"
+fn main(b: bool) {
+    let _ = match b$0 {
+        true => 1
+    };
+}
+"#,
+            r#"
+fn main(b: bool) {
+    let _ = match b {
+        true => 1,
+        _ => todo!(),
+    };
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn wildcard_arm_in_empty_match() {
+        check_fix(
+            r#"
+fn main(b: bool) {
+    match b$0 {}
+}
+"#,
+            r#"
+fn main(b: bool) {
+    match b {
+        _ => todo!(),
+    }
+}
+"#,
+        );
+        check_fix(
+            r#"
+fn main(b: bool) {
+    match b$0 {
+    }
+}
+"#,
+            r#"
+fn main(b: bool) {
+    match b {
+        _ => todo!(),
+    }
+}
+"#,
+        );
+    }
+
     mod false_negatives {
         //! The implementation of match checking here is a work in progress. As we roll this out, we
         //! prefer false negatives to false positives (ideally there would be no false positives). This
@@ -1001,31 +1643,13 @@ mod false_negatives {
         //!   2. It ensures the code doesn't panic when handling these cases.
         use super::*;
 
-        #[test]
-        fn integers() {
-            cov_mark::check_count!(validate_match_bailed_out, 1);
-
-            // We don't currently check integer exhaustiveness.
-            check_diagnostics(
-                r#"
-fn main() {
-    match 5 {
-        10 => (),
-        11..20 => (),
-    }
-}
-"#,
-            );
-        }
-
         #[test]
         fn reference_patterns_at_top_level() {
-            cov_mark::check_count!(validate_match_bailed_out, 1);
-
             check_diagnostics(
                 r#"
 fn main() {
     match &false {
+        //^^^^^^ 💡 error: missing match arm: `&_` not covered
         &true => {}
     }
 }
@@ -1035,7 +1659,7 @@ fn main() {
 
         #[test]
         fn reference_patterns_in_fields() {
-            cov_mark::check_count!(validate_match_bailed_out, 2);
+            cov_mark::check_count!(validate_match_bailed_out, 1);
             check_diagnostics(
                 r#"
 fn main() {
@@ -1043,6 +1667,7 @@ fn main() {
         (true,) => {}
     }
     match (&false,) {
+        //^^^^^^^^^ 💡 error: missing match arm: `(&_,)` not covered
         (&true,) => {}
     }
 }
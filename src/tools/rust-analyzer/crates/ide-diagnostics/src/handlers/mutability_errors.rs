@@ -292,6 +292,24 @@ fn main() {
         );
     }
 
+    #[test]
+    fn raw_borrow() {
+        check_diagnostics(
+            r#"
+fn main() {
+    let mut x = 2;
+    let _p = &raw mut x;
+    let y = 5;
+    let _q = &raw mut y;
+           //^^^^^^^^^^ 💡 error: cannot mutate immutable variable `y`
+    let mut z = 7;
+      //^^^^^ 💡 weak: variable does not need to be mutable
+    let _r = &raw const z;
+}
+"#,
+        );
+    }
+
     #[test]
     fn mutable_reference() {
         check_diagnostics(
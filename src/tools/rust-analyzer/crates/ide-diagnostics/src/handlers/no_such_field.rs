@@ -1,5 +1,7 @@
+use either::Either;
 use hir::{db::ExpandDatabase, HasSource, HirDisplay, Semantics};
 use ide_db::{base_db::FileId, source_change::SourceChange, RootDatabase};
+use itertools::Itertools;
 use syntax::{
     ast::{self, edit::IndentLevel, make},
     AstNode,
@@ -20,6 +22,26 @@ pub(crate) fn no_such_field(ctx: &DiagnosticsContext<'_>, d: &hir::NoSuchField)
     .with_fixes(fixes(ctx, d))
 }
 
+// Diagnostic: no-such-field
+//
+// This diagnostic is also triggered if a record pattern names fields its structure doesn't have.
+pub(crate) fn no_such_record_pat_fields(
+    ctx: &DiagnosticsContext<'_>,
+    d: &hir::NoSuchRecordPatFields,
+) -> Diagnostic {
+    let s = if d.fields.len() == 1 { "" } else { "s" };
+    Diagnostic::new(
+        "no-such-field",
+        format!("no such field{s}: {}", d.fields.iter().map(|it| format!("`{it}`")).join(", ")),
+        ctx.sema
+            .diagnostics_display_range(d.pat.clone().map(|it| match it {
+                Either::Left(it) => it.into(),
+                Either::Right(it) => it.into(),
+            }))
+            .range,
+    )
+}
+
 fn fixes(ctx: &DiagnosticsContext<'_>, d: &hir::NoSuchField) -> Option<Vec<Assist>> {
     let root = ctx.sema.db.parse_or_expand(d.field.file_id)?;
     missing_record_expr_field_fixes(
@@ -130,6 +152,46 @@ fn new() -> S {
 "#,
         );
     }
+    #[test]
+    fn no_such_field_in_record_pat() {
+        check_diagnostics(
+            r#"
+struct S { foo: bool }
+enum E { A { foo: bool }, B }
+fn f(s: S, e: E) {
+    match s {
+        S { foo: true, bar, .. } => (),
+      //^^^^^^^^^^^^^^^^^^^^^^^^ error: no such field: `bar`
+        S { foo: false } => (),
+    }
+    match e {
+        //^ 💡 error: missing match arm: `A { foo: false }` not covered
+        E::A { foo: true, bar: _, baz: _ } => (),
+      //^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^ error: no such fields: `bar`, `baz`
+        E::B => (),
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn no_such_field_in_record_pat_outside_match() {
+        check_diagnostics(
+            r#"
+struct S { foo: bool }
+fn f(S { foo, bar }: S) {
+   //^^^^^^^^^^^^^^ error: no such field: `bar`
+    let S { foo, baz } = S { foo: true };
+      //^^^^^^^^^^^^^^ error: no such field: `baz`
+    let s = S { foo: false };
+    if let S { foo: true, qux: _ } = s {}
+         //^^^^^^^^^^^^^^^^^^^^^^^ error: no such field: `qux`
+}
+"#,
+        );
+    }
+
     #[test]
     fn no_such_field_with_feature_flag_diagnostics() {
         check_diagnostics(
@@ -0,0 +1,75 @@
+use either::Either;
+
+use crate::{Diagnostic, DiagnosticsContext, Severity};
+
+// Diagnostic: overlapping-range-endpoints
+//
+// This diagnostic is triggered if a range pattern shares only an endpoint with a range pattern
+// before it that can match the same values, like `5..=10` after `0..=5`, which likely meant to
+// leave the endpoint out.
+pub(crate) fn overlapping_range_endpoints(
+    ctx: &DiagnosticsContext<'_>,
+    d: &hir::OverlappingRangeEndpoints,
+) -> Diagnostic {
+    let overlaps = d.overlaps.iter().map(|it| format!("`{it}`")).collect::<Vec<_>>().join(", ");
+    Diagnostic::new(
+        "overlapping-range-endpoints",
+        format!("multiple patterns overlap on their endpoints: this range overlaps on {overlaps}"),
+        ctx.sema
+            .diagnostics_display_range(d.pat.clone().map(|it| match it {
+                Either::Left(it) => it.into(),
+                Either::Right(it) => it.into(),
+            }))
+            .range,
+    )
+    .severity(Severity::WeakWarning)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::check_diagnostics;
+
+    #[test]
+    fn shared_endpoints() {
+        check_diagnostics(
+            r#"
+fn main(x: u8, y: i8) {
+    match x {
+        0..=5 | 5..=10 => {}
+              //^^^^^^ weak: multiple patterns overlap on their endpoints: this range overlaps on `5_u8`
+        20..=30 => {}
+        10..=20 => {}
+      //^^^^^^^ weak: multiple patterns overlap on their endpoints: this range overlaps on `10_u8`, `20_u8`
+        _ => {}
+    }
+    match y {
+        ..=-1 | -1..=0 => {}
+              //^^^^^^ weak: multiple patterns overlap on their endpoints: this range overlaps on `-1_i8`
+        _ => {}
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn no_overlap_for_disjoint_contained_or_exclusive_ranges() {
+        check_diagnostics(
+            r#"
+fn main(x: u8) {
+    match x {
+        0..=5 | 6..=10 => {}
+        20..30 => {}
+        30 | 30..=40 => {}
+        _ => {}
+    }
+    match (x, true) {
+        (0..=5, true) => {}
+        (5..=10, false) => {}
+        _ => {}
+    }
+}
+"#,
+        );
+    }
+}
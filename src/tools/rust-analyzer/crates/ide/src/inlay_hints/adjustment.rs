@@ -119,8 +119,12 @@ pub(super) fn hints(
                     }
                     PointerCast::ArrayToPointer => ("<array-ptr-to-element-ptr>", ""),
                     PointerCast::Unsize => ("<unsize>", "unsize"),
+                    PointerCast::TraitUpcasting { .. } => ("<trait-upcast>", "trait upcasting"),
                 }
             }
+            Adjust::DynStar if config.adjustment_hints == AdjustmentHints::Always => {
+                ("<dyn-star>", "dyn star")
+            }
             _ => continue,
         };
         acc.push(InlayHint {
@@ -52,10 +52,13 @@ macro_rules! eprintln {
 mod status;
 mod syntax_highlighting;
 mod syntax_tree;
+mod taken_match_arms;
 mod typing;
+mod unreachable_patterns;
 mod view_crate_graph;
 mod view_hir;
 mod view_mir;
+mod view_match_patterns;
 mod view_item_tree;
 mod shuffle_crate_graph;
 
@@ -101,8 +104,10 @@ macro_rules! eprintln {
         tags::{Highlight, HlMod, HlMods, HlOperator, HlPunct, HlTag},
         HighlightConfig, HlRange,
     },
+    taken_match_arms::TakenMatchArm,
+    unreachable_patterns::UnreachablePattern,
 };
-pub use hir::{Documentation, Semantics};
+pub use hir::{DecisionTreeFormat, Documentation, Semantics, UnreachablePatternKind};
 pub use ide_assists::{
     Assist, AssistConfig, AssistId, AssistKind, AssistResolveStrategy, SingleResolve,
 };
@@ -313,6 +318,18 @@ pub fn view_mir(&self, position: FilePosition) -> Cancellable<String> {
         self.with_db(|db| view_mir::view_mir(db, position))
     }
 
+    pub fn view_match_patterns(&self, frange: FileRange) -> Cancellable<String> {
+        self.with_db(|db| view_match_patterns::view_match_patterns(db, frange))
+    }
+
+    pub fn view_match_decision_tree(
+        &self,
+        position: FilePosition,
+        format: DecisionTreeFormat,
+    ) -> Cancellable<String> {
+        self.with_db(|db| view_match_patterns::view_match_decision_tree(db, position, format))
+    }
+
     pub fn view_item_tree(&self, file_id: FileId) -> Cancellable<String> {
         self.with_db(|db| view_item_tree::view_item_tree(db, file_id))
     }
@@ -563,6 +580,18 @@ pub fn highlight_as_html(&self, file_id: FileId, rainbow: bool) -> Cancellable<S
         self.with_db(|db| syntax_highlighting::highlight_as_html(db, file_id, rainbow))
     }
 
+    /// Computes the arms of the `match` expressions of the file, and the alternatives of the
+    /// or-patterns in them, that can never be reached.
+    pub fn unreachable_patterns(&self, file_id: FileId) -> Cancellable<Vec<UnreachablePattern>> {
+        self.with_db(|db| unreachable_patterns::unreachable_patterns(db, file_id))
+    }
+
+    /// Computes the arms taken by the `match` expressions of the file whose scrutinee is known at
+    /// compile time.
+    pub fn taken_match_arms(&self, file_id: FileId) -> Cancellable<Vec<TakenMatchArm>> {
+        self.with_db(|db| taken_match_arms::taken_match_arms(db, file_id))
+    }
+
     /// Computes completions at the given position.
     pub fn completions(
         &self,
@@ -0,0 +1,125 @@
+//! The arms taken by the `match` expressions of a file whose scrutinee is known at compile time,
+//! for editors to highlight them and render the other arms dimmed like unused code.
+
+use hir::Semantics;
+use ide_db::{base_db::FileId, FxHashSet, RootDatabase};
+use syntax::{ast, AstNode, TextRange};
+
+use crate::view_match_patterns::owner;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TakenMatchArm {
+    /// The range of the taken arm.
+    pub taken: TextRange,
+    /// The ranges of the other arms of the `match`.
+    pub not_taken: Vec<TextRange>,
+}
+
+pub(crate) fn taken_match_arms(db: &RootDatabase, file_id: FileId) -> Vec<TakenMatchArm> {
+    let sema = Semantics::new(db);
+    let source_file = sema.parse(file_id);
+    let owners: FxHashSet<_> = source_file
+        .syntax()
+        .descendants()
+        .filter_map(ast::MatchExpr::cast)
+        .filter_map(|match_expr| owner(&sema, &match_expr))
+        .collect();
+
+    let arm_range = |pat: hir::InFile<syntax::AstPtr<ast::Pat>>| {
+        // Patterns in macro expansions have no range in the file.
+        if pat.file_id != file_id.into() {
+            return None;
+        }
+        let pat = pat.value.to_node(source_file.syntax());
+        Some(pat.syntax().parent().filter(|it| ast::MatchArm::can_cast(it.kind()))?.text_range())
+    };
+    let mut res: Vec<_> = owners
+        .into_iter()
+        .flat_map(|owner| owner.taken_match_arms(db))
+        .filter_map(|it| {
+            let taken = arm_range(it.taken)?;
+            let not_taken = it.not_taken.into_iter().filter_map(arm_range).collect();
+            Some(TakenMatchArm { taken, not_taken })
+        })
+        .collect();
+    res.sort_by_key(|it| it.taken.start());
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use expect_test::{expect, Expect};
+
+    use crate::fixture;
+
+    fn check(ra_fixture: &str, expect: Expect) {
+        let (analysis, file_id) = fixture::file(ra_fixture);
+        let text = analysis.file_text(file_id).unwrap();
+        let actual = analysis
+            .taken_match_arms(file_id)
+            .unwrap()
+            .into_iter()
+            .map(|it| {
+                let not_taken = it.not_taken.iter().map(|&range| &text[range]).collect::<Vec<_>>();
+                format!("taken: {}, not taken: {}\n", &text[it.taken], not_taken.join("; "))
+            })
+            .collect::<String>();
+        expect.assert_eq(&actual);
+    }
+
+    #[test]
+    fn taken_arms_of_literals_and_consts() {
+        check(
+            r#"
+//- minicore: option
+enum E { A, B }
+const YES: bool = true;
+const N: u8 = 2;
+fn f() {
+    match 3 {
+        1 => {}
+        3 | 4 => {}
+        _ => {}
+    }
+    match YES {
+        false => {}
+        true => {}
+    }
+    match (Some(E::B), N) {
+        (Some(E::A), _) => {}
+        (_, 1) => {}
+        (Some(_), N) if true => {}
+        _ => {}
+    }
+}
+"#,
+            expect![[r#"
+                taken: 3 | 4 => {}, not taken: 1 => {}; _ => {}
+                taken: true => {}, not taken: false => {}
+                taken: (Some(_), N) if true => {}, not taken: (Some(E::A), _) => {}; (_, 1) => {}; _ => {}
+            "#]],
+        );
+    }
+
+    #[test]
+    fn no_taken_arm_for_unknown_values() {
+        check(
+            r#"
+fn f(x: u8, b: bool) {
+    match x {
+        _ => {}
+    }
+    match (3, x) {
+        (3, 1) => {}
+        _ => {}
+    }
+    match true {
+        true if b => {}
+        _ => {}
+    }
+}
+"#,
+            expect![[""]],
+        );
+    }
+}
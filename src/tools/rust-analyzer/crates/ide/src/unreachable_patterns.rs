@@ -0,0 +1,329 @@
+//! The arms of the `match` expressions of a file that can never be reached, and the alternatives of
+//! or-patterns that can never be reached, for editors to render them dimmed like unused code, and
+//! the ranges to delete to remove them.
+
+use std::iter;
+
+use hir::{Semantics, UnreachablePatternKind};
+use ide_db::{base_db::FileId, FxHashSet, RootDatabase};
+use syntax::{
+    ast, AstNode, Direction, NodeOrToken, SyntaxElement,
+    SyntaxKind::{COMMENT, WHITESPACE},
+    TextRange, T,
+};
+
+use crate::view_match_patterns::owner;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnreachablePattern {
+    /// The whole arm for an unreachable arm, the pattern for an or-pattern alternative.
+    pub range: TextRange,
+    pub kind: UnreachablePatternKind,
+    /// The range to delete to remove the arm or the alternative, including its separator, the
+    /// comments attached to it and the whitespace before it. Ranges are meant to be deleted one
+    /// at a time: those of adjacent alternatives overlap.
+    pub delete: TextRange,
+}
+
+pub(crate) fn unreachable_patterns(db: &RootDatabase, file_id: FileId) -> Vec<UnreachablePattern> {
+    let sema = Semantics::new(db);
+    let source_file = sema.parse(file_id);
+    let owners: FxHashSet<_> = source_file
+        .syntax()
+        .descendants()
+        .filter_map(ast::MatchExpr::cast)
+        .filter_map(|match_expr| owner(&sema, &match_expr))
+        .collect();
+
+    let mut res: Vec<_> = owners
+        .into_iter()
+        .flat_map(|owner| owner.unreachable_patterns(db))
+        // Patterns in macro expansions have no range in the file.
+        .filter(|it| it.pat.file_id == file_id.into())
+        .map(|it| {
+            let pat = it.pat.value.to_node(source_file.syntax());
+            let arm = match it.kind {
+                UnreachablePatternKind::Arm => pat.syntax().parent().and_then(ast::MatchArm::cast),
+                UnreachablePatternKind::OrAlternative => None,
+            };
+            // A nested or-pattern is an alternative together with its parentheses.
+            let pat = iter::successors(Some(pat), |it| {
+                it.syntax().parent().and_then(ast::ParenPat::cast).map(ast::Pat::ParenPat)
+            })
+            .last()
+            .unwrap();
+            let (range, delete) = match arm {
+                Some(arm) => (arm.syntax().text_range(), arm_deletion_range(&arm)),
+                None => (pat.syntax().text_range(), alternative_deletion_range(&pat)),
+            };
+            UnreachablePattern { range, kind: it.kind, delete }
+        })
+        .collect();
+    res.sort_by_key(|it| it.range.start());
+    res
+}
+
+/// The arm with its comma, the comments on the lines right above it and on the same line after
+/// it, and the whitespace before all of that, so that no blank line is left behind.
+fn arm_deletion_range(arm: &ast::MatchArm) -> TextRange {
+    let mut first = SyntaxElement::from(arm.syntax().clone());
+    while let Some(comment) = first
+        .prev_sibling_or_token()
+        .filter(|ws| ws.kind() == WHITESPACE && ws.to_string().matches('\n').count() == 1)
+        .and_then(|ws| ws.prev_sibling_or_token())
+        .filter(|it| it.kind() == COMMENT && starts_line(it))
+    {
+        first = comment;
+    }
+    if let Some(ws) = first.prev_sibling_or_token().filter(|it| it.kind() == WHITESPACE) {
+        first = ws;
+    }
+
+    let mut last = SyntaxElement::from(arm.syntax().clone());
+    if let Some(comment) = last
+        .next_sibling_or_token()
+        .filter(|ws| ws.kind() == WHITESPACE && !ws.to_string().contains('\n'))
+        .and_then(|ws| ws.next_sibling_or_token())
+        .filter(|it| it.kind() == COMMENT)
+    {
+        last = comment;
+    }
+    TextRange::new(first.text_range().start(), last.text_range().end())
+}
+
+/// Whether there is only whitespace between the start of the line and `element`.
+fn starts_line(element: &SyntaxElement) -> bool {
+    match element.prev_sibling_or_token() {
+        Some(NodeOrToken::Token(ws)) if ws.kind() == WHITESPACE => ws.text().contains('\n'),
+        _ => false,
+    }
+}
+
+/// The alternative with the `|` before it, or after it for the first alternative.
+fn alternative_deletion_range(pat: &ast::Pat) -> TextRange {
+    let range = pat.syntax().text_range();
+    let sibling_pat = |direction| {
+        pat.syntax()
+            .siblings_with_tokens(direction)
+            .skip(1)
+            .skip_while(|it| it.kind() == WHITESPACE || it.kind() == COMMENT)
+            .skip_while(|it| it.kind() == T![|])
+            .skip_while(|it| it.kind() == WHITESPACE || it.kind() == COMMENT)
+            .next()
+            .and_then(|it| it.into_node())
+            .filter(|it| ast::Pat::can_cast(it.kind()))
+    };
+    match sibling_pat(Direction::Prev) {
+        Some(prev) => TextRange::new(prev.text_range().end(), range.end()),
+        None => match sibling_pat(Direction::Next) {
+            Some(next) => TextRange::new(range.start(), next.text_range().start()),
+            None => range,
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use expect_test::{expect, Expect};
+
+    use crate::fixture;
+
+    fn check(ra_fixture: &str, expect: Expect) {
+        let (analysis, file_id) = fixture::file(ra_fixture);
+        let text = analysis.file_text(file_id).unwrap();
+        let actual = analysis
+            .unreachable_patterns(file_id)
+            .unwrap()
+            .into_iter()
+            .map(|it| format!("{:?} {} delete {:?}\n", it.kind, &text[it.range], &text[it.delete]))
+            .collect::<String>();
+        expect.assert_eq(&actual);
+    }
+
+    #[test]
+    fn unreachable_arms_and_alternatives() {
+        check(
+            r#"
+//- minicore: option
+enum E { A, B, C }
+fn f(e: E, x: Option<E>) {
+    match e {
+        E::A | E::B => {}
+        E::B | E::C => {}
+        E::A => {}
+        _ => {}
+    }
+    match x {
+        Some(E::A | E::A) => {}
+        None => {}
+        _ => {}
+        Some(_) => {}
+    }
+}
+"#,
+            expect![[r#"
+                OrAlternative E::B delete "E::B | "
+                Arm E::A => {} delete "\n        E::A => {}"
+                Arm _ => {} delete "\n        _ => {}"
+                OrAlternative E::A delete " | E::A"
+                Arm Some(_) => {} delete "\n        Some(_) => {}"
+            "#]],
+        );
+    }
+
+    #[test]
+    fn deletion_ranges_include_comments_and_commas() {
+        check(
+            r#"
+enum E { A, B }
+fn f(e: E) {
+    let _ = match e {
+        E::A => 1,
+        // Never reached.
+        // Really.
+        E::A => 2, // trailing
+
+        // Not attached.
+
+        E::A => 3,
+        E::B => 4,
+    };
+    let _ = match e { E::A | E::B => 1, E::B | E::A => 2 };
+}
+"#,
+            expect![[r#"
+                Arm E::A => 2, delete "\n        // Never reached.\n        // Really.\n        E::A => 2, // trailing"
+                Arm E::A => 3, delete "\n\n        E::A => 3,"
+                Arm E::B | E::A => 2 delete " E::B | E::A => 2"
+            "#]],
+        );
+    }
+
+    #[test]
+    fn unreachable_alternatives_behind_references() {
+        check(
+            r#"
+//- minicore: option
+enum E { A, B }
+fn f(x: Option<&E>) {
+    match x {
+        Some(E::A | E::A) => {}
+        _ => {}
+    }
+}
+"#,
+            expect![[r#"
+                OrAlternative E::A delete " | E::A"
+            "#]],
+        );
+    }
+
+    #[test]
+    fn unreachable_nested_alternatives() {
+        check(
+            r#"
+//- minicore: option, result
+enum E { A, B, C }
+fn f(x: Option<u8>, r: Result<E, ()>, e: E) {
+    match x {
+        Some(1 | (2 | 1)) => {}
+        _ => {}
+    }
+    match r {
+        Ok(E::A | E::B) | Ok(E::A) => {}
+        _ => {}
+    }
+    match e {
+        E::A | (E::B | E::A) | (E::A | (E::B)) => {}
+        _ => {}
+    }
+}
+"#,
+            expect![[r#"
+                OrAlternative 1 delete " | 1"
+                OrAlternative Ok(E::A) delete " | Ok(E::A)"
+                OrAlternative E::A delete " | E::A"
+                OrAlternative (E::A | (E::B)) delete " | (E::A | (E::B))"
+            "#]],
+        );
+    }
+
+    #[test]
+    fn unreachable_range_alternatives() {
+        check(
+            r#"
+fn f(x: u8) {
+    match x {
+        0..=10 | 3..=4 => {}
+        5 | 20..=30 | 25..30 => {}
+        _ => {}
+    }
+}
+"#,
+            expect![[r#"
+                OrAlternative 3..=4 delete " | 3..=4"
+                OrAlternative 5 delete "5 | "
+                OrAlternative 25..30 delete " | 25..30"
+            "#]],
+        );
+    }
+
+    #[test]
+    fn unreachable_string_patterns() {
+        check(
+            r#"
+const S: &str = "same value";
+mod m {
+    pub const T: &str = "other";
+}
+fn f(s: &str) {
+    match s {
+        S => {}
+        "same value" => {}
+        "other" | m::T => {}
+        _ => {}
+    }
+}
+"#,
+            expect![[r#"
+                Arm "same value" => {} delete "\n        \"same value\" => {}"
+                OrAlternative m::T delete " | m::T"
+            "#]],
+        );
+    }
+
+    #[test]
+    fn no_unreachable_patterns_with_unresolved_patterns() {
+        check(
+            r#"
+enum E { A, B }
+fn f(e: E) {
+    match e {
+        E::Unresolved => {}
+        E::A => {}
+        E::B => {}
+    }
+}
+"#,
+            expect![[""]],
+        );
+    }
+
+    #[test]
+    fn no_unreachable_patterns_after_approximated_arm() {
+        // The first arm has too many alternatives and is checked as `(true, _, .., _)`, which
+        // would make the second arm look unreachable.
+        check(
+            r#"
+fn f(x: (bool, u8, u8, u8, u8, u8, u8, u8, u8, u8, u8, u8)) {
+    match x {
+        (true, 1 | 2, 1 | 2, 1 | 2, 1 | 2, 1 | 2, 1 | 2, 1 | 2, 1 | 2, 1 | 2, 1 | 2, 1 | 2) => {}
+        (true, 3, _, _, _, _, _, _, _, _, _, _) => {}
+        _ => {}
+    }
+}
+"#,
+            expect![[""]],
+        );
+    }
+}
@@ -0,0 +1,78 @@
+//! Shows the patterns of the arms of the `match` expressions starting in a range, or of the
+//! innermost one containing its start, as lowered for match checking. When match checking reports
+//! wrong diagnostics, this tells whether the patterns were lowered wrong. Also exports the
+//! decision tree of the innermost `match` containing a position, to visualize it.
+
+use hir::{DecisionTreeFormat, DefWithBody, InFile, Semantics};
+use ide_db::{
+    base_db::{FilePosition, FileRange},
+    LineIndexDatabase, RootDatabase,
+};
+use stdx::format_to;
+use syntax::{algo::find_node_at_offset, ast, AstNode};
+
+pub(crate) fn view_match_patterns(db: &RootDatabase, frange: FileRange) -> String {
+    let sema = Semantics::new(db);
+    let source_file = sema.parse(frange.file_id);
+    let line_index = db.line_index(frange.file_id);
+
+    let mut matches = source_file
+        .syntax()
+        .descendants()
+        .filter_map(ast::MatchExpr::cast)
+        .filter(|it| frange.range.contains_inclusive(it.syntax().text_range().start()))
+        .collect::<Vec<_>>();
+    if matches.is_empty() {
+        matches.extend(find_node_at_offset::<ast::MatchExpr>(
+            source_file.syntax(),
+            frange.range.start(),
+        ));
+    }
+    if matches.is_empty() {
+        return "No match expression here".to_string();
+    }
+
+    let mut buf = String::new();
+    for match_expr in matches {
+        let line = line_index.line_col(match_expr.syntax().text_range().start()).line + 1;
+        let scrutinee = match_expr.expr().map(|it| it.syntax().text().to_string());
+        format_to!(buf, "match {} (line {line})\n", scrutinee.as_deref().unwrap_or("<missing>"));
+        let patterns = owner(&sema, &match_expr).and_then(|owner| {
+            owner.debug_match_patterns(db, InFile::new(frange.file_id.into(), &match_expr))
+        });
+        buf.push_str(patterns.as_deref().unwrap_or("Not inside a function body\n"));
+    }
+    buf
+}
+
+pub(crate) fn view_match_decision_tree(
+    db: &RootDatabase,
+    position: FilePosition,
+    format: DecisionTreeFormat,
+) -> String {
+    let sema = Semantics::new(db);
+    let source_file = sema.parse(position.file_id);
+    let Some(match_expr) =
+        find_node_at_offset::<ast::MatchExpr>(source_file.syntax(), position.offset)
+    else {
+        return "No match expression here".to_string();
+    };
+    owner(&sema, &match_expr)
+        .and_then(|owner| {
+            owner.match_decision_tree(db, InFile::new(position.file_id.into(), &match_expr), format)
+        })
+        .unwrap_or_else(|| "Not inside a function body".to_string())
+}
+
+/// The def whose body contains `match_expr`.
+pub(crate) fn owner(
+    sema: &Semantics<'_, RootDatabase>,
+    match_expr: &ast::MatchExpr,
+) -> Option<DefWithBody> {
+    match_expr.syntax().ancestors().filter_map(ast::Item::cast).find_map(|item| match item {
+        ast::Item::Fn(it) => Some(sema.to_def(&it)?.into()),
+        ast::Item::Const(it) => Some(sema.to_def(&it)?.into()),
+        ast::Item::Static(it) => Some(sema.to_def(&it)?.into()),
+        _ => None,
+    })
+}
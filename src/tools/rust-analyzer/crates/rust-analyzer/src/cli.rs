@@ -7,6 +7,8 @@
 mod highlight;
 mod analysis_stats;
 mod diagnostics;
+mod match_check_diff;
+mod view_match_patterns;
 mod ssr;
 mod lsif;
 mod scip;
@@ -74,7 +74,7 @@ pub fn run(self) -> anyhow::Result<()> {
     }
 }
 
-fn all_modules(db: &dyn HirDatabase) -> Vec<Module> {
+pub(super) fn all_modules(db: &dyn HirDatabase) -> Vec<Module> {
     let mut worklist: Vec<_> =
         Crate::all(db).into_iter().map(|krate| krate.root_module(db)).collect();
     let mut modules = Vec::new();
@@ -92,6 +92,31 @@
             optional --disable-proc-macros
         }
 
+        /// Compare the match checking diagnostics with the errors of `cargo check`.
+        cmd match-check-diff {
+            /// Directory with Cargo.toml.
+            required path: PathBuf
+
+            /// Don't run build scripts or load `OUT_DIR` values by running `cargo check` before analysis.
+            optional --disable-build-scripts
+            /// Don't use expand proc macros.
+            optional --disable-proc-macros
+        }
+
+        /// Print the patterns of the matches on a line as lowered for match checking, with their
+        /// types.
+        cmd view-match-patterns {
+            /// Directory with Cargo.toml.
+            required path: PathBuf
+            /// `<file>:<line>` of the matches, the file relative to the current directory.
+            required location: String
+
+            /// Don't run build scripts or load `OUT_DIR` values by running `cargo check` before analysis.
+            optional --disable-build-scripts
+            /// Don't use expand proc macros.
+            optional --disable-proc-macros
+        }
+
         cmd ssr {
             /// A structured search replace rule (`$a.foo($b) ==> bar($a, $b)`)
             repeated rule: SsrRule
@@ -137,6 +162,8 @@ pub enum RustAnalyzerCmd {
     Highlight(Highlight),
     AnalysisStats(AnalysisStats),
     Diagnostics(Diagnostics),
+    MatchCheckDiff(MatchCheckDiff),
+    ViewMatchPatterns(ViewMatchPatterns),
     Ssr(Ssr),
     Search(Search),
     ProcMacro(ProcMacro),
@@ -188,6 +215,23 @@ pub struct Diagnostics {
     pub disable_proc_macros: bool,
 }
 
+#[derive(Debug)]
+pub struct MatchCheckDiff {
+    pub path: PathBuf,
+
+    pub disable_build_scripts: bool,
+    pub disable_proc_macros: bool,
+}
+
+#[derive(Debug)]
+pub struct ViewMatchPatterns {
+    pub path: PathBuf,
+    pub location: String,
+
+    pub disable_build_scripts: bool,
+    pub disable_proc_macros: bool,
+}
+
 #[derive(Debug)]
 pub struct Ssr {
     pub rule: Vec<SsrRule>,
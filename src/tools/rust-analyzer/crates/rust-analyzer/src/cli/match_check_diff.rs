@@ -0,0 +1,160 @@
+//! Compare the match checking diagnostics of all modules in a project with the errors `cargo check`
+//! reports for them, to measure the false positives and false negatives of match checking.
+
+use std::{
+    collections::BTreeMap,
+    env,
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+};
+
+use anyhow::Context;
+use ide::{AssistResolveStrategy, DiagnosticsConfig};
+use ide_db::base_db::SourceDatabaseExt;
+use project_model::{CargoConfig, ProjectManifest, ProjectWorkspace, RustLibSource};
+use rustc_hash::FxHashSet;
+use vfs::AbsPathBuf;
+
+use crate::cli::{
+    diagnostics::all_modules,
+    flags,
+    load_cargo::{load_workspace, LoadCargoConfig, ProcMacroServerChoice},
+};
+
+/// Our match checking diagnostics, and the rustc errors they correspond to.
+const MATCH_DIAGNOSTICS: &[(&str, &str)] = &[("missing-match-arm", "E0004")];
+
+/// A diagnostic, by the rustc error code it corresponds to, the file it is in and its line
+/// (1-based), mapped to its message.
+type Reported = BTreeMap<(&'static str, PathBuf, u32), String>;
+
+impl flags::MatchCheckDiff {
+    pub fn run(self) -> anyhow::Result<()> {
+        let mut cargo_config = CargoConfig::default();
+        cargo_config.sysroot = Some(RustLibSource::Discover);
+        let no_progress = &|_| ();
+
+        let path = AbsPathBuf::assert(env::current_dir()?.join(&self.path));
+        let manifest = ProjectManifest::discover_single(&path)?;
+        let workspace = ProjectWorkspace::load(manifest, &cargo_config, no_progress)?;
+        let workspace_root = match &workspace {
+            ProjectWorkspace::Cargo { cargo, .. } => {
+                PathBuf::from(cargo.workspace_root().to_path_buf())
+            }
+            _ => anyhow::bail!("`match-check-diff` only supports cargo workspaces"),
+        };
+        let load_cargo_config = LoadCargoConfig {
+            load_out_dirs_from_check: !self.disable_build_scripts,
+            with_proc_macro_server: if self.disable_proc_macros {
+                ProcMacroServerChoice::None
+            } else {
+                ProcMacroServerChoice::Sysroot
+            },
+            prefill_caches: false,
+        };
+        let (host, vfs, _proc_macro) =
+            load_workspace(workspace, &cargo_config.extra_env, &load_cargo_config)?;
+        let db = host.raw_database();
+        let analysis = host.analysis();
+
+        let mut ours = Reported::new();
+        let mut too_complex = 0;
+        let mut visited_files = FxHashSet::default();
+        for module in all_modules(db) {
+            let file_id = module.definition_source(db).file_id.original_file(db);
+            let source_root = db.source_root(db.file_source_root(file_id));
+            if source_root.is_library || !visited_files.insert(file_id) {
+                continue;
+            }
+            let Some(path) =
+                vfs.file_path(file_id).as_path().map(|it| PathBuf::from(it.to_path_buf()))
+            else {
+                continue;
+            };
+            let line_index = analysis.file_line_index(file_id).unwrap();
+            let diagnostics = analysis
+                .diagnostics(
+                    &DiagnosticsConfig::test_sample(),
+                    AssistResolveStrategy::None,
+                    file_id,
+                )
+                .unwrap();
+            for diagnostic in diagnostics {
+                if diagnostic.code.as_str() == "match-check-too-complex" {
+                    too_complex += 1;
+                }
+                let Some(&(_, rustc_code)) =
+                    MATCH_DIAGNOSTICS.iter().find(|(code, _)| *code == diagnostic.code.as_str())
+                else {
+                    continue;
+                };
+                let line = line_index.line_col(diagnostic.range.start()).line + 1;
+                ours.insert((rustc_code, path.clone(), line), diagnostic.message);
+            }
+        }
+
+        let rustc = rustc_errors(&workspace_root)?;
+
+        let mut divergences = 0;
+        for ((code, path, line), message) in &ours {
+            if !rustc.contains_key(&(*code, path.clone(), *line)) {
+                divergences += 1;
+                println!("false positive: {}:{line}: {message}", path.display());
+            }
+        }
+        for ((code, path, line), message) in &rustc {
+            if !ours.contains_key(&(*code, path.clone(), *line)) {
+                divergences += 1;
+                println!("false negative: {}:{line}: {message}", path.display());
+            }
+        }
+
+        println!();
+        println!(
+            "{} diagnostics, {} rustc errors, {divergences} divergences, \
+             {too_complex} matches too complex to check",
+            ours.len(),
+            rustc.len()
+        );
+        if divergences != 0 {
+            anyhow::bail!("match checking diverges from rustc")
+        }
+        Ok(())
+    }
+}
+
+/// Runs `cargo check` on the workspace and collects the errors match checking should report.
+fn rustc_errors(workspace_root: &Path) -> anyhow::Result<Reported> {
+    let mut cmd = Command::new(toolchain::cargo());
+    cmd.current_dir(workspace_root)
+        .args(["check", "--workspace", "--all-targets", "--message-format=json"])
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null());
+    let output = cmd.output().with_context(|| format!("failed to run {cmd:?}"))?;
+
+    let mut errors = Reported::new();
+    for line in String::from_utf8_lossy(&output.stdout).lines() {
+        let Ok(message) = serde_json::from_str::<serde_json::Value>(line) else { continue };
+        if message["reason"] != "compiler-message" {
+            continue;
+        }
+        let message = &message["message"];
+        let Some(&(_, code)) =
+            MATCH_DIAGNOSTICS.iter().find(|(_, code)| message["code"]["code"] == *code)
+        else {
+            continue;
+        };
+        let mut spans = message["spans"].as_array().into_iter().flatten();
+        let Some(span) = spans.find(|span| span["is_primary"] == true) else {
+            continue;
+        };
+        let (Some(file_name), Some(line)) =
+            (span["file_name"].as_str(), span["line_start"].as_u64())
+        else {
+            continue;
+        };
+        let text = message["message"].as_str().unwrap_or_default().to_owned();
+        errors.insert((code, workspace_root.join(file_name), line as u32), text);
+    }
+    Ok(errors)
+}
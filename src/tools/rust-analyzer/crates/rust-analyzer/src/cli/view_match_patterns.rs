@@ -0,0 +1,53 @@
+//! Print the patterns of the matches on a line of a file, as lowered for match checking.
+
+use std::env;
+
+use anyhow::Context;
+use ide::{FileRange, LineCol, TextRange, TextSize};
+use project_model::{CargoConfig, RustLibSource};
+use vfs::{AbsPathBuf, VfsPath};
+
+use crate::cli::{
+    flags,
+    load_cargo::{load_workspace_at, LoadCargoConfig, ProcMacroServerChoice},
+};
+
+impl flags::ViewMatchPatterns {
+    pub fn run(self) -> anyhow::Result<()> {
+        let (file, line) = self
+            .location
+            .rsplit_once(':')
+            .and_then(|(file, line)| Some((file, line.parse::<u32>().ok()?.checked_sub(1)?)))
+            .context("the location must be `<file>:<line>`")?;
+
+        let mut cargo_config = CargoConfig::default();
+        cargo_config.sysroot = Some(RustLibSource::Discover);
+        let load_cargo_config = LoadCargoConfig {
+            load_out_dirs_from_check: !self.disable_build_scripts,
+            with_proc_macro_server: if self.disable_proc_macros {
+                ProcMacroServerChoice::None
+            } else {
+                ProcMacroServerChoice::Sysroot
+            },
+            prefill_caches: false,
+        };
+        let (host, vfs, _proc_macro) =
+            load_workspace_at(&self.path, &cargo_config, &load_cargo_config, &|_| {})?;
+        let analysis = host.analysis();
+
+        let path = VfsPath::from(AbsPathBuf::assert(env::current_dir()?.join(file)));
+        let file_id =
+            vfs.file_id(&path).with_context(|| format!("{path} is not in the project"))?;
+        let line_index = analysis.file_line_index(file_id)?;
+        let start = line_index
+            .offset(LineCol { line, col: 0 })
+            .with_context(|| format!("{path} has less than {} lines", line + 1))?;
+        let end = line_index
+            .offset(LineCol { line: line + 1, col: 0 })
+            .unwrap_or_else(|| TextSize::of(&*analysis.file_text(file_id).unwrap()));
+
+        let range = TextRange::new(start, end);
+        print!("{}", analysis.view_match_patterns(FileRange { file_id, range })?);
+        Ok(())
+    }
+}
@@ -223,6 +223,8 @@ fn check_todo(path: &Path, text: &str) {
         "ide-assists/src/tests/generated.rs",
         // The tests for missing fields
         "ide-diagnostics/src/handlers/missing_fields.rs",
+        // The wildcard arm fix for missing match arms
+        "ide-diagnostics/src/handlers/missing_match_arms.rs",
     ];
     if need_todo.iter().any(|p| path.ends_with(p)) {
         return;
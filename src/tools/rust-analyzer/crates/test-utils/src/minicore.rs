@@ -12,6 +12,7 @@
 //!     as_ref: sized
 //!     bool_impl: option, fn
 //!     clone: sized
+//!     coerce_pointee: coerce_unsized, derive
 //!     coerce_unsized: unsize
 //!     copy: clone
 //!     default: sized
@@ -34,6 +35,7 @@
 //!     non_zero:
 //!     option:
 //!     ord: eq, option
+//!     pattern_types:
 //!     pin:
 //!     range:
 //!     result:
@@ -77,6 +79,11 @@ impl<T: ?Sized> !Sync for *mut T {}
     pub trait Unsize<T: ?Sized> {}
     // endregion:unsize
 
+    // region:coerce_pointee
+    #[rustc_builtin_macro(CoercePointee, attributes(pointee))]
+    pub macro CoercePointee($item:item) {}
+    // endregion:coerce_pointee
+
     // region:copy
     #[lang = "copy"]
     pub trait Copy: Clone {}
@@ -204,8 +211,21 @@ impl<'a, T: ?Sized + Unsize<U>, U: ?Sized> CoerceUnsized<*const U> for &'a T {}
         impl<T: ?Sized + Unsize<U>, U: ?Sized> CoerceUnsized<*mut U> for *mut T {}
         impl<T: ?Sized + Unsize<U>, U: ?Sized> CoerceUnsized<*const U> for *mut T {}
         impl<T: ?Sized + Unsize<U>, U: ?Sized> CoerceUnsized<*const U> for *const T {}
+
+        // region:coerce_pointee
+        #[lang = "dispatch_from_dyn"]
+        pub trait DispatchFromDyn<T> {}
+
+        impl<'a, T: ?Sized + Unsize<U>, U: ?Sized> DispatchFromDyn<&'a U> for &'a T {}
+        impl<'a, T: ?Sized + Unsize<U>, U: ?Sized> DispatchFromDyn<&'a mut U> for &'a mut T {}
+        impl<T: ?Sized + Unsize<U>, U: ?Sized> DispatchFromDyn<*const U> for *const T {}
+        impl<T: ?Sized + Unsize<U>, U: ?Sized> DispatchFromDyn<*mut U> for *mut T {}
+        // endregion:coerce_pointee
     }
     pub use self::unsize::CoerceUnsized;
+    // region:coerce_pointee
+    pub use self::unsize::DispatchFromDyn;
+    // endregion:coerce_pointee
     // endregion:coerce_unsized
 
     // region:deref
@@ -829,6 +849,15 @@ fn source(&self) -> Option<&(dyn Error + 'static)> {
 }
 // endregion:error
 
+// region:pattern_types
+pub mod pat {
+    #[rustc_builtin_macro(pattern_type)]
+    pub macro pattern_type($($arg:tt)*) {
+        /* compiler built-in */
+    }
+}
+// endregion:pattern_types
+
 pub mod prelude {
     pub mod v1 {
         pub use crate::{